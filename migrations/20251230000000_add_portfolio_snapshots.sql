@@ -0,0 +1,10 @@
+-- Persist portfolio value snapshots for long-term equity curves
+CREATE TABLE IF NOT EXISTS portfolio_snapshots (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    timestamp BIGINT NOT NULL,
+    symbol VARCHAR(20) NOT NULL,
+    total_value_usd DOUBLE PRECISION NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_portfolio_snapshots_timestamp ON portfolio_snapshots (timestamp);
@@ -0,0 +1,7 @@
+-- Real timestamp for order book snapshots so retention no longer relies on last_update_id
+ALTER TABLE order_books ADD COLUMN IF NOT EXISTS recorded_at TIMESTAMP WITH TIME ZONE;
+UPDATE order_books SET recorded_at = COALESCE(created_at, CURRENT_TIMESTAMP) WHERE recorded_at IS NULL;
+ALTER TABLE order_books ALTER COLUMN recorded_at SET DEFAULT CURRENT_TIMESTAMP;
+ALTER TABLE order_books ALTER COLUMN recorded_at SET NOT NULL;
+
+CREATE INDEX IF NOT EXISTS idx_order_books_market_type_recorded_at ON order_books (market_type, recorded_at);
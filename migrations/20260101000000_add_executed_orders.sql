@@ -0,0 +1,15 @@
+-- Journal of orders the system itself executed
+CREATE TABLE IF NOT EXISTS executed_orders (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    timestamp BIGINT NOT NULL,
+    strategy VARCHAR(64) NOT NULL,
+    symbol VARCHAR(20) NOT NULL,
+    side VARCHAR(4) NOT NULL,
+    price DOUBLE PRECISION NOT NULL,
+    quantity DOUBLE PRECISION NOT NULL,
+    fee DOUBLE PRECISION NOT NULL,
+    pnl DOUBLE PRECISION NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_executed_orders_symbol_timestamp ON executed_orders (symbol, timestamp);
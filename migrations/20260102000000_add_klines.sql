@@ -0,0 +1,16 @@
+-- Downloaded candlesticks, used for long backtests instead of raw aggTrades
+CREATE TABLE IF NOT EXISTS klines (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    symbol VARCHAR(20) NOT NULL,
+    market_type VARCHAR(10) NOT NULL,
+    kline_interval VARCHAR(4) NOT NULL,
+    open_time BIGINT NOT NULL,
+    open DOUBLE PRECISION NOT NULL,
+    high DOUBLE PRECISION NOT NULL,
+    low DOUBLE PRECISION NOT NULL,
+    close DOUBLE PRECISION NOT NULL,
+    volume DOUBLE PRECISION NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_klines_unique ON klines (symbol, market_type, kline_interval, open_time);
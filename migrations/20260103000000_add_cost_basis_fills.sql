@@ -0,0 +1,13 @@
+-- Spot fills behind the FIFO cost basis, replayed on startup to rebuild the open and closed lots
+CREATE TABLE IF NOT EXISTS cost_basis_fills (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    timestamp BIGINT NOT NULL,
+    symbol VARCHAR(20) NOT NULL,
+    side VARCHAR(4) NOT NULL,
+    price DOUBLE PRECISION NOT NULL,
+    quantity DOUBLE PRECISION NOT NULL,
+    fee DOUBLE PRECISION NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_cost_basis_fills_timestamp ON cost_basis_fills (timestamp);
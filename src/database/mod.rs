@@ -1,14 +1,127 @@
-use sqlx::postgres::PgPoolOptions;
+use schemars::JsonSchema;
+use serde::Serialize;
+use sqlx::postgres::{PgConnectOptions, PgPoolOptions};
 use sqlx::{Pool, Postgres};
 use std::env;
+use std::future::Future;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use tokio::sync::Semaphore;
 
 pub mod repository;
 
+/// Server-side limit per statement; long enough for retention cleanup and backtest range loads
+pub const DEFAULT_STATEMENT_TIMEOUT_MS: u64 = 30_000;
+/// How long a query waits for a free pooled connection before failing
+pub const DEFAULT_ACQUIRE_TIMEOUT_SECS: u64 = 5;
+/// Fire-and-forget writes allowed in flight before new ones are dropped
+pub const DEFAULT_MAX_CONCURRENT_WRITES: usize = 50;
+
+fn env_or<T: FromStr>(key: &str, default: T) -> T {
+    env::var(key).ok().and_then(|v| v.parse::<T>().ok()).unwrap_or(default)
+}
+
+/// Connects with DB_STATEMENT_TIMEOUT_MS (default 30000) set on every connection and
+/// DB_ACQUIRE_TIMEOUT_SECS (default 5) bounding the wait for a pooled connection
 pub async fn establish_connection() -> Pool<Postgres> {
     let database_url = env::var("DATABASE_URL").expect("DATABASE_URL must be set");
+    let statement_timeout_ms = env_or("DB_STATEMENT_TIMEOUT_MS", DEFAULT_STATEMENT_TIMEOUT_MS);
+    let options = PgConnectOptions::from_str(&database_url)
+        .expect("Invalid DATABASE_URL")
+        .options([("statement_timeout", statement_timeout_ms.to_string())]);
     PgPoolOptions::new()
         .max_connections(5)
-        .connect(&database_url)
+        .acquire_timeout(Duration::from_secs(env_or("DB_ACQUIRE_TIMEOUT_SECS", DEFAULT_ACQUIRE_TIMEOUT_SECS)))
+        .connect_with(options)
         .await
         .expect("Failed to create pool")
 }
+
+/// Bounds the background writes the event loop spawns (trades, books, snapshots) so a
+/// slow database sheds market data instead of piling up tasks and connections
+pub struct WriteLimiter {
+    permits: Arc<Semaphore>,
+    max_concurrent: usize,
+    dropped: AtomicU64,
+}
+
+impl WriteLimiter {
+    pub fn new(max_concurrent: usize) -> Self {
+        let max_concurrent = max_concurrent.max(1);
+        Self { permits: Arc::new(Semaphore::new(max_concurrent)), max_concurrent, dropped: AtomicU64::new(0) }
+    }
+
+    /// Reads DB_MAX_CONCURRENT_WRITES (default 50) from the environment
+    pub fn from_env() -> Self {
+        Self::new(env_or("DB_MAX_CONCURRENT_WRITES", DEFAULT_MAX_CONCURRENT_WRITES))
+    }
+
+    /// Spawns `write` if a slot is free, otherwise drops it. Never waits.
+    pub fn spawn<F>(&self, write: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        match self.permits.clone().try_acquire_owned() {
+            Ok(permit) => {
+                tokio::spawn(async move {
+                    write.await;
+                    drop(permit);
+                });
+            }
+            Err(_) => {
+                let dropped = self.dropped.fetch_add(1, Ordering::Relaxed) + 1;
+                if dropped.is_power_of_two() {
+                    log::warn!("Database writes saturated ({} in flight): {} writes dropped so far", self.max_concurrent, dropped);
+                }
+            }
+        }
+    }
+
+    /// Waits up to `timeout` for every spawned write to finish, then refuses new ones.
+    /// Returns false if writes were still running when the time ran out.
+    pub async fn drain(&self, timeout: Duration) -> bool {
+        let drained = tokio::time::timeout(timeout, self.permits.acquire_many(self.max_concurrent as u32)).await;
+        self.permits.close();
+        matches!(drained, Ok(Ok(_)))
+    }
+
+    pub fn in_flight(&self) -> usize {
+        self.max_concurrent - self.permits.available_permits()
+    }
+
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+}
+
+/// Connection pool and background write state for /api/status
+#[derive(Serialize, Clone, Debug, JsonSchema)]
+pub struct DbPoolStatus {
+    /// Open connections, idle or in use
+    pub size: u32,
+    pub idle: u32,
+    /// Connections currently running a query
+    pub active: u32,
+    pub max_connections: u32,
+    /// Background writes running or waiting for a connection
+    pub writes_in_flight: usize,
+    /// Background writes dropped because too many were in flight
+    pub writes_dropped: u64,
+}
+
+impl DbPoolStatus {
+    pub fn new(pool: &Pool<Postgres>, writes: &WriteLimiter) -> Self {
+        let size = pool.size();
+        let idle = pool.num_idle() as u32;
+        Self {
+            size,
+            idle,
+            active: size.saturating_sub(idle),
+            max_connections: pool.options().get_max_connections(),
+            writes_in_flight: writes.in_flight(),
+            writes_dropped: writes.dropped(),
+        }
+    }
+}
@@ -1,6 +1,8 @@
 use sqlx::{Pool, Postgres};
 use binance::model::{TradeEvent, OrderBook, AggrTradesEvent};
 use serde_json::json;
+use crate::market_data::Candle;
+use crate::strategy::{Side, Signal};
 
 
 pub async fn save_trade(pool: &Pool<Postgres>, event: &TradeEvent, market_type: &str) -> Result<(), sqlx::Error> {
@@ -82,23 +84,54 @@ pub async fn save_aggr_trades_bulk(pool: &Pool<Postgres>, events: &[AggrTradesEv
     }
 }
 
-pub async fn cleanup_old_data(pool: &Pool<Postgres>, hours: i64) -> Result<u64, sqlx::Error> {
-    let threshold = std::time::SystemTime::now()
+/// How long raw market data is kept, per market type
+#[derive(Debug, Clone, Copy)]
+pub struct RetentionPolicy {
+    pub spot_hours: i64,
+    pub futures_hours: i64,
+}
+
+impl RetentionPolicy {
+    /// Reads RETENTION_SPOT_HOURS and RETENTION_FUTURES_HOURS (default 24 each)
+    pub fn from_env() -> Self {
+        let hours = |key: &str| std::env::var(key).ok().and_then(|v| v.parse::<i64>().ok()).unwrap_or(24);
+        Self {
+            spot_hours: hours("RETENTION_SPOT_HOURS"),
+            futures_hours: hours("RETENTION_FUTURES_HOURS"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CleanupStats {
+    pub trades: u64,
+    pub order_books: u64,
+}
+
+pub async fn cleanup_old_data(pool: &Pool<Postgres>, policy: &RetentionPolicy) -> Result<CleanupStats, sqlx::Error> {
+    let now_ms = std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
         .unwrap()
-        .as_millis() as i64 - (hours * 3600 * 1000);
+        .as_millis() as i64;
+
+    let mut stats = CleanupStats::default();
+    for (market_type, hours) in [("SPOT", policy.spot_hours), ("FUTURES", policy.futures_hours)] {
+        let res = sqlx::query("DELETE FROM trades WHERE market_type = $1 AND event_time < $2")
+            .bind(market_type)
+            .bind(now_ms - hours * 3600 * 1000)
+            .execute(pool)
+            .await?;
+        stats.trades += res.rows_affected();
+
+        let res = sqlx::query("DELETE FROM order_books WHERE market_type = $1 AND recorded_at < NOW() - make_interval(hours => $2)")
+            .bind(market_type)
+            .bind(hours as i32)
+            .execute(pool)
+            .await?;
+        stats.order_books += res.rows_affected();
+    }
 
-    let res = sqlx::query("DELETE FROM trades WHERE event_time < $1")
-        .bind(threshold)
-        .execute(pool)
-        .await?;
-    
-    let res2 = sqlx::query("DELETE FROM order_books WHERE last_update_id < $1")
-        .bind(threshold / 1000) // Rough approximation for last_update_id if not timestamped
-        .execute(pool)
-        .await?;
-    
-    Ok(res.rows_affected() + res2.rows_affected())
+    Ok(stats)
 }
 
 pub async fn save_order_book(pool: &Pool<Postgres>, symbol: &str, book: &OrderBook, market_type: &str) -> Result<(), sqlx::Error> {
@@ -220,6 +253,187 @@ pub async fn get_aggregated_trades(
     Ok(data)
 }
 
+/// Builds OHLCV candles from raw trades, bucketed by `interval_secs`.
+/// Open/close are the first/last trade in each bucket by event_time.
+pub async fn get_ohlcv(
+    pool: &Pool<Postgres>,
+    symbol: &str,
+    market_type: &str,
+    interval_secs: i64,
+) -> Result<Vec<Candle>, sqlx::Error> {
+    get_ohlcv_range(pool, symbol, market_type, interval_secs, 0, i64::MAX).await
+}
+
+/// Like `get_ohlcv`, limited to trades with `start_ms <= event_time <= end_ms`
+pub async fn get_ohlcv_range(
+    pool: &Pool<Postgres>,
+    symbol: &str,
+    market_type: &str,
+    interval_secs: i64,
+    start_ms: i64,
+    end_ms: i64,
+) -> Result<Vec<Candle>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        WITH bucketed AS (
+            SELECT
+                (event_time / ($1 * 1000)) * $1 as bucket,
+                price::FLOAT8 as price,
+                quantity::FLOAT8 as quantity,
+                FIRST_VALUE(price::FLOAT8) OVER w as open,
+                LAST_VALUE(price::FLOAT8) OVER w as close
+            FROM trades
+            WHERE symbol = $2 AND market_type = $3 AND event_time BETWEEN $4 AND $5
+            WINDOW w AS (
+                PARTITION BY event_time / ($1 * 1000)
+                ORDER BY event_time
+                ROWS BETWEEN UNBOUNDED PRECEDING AND UNBOUNDED FOLLOWING
+            )
+        )
+        SELECT
+            bucket,
+            MIN(open) as open,
+            MAX(price) as high,
+            MIN(price) as low,
+            MIN(close) as close,
+            SUM(quantity) as volume
+        FROM bucketed
+        GROUP BY bucket
+        ORDER BY bucket ASC
+        "#,
+    )
+    .bind(interval_secs)
+    .bind(symbol)
+    .bind(market_type)
+    .bind(start_ms)
+    .bind(end_ms)
+    .fetch_all(pool)
+    .await?;
+
+    let candles = rows.into_iter().map(|row| {
+        use sqlx::Row;
+        Candle {
+            symbol: symbol.to_string(),
+            timestamp: row.get::<i64, _>("bucket"),
+            open: row.get::<f64, _>("open"),
+            high: row.get::<f64, _>("high"),
+            low: row.get::<f64, _>("low"),
+            close: row.get::<f64, _>("close"),
+            volume: row.get::<f64, _>("volume"),
+        }
+    }).collect();
+
+    Ok(candles)
+}
+
+/// Stores downloaded klines as candles. Candles already stored for the same open time are kept.
+pub async fn save_klines(pool: &Pool<Postgres>, candles: &[Candle], market_type: &str, interval: &str) -> Result<(), sqlx::Error> {
+    if candles.is_empty() {
+        return Ok(());
+    }
+
+    let mut query_builder: sqlx::QueryBuilder<Postgres> = sqlx::QueryBuilder::new(
+        "INSERT INTO klines (symbol, market_type, kline_interval, open_time, open, high, low, close, volume) "
+    );
+    query_builder.push_values(candles.iter(), |mut b, candle| {
+        b.push_bind(&candle.symbol)
+            .push_bind(market_type)
+            .push_bind(interval)
+            .push_bind(candle.timestamp * 1000)
+            .push_bind(candle.open)
+            .push_bind(candle.high)
+            .push_bind(candle.low)
+            .push_bind(candle.close)
+            .push_bind(candle.volume);
+    });
+    query_builder.push(" ON CONFLICT (symbol, market_type, kline_interval, open_time) DO NOTHING");
+
+    query_builder.build().execute(pool).await?;
+    Ok(())
+}
+
+/// Stored klines whose open time (ms) falls in the range, oldest first
+pub async fn get_klines_range(
+    pool: &Pool<Postgres>,
+    symbol: &str,
+    market_type: &str,
+    interval: &str,
+    start_time: u64,
+    end_time: u64,
+) -> Result<Vec<Candle>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT open_time, open, high, low, close, volume
+        FROM klines
+        WHERE symbol = $1 AND market_type = $2 AND kline_interval = $3 AND open_time BETWEEN $4 AND $5
+        ORDER BY open_time ASC
+        "#,
+    )
+    .bind(symbol)
+    .bind(market_type)
+    .bind(interval)
+    .bind(start_time as i64)
+    .bind(end_time.min(i64::MAX as u64) as i64)
+    .fetch_all(pool)
+    .await?;
+
+    let candles = rows.into_iter().map(|row| {
+        use sqlx::Row;
+        Candle {
+            symbol: symbol.to_string(),
+            timestamp: row.get::<i64, _>("open_time") / 1000,
+            open: row.get::<f64, _>("open"),
+            high: row.get::<f64, _>("high"),
+            low: row.get::<f64, _>("low"),
+            close: row.get::<f64, _>("close"),
+            volume: row.get::<f64, _>("volume"),
+        }
+    }).collect();
+
+    Ok(candles)
+}
+
+/// Stored order book snapshots in a time range (ms), oldest first, paired with their recorded time in ms
+pub async fn get_historical_orderbooks_range(
+    pool: &Pool<Postgres>,
+    symbol: &str,
+    market_type: &str,
+    start_time: Option<u64>,
+    end_time: Option<u64>
+) -> Result<Vec<(u64, OrderBook)>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT ts_ms, last_update_id, bids, asks FROM (
+            SELECT (EXTRACT(EPOCH FROM recorded_at) * 1000)::BIGINT AS ts_ms, last_update_id, bids, asks
+            FROM order_books
+            WHERE symbol = $1 AND market_type = $2
+        ) books
+        WHERE ts_ms >= $3 AND ts_ms <= $4
+        ORDER BY ts_ms ASC
+        "#,
+    )
+    .bind(symbol)
+    .bind(market_type)
+    .bind(start_time.unwrap_or(0).min(i64::MAX as u64) as i64)
+    .bind(end_time.unwrap_or(u64::MAX).min(i64::MAX as u64) as i64)
+    .fetch_all(pool)
+    .await?;
+
+    let books = rows.into_iter().filter_map(|row| {
+        use sqlx::Row;
+        let bids = serde_json::from_value(row.get::<serde_json::Value, _>("bids")).ok()?;
+        let asks = serde_json::from_value(row.get::<serde_json::Value, _>("asks")).ok()?;
+        let book = OrderBook {
+            last_update_id: row.get::<i64, _>("last_update_id") as u64,
+            bids,
+            asks,
+        };
+        Some((row.get::<i64, _>("ts_ms") as u64, book))
+    }).collect();
+
+    Ok(books)
+}
+
 pub async fn get_data_range(pool: &Pool<Postgres>, symbol: &str, market_type: &str) -> Result<(Option<u64>, Option<u64>), sqlx::Error> {
     let row: (Option<i64>, Option<i64>) = sqlx::query_as(
         "SELECT MIN(event_time), MAX(event_time) FROM trades WHERE symbol = $1 AND market_type = $2"
@@ -231,3 +445,258 @@ pub async fn get_data_range(pool: &Pool<Postgres>, symbol: &str, market_type: &s
 
     Ok((row.0.map(|v| v as u64), row.1.map(|v| v as u64)))
 }
+
+/// Indexes (`event_time / bucket_ms`) of the buckets holding at least one trade with
+/// `start_ms <= event_time < end_ms`, ascending
+pub async fn get_trade_buckets(
+    pool: &Pool<Postgres>,
+    symbol: &str,
+    market_type: &str,
+    bucket_ms: i64,
+    start_ms: i64,
+    end_ms: i64,
+) -> Result<Vec<i64>, sqlx::Error> {
+    let rows: Vec<(i64,)> = sqlx::query_as(
+        r#"
+        SELECT DISTINCT event_time / $1 AS bucket
+        FROM trades
+        WHERE symbol = $2 AND market_type = $3 AND event_time >= $4 AND event_time < $5
+        ORDER BY bucket
+        "#,
+    )
+    .bind(bucket_ms)
+    .bind(symbol)
+    .bind(market_type)
+    .bind(start_ms)
+    .bind(end_ms)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.into_iter().map(|(bucket,)| bucket).collect())
+}
+
+#[derive(Debug, serde::Serialize)]
+pub struct StoredPortfolioSnapshot {
+    pub timestamp: u64,
+    pub total_value_usd: f64,
+    pub symbol: String,
+}
+
+pub async fn save_portfolio_snapshot(pool: &Pool<Postgres>, ts: u64, total_value_usd: f64, symbol: &str) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO portfolio_snapshots (timestamp, symbol, total_value_usd)
+        VALUES ($1, $2, $3)
+        "#,
+    )
+    .bind(ts as i64)
+    .bind(symbol)
+    .bind(total_value_usd)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn get_portfolio_snapshots(pool: &Pool<Postgres>, since_ts: u64) -> Result<Vec<StoredPortfolioSnapshot>, sqlx::Error> {
+    let rows = sqlx::query(
+        r#"
+        SELECT timestamp, symbol, total_value_usd
+        FROM portfolio_snapshots
+        WHERE timestamp >= $1
+        ORDER BY timestamp ASC
+        "#,
+    )
+    .bind(since_ts as i64)
+    .fetch_all(pool)
+    .await?;
+
+    let snapshots = rows.into_iter().map(|row| {
+        use sqlx::Row;
+        StoredPortfolioSnapshot {
+            timestamp: row.get::<i64, _>("timestamp") as u64,
+            total_value_usd: row.get::<f64, _>("total_value_usd"),
+            symbol: row.get::<String, _>("symbol"),
+        }
+    }).collect();
+
+    Ok(snapshots)
+}
+
+/// Journals an order executed by the system. Cancels are not recorded.
+pub async fn save_executed_order(
+    pool: &Pool<Postgres>,
+    strategy: &str,
+    signal: &Signal,
+    fill_price: f64,
+    qty: f64,
+    fee: f64,
+    pnl: f64,
+    ts: u64,
+) -> Result<(), sqlx::Error> {
+    let side = match signal {
+        Signal::Buy { .. } | Signal::LimitBuy { .. } | Signal::StopLimit { side: Side::Buy, .. } => "BUY",
+        Signal::Sell { .. } | Signal::SellAll { .. } | Signal::LimitSell { .. } | Signal::StopLimit { side: Side::Sell, .. } => "SELL",
+        Signal::Cancel { .. } => return Ok(()),
+    };
+
+    sqlx::query(
+        r#"
+        INSERT INTO executed_orders (timestamp, strategy, symbol, side, price, quantity, fee, pnl)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        "#,
+    )
+    .bind(ts as i64)
+    .bind(strategy)
+    .bind(signal.symbol())
+    .bind(side)
+    .bind(fill_price)
+    .bind(qty)
+    .bind(fee)
+    .bind(pnl)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// A buy matched (FIFO) against the sell that closed it
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct RoundTrip {
+    pub strategy: String, // Strategy that opened the position
+    pub symbol: String,
+    pub quantity: f64,
+    pub entry_ts: u64,
+    pub exit_ts: u64,
+    pub entry_price: f64,
+    pub exit_price: f64,
+    pub fees: f64,
+    pub pnl: f64, // Net of the entry and exit fees attributed to this quantity
+    pub hold_ms: u64,
+}
+
+/// Rebuilds round trips from the order journal by matching each sell against the oldest open buys
+/// of the same symbol. Returns the most recent `limit`, newest first.
+pub async fn get_round_trips(pool: &Pool<Postgres>, limit: usize) -> Result<Vec<RoundTrip>, sqlx::Error> {
+    use sqlx::Row;
+
+    struct OpenLot {
+        strategy: String,
+        ts: u64,
+        price: f64,
+        remaining: f64,
+        fee_per_unit: f64,
+    }
+
+    let rows = sqlx::query(
+        "SELECT timestamp, strategy, symbol, side, price, quantity, fee FROM executed_orders ORDER BY timestamp ASC"
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut open_lots: std::collections::HashMap<String, std::collections::VecDeque<OpenLot>> = std::collections::HashMap::new();
+    let mut round_trips = Vec::new();
+
+    for row in rows {
+        let ts = row.get::<i64, _>("timestamp") as u64;
+        let symbol = row.get::<String, _>("symbol");
+        let price = row.get::<f64, _>("price");
+        let quantity = row.get::<f64, _>("quantity");
+        let fee = row.get::<f64, _>("fee");
+        if quantity <= 0.0 {
+            continue;
+        }
+        let lots = open_lots.entry(symbol.clone()).or_default();
+
+        if row.get::<String, _>("side") == "BUY" {
+            lots.push_back(OpenLot {
+                strategy: row.get::<String, _>("strategy"),
+                ts,
+                price,
+                remaining: quantity,
+                fee_per_unit: fee / quantity,
+            });
+            continue;
+        }
+
+        let exit_fee_per_unit = fee / quantity;
+        let mut to_close = quantity;
+        while to_close > 1e-12 {
+            let Some(lot) = lots.front_mut() else { break };
+            let matched = lot.remaining.min(to_close);
+            let fees = matched * (lot.fee_per_unit + exit_fee_per_unit);
+            round_trips.push(RoundTrip {
+                strategy: lot.strategy.clone(),
+                symbol: symbol.clone(),
+                quantity: matched,
+                entry_ts: lot.ts,
+                exit_ts: ts,
+                entry_price: lot.price,
+                exit_price: price,
+                fees,
+                pnl: (price - lot.price) * matched - fees,
+                hold_ms: ts.saturating_sub(lot.ts),
+            });
+            lot.remaining -= matched;
+            to_close -= matched;
+            if lot.remaining <= 1e-12 {
+                lots.pop_front();
+            }
+        }
+    }
+
+    round_trips.reverse();
+    round_trips.truncate(limit);
+    Ok(round_trips)
+}
+
+/// A spot fill that fed the cost basis tracker
+#[derive(Debug, Clone)]
+pub struct CostBasisFill {
+    pub timestamp: u64,
+    pub symbol: String,
+    pub is_buy: bool,
+    pub price: f64,
+    pub quantity: f64,
+    pub fee: f64,
+}
+
+pub async fn save_cost_basis_fill(pool: &Pool<Postgres>, fill: &CostBasisFill) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO cost_basis_fills (timestamp, symbol, side, price, quantity, fee)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        "#,
+    )
+    .bind(fill.timestamp as i64)
+    .bind(&fill.symbol)
+    .bind(if fill.is_buy { "BUY" } else { "SELL" })
+    .bind(fill.price)
+    .bind(fill.quantity)
+    .bind(fill.fee)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Every recorded cost basis fill, oldest first, for rebuilding the lots
+pub async fn get_cost_basis_fills(pool: &Pool<Postgres>) -> Result<Vec<CostBasisFill>, sqlx::Error> {
+    use sqlx::Row;
+    let rows = sqlx::query(
+        "SELECT timestamp, symbol, side, price, quantity, fee FROM cost_basis_fills ORDER BY timestamp ASC, created_at ASC"
+    )
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.into_iter().map(|row| CostBasisFill {
+        timestamp: row.get::<i64, _>("timestamp") as u64,
+        symbol: row.get::<String, _>("symbol"),
+        is_buy: row.get::<String, _>("side") == "BUY",
+        price: row.get::<f64, _>("price"),
+        quantity: row.get::<f64, _>("quantity"),
+        fee: row.get::<f64, _>("fee"),
+    }).collect())
+}
+
+pub async fn clear_cost_basis_fills(pool: &Pool<Postgres>) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM cost_basis_fills").execute(pool).await?;
+    Ok(())
+}
@@ -13,10 +13,19 @@
 //! The worker thread is completely isolated from the tokio runtime,
 //! eliminating the "Cannot drop a runtime" panic.
 
-use binance::account::Account;
-use binance::api::Binance;
-use std::sync::mpsc;
+use binance::account::{Account, OrderSide, OrderType, TimeInForce as BinanceTimeInForce};
+use binance::api::{Binance, API, Spot};
+use binance::general::General;
+use binance::market::Market;
+use binance::model::{Filters, Order, Transaction};
+use binance::util::build_signed_request;
+use crate::strategy::{Side, TimeInForce};
+use super::rate_limiter::{self, RateLimiter};
+use super::retry::{self, RetryPolicy, RetryStats};
+use std::collections::BTreeMap;
+use std::sync::{mpsc, Arc};
 use std::thread::{self, JoinHandle};
+use std::time::Instant;
 use log::{info, error, warn};
 
 /// Commands that can be sent to the Binance worker
@@ -32,11 +41,45 @@ pub enum BinanceCommand {
         quantity: f64,
         response_tx: tokio::sync::oneshot::Sender<BinanceResponse>,
     },
+    LimitBuy {
+        symbol: String,
+        quantity: f64,
+        price: f64,
+        time_in_force: TimeInForce,
+        response_tx: tokio::sync::oneshot::Sender<BinanceResponse>,
+    },
+    LimitSell {
+        symbol: String,
+        quantity: f64,
+        price: f64,
+        time_in_force: TimeInForce,
+        response_tx: tokio::sync::oneshot::Sender<BinanceResponse>,
+    },
+    /// Post-only limit order, rejected by the exchange instead of taking liquidity
+    LimitMaker {
+        symbol: String,
+        side: Side,
+        quantity: f64,
+        price: f64,
+        response_tx: tokio::sync::oneshot::Sender<BinanceResponse>,
+    },
+    StopLimit {
+        symbol: String,
+        side: Side,
+        quantity: f64,
+        stop_price: f64,
+        limit_price: f64,
+        response_tx: tokio::sync::oneshot::Sender<BinanceResponse>,
+    },
     CancelOrder {
         symbol: String,
         order_id: u64,
         response_tx: tokio::sync::oneshot::Sender<BinanceResponse>,
     },
+    CancelAllOrders {
+        symbol: String,
+        response_tx: tokio::sync::oneshot::Sender<BinanceResponse>,
+    },
     GetAccount {
         response_tx: tokio::sync::oneshot::Sender<BinanceResponse>,
     },
@@ -45,6 +88,19 @@ pub enum BinanceCommand {
         limit: u16,
         response_tx: tokio::sync::oneshot::Sender<BinanceResponse>,
     },
+    GetSymbolFilters {
+        symbol: String,
+        response_tx: tokio::sync::oneshot::Sender<BinanceResponse>,
+    },
+    GetPrice {
+        symbol: String,
+        response_tx: tokio::sync::oneshot::Sender<BinanceResponse>,
+    },
+    GetOrderStatus {
+        symbol: String,
+        order_id: u64,
+        response_tx: tokio::sync::oneshot::Sender<BinanceResponse>,
+    },
     Shutdown,
 }
 
@@ -55,22 +111,266 @@ pub struct TradeInfo {
     pub price: f64,
     pub qty: f64,
     pub commission: f64,
-    pub commission_asset: String,
+    pub commission_asset: String, // Asset the commission was charged in: quote, base or BNB
     pub is_buyer: bool,
     pub time: u64,
 }
 
+/// Exchange trading rules for one symbol
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SymbolFilters {
+    pub qty_precision: u32,   // Decimals allowed by LOT_SIZE stepSize
+    pub price_precision: u32, // Decimals allowed by PRICE_FILTER tickSize
+    pub min_notional: f64,    // Minimum order value in quote currency
+}
+
+impl Default for SymbolFilters {
+    /// BTCUSDT's rules, used whenever exchange info is unavailable
+    fn default() -> Self {
+        Self { qty_precision: 5, price_precision: 2, min_notional: 5.0 }
+    }
+}
+
+impl SymbolFilters {
+    fn from_exchange(filters: &[Filters]) -> Self {
+        let mut parsed = Self::default();
+        for filter in filters {
+            match filter {
+                Filters::LotSize { step_size, .. } => parsed.qty_precision = precision_from_step(step_size),
+                Filters::PriceFilter { tick_size, .. } => parsed.price_precision = precision_from_step(tick_size),
+                Filters::MinNotional { min_notional, notional, .. } | Filters::Notional { min_notional, notional, .. } => {
+                    if let Some(value) = min_notional.as_ref().or(notional.as_ref()).and_then(|v| v.parse::<f64>().ok()) {
+                        parsed.min_notional = value;
+                    }
+                }
+                _ => {}
+            }
+        }
+        parsed
+    }
+}
+
+/// Decimal places of a step such as "0.00010000" (4). Whole-number steps give 0.
+fn precision_from_step(step: &str) -> u32 {
+    match step.split_once('.') {
+        Some((_, decimals)) => decimals.trim_end_matches('0').len() as u32,
+        None => 0,
+    }
+}
+
 /// Responses from the Binance worker
 #[derive(Debug, Clone)]
 pub enum BinanceResponse {
-    OrderSuccess { order_id: u64, symbol: String, qty: f64 },
+    /// `qty` is what was requested; `executed_qty` is what actually filled (0 for a resting limit order)
+    OrderSuccess { order_id: u64, symbol: String, qty: f64, executed_qty: f64, avg_fill_price: f64 },
     OrderFailed { error: String },
     AccountInfo { balances: Vec<(String, f64)> },
     TradeHistory { trades: Vec<TradeInfo> },
+    SymbolFilters { filters: SymbolFilters },
+    Price { price: f64 },
+    OrderStatus { state: OrderState },
     Cancelled,
     Failed { error: String },
 }
 
+/// What a market order actually did, which can be less than requested
+#[derive(Debug, Clone, Copy)]
+pub struct OrderFill {
+    pub order_id: u64,
+    pub executed_qty: f64,
+    pub avg_fill_price: f64, // 0 when nothing filled
+}
+
+/// How far a resting order has filled, as reported by the exchange
+#[derive(Debug, Clone)]
+pub struct OrderState {
+    pub status: String, // NEW, PARTIALLY_FILLED, FILLED, CANCELED, EXPIRED, REJECTED
+    pub executed_qty: f64,
+    pub quote_qty: f64, // Quote spent or received across all fills so far
+}
+
+impl OrderState {
+    /// The order can no longer fill
+    pub fn is_closed(&self) -> bool {
+        !matches!(self.status.as_str(), "NEW" | "PARTIALLY_FILLED")
+    }
+}
+
+/// Reads the filled quantity and average price from an order response
+fn order_success(answer: &Transaction, symbol: &str, qty: f64) -> BinanceResponse {
+    let avg_fill_price = if answer.executed_qty > 0.0 && answer.cummulative_quote_qty > 0.0 {
+        answer.cummulative_quote_qty / answer.executed_qty
+    } else {
+        // Older responses may lack the quote total; fall back to the fills
+        let fills = answer.fills.as_deref().unwrap_or_default();
+        let filled: f64 = fills.iter().map(|f| f.qty).sum();
+        if filled > 0.0 { fills.iter().map(|f| f.price * f.qty).sum::<f64>() / filled } else { 0.0 }
+    };
+    BinanceResponse::OrderSuccess {
+        order_id: answer.order_id,
+        symbol: symbol.to_string(),
+        qty,
+        executed_qty: answer.executed_qty,
+        avg_fill_price,
+    }
+}
+
+/// Reads the filled quantity and average price from an order found by its client order id.
+/// An order the exchange expired or rejected without filling anything failed.
+fn order_lookup_success(order: &Order, symbol: &str, qty: f64) -> BinanceResponse {
+    let executed_qty = order.executed_qty.parse::<f64>().unwrap_or(0.0);
+    let quote_qty = order.cummulative_quote_qty.parse::<f64>().unwrap_or(0.0);
+    if executed_qty <= 0.0 && matches!(order.status.as_str(), "EXPIRED" | "EXPIRED_IN_MATCH" | "REJECTED" | "CANCELED") {
+        return BinanceResponse::OrderFailed { error: format!("Order {} was {} without filling", order.client_order_id, order.status) };
+    }
+    BinanceResponse::OrderSuccess {
+        order_id: order.order_id,
+        symbol: symbol.to_string(),
+        qty,
+        executed_qty,
+        avg_fill_price: if executed_qty > 0.0 && quote_qty > 0.0 { quote_qty / executed_qty } else { 0.0 },
+    }
+}
+
+/// Looks up a spot order by the client order id it was sent with (binance-rs only queries by order id)
+fn order_by_client_id(account: &Account, symbol: &str, client_order_id: &str) -> binance::errors::Result<Order> {
+    let mut parameters: BTreeMap<String, String> = BTreeMap::new();
+    parameters.insert("symbol".into(), symbol.to_string());
+    parameters.insert("origClientOrderId".into(), client_order_id.to_string());
+    let request = build_signed_request(parameters, account.recv_window)?;
+    account.client.get_signed(API::Spot(Spot::Order), Some(request))
+}
+
+/// Free balance still covers the order: quote at the current ticker price for a buy, base for a sell
+fn check_retry_balance(account: &Account, market: &Market, limiter: &RateLimiter, quote_asset: &str, symbol: &str, side: Side, quantity: f64) -> Result<(), String> {
+    let info = limiter.call(rate_limiter::ACCOUNT_WEIGHT, || account.get_account())
+        .map_err(|e| format!("Balance re-check failed: {:?}", e))?;
+    let free = |asset: &str| info.balances.iter()
+        .find(|b| b.asset == asset)
+        .and_then(|b| b.free.parse::<f64>().ok())
+        .unwrap_or(0.0);
+    let (asset, available, required) = match side {
+        Side::Buy => {
+            let price = limiter.call(rate_limiter::TICKER_PRICE_WEIGHT, || market.get_price(symbol))
+                .map_err(|e| format!("Price fetch for balance re-check failed: {:?}", e))?
+                .price;
+            (quote_asset, free(quote_asset), quantity * price)
+        }
+        Side::Sell => {
+            let base = super::base_asset(symbol, quote_asset);
+            (base, free(base), quantity)
+        }
+    };
+    if available < required {
+        return Err(format!("Insufficient {} for retry: {} free, {} needed", asset, available, required));
+    }
+    Ok(())
+}
+
+/// Sends a market order under one client order id. It is re-sent only when the connection
+/// failed, so the request never reached the exchange. Timeouts and 5xx answers leave the
+/// order's outcome unknown; it is then looked up until the exchange knows it, never re-sent.
+/// Sleeps between attempts block this worker thread, so their total is capped at MAX_RETRY_TIME.
+fn market_order_with_retry(
+    account: &Account,
+    market: &Market,
+    limiter: &RateLimiter,
+    policy: &RetryPolicy,
+    stats: &RetryStats,
+    quote_asset: &str,
+    symbol: &str,
+    side: Side,
+    quantity: f64,
+) -> BinanceResponse {
+    let client_order_id = retry::client_order_id();
+    let started = Instant::now();
+    let mut attempt = 1;
+    loop {
+        let order_side = match side {
+            Side::Buy => OrderSide::Buy,
+            Side::Sell => OrderSide::Sell,
+        };
+        // Price 0 leaves price and time-in-force out of the request, as a market order needs
+        let result = limiter.call(rate_limiter::ORDER_WEIGHT, || {
+            account.custom_order(symbol, quantity, 0.0, None, order_side, OrderType::Market, BinanceTimeInForce::GTC, Some(client_order_id.clone()))
+        });
+        let error = match result {
+            Ok(answer) => {
+                if attempt > 1 {
+                    RetryStats::count(&stats.recovered);
+                }
+                info!("Order {} ({}) placed successfully", answer.order_id, client_order_id);
+                return order_success(&answer, symbol, quantity);
+            }
+            Err(e) => format!("{:?}", e),
+        };
+        let unsent = retry::is_unsent(&error);
+        if !unsent && retry::is_status_unknown(&error) {
+            warn!("{} order {} outcome unknown: {}. Looking it up", side.as_str(), client_order_id, error);
+            return confirm_order(account, limiter, policy, stats, symbol, quantity, &client_order_id, error, started);
+        }
+        let delay = policy.backoff(attempt);
+        if !unsent || attempt >= policy.max_attempts || started.elapsed() + delay > retry::MAX_RETRY_TIME {
+            if unsent && policy.max_attempts > 1 {
+                RetryStats::count(&stats.exhausted);
+            }
+            error!("{} order {} failed after {} attempt(s): {}", side.as_str(), client_order_id, attempt, error);
+            return BinanceResponse::OrderFailed { error };
+        }
+
+        warn!("{} order {} attempt {}/{} never reached the exchange: {}. Retrying in {:?}", side.as_str(), client_order_id, attempt, policy.max_attempts, error, delay);
+        thread::sleep(delay);
+
+        if let Err(reason) = check_retry_balance(account, market, limiter, quote_asset, symbol, side, quantity) {
+            RetryStats::count(&stats.aborted_balance);
+            warn!("Abandoning retry of order {}: {}", client_order_id, reason);
+            return BinanceResponse::OrderFailed { error: format!("{} ({})", error, reason) };
+        }
+
+        attempt += 1;
+        RetryStats::count(&stats.retries);
+    }
+}
+
+/// Looks up an order whose send ended in an ambiguous error until the exchange reports it, for
+/// at most `policy.max_attempts` lookups within MAX_RETRY_TIME. A "no such order" answer may only
+/// mean the order is still in flight, so it is asked again. The order is never re-sent.
+fn confirm_order(
+    account: &Account,
+    limiter: &RateLimiter,
+    policy: &RetryPolicy,
+    stats: &RetryStats,
+    symbol: &str,
+    quantity: f64,
+    client_order_id: &str,
+    error: String,
+    started: Instant,
+) -> BinanceResponse {
+    for lookup in 1..=policy.max_attempts {
+        let delay = policy.backoff(lookup);
+        if started.elapsed() + delay > retry::MAX_RETRY_TIME {
+            break;
+        }
+        thread::sleep(delay);
+        match limiter.call(rate_limiter::ORDER_STATUS_WEIGHT, || order_by_client_id(account, symbol, client_order_id)) {
+            Ok(order) => {
+                RetryStats::count(&stats.found_after_error);
+                info!("Order {} found after the error (status {})", client_order_id, order.status);
+                return order_lookup_success(&order, symbol, quantity);
+            }
+            Err(e) => {
+                let lookup_error = format!("{:?}", e);
+                if !retry::is_unknown_order(&lookup_error) {
+                    warn!("Lookup {} of order {} failed: {}", lookup, client_order_id, lookup_error);
+                }
+            }
+        }
+    }
+    RetryStats::count(&stats.exhausted);
+    error!("Order {} outcome still unknown; not re-sending it. Check the exchange", client_order_id);
+    BinanceResponse::OrderFailed { error: format!("{} (order state unknown)", error) }
+}
+
 /// The Binance Worker - runs in an isolated thread
 pub struct BinanceWorker {
     command_tx: mpsc::Sender<BinanceCommand>,
@@ -80,7 +380,9 @@ pub struct BinanceWorker {
 impl BinanceWorker {
     /// Creates a new Binance worker with the given API credentials.
     /// Spawns a dedicated thread that will handle all API calls.
-    pub fn new(api_key: String, secret_key: String) -> Self {
+    /// Every call first draws its request weight from the shared `limiter`.
+    /// Market orders are retried under `retry`; balance re-checks look at `quote_asset`.
+    pub fn new(api_key: String, secret_key: String, limiter: Arc<RateLimiter>, quote_asset: String, retry: RetryPolicy, retry_stats: Arc<RetryStats>) -> Self {
         let (command_tx, command_rx) = mpsc::channel::<BinanceCommand>();
         
         // Spawn the worker thread - completely isolated from tokio
@@ -91,6 +393,8 @@ impl BinanceWorker {
                 
                 // Create the Binance account client INSIDE this thread
                 let account = Account::new(Some(api_key), Some(secret_key));
+                let general: General = Binance::new(None, None);
+                let market: Market = Binance::new(None, None);
                 
                 // Process commands until shutdown
                 loop {
@@ -101,35 +405,81 @@ impl BinanceWorker {
                         }
                         Ok(BinanceCommand::MarketBuy { symbol, quantity, response_tx }) => {
                             info!("Worker: Executing MARKET BUY {} x {}", quantity, symbol);
-                            let response = match account.market_buy(&symbol, quantity) {
+                            let response = market_order_with_retry(&account, &market, &limiter, &retry, &retry_stats, &quote_asset, &symbol, Side::Buy, quantity);
+                            let _ = response_tx.send(response);
+                        }
+                        Ok(BinanceCommand::MarketSell { symbol, quantity, response_tx }) => {
+                            info!("Worker: Executing MARKET SELL {} x {}", quantity, symbol);
+                            let response = market_order_with_retry(&account, &market, &limiter, &retry, &retry_stats, &quote_asset, &symbol, Side::Sell, quantity);
+                            let _ = response_tx.send(response);
+                        }
+                        Ok(BinanceCommand::LimitBuy { symbol, quantity, price, time_in_force, response_tx }) => {
+                            info!("Worker: Placing LIMIT BUY {} x {} @ {} ({:?})", quantity, symbol, price, time_in_force);
+                            let result = limiter.call(rate_limiter::ORDER_WEIGHT, || match time_in_force {
+                                TimeInForce::GTC => account.limit_buy(&symbol, quantity, price),
+                                tif => account.custom_order(&symbol, quantity, price, None, OrderSide::Buy, OrderType::Limit, to_binance_tif(tif), None),
+                            });
+                            let response = match result {
                                 Ok(answer) => {
-                                    info!("Order {} placed successfully", answer.order_id);
-                                    BinanceResponse::OrderSuccess { 
-                                        order_id: answer.order_id, 
-                                        symbol: symbol.clone(),
-                                        qty: quantity,
-                                    }
+                                    info!("Limit order {} placed successfully", answer.order_id);
+                                    order_success(&answer, &symbol, quantity)
                                 }
                                 Err(e) => {
-                                    error!("Buy order failed: {:?}", e);
+                                    error!("Limit buy order failed: {:?}", e);
                                     BinanceResponse::OrderFailed { error: format!("{:?}", e) }
                                 }
                             };
                             let _ = response_tx.send(response);
                         }
-                        Ok(BinanceCommand::MarketSell { symbol, quantity, response_tx }) => {
-                            info!("Worker: Executing MARKET SELL {} x {}", quantity, symbol);
-                            let response = match account.market_sell(&symbol, quantity) {
+                        Ok(BinanceCommand::LimitSell { symbol, quantity, price, time_in_force, response_tx }) => {
+                            info!("Worker: Placing LIMIT SELL {} x {} @ {} ({:?})", quantity, symbol, price, time_in_force);
+                            let result = limiter.call(rate_limiter::ORDER_WEIGHT, || match time_in_force {
+                                TimeInForce::GTC => account.limit_sell(&symbol, quantity, price),
+                                tif => account.custom_order(&symbol, quantity, price, None, OrderSide::Sell, OrderType::Limit, to_binance_tif(tif), None),
+                            });
+                            let response = match result {
+                                Ok(answer) => {
+                                    info!("Limit order {} placed successfully", answer.order_id);
+                                    order_success(&answer, &symbol, quantity)
+                                }
+                                Err(e) => {
+                                    error!("Limit sell order failed: {:?}", e);
+                                    BinanceResponse::OrderFailed { error: format!("{:?}", e) }
+                                }
+                            };
+                            let _ = response_tx.send(response);
+                        }
+                        Ok(BinanceCommand::LimitMaker { symbol, side, quantity, price, response_tx }) => {
+                            info!("Worker: Placing LIMIT_MAKER {} {} x {} @ {}", side.as_str().to_uppercase(), quantity, symbol, price);
+                            let order_side = if side == Side::Buy { OrderSide::Buy } else { OrderSide::Sell };
+                            let result = limiter.call(rate_limiter::ORDER_WEIGHT, || {
+                                account.custom_order(&symbol, quantity, price, None, order_side, OrderType::LimitMaker, BinanceTimeInForce::GTC, None)
+                            });
+                            let response = match result {
+                                Ok(answer) => {
+                                    info!("Post-only order {} placed successfully", answer.order_id);
+                                    order_success(&answer, &symbol, quantity)
+                                }
+                                Err(e) => {
+                                    error!("Post-only order failed: {:?}", e);
+                                    BinanceResponse::OrderFailed { error: format!("{:?}", e) }
+                                }
+                            };
+                            let _ = response_tx.send(response);
+                        }
+                        Ok(BinanceCommand::StopLimit { symbol, side, quantity, stop_price, limit_price, response_tx }) => {
+                            info!("Worker: Placing STOP-LIMIT {} {} x {} stop {} limit {}", side.as_str().to_uppercase(), quantity, symbol, stop_price, limit_price);
+                            let result = limiter.call(rate_limiter::ORDER_WEIGHT, || match side {
+                                Side::Buy => account.stop_limit_buy_order(&symbol, quantity, limit_price, stop_price, BinanceTimeInForce::GTC),
+                                Side::Sell => account.stop_limit_sell_order(&symbol, quantity, limit_price, stop_price, BinanceTimeInForce::GTC),
+                            });
+                            let response = match result {
                                 Ok(answer) => {
-                                    info!("Order {} placed successfully", answer.order_id);
-                                    BinanceResponse::OrderSuccess { 
-                                        order_id: answer.order_id, 
-                                        symbol: symbol.clone(),
-                                        qty: quantity,
-                                    }
+                                    info!("Stop-limit order {} placed successfully", answer.order_id);
+                                    order_success(&answer, &symbol, quantity)
                                 }
                                 Err(e) => {
-                                    error!("Sell order failed: {:?}", e);
+                                    error!("Stop-limit order failed: {:?}", e);
                                     BinanceResponse::OrderFailed { error: format!("{:?}", e) }
                                 }
                             };
@@ -137,7 +487,7 @@ impl BinanceWorker {
                         }
                         Ok(BinanceCommand::CancelOrder { symbol, order_id, response_tx }) => {
                             info!("Worker: Cancelling order {} for {}", order_id, symbol);
-                            let response = match account.cancel_order(&symbol, order_id) {
+                            let response = match limiter.call(rate_limiter::CANCEL_WEIGHT, || account.cancel_order(&symbol, order_id)) {
                                 Ok(_) => {
                                     info!("Order cancelled successfully");
                                     BinanceResponse::Cancelled
@@ -149,8 +499,22 @@ impl BinanceWorker {
                             };
                             let _ = response_tx.send(response);
                         }
+                        Ok(BinanceCommand::CancelAllOrders { symbol, response_tx }) => {
+                            info!("Worker: Cancelling all open orders for {}", symbol);
+                            let response = match limiter.call(rate_limiter::CANCEL_WEIGHT, || account.cancel_all_open_orders(&symbol)) {
+                                Ok(cancelled) => {
+                                    info!("Cancelled {} open orders for {}", cancelled.len(), symbol);
+                                    BinanceResponse::Cancelled
+                                }
+                                Err(e) => {
+                                    error!("Cancel all failed: {:?}", e);
+                                    BinanceResponse::Failed { error: format!("{:?}", e) }
+                                }
+                            };
+                            let _ = response_tx.send(response);
+                        }
                         Ok(BinanceCommand::GetAccount { response_tx }) => {
-                            let response = match account.get_account() {
+                            let response = match limiter.call(rate_limiter::ACCOUNT_WEIGHT, || account.get_account()) {
                                 Ok(info) => {
                                     let balances: Vec<(String, f64)> = info.balances
                                         .iter()
@@ -174,9 +538,49 @@ impl BinanceWorker {
                             };
                             let _ = response_tx.send(response);
                         }
+                        Ok(BinanceCommand::GetSymbolFilters { symbol, response_tx }) => {
+                            let response = match limiter.call(rate_limiter::EXCHANGE_INFO_WEIGHT, || general.get_symbol_info(&symbol)) {
+                                Ok(info) => {
+                                    let filters = SymbolFilters::from_exchange(&info.filters);
+                                    info!("Worker: {} filters {:?}", symbol, filters);
+                                    BinanceResponse::SymbolFilters { filters }
+                                }
+                                Err(e) => {
+                                    warn!("Failed to fetch exchange info for {}: {:?}", symbol, e);
+                                    BinanceResponse::Failed { error: format!("{:?}", e) }
+                                }
+                            };
+                            let _ = response_tx.send(response);
+                        }
+                        Ok(BinanceCommand::GetPrice { symbol, response_tx }) => {
+                            let response = match limiter.call(rate_limiter::TICKER_PRICE_WEIGHT, || market.get_price(&symbol)) {
+                                Ok(ticker) => BinanceResponse::Price { price: ticker.price },
+                                Err(e) => {
+                                    warn!("Failed to fetch price for {}: {:?}", symbol, e);
+                                    BinanceResponse::Failed { error: format!("{:?}", e) }
+                                }
+                            };
+                            let _ = response_tx.send(response);
+                        }
+                        Ok(BinanceCommand::GetOrderStatus { symbol, order_id, response_tx }) => {
+                            let response = match limiter.call(rate_limiter::ORDER_STATUS_WEIGHT, || account.order_status(&symbol, order_id)) {
+                                Ok(order) => BinanceResponse::OrderStatus {
+                                    state: OrderState {
+                                        status: order.status,
+                                        executed_qty: order.executed_qty.parse::<f64>().unwrap_or(0.0),
+                                        quote_qty: order.cummulative_quote_qty.parse::<f64>().unwrap_or(0.0),
+                                    },
+                                },
+                                Err(e) => {
+                                    warn!("Failed to query order {} for {}: {:?}", order_id, symbol, e);
+                                    BinanceResponse::Failed { error: format!("{:?}", e) }
+                                }
+                            };
+                            let _ = response_tx.send(response);
+                        }
                         Ok(BinanceCommand::GetTradeHistory { symbol, limit, response_tx }) => {
                             info!("Worker: Fetching trade history for {}", symbol);
-                            let response = match account.trade_history(&symbol) {
+                            let response = match limiter.call(rate_limiter::TRADE_HISTORY_WEIGHT, || account.trade_history(&symbol)) {
                                 Ok(trades) => {
                                     let trade_infos: Vec<TradeInfo> = trades
                                         .iter()
@@ -185,8 +589,11 @@ impl BinanceWorker {
                                             id: t.id,
                                             price: t.price,
                                             qty: t.qty,
-                                            commission: t.commission.parse::<f64>().unwrap_or(0.0),
-                                            commission_asset: t.commission_asset.clone(),
+                                            commission: t.commission.parse::<f64>().unwrap_or_else(|_| {
+                                                warn!("Trade {}: unparsable commission '{}'", t.id, t.commission);
+                                                0.0
+                                            }),
+                                            commission_asset: t.commission_asset.trim().to_uppercase(),
                                             is_buyer: t.is_buyer,
                                             time: t.time,
                                         })
@@ -220,7 +627,7 @@ impl BinanceWorker {
     }
     
     /// Places a market buy order asynchronously
-    pub async fn market_buy(&self, symbol: String, quantity: f64) -> Result<u64, String> {
+    pub async fn market_buy(&self, symbol: String, quantity: f64) -> Result<OrderFill, String> {
         let (tx, rx) = tokio::sync::oneshot::channel();
         
         self.command_tx
@@ -228,7 +635,7 @@ impl BinanceWorker {
             .map_err(|e| format!("Failed to send command: {}", e))?;
         
         match rx.await {
-            Ok(BinanceResponse::OrderSuccess { order_id, .. }) => Ok(order_id),
+            Ok(BinanceResponse::OrderSuccess { order_id, executed_qty, avg_fill_price, .. }) => Ok(OrderFill { order_id, executed_qty, avg_fill_price }),
             Ok(BinanceResponse::OrderFailed { error }) => Err(error),
             Ok(_) => Err("Unexpected response".to_string()),
             Err(e) => Err(format!("Response channel error: {}", e)),
@@ -236,13 +643,77 @@ impl BinanceWorker {
     }
     
     /// Places a market sell order asynchronously
-    pub async fn market_sell(&self, symbol: String, quantity: f64) -> Result<u64, String> {
+    pub async fn market_sell(&self, symbol: String, quantity: f64) -> Result<OrderFill, String> {
         let (tx, rx) = tokio::sync::oneshot::channel();
         
         self.command_tx
             .send(BinanceCommand::MarketSell { symbol, quantity, response_tx: tx })
             .map_err(|e| format!("Failed to send command: {}", e))?;
         
+        match rx.await {
+            Ok(BinanceResponse::OrderSuccess { order_id, executed_qty, avg_fill_price, .. }) => Ok(OrderFill { order_id, executed_qty, avg_fill_price }),
+            Ok(BinanceResponse::OrderFailed { error }) => Err(error),
+            Ok(_) => Err("Unexpected response".to_string()),
+            Err(e) => Err(format!("Response channel error: {}", e)),
+        }
+    }
+    
+    /// Places a limit buy order asynchronously, returning the order id
+    pub async fn limit_buy(&self, symbol: String, quantity: f64, price: f64, time_in_force: TimeInForce) -> Result<u64, String> {
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        
+        self.command_tx
+            .send(BinanceCommand::LimitBuy { symbol, quantity, price, time_in_force, response_tx: tx })
+            .map_err(|e| format!("Failed to send command: {}", e))?;
+        
+        match rx.await {
+            Ok(BinanceResponse::OrderSuccess { order_id, .. }) => Ok(order_id),
+            Ok(BinanceResponse::OrderFailed { error }) => Err(error),
+            Ok(_) => Err("Unexpected response".to_string()),
+            Err(e) => Err(format!("Response channel error: {}", e)),
+        }
+    }
+    
+    /// Places a limit sell order asynchronously, returning the order id
+    pub async fn limit_sell(&self, symbol: String, quantity: f64, price: f64, time_in_force: TimeInForce) -> Result<u64, String> {
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        
+        self.command_tx
+            .send(BinanceCommand::LimitSell { symbol, quantity, price, time_in_force, response_tx: tx })
+            .map_err(|e| format!("Failed to send command: {}", e))?;
+        
+        match rx.await {
+            Ok(BinanceResponse::OrderSuccess { order_id, .. }) => Ok(order_id),
+            Ok(BinanceResponse::OrderFailed { error }) => Err(error),
+            Ok(_) => Err("Unexpected response".to_string()),
+            Err(e) => Err(format!("Response channel error: {}", e)),
+        }
+    }
+    
+    /// Places a post-only (LIMIT_MAKER) order asynchronously, returning the order id
+    pub async fn limit_maker(&self, symbol: String, side: Side, quantity: f64, price: f64) -> Result<u64, String> {
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        
+        self.command_tx
+            .send(BinanceCommand::LimitMaker { symbol, side, quantity, price, response_tx: tx })
+            .map_err(|e| format!("Failed to send command: {}", e))?;
+        
+        match rx.await {
+            Ok(BinanceResponse::OrderSuccess { order_id, .. }) => Ok(order_id),
+            Ok(BinanceResponse::OrderFailed { error }) => Err(error),
+            Ok(_) => Err("Unexpected response".to_string()),
+            Err(e) => Err(format!("Response channel error: {}", e)),
+        }
+    }
+    
+    /// Places a stop-limit order asynchronously, returning the order id
+    pub async fn stop_limit(&self, symbol: String, side: Side, quantity: f64, stop_price: f64, limit_price: f64) -> Result<u64, String> {
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        
+        self.command_tx
+            .send(BinanceCommand::StopLimit { symbol, side, quantity, stop_price, limit_price, response_tx: tx })
+            .map_err(|e| format!("Failed to send command: {}", e))?;
+        
         match rx.await {
             Ok(BinanceResponse::OrderSuccess { order_id, .. }) => Ok(order_id),
             Ok(BinanceResponse::OrderFailed { error }) => Err(error),
@@ -267,6 +738,75 @@ impl BinanceWorker {
         }
     }
     
+    /// Cancels every open order on a symbol
+    pub async fn cancel_all_orders(&self, symbol: String) -> Result<(), String> {
+        let (tx, rx) = tokio::sync::oneshot::channel();
+
+        self.command_tx
+            .send(BinanceCommand::CancelAllOrders { symbol, response_tx: tx })
+            .map_err(|e| format!("Failed to send command: {}", e))?;
+
+        match rx.await {
+            Ok(BinanceResponse::Cancelled) => Ok(()),
+            Ok(BinanceResponse::Failed { error }) => Err(error),
+            Ok(_) => Err("Unexpected response".to_string()),
+            Err(e) => Err(format!("Response channel error: {}", e)),
+        }
+    }
+
+    /// Fetches the symbol's LOT_SIZE, PRICE_FILTER and notional rules
+    pub async fn get_symbol_filters(&self, symbol: String) -> Result<SymbolFilters, String> {
+        let (tx, rx) = tokio::sync::oneshot::channel();
+
+        self.command_tx
+            .send(BinanceCommand::GetSymbolFilters { symbol, response_tx: tx })
+            .map_err(|e| format!("Failed to send command: {}", e))?;
+
+        match rx.await {
+            Ok(BinanceResponse::SymbolFilters { filters }) => Ok(filters),
+            Ok(BinanceResponse::Failed { error }) => Err(error),
+            Ok(_) => Err("Unexpected response".to_string()),
+            Err(e) => Err(format!("Response channel error: {}", e)),
+        }
+    }
+
+    /// Latest traded price of a symbol from the public ticker
+    pub async fn get_price(&self, symbol: String) -> Result<f64, String> {
+        let (tx, rx) = tokio::sync::oneshot::channel();
+
+        self.command_tx
+            .send(BinanceCommand::GetPrice { symbol, response_tx: tx })
+            .map_err(|e| format!("Failed to send command: {}", e))?;
+
+        match rx.await {
+            Ok(BinanceResponse::Price { price }) => Ok(price),
+            Ok(BinanceResponse::Failed { error }) => Err(error),
+            Ok(_) => Err("Unexpected response".to_string()),
+            Err(e) => Err(format!("Response channel error: {}", e)),
+        }
+    }
+
+    /// Queries how far an order has filled
+    pub async fn order_status(&self, symbol: String, order_id: u64) -> Result<OrderState, String> {
+        let (tx, rx) = tokio::sync::oneshot::channel();
+
+        self.command_tx
+            .send(BinanceCommand::GetOrderStatus { symbol, order_id, response_tx: tx })
+            .map_err(|e| format!("Failed to send command: {}", e))?;
+
+        match rx.await {
+            Ok(BinanceResponse::OrderStatus { state }) => Ok(state),
+            Ok(BinanceResponse::Failed { error }) => Err(error),
+            Ok(_) => Err("Unexpected response".to_string()),
+            Err(e) => Err(format!("Response channel error: {}", e)),
+        }
+    }
+
+    /// Stops the worker thread once it has drained the commands already queued
+    pub fn shutdown(&self) {
+        let _ = self.command_tx.send(BinanceCommand::Shutdown);
+    }
+
     /// Gets account information asynchronously
     pub async fn get_account(&self) -> Result<Vec<(String, f64)>, String> {
         let (tx, rx) = tokio::sync::oneshot::channel();
@@ -300,6 +840,15 @@ impl BinanceWorker {
     }
 }
 
+/// Maps our time-in-force onto the binance-rs enum
+fn to_binance_tif(tif: TimeInForce) -> BinanceTimeInForce {
+    match tif {
+        TimeInForce::GTC => BinanceTimeInForce::GTC,
+        TimeInForce::IOC => BinanceTimeInForce::IOC,
+        TimeInForce::FOK => BinanceTimeInForce::FOK,
+    }
+}
+
 // Allow BinanceWorker to be shared across threads
 unsafe impl Send for BinanceWorker {}
 unsafe impl Sync for BinanceWorker {}
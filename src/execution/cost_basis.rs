@@ -0,0 +1,177 @@
+use log::warn;
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+
+/// Quantities below this are treated as fully consumed
+const DUST: f64 = 1e-12;
+
+/// Most recent closed lots kept in memory and served; the realized gain still covers all of them
+const MAX_CLOSED_LOTS: usize = 1000;
+
+/// A buy, or what remains of it, that has not been sold yet
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct OpenLot {
+    pub symbol: String,
+    pub acquired_ts: u64, // ms
+    pub quantity: f64,
+    /// Per unit, including the buy fee
+    pub unit_cost: f64,
+}
+
+/// The part of a lot disposed of by one sell
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ClosedLot {
+    pub symbol: String,
+    pub acquired_ts: u64, // ms
+    pub disposed_ts: u64, // ms
+    pub quantity: f64,
+    /// Purchase cost of the quantity, including its share of the buy fee
+    pub cost_basis: f64,
+    /// Sale value of the quantity, net of its share of the sell fee
+    pub proceeds: f64,
+    pub realized_gain: f64,
+}
+
+/// Remaining basis of one symbol across its open lots
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct OpenBasis {
+    pub symbol: String,
+    pub quantity: f64,
+    pub cost_basis: f64,
+    pub avg_unit_cost: f64,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct CostBasisReport {
+    /// The most recent closed lots, oldest first
+    pub closed_lots: Vec<ClosedLot>,
+    /// Lots closed since tracking started, including those no longer listed
+    pub closed_lot_count: u64,
+    pub open_lots: Vec<OpenLot>,
+    pub open_basis: Vec<OpenBasis>,
+    pub total_realized_gain: f64,
+    /// Quantity sold per symbol with no recorded buy to match (held before tracking started)
+    pub unmatched_sells: HashMap<String, f64>,
+}
+
+/// FIFO tax lots for long holdings: each buy opens a lot, each sell consumes the oldest lots first.
+/// Fees are part of the basis on buys and reduce proceeds on sells, split pro rata across lots.
+/// Only the last MAX_CLOSED_LOTS closed lots are kept; totals cover every lot.
+#[derive(Default)]
+pub struct CostBasisTracker {
+    open: HashMap<String, VecDeque<OpenLot>>,
+    closed: VecDeque<ClosedLot>,
+    closed_count: u64,
+    realized_gain: f64,
+    unmatched: HashMap<String, f64>,
+}
+
+impl CostBasisTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record_buy(&mut self, symbol: &str, ts: u64, price: f64, quantity: f64, fee: f64) {
+        if quantity <= DUST {
+            return;
+        }
+        self.open.entry(symbol.to_string()).or_default().push_back(OpenLot {
+            symbol: symbol.to_string(),
+            acquired_ts: ts,
+            quantity,
+            unit_cost: (price * quantity + fee) / quantity,
+        });
+    }
+
+    /// Consumes lots FIFO. Returns the realized gain of the matched quantity.
+    pub fn record_sell(&mut self, symbol: &str, ts: u64, price: f64, quantity: f64, fee: f64) -> f64 {
+        if quantity <= DUST {
+            return 0.0;
+        }
+        let net_unit_proceeds = (price * quantity - fee) / quantity;
+        let lots = self.open.entry(symbol.to_string()).or_default();
+        let mut remaining = quantity;
+        let mut realized = 0.0;
+        while remaining > DUST {
+            let Some(lot) = lots.front_mut() else { break };
+            let matched = remaining.min(lot.quantity);
+            let cost_basis = matched * lot.unit_cost;
+            let proceeds = matched * net_unit_proceeds;
+            self.closed.push_back(ClosedLot {
+                symbol: symbol.to_string(),
+                acquired_ts: lot.acquired_ts,
+                disposed_ts: ts,
+                quantity: matched,
+                cost_basis,
+                proceeds,
+                realized_gain: proceeds - cost_basis,
+            });
+            if self.closed.len() > MAX_CLOSED_LOTS {
+                self.closed.pop_front();
+            }
+            self.closed_count += 1;
+            self.realized_gain += proceeds - cost_basis;
+            realized += proceeds - cost_basis;
+            lot.quantity -= matched;
+            remaining -= matched;
+            if lot.quantity <= DUST {
+                lots.pop_front();
+            }
+        }
+        if lots.is_empty() {
+            self.open.remove(symbol);
+        }
+        if remaining > DUST {
+            warn!("Cost basis: sold {} {} with no open lot to match", remaining, symbol);
+            *self.unmatched.entry(symbol.to_string()).or_insert(0.0) += remaining;
+        }
+        realized
+    }
+
+    /// Applies a fill from either side
+    pub fn record_fill(&mut self, symbol: &str, is_buy: bool, ts: u64, price: f64, quantity: f64, fee: f64) {
+        if is_buy {
+            self.record_buy(symbol, ts, price, quantity, fee);
+        } else {
+            self.record_sell(symbol, ts, price, quantity, fee);
+        }
+    }
+
+    /// Average cost per unit of the open lots of `symbol`, fees included
+    pub fn avg_unit_cost(&self, symbol: &str) -> Option<f64> {
+        let lots = self.open.get(symbol)?;
+        let quantity: f64 = lots.iter().map(|l| l.quantity).sum();
+        (quantity > DUST).then(|| lots.iter().map(|l| l.quantity * l.unit_cost).sum::<f64>() / quantity)
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    pub fn report(&self) -> CostBasisReport {
+        let mut open_lots: Vec<OpenLot> = self.open.values().flatten().cloned().collect();
+        open_lots.sort_by(|a, b| a.acquired_ts.cmp(&b.acquired_ts).then_with(|| a.symbol.cmp(&b.symbol)));
+
+        let mut open_basis: Vec<OpenBasis> = self.open.iter().map(|(symbol, lots)| {
+            let quantity: f64 = lots.iter().map(|l| l.quantity).sum();
+            let cost_basis: f64 = lots.iter().map(|l| l.quantity * l.unit_cost).sum();
+            OpenBasis {
+                symbol: symbol.clone(),
+                quantity,
+                cost_basis,
+                avg_unit_cost: if quantity > 0.0 { cost_basis / quantity } else { 0.0 },
+            }
+        }).collect();
+        open_basis.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+
+        CostBasisReport {
+            closed_lots: self.closed.iter().cloned().collect(),
+            closed_lot_count: self.closed_count,
+            open_lots,
+            open_basis,
+            total_realized_gain: self.realized_gain,
+            unmatched_sells: self.unmatched.clone(),
+        }
+    }
+}
@@ -3,11 +3,21 @@
 //! This module handles Futures trading API calls in a dedicated thread,
 //! similar to the Spot trading BinanceWorker.
 
-use binance::futures::account::FuturesAccount;
+use binance::futures::account::{
+    CustomOrderRequest, FuturesAccount, OrderSide as BinanceOrderSide, OrderType, TimeInForce,
+};
+use binance::futures::market::FuturesMarket;
 use binance::api::Binance;
-use std::sync::mpsc;
+use std::collections::HashMap;
+use std::sync::{mpsc, Arc};
 use std::thread::{self, JoinHandle};
+use std::time::Duration;
 use log::{info, error, warn};
+use super::rate_limiter::{self, RateLimiter};
+use super::binance_worker::OrderFill;
+
+/// How often the OCO monitor checks whether one leg has filled
+const OCO_POLL_INTERVAL: Duration = Duration::from_secs(1);
 
 /// Margin type for positions
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -43,17 +53,37 @@ impl std::fmt::Display for PositionSide {
     }
 }
 
+/// Order side for protective (exit) orders
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OrderSide {
+    Buy,
+    Sell,
+}
+
+impl OrderSide {
+    fn to_binance(self) -> BinanceOrderSide {
+        match self {
+            OrderSide::Buy => BinanceOrderSide::Buy,
+            OrderSide::Sell => BinanceOrderSide::Sell,
+        }
+    }
+}
+
 /// Commands for Futures worker
 #[derive(Debug)]
 pub enum FuturesCommand {
     MarketBuy { 
         symbol: String, 
         quantity: f64,
+        /// Only reduce an open position; never opens or flips one
+        reduce_only: bool,
         response_tx: tokio::sync::oneshot::Sender<FuturesResponse>,
     },
     MarketSell { 
         symbol: String, 
         quantity: f64,
+        /// Only reduce an open position; never opens or flips one
+        reduce_only: bool,
         response_tx: tokio::sync::oneshot::Sender<FuturesResponse>,
     },
     SetLeverage {
@@ -66,12 +96,32 @@ pub enum FuturesCommand {
         margin_type: MarginType,
         response_tx: tokio::sync::oneshot::Sender<FuturesResponse>,
     },
+    /// Take-profit limit + stop-market pair; when one fills the other is cancelled
+    OcoBracket {
+        symbol: String,
+        quantity: f64,
+        tp_price: f64,
+        sl_price: f64,
+        side: OrderSide,
+        response_tx: tokio::sync::oneshot::Sender<FuturesResponse>,
+    },
+    CancelAllOrders {
+        symbol: String,
+        response_tx: tokio::sync::oneshot::Sender<FuturesResponse>,
+    },
     GetAccount {
         response_tx: tokio::sync::oneshot::Sender<FuturesResponse>,
     },
     GetPositions {
         response_tx: tokio::sync::oneshot::Sender<FuturesResponse>,
     },
+    GetFundingRate {
+        symbol: String,
+        response_tx: tokio::sync::oneshot::Sender<FuturesResponse>,
+    },
+    GetMarkPrices {
+        response_tx: tokio::sync::oneshot::Sender<FuturesResponse>,
+    },
     Shutdown,
 }
 
@@ -100,12 +150,17 @@ pub struct FuturesBalance {
 /// Responses from Futures worker
 #[derive(Debug, Clone)]
 pub enum FuturesResponse {
-    OrderSuccess { order_id: u64, symbol: String, qty: f64 },
+    /// `executed_qty`/`avg_fill_price` are 0 when the exchange only acknowledged the order
+    OrderSuccess { order_id: u64, symbol: String, qty: f64, executed_qty: f64, avg_fill_price: f64 },
     OrderFailed { error: String },
     LeverageSet { symbol: String, leverage: u8 },
     MarginTypeSet { symbol: String, margin_type: String },
+    OcoPlaced { tp_order_id: u64, sl_order_id: u64 },
+    Cancelled,
     AccountInfo { balances: Vec<FuturesBalance> },
     Positions { positions: Vec<FuturesPosition> },
+    FundingRate { funding_rate: f64 },
+    MarkPrices { prices: HashMap<String, f64> },
     Failed { error: String },
 }
 
@@ -116,8 +171,9 @@ pub struct FuturesWorker {
 }
 
 impl FuturesWorker {
-    /// Creates a new Futures worker with the given API credentials
-    pub fn new(api_key: String, secret_key: String) -> Self {
+    /// Creates a new Futures worker with the given API credentials.
+    /// Shares the request-weight budget with the spot worker through `limiter`.
+    pub fn new(api_key: String, secret_key: String, limiter: Arc<RateLimiter>) -> Self {
         let (command_tx, command_rx) = mpsc::channel::<FuturesCommand>();
         
         let handle = thread::Builder::new()
@@ -126,7 +182,8 @@ impl FuturesWorker {
                 info!("Binance Futures Worker thread started");
                 
                 // Create the Futures account client INSIDE this thread
-                let account = FuturesAccount::new(Some(api_key), Some(secret_key));
+                let account = FuturesAccount::new(Some(api_key.clone()), Some(secret_key.clone()));
+                let market = FuturesMarket::new(None, None);
                 
                 loop {
                     match command_rx.recv() {
@@ -134,15 +191,17 @@ impl FuturesWorker {
                             info!("Futures Worker shutting down");
                             break;
                         }
-                        Ok(FuturesCommand::MarketBuy { symbol, quantity, response_tx }) => {
-                            info!("Futures Worker: MARKET BUY {} x {}", quantity, symbol);
-                            let response = match account.market_buy(&symbol, quantity) {
+                        Ok(FuturesCommand::MarketBuy { symbol, quantity, reduce_only, response_tx }) => {
+                            info!("Futures Worker: MARKET BUY {} x {}{}", quantity, symbol, if reduce_only { " (reduce-only)" } else { "" });
+                            let response = match limiter.call(rate_limiter::ORDER_WEIGHT, || market_order(&account, &symbol, quantity, OrderSide::Buy, reduce_only)) {
                                 Ok(answer) => {
                                     info!("Futures Order {} placed", answer.order_id);
                                     FuturesResponse::OrderSuccess { 
                                         order_id: answer.order_id, 
                                         symbol: symbol.clone(),
                                         qty: quantity,
+                                        executed_qty: answer.executed_qty,
+                                        avg_fill_price: answer.avg_price,
                                     }
                                 }
                                 Err(e) => {
@@ -152,15 +211,17 @@ impl FuturesWorker {
                             };
                             let _ = response_tx.send(response);
                         }
-                        Ok(FuturesCommand::MarketSell { symbol, quantity, response_tx }) => {
-                            info!("Futures Worker: MARKET SELL {} x {}", quantity, symbol);
-                            let response = match account.market_sell(&symbol, quantity) {
+                        Ok(FuturesCommand::MarketSell { symbol, quantity, reduce_only, response_tx }) => {
+                            info!("Futures Worker: MARKET SELL {} x {}{}", quantity, symbol, if reduce_only { " (reduce-only)" } else { "" });
+                            let response = match limiter.call(rate_limiter::ORDER_WEIGHT, || market_order(&account, &symbol, quantity, OrderSide::Sell, reduce_only)) {
                                 Ok(answer) => {
                                     info!("Futures Order {} placed", answer.order_id);
                                     FuturesResponse::OrderSuccess { 
                                         order_id: answer.order_id, 
                                         symbol: symbol.clone(),
                                         qty: quantity,
+                                        executed_qty: answer.executed_qty,
+                                        avg_fill_price: answer.avg_price,
                                     }
                                 }
                                 Err(e) => {
@@ -172,7 +233,7 @@ impl FuturesWorker {
                         }
                         Ok(FuturesCommand::SetLeverage { symbol, leverage, response_tx }) => {
                             info!("Futures Worker: Setting leverage {}x for {}", leverage, symbol);
-                            let response = match account.change_initial_leverage(&symbol, leverage) {
+                            let response = match limiter.call(rate_limiter::FUTURES_CONFIG_WEIGHT, || account.change_initial_leverage(&symbol, leverage)) {
                                 Ok(_) => {
                                     info!("Leverage set to {}x", leverage);
                                     FuturesResponse::LeverageSet { symbol, leverage }
@@ -188,7 +249,7 @@ impl FuturesWorker {
                             info!("Futures Worker: Setting margin type {:?} for {}", margin_type, symbol);
                             let isolated = margin_type == MarginType::Isolated;
                             let margin_str = margin_type.to_string();
-                            let response = match account.change_margin_type(&symbol, isolated) {
+                            let response = match limiter.call(rate_limiter::FUTURES_CONFIG_WEIGHT, || account.change_margin_type(&symbol, isolated)) {
                                 Ok(_) => {
                                     info!("Margin type set to {:?}", margin_type);
                                     FuturesResponse::MarginTypeSet { symbol, margin_type: margin_str }
@@ -201,8 +262,23 @@ impl FuturesWorker {
                             };
                             let _ = response_tx.send(response);
                         }
+                        Ok(FuturesCommand::OcoBracket { symbol, quantity, tp_price, sl_price, side, response_tx }) => {
+                            info!("Futures Worker: OCO {:?} {} x {} TP={} SL={}", side, quantity, symbol, tp_price, sl_price);
+                            let response = match place_oco_legs(&account, &limiter, &symbol, quantity, tp_price, sl_price, side) {
+                                Ok((tp_order_id, sl_order_id)) => {
+                                    info!("OCO placed: TP order {}, SL order {}", tp_order_id, sl_order_id);
+                                    spawn_oco_monitor(api_key.clone(), secret_key.clone(), limiter.clone(), symbol, tp_order_id, sl_order_id);
+                                    FuturesResponse::OcoPlaced { tp_order_id, sl_order_id }
+                                }
+                                Err(e) => {
+                                    error!("OCO placement failed: {}", e);
+                                    FuturesResponse::OrderFailed { error: e }
+                                }
+                            };
+                            let _ = response_tx.send(response);
+                        }
                         Ok(FuturesCommand::GetAccount { response_tx }) => {
-                            let response = match account.account_balance() {
+                            let response = match limiter.call(rate_limiter::FUTURES_ACCOUNT_WEIGHT, || account.account_balance()) {
                                 Ok(balances) => {
                                     let filtered: Vec<FuturesBalance> = balances
                                         .iter()
@@ -225,7 +301,7 @@ impl FuturesWorker {
                             let _ = response_tx.send(response);
                         }
                         Ok(FuturesCommand::GetPositions { response_tx }) => {
-                            let response = match account.account_information() {
+                            let response = match limiter.call(rate_limiter::FUTURES_ACCOUNT_WEIGHT, || account.account_information()) {
                                 Ok(info) => {
                                     let positions: Vec<FuturesPosition> = info.positions
                                         .iter()
@@ -249,6 +325,37 @@ impl FuturesWorker {
                             };
                             let _ = response_tx.send(response);
                         }
+                        Ok(FuturesCommand::CancelAllOrders { symbol, response_tx }) => {
+                            info!("Futures Worker: Cancelling all open orders for {}", symbol);
+                            let response = match limiter.call(rate_limiter::CANCEL_WEIGHT, || account.cancel_all_open_orders(&symbol)) {
+                                Ok(_) => FuturesResponse::Cancelled,
+                                Err(e) => {
+                                    error!("Futures cancel all failed: {:?}", e);
+                                    FuturesResponse::Failed { error: format!("{:?}", e) }
+                                }
+                            };
+                            let _ = response_tx.send(response);
+                        }
+                        Ok(FuturesCommand::GetFundingRate { symbol, response_tx }) => {
+                            let response = match limiter.call(rate_limiter::FUNDING_RATE_WEIGHT, || fetch_funding_rate(&market, &symbol)) {
+                                Ok(funding_rate) => FuturesResponse::FundingRate { funding_rate },
+                                Err(error) => {
+                                    warn!("Failed to get funding rate for {}: {:?}", symbol, error);
+                                    FuturesResponse::Failed { error: format!("{:?}", error) }
+                                }
+                            };
+                            let _ = response_tx.send(response);
+                        }
+                        Ok(FuturesCommand::GetMarkPrices { response_tx }) => {
+                            let response = match limiter.call(rate_limiter::MARK_PRICE_WEIGHT, || fetch_mark_prices(&market)) {
+                                Ok(prices) => FuturesResponse::MarkPrices { prices },
+                                Err(error) => {
+                                    warn!("Failed to get mark prices: {:?}", error);
+                                    FuturesResponse::Failed { error: format!("{:?}", error) }
+                                }
+                            };
+                            let _ = response_tx.send(response);
+                        }
                         Err(_) => {
                             info!("Futures Worker: command channel closed");
                             break;
@@ -266,32 +373,32 @@ impl FuturesWorker {
         }
     }
     
-    /// Places a market buy order asynchronously
-    pub async fn market_buy(&self, symbol: String, quantity: f64) -> Result<u64, String> {
+    /// Places a market buy order asynchronously. `reduce_only` buys can only close a short.
+    pub async fn market_buy(&self, symbol: String, quantity: f64, reduce_only: bool) -> Result<OrderFill, String> {
         let (tx, rx) = tokio::sync::oneshot::channel();
         
         self.command_tx
-            .send(FuturesCommand::MarketBuy { symbol, quantity, response_tx: tx })
+            .send(FuturesCommand::MarketBuy { symbol, quantity, reduce_only, response_tx: tx })
             .map_err(|e| format!("Failed to send command: {}", e))?;
         
         match rx.await {
-            Ok(FuturesResponse::OrderSuccess { order_id, .. }) => Ok(order_id),
+            Ok(FuturesResponse::OrderSuccess { order_id, executed_qty, avg_fill_price, .. }) => Ok(OrderFill { order_id, executed_qty, avg_fill_price }),
             Ok(FuturesResponse::OrderFailed { error }) => Err(error),
             Ok(_) => Err("Unexpected response".to_string()),
             Err(e) => Err(format!("Response channel error: {}", e)),
         }
     }
     
-    /// Places a market sell order asynchronously
-    pub async fn market_sell(&self, symbol: String, quantity: f64) -> Result<u64, String> {
+    /// Places a market sell order asynchronously. `reduce_only` sells can only close a long.
+    pub async fn market_sell(&self, symbol: String, quantity: f64, reduce_only: bool) -> Result<OrderFill, String> {
         let (tx, rx) = tokio::sync::oneshot::channel();
         
         self.command_tx
-            .send(FuturesCommand::MarketSell { symbol, quantity, response_tx: tx })
+            .send(FuturesCommand::MarketSell { symbol, quantity, reduce_only, response_tx: tx })
             .map_err(|e| format!("Failed to send command: {}", e))?;
         
         match rx.await {
-            Ok(FuturesResponse::OrderSuccess { order_id, .. }) => Ok(order_id),
+            Ok(FuturesResponse::OrderSuccess { order_id, executed_qty, avg_fill_price, .. }) => Ok(OrderFill { order_id, executed_qty, avg_fill_price }),
             Ok(FuturesResponse::OrderFailed { error }) => Err(error),
             Ok(_) => Err("Unexpected response".to_string()),
             Err(e) => Err(format!("Response channel error: {}", e)),
@@ -330,6 +437,76 @@ impl FuturesWorker {
         }
     }
     
+    /// Cancels every open order on a symbol, including OCO legs
+    pub async fn cancel_all_orders(&self, symbol: String) -> Result<(), String> {
+        let (tx, rx) = tokio::sync::oneshot::channel();
+
+        self.command_tx
+            .send(FuturesCommand::CancelAllOrders { symbol, response_tx: tx })
+            .map_err(|e| format!("Failed to send command: {}", e))?;
+
+        match rx.await {
+            Ok(FuturesResponse::Cancelled) => Ok(()),
+            Ok(FuturesResponse::Failed { error }) => Err(error),
+            Ok(_) => Err("Unexpected response".to_string()),
+            Err(e) => Err(format!("Response channel error: {}", e)),
+        }
+    }
+
+    /// Stops the worker thread once it has drained the commands already queued
+    pub fn shutdown(&self) {
+        let _ = self.command_tx.send(FuturesCommand::Shutdown);
+    }
+
+    /// Latest settled funding rate for a perpetual symbol
+    pub async fn get_funding_rate(&self, symbol: String) -> Result<f64, String> {
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        
+        self.command_tx
+            .send(FuturesCommand::GetFundingRate { symbol, response_tx: tx })
+            .map_err(|e| format!("Failed to send command: {}", e))?;
+        
+        match rx.await {
+            Ok(FuturesResponse::FundingRate { funding_rate }) => Ok(funding_rate),
+            Ok(FuturesResponse::Failed { error }) => Err(error),
+            Ok(_) => Err("Unexpected response".to_string()),
+            Err(e) => Err(format!("Response channel error: {}", e)),
+        }
+    }
+    
+    /// Current mark price of a perpetual symbol
+    pub async fn get_mark_prices(&self) -> Result<HashMap<String, f64>, String> {
+        let (tx, rx) = tokio::sync::oneshot::channel();
+
+        self.command_tx
+            .send(FuturesCommand::GetMarkPrices { response_tx: tx })
+            .map_err(|e| format!("Failed to send command: {}", e))?;
+
+        match rx.await {
+            Ok(FuturesResponse::MarkPrices { prices }) => Ok(prices),
+            Ok(FuturesResponse::Failed { error }) => Err(error),
+            Ok(_) => Err("Unexpected response".to_string()),
+            Err(e) => Err(format!("Response channel error: {}", e)),
+        }
+    }
+
+    /// Places a take-profit limit and a stop-market as one OCO unit.
+    /// `side` is the side of the exit orders (Sell to protect a long). Returns (tp_order_id, sl_order_id).
+    pub async fn place_oco(&self, symbol: String, quantity: f64, tp_price: f64, sl_price: f64, side: OrderSide) -> Result<(u64, u64), String> {
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        
+        self.command_tx
+            .send(FuturesCommand::OcoBracket { symbol, quantity, tp_price, sl_price, side, response_tx: tx })
+            .map_err(|e| format!("Failed to send command: {}", e))?;
+        
+        match rx.await {
+            Ok(FuturesResponse::OcoPlaced { tp_order_id, sl_order_id }) => Ok((tp_order_id, sl_order_id)),
+            Ok(FuturesResponse::OrderFailed { error }) => Err(error),
+            Ok(_) => Err("Unexpected response".to_string()),
+            Err(e) => Err(format!("Response channel error: {}", e)),
+        }
+    }
+    
     /// Gets Futures account balances
     pub async fn get_account(&self) -> Result<Vec<FuturesBalance>, String> {
         let (tx, rx) = tokio::sync::oneshot::channel();
@@ -363,6 +540,134 @@ impl FuturesWorker {
     }
 }
 
+/// Most recent funding rate from the public funding-rate endpoint (no credentials needed)
+pub fn fetch_funding_rate(market: &FuturesMarket, symbol: &str) -> binance::errors::Result<f64> {
+    let rates = market.get_funding_rate(symbol, None, None, 1u16)?;
+    rates
+        .last()
+        .map(|r| r.funding_rate)
+        .ok_or_else(|| binance::errors::ErrorKind::Msg(format!("No funding rate returned for {}", symbol)).into())
+}
+
+/// Current mark price of every symbol from the public premium index (no credentials needed).
+/// One request covers all symbols, so callers fetch the list once rather than once per symbol.
+pub fn fetch_mark_prices(market: &FuturesMarket) -> binance::errors::Result<HashMap<String, f64>> {
+    let binance::futures::model::MarkPrices::AllMarkPrices(marks) = market.get_mark_prices()?;
+    Ok(marks.into_iter().map(|m| (m.symbol, m.mark_price)).collect())
+}
+
+/// Market order; reduce-only orders go through the custom order endpoint with reduceOnly set
+fn market_order(account: &FuturesAccount, symbol: &str, quantity: f64, side: OrderSide, reduce_only: bool) -> binance::errors::Result<binance::futures::model::Transaction> {
+    if !reduce_only {
+        return match side {
+            OrderSide::Buy => account.market_buy(symbol, quantity),
+            OrderSide::Sell => account.market_sell(symbol, quantity),
+        };
+    }
+    account.custom_order(CustomOrderRequest {
+        symbol: symbol.to_string(),
+        side: side.to_binance(),
+        position_side: None,
+        order_type: OrderType::Market,
+        time_in_force: None,
+        qty: Some(quantity),
+        reduce_only: Some(true),
+        price: None,
+        stop_price: None,
+        close_position: None,
+        activation_price: None,
+        callback_rate: None,
+        working_type: None,
+        price_protect: None,
+    })
+}
+
+/// Places both OCO legs as reduce-only orders. If the stop leg fails, the take-profit is rolled back.
+fn place_oco_legs(account: &FuturesAccount, limiter: &RateLimiter, symbol: &str, quantity: f64, tp_price: f64, sl_price: f64, side: OrderSide) -> Result<(u64, u64), String> {
+    let take_profit = limiter.call(rate_limiter::ORDER_WEIGHT, || account.custom_order(CustomOrderRequest {
+        symbol: symbol.to_string(),
+        side: side.to_binance(),
+        position_side: None,
+        order_type: OrderType::Limit,
+        time_in_force: Some(TimeInForce::GTC),
+        qty: Some(quantity),
+        reduce_only: Some(true),
+        price: Some(tp_price),
+        stop_price: None,
+        close_position: None,
+        activation_price: None,
+        callback_rate: None,
+        working_type: None,
+        price_protect: None,
+    })).map_err(|e| format!("Take-profit leg failed: {:?}", e))?;
+
+    let stop_loss = limiter.call(rate_limiter::ORDER_WEIGHT, || account.custom_order(CustomOrderRequest {
+        symbol: symbol.to_string(),
+        side: side.to_binance(),
+        position_side: None,
+        order_type: OrderType::StopMarket,
+        time_in_force: None,
+        qty: Some(quantity),
+        reduce_only: Some(true),
+        price: None,
+        stop_price: Some(sl_price),
+        close_position: None,
+        activation_price: None,
+        callback_rate: None,
+        working_type: None,
+        price_protect: None,
+    }));
+
+    match stop_loss {
+        Ok(sl) => Ok((take_profit.order_id, sl.order_id)),
+        Err(e) => {
+            if let Err(cancel_err) = limiter.call(rate_limiter::CANCEL_WEIGHT, || account.cancel_order(symbol, take_profit.order_id)) {
+                error!("Failed to roll back take-profit {}: {:?}", take_profit.order_id, cancel_err);
+            }
+            Err(format!("Stop-loss leg failed: {:?}", e))
+        }
+    }
+}
+
+/// Polls open orders on a separate thread (so the worker stays responsive) and
+/// cancels the remaining leg as soon as the other one is no longer open.
+fn spawn_oco_monitor(api_key: String, secret_key: String, limiter: Arc<RateLimiter>, symbol: String, tp_order_id: u64, sl_order_id: u64) {
+    let spawned = thread::Builder::new()
+        .name(format!("oco-monitor-{}", tp_order_id))
+        .spawn(move || {
+            let account = FuturesAccount::new(Some(api_key), Some(secret_key));
+            loop {
+                thread::sleep(OCO_POLL_INTERVAL);
+                let open_orders = match limiter.call(rate_limiter::OPEN_ORDERS_WEIGHT, || account.get_all_open_orders(&symbol)) {
+                    Ok(orders) => orders,
+                    Err(e) => {
+                        warn!("OCO monitor: failed to fetch open orders for {}: {:?}", symbol, e);
+                        continue;
+                    }
+                };
+                let tp_open = open_orders.iter().any(|o| o.order_id == tp_order_id);
+                let sl_open = open_orders.iter().any(|o| o.order_id == sl_order_id);
+
+                let remaining = match (tp_open, sl_open) {
+                    (true, true) => continue,
+                    (false, true) => Some(sl_order_id),
+                    (true, false) => Some(tp_order_id),
+                    (false, false) => None,
+                };
+                if let Some(order_id) = remaining {
+                    info!("OCO monitor: one leg closed, cancelling order {} for {}", order_id, symbol);
+                    if let Err(e) = limiter.call(rate_limiter::CANCEL_WEIGHT, || account.cancel_order(&symbol, order_id)) {
+                        error!("OCO monitor: cancel of {} failed: {:?}", order_id, e);
+                    }
+                }
+                break;
+            }
+        });
+    if let Err(e) = spawned {
+        error!("Failed to spawn OCO monitor thread: {}", e);
+    }
+}
+
 // Allow FuturesWorker to be shared across threads
 unsafe impl Send for FuturesWorker {}
 unsafe impl Sync for FuturesWorker {}
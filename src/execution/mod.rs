@@ -6,28 +6,53 @@
 //! 3. Live Futures Mode: Uses FuturesWorker for Futures API calls
 
 mod binance_worker;
+mod cost_basis;
 mod futures_worker;
+mod netting;
+mod rate_limiter;
+mod reconcile;
+mod retry;
 
-use binance_worker::BinanceWorker;
+use binance_worker::{BinanceWorker, OrderFill};
+pub use binance_worker::SymbolFilters;
+use futures_worker::FuturesWorker;
+use netting::SignalNetter;
 // Re-exports for other modules
+pub use cost_basis::{CostBasisReport, CostBasisTracker};
+pub use futures_worker::MarginType;
+pub use netting::{NettingConfig, NettingStatus};
+pub use reconcile::{BalanceReconciler, ReconciliationReport};
+pub use rate_limiter::{is_rate_limited, RateLimiter};
+pub use retry::RetryStatus;
+use retry::{RetryPolicy, RetryStats};
 
+use schemars::JsonSchema;
 use serde::{Serialize, Deserialize};
-use crate::strategy::Signal;
+use crate::strategy::{Signal, OrderType, Side, TimeInForce};
+use crate::market_data::downloader::MarketType;
 use log::{info, warn, error};
 use async_trait::async_trait;
+use std::collections::{HashMap, VecDeque};
 use std::env;
 use std::sync::Arc;
-#[derive(Serialize, Clone, Debug, Deserialize, Default)]
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+#[derive(Serialize, Clone, Debug, Deserialize, Default, JsonSchema)]
 pub struct TradeStats {
     pub total_trades: u64,
     pub buy_trades: u64,
     pub sell_trades: u64,
     pub total_volume: f64,
+    /// Commission valued in `commission_asset`, which is the quote asset. Assets listed in
+    /// `unpriced_commission_assets` are left out of it.
     pub total_commission: f64,
     pub commission_asset: String,
+    /// Raw commission paid per asset (quote, base or BNB)
+    pub commission_by_asset: HashMap<String, f64>,
+    /// Commission assets that could not be priced in the quote asset
+    pub unpriced_commission_assets: Vec<String>,
 }
 
-#[derive(Serialize, Clone, Debug, Deserialize)]
+#[derive(Serialize, Clone, Debug, Deserialize, JsonSchema)]
 pub struct PositionInfo {
     pub symbol: String,
     pub amount: f64,
@@ -37,264 +62,1162 @@ pub struct PositionInfo {
     pub side: String,
 }
 
+/// Base asset of a symbol quoted in `quote_asset` (e.g. "ETHUSDT", "USDT" -> "ETH")
+pub fn base_asset<'a>(symbol: &'a str, quote_asset: &str) -> &'a str {
+    symbol.strip_suffix(quote_asset).unwrap_or(symbol)
+}
+
+/// Asset every traded pair is quoted in and balances are valued in: QUOTE_ASSET (default USDT)
+pub fn quote_asset_from_env() -> String {
+    std::env::var("QUOTE_ASSET")
+        .ok()
+        .map(|v| v.trim().to_uppercase())
+        .filter(|v| !v.is_empty())
+        .unwrap_or_else(|| "USDT".to_string())
+}
+
+/// Whether trade stats value commission paid in other assets in the quote asset:
+/// COMMISSION_CONVERSION (default true). When false only quote-asset commission is totalled.
+pub fn commission_conversion_from_env() -> bool {
+    std::env::var("COMMISSION_CONVERSION").map(|v| !v.eq_ignore_ascii_case("false")).unwrap_or(true)
+}
+
+/// Maker/taker fee rates applied to simulated fills
+#[derive(Serialize, Clone, Copy, Debug, Deserialize)]
+pub struct FeeSchedule {
+    pub maker: f64,
+    pub taker: f64,
+}
+
+impl Default for FeeSchedule {
+    fn default() -> Self {
+        // Binance spot base tier (0.1% / 0.1%)
+        Self { maker: 0.001, taker: 0.001 }
+    }
+}
+
+impl FeeSchedule {
+    /// Reads FEE_MAKER / FEE_TAKER from the environment, falling back to the base tier
+    pub fn from_env() -> Self {
+        let default = Self::default();
+        let read = |key: &str, fallback: f64| {
+            env::var(key).ok().and_then(|v| v.parse::<f64>().ok()).unwrap_or(fallback)
+        };
+        Self {
+            maker: read("FEE_MAKER", default.maker),
+            taker: read("FEE_TAKER", default.taker),
+        }
+    }
+}
+
+/// Number of recent market trades averaged by `SlippageModel::VolumeProportional`
+const SLIPPAGE_VOLUME_WINDOW: usize = 100;
+
+/// How often live resting orders are polled for fills
+const LIVE_ORDER_POLL_MS: u64 = 5_000;
+
+/// Quote asset a simulated account starts with unless overridden with `with_starting_balance`
+pub const DEFAULT_SIM_BALANCE_USDT: f64 = 10000.0;
+
+/// How simulated market orders move away from the requested price
+#[derive(Serialize, Clone, Copy, Debug, Deserialize, Default, PartialEq)]
+pub enum SlippageModel {
+    #[default]
+    None,
+    /// Constant slippage in basis points
+    Fixed(f64),
+    /// Basis points scaled by order size relative to the rolling average trade size
+    VolumeProportional(f64),
+}
+
+impl SlippageModel {
+    /// Reads SLIPPAGE_MODEL (none/fixed/volume) and SLIPPAGE_BPS (default 5) from the environment
+    pub fn from_env() -> Self {
+        let bps = env::var("SLIPPAGE_BPS").ok().and_then(|v| v.parse::<f64>().ok()).unwrap_or(5.0);
+        match env::var("SLIPPAGE_MODEL").unwrap_or_default().to_lowercase().as_str() {
+            "fixed" => SlippageModel::Fixed(bps),
+            "volume" => SlippageModel::VolumeProportional(bps),
+            _ => SlippageModel::None,
+        }
+    }
+
+    /// Slippage as a fraction of price for an order of `quantity`
+    fn rate(&self, quantity: f64, avg_trade_qty: Option<f64>) -> f64 {
+        match *self {
+            SlippageModel::None => 0.0,
+            SlippageModel::Fixed(bps) => bps / 10_000.0,
+            SlippageModel::VolumeProportional(bps) => {
+                let scale = match avg_trade_qty {
+                    Some(avg) if avg > 0.0 => quantity / avg,
+                    _ => 1.0,
+                };
+                bps * scale / 10_000.0
+            }
+        }
+    }
+}
+
+/// A simulated limit order fill
+#[derive(Serialize, Clone, Debug, Deserialize)]
+pub struct Fill {
+    pub order_id: u64,
+    pub symbol: String,
+    pub side: String,
+    pub price: f64,
+    pub quantity: f64,
+    pub fee: f64,
+    pub realized_pnl: f64,
+}
+
+/// A stop-limit order whose stop price was touched, turning it into a resting limit order
+#[derive(Serialize, Clone, Debug, Deserialize)]
+pub struct StopTrigger {
+    pub order_id: u64,
+    pub symbol: String,
+    pub side: String,
+    pub stop_price: f64,
+    pub limit_price: f64,
+    pub trigger_price: f64, // Trade price that touched the stop
+}
+
+/// What a trade print did to resting simulated orders
+#[derive(Clone, Debug, Default)]
+pub struct OrderUpdates {
+    pub fills: Vec<Fill>,
+    pub triggered: Vec<StopTrigger>,
+}
+
+/// A resting limit order tracked in simulation mode
+#[derive(Serialize, Clone, Debug, Deserialize)]
+pub struct SimOrder {
+    pub order_id: u64,
+    pub symbol: String,
+    pub side: String,
+    pub price: f64,
+    pub quantity: f64,
+    pub time_in_force: TimeInForce,
+    pub stop_price: Option<f64>, // Stop-limit orders not yet triggered; they cannot fill
+}
+
+impl SimOrder {
+    /// Returns true if a trade at `price` would fill this order
+    fn crosses(&self, price: f64) -> bool {
+        if self.side == "Buy" { price <= self.price } else { price >= self.price }
+    }
+
+    /// Returns true if a trade at `price` touches this order's stop (buy stops sit above the market)
+    fn stop_hit(&self, price: f64) -> bool {
+        match self.stop_price {
+            Some(stop) if self.side == "Buy" => price >= stop,
+            Some(stop) => price <= stop,
+            None => false,
+        }
+    }
+}
+
+/// Quantity and average price a market order actually filled at
+#[derive(Serialize, Clone, Copy, Debug, Deserialize)]
+pub struct MarketFill {
+    pub quantity: f64,
+    pub price: f64,
+}
+
+/// A limit order the (simulated) exchange accepted
+#[derive(Serialize, Clone, Copy, Debug, Deserialize)]
+pub struct PlacedOrder {
+    pub order_id: u64,
+    /// Quantity after truncation to the symbol's step size
+    pub quantity: f64,
+}
+
+/// A live resting order whose fills are polled from the exchange
+#[derive(Debug, Clone)]
+struct LiveOrder {
+    order_id: u64,
+    symbol: String,
+    side: Side,
+    executed_qty: f64, // Filled so far, as last reported
+    quote_qty: f64,
+}
+
+/// A limit or stop-limit order ready to rest on the book
+#[derive(Debug, Clone)]
+struct RestingOrder {
+    symbol: String,
+    side: Side,
+    price: f64,
+    quantity: f64,
+    time_in_force: TimeInForce,
+    stop_price: Option<f64>, // Set for stop-limit orders
+    post_only: bool,         // Sent as LIMIT_MAKER, which the exchange rejects rather than let it take
+}
+
+/// A signal resolved into exactly one exchange order: SellAll has been sized into a market
+/// sell and Buy/Sell asking for a limit have become resting orders
+#[derive(Debug, Clone)]
+enum Order {
+    /// `reduce_only` is for futures: the order may only shrink the open position, never flip it
+    Market { symbol: String, side: Side, price: Option<f64>, quantity: f64, reduce_only: bool },
+    Resting(RestingOrder),
+    Cancel { symbol: String, order_id: u64 },
+}
+
+impl Order {
+    fn symbol(&self) -> &str {
+        match self {
+            Order::Market { symbol, .. } | Order::Cancel { symbol, .. } => symbol,
+            Order::Resting(order) => &order.symbol,
+        }
+    }
+}
+
+/// Why a signal never reached the (simulated) exchange
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NotSent {
+    /// Live trading is not armed, so this was a dry run
+    Disarmed,
+    MissingPrice,
+    /// SellAll with nothing to sell
+    NothingHeld,
+    InsufficientBalance,
+    BelowMinNotional,
+    /// Dropped against recent opposing orders (NETTING)
+    Netted,
+    /// Not supported on this market
+    Unsupported,
+}
+
+/// Outcome of executing one signal
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Execution {
+    pub pnl: f64,
+    /// Set for market orders the exchange (or simulation) reported a fill for; None for limits and cancels
+    pub fill: Option<MarketFill>,
+    /// Set for limit and stop-limit orders the (simulated) exchange accepted
+    pub placed: Option<PlacedOrder>,
+    /// Set for cancels: whether a resting order was actually removed
+    pub cancelled: Option<bool>,
+    /// Set when nothing was sent; such an execution must not be journaled or counted as a trade
+    pub not_sent: Option<NotSent>,
+}
+
+impl Execution {
+    pub fn not_sent(reason: NotSent) -> Self {
+        Self { not_sent: Some(reason), ..Self::default() }
+    }
+
+    /// Quantity and price actually filled, if anything did
+    pub fn filled(&self) -> Option<MarketFill> {
+        self.fill.filter(|f| f.quantity > 0.0 && self.not_sent.is_none())
+    }
+}
+
+/// Live orders skipped or failed since startup, shared with AppState for /api/status
+#[derive(Debug, Default)]
+pub struct OrderRejections {
+    pub balance: AtomicU64,
+    pub notional: AtomicU64,
+    pub api_error: AtomicU64,
+}
+
+impl OrderRejections {
+    fn count(counter: &AtomicU64) {
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
 #[async_trait]
 pub trait Executor: Send + Sync {
-    async fn execute(&self, signal: Signal) -> Result<f64, Box<dyn std::error::Error + Send + Sync>>;
+    /// Executes a signal, reporting its realized PnL and how much of a market order filled at what price
+    async fn execute_with_fill(&self, signal: Signal) -> Result<Execution, Box<dyn std::error::Error + Send + Sync>> {
+        self.execute_owned(signal, None).await
+    }
+    /// `execute_with_fill` for a strategy sharing the account: `owned` is how much of the symbol
+    /// it bought and still holds, and a SellAll never sells more than that
+    async fn execute_owned(&self, signal: Signal, owned: Option<f64>) -> Result<Execution, Box<dyn std::error::Error + Send + Sync>>;
+    /// Shared flag that must be set before live orders reach the exchange
+    fn armed_flag(&self) -> Arc<AtomicBool>;
+    /// Binance request weight consumed in the current minute (0 when not live)
+    fn api_weight_used(&self) -> u32;
+    /// Shared counters of live orders rejected for balance, min notional or API errors
+    fn order_rejections(&self) -> Arc<OrderRejections>;
+    /// Market entries netted or dropped against recent opposing orders since startup
+    fn netting_status(&self) -> NettingStatus;
+    /// Live market order retries since startup
+    fn order_retries(&self) -> RetryStatus;
+    /// Market this executor trades (spot balances vs futures positions)
+    fn market_type(&self) -> MarketType;
+    /// Asset pairs are quoted in and the account is valued in
+    fn quote_asset(&self) -> &str;
     async fn get_balances(&self) -> Result<Vec<(String, f64)>, Box<dyn std::error::Error + Send + Sync>>;
     async fn get_positions(&self) -> Result<Vec<PositionInfo>, Box<dyn std::error::Error + Send + Sync>>;
     async fn get_trade_stats(&self, symbol: &str) -> Result<TradeStats, Box<dyn std::error::Error + Send + Sync>>;
+    /// Feeds the latest trade price so simulated stops can trigger and resting orders fill.
+    /// `ts` (event time, ms) is the clock netting windows are measured against.
+    async fn on_trade_price(&self, symbol: &str, price: f64, quantity: f64, ts: u64) -> Result<OrderUpdates, Box<dyn std::error::Error + Send + Sync>>;
+    /// Total cost of simulated slippage so far, in quote currency
+    async fn slippage_cost(&self) -> f64;
+    /// Latest perpetual funding rate for a symbol
+    async fn get_funding_rate(&self, symbol: &str) -> Result<f64, Box<dyn std::error::Error + Send + Sync>>;
+    /// Exchange reference prices of `symbols` for valuation: futures mark prices, fetched in one
+    /// request, or spot ticker prices. Symbols the exchange has no price for are left out.
+    async fn get_mark_prices(&self, symbols: &[String]) -> Result<HashMap<String, f64>, Box<dyn std::error::Error + Send + Sync>>;
+    /// Settles one funding interval against simulated futures positions. Returns the PnL (negative when paid).
+    async fn apply_funding(&self, symbol: &str, funding_rate: f64, mark_price: f64) -> Result<f64, Box<dyn std::error::Error + Send + Sync>>;
+    /// Restores the simulated account to its starting balances. Errors in live mode.
+    async fn reset_simulation(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+    /// Sets the futures leverage for a symbol
+    async fn set_leverage(&self, symbol: &str, leverage: u8) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+    /// Sets the futures margin type (cross/isolated) for a symbol
+    async fn set_margin_type(&self, symbol: &str, margin_type: MarginType) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+    /// Cancels one resting order. Ok(false) when no such simulated order is open.
+    async fn cancel_order(&self, symbol: &str, order_id: u64) -> Result<bool, Box<dyn std::error::Error + Send + Sync>>;
+    /// Cancels every resting order on a symbol
+    async fn cancel_all_orders(&self, symbol: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>>;
+    /// Stops the worker threads. No further live calls succeed afterwards.
+    fn shutdown(&self);
+    /// Quantity/price precision and minimum notional for a symbol (defaults in simulation)
+    async fn symbol_filters(&self, symbol: &str) -> SymbolFilters;
 }
 
 pub struct ExecutionManager {
     worker: Option<Arc<BinanceWorker>>,
+    futures_worker: Option<Arc<FuturesWorker>>,
+    market_type: MarketType,
+    quote_asset: String,
+    commission_conversion: bool,
     is_simulation: bool,
+    fees: FeeSchedule,
+    slippage: SlippageModel,
+    armed: Arc<AtomicBool>,
+    rate_limiter: Arc<RateLimiter>,
+    symbol_filters: Arc<tokio::sync::Mutex<HashMap<String, SymbolFilters>>>,
+    rejections: Arc<OrderRejections>,
+    retry_policy: RetryPolicy,
+    retry_stats: Arc<RetryStats>, // Shared with the spot worker thread
+    netter: parking_lot::Mutex<SignalNetter>,
+    event_ms: AtomicU64, // Latest trade event time seen by on_trade_price
+    // Live resting orders, polled for fills in the background and drained by on_trade_price
+    live_open_orders: Arc<tokio::sync::Mutex<Vec<LiveOrder>>>,
+    live_fills: Arc<tokio::sync::Mutex<Vec<Fill>>>,
+    live_poll_ms: AtomicU64,
     // In-memory tracking for simulation mode
+    sim_starting_usdt: f64,
+    sim_credit_usdt: f64, // Quote a simulated buy may borrow beyond the balance (margin backtests)
     sim_balances: std::sync::Arc<tokio::sync::Mutex<std::collections::HashMap<String, f64>>>,
     sim_positions: std::sync::Arc<tokio::sync::Mutex<Vec<PositionInfo>>>,
+    sim_open_orders: std::sync::Arc<tokio::sync::Mutex<Vec<SimOrder>>>,
+    sim_fills: std::sync::Arc<tokio::sync::Mutex<Vec<Fill>>>, // IOC/FOK fills made at placement, reported by on_trade_price
+    last_prices: std::sync::Arc<tokio::sync::Mutex<HashMap<String, f64>>>, // Last trade per symbol, fed by on_trade_price
+    sim_trade_sizes: std::sync::Arc<tokio::sync::Mutex<HashMap<String, VecDeque<f64>>>>,
+    sim_slippage_cost: std::sync::Arc<tokio::sync::Mutex<f64>>,
+    next_order_id: AtomicU64,
 }
 
 impl ExecutionManager {
-    pub fn new(is_simulation: bool) -> Self {
+    pub fn new(is_simulation: bool, fees: FeeSchedule, market_type: MarketType) -> Self {
         let api_key = env::var("BINANCE_API_KEY").ok();
         let secret_key = env::var("BINANCE_API_SECRET").ok();
+        let rate_limiter = Arc::new(RateLimiter::new(rate_limiter::WEIGHT_PER_MINUTE));
+        let quote_asset = quote_asset_from_env();
+        let retry_policy = RetryPolicy::from_env();
+        let retry_stats = Arc::new(RetryStats::default());
 
-        let (worker, use_simulation) = if is_simulation {
+        let (worker, futures_worker, use_simulation) = if is_simulation {
             info!("Running in SIMULATION mode (backtest)");
-            (None, true)
+            (None, None, true)
         } else if let (Some(key), Some(secret)) = (api_key, secret_key) {
-            info!("Binance API credentials found. Initializing LIVE trading mode.");
+            info!("Binance API credentials found. Initializing LIVE {} trading mode.", market_type.as_str());
             warn!("REAL MONEY will be used for trades!");
             
-            // Create the isolated worker thread
-            let worker = BinanceWorker::new(key, secret);
-            (Some(Arc::new(worker)), false)
+            // Create the isolated worker thread for the selected market
+            match market_type {
+                MarketType::Spot => {
+                    info!("Market orders retried up to {} attempts, backoff from {:?}", retry_policy.max_attempts, retry_policy.backoff);
+                    let worker = BinanceWorker::new(key, secret, rate_limiter.clone(), quote_asset.clone(), retry_policy, retry_stats.clone());
+                    (Some(Arc::new(worker)), None, false)
+                }
+                MarketType::Futures => (None, Some(Arc::new(FuturesWorker::new(key, secret, rate_limiter.clone()))), false),
+            }
         } else {
             warn!("Binance API credentials NOT found. Using PAPER TRADING mode.");
-            (None, true)
+            (None, None, true)
         };
 
-        let mut balances = std::collections::HashMap::new();
-        balances.insert("USDT".to_string(), 10000.0);
-        balances.insert("BTC".to_string(), 0.0);
-
         Self {
             worker,
+            futures_worker,
+            market_type,
             is_simulation: use_simulation,
-            sim_balances: std::sync::Arc::new(tokio::sync::Mutex::new(balances)),
+            fees,
+            sim_starting_usdt: DEFAULT_SIM_BALANCE_USDT,
+            sim_credit_usdt: 0.0,
+            sim_balances: std::sync::Arc::new(tokio::sync::Mutex::new(Self::initial_sim_balances(&quote_asset, DEFAULT_SIM_BALANCE_USDT))),
+            quote_asset,
+            commission_conversion: commission_conversion_from_env(),
             sim_positions: std::sync::Arc::new(tokio::sync::Mutex::new(Vec::new())),
+            sim_open_orders: std::sync::Arc::new(tokio::sync::Mutex::new(Vec::new())),
+            sim_fills: std::sync::Arc::new(tokio::sync::Mutex::new(Vec::new())),
+            last_prices: std::sync::Arc::new(tokio::sync::Mutex::new(HashMap::new())),
+            sim_trade_sizes: std::sync::Arc::new(tokio::sync::Mutex::new(HashMap::new())),
+            sim_slippage_cost: std::sync::Arc::new(tokio::sync::Mutex::new(0.0)),
+            next_order_id: AtomicU64::new(1),
+            live_open_orders: Arc::new(tokio::sync::Mutex::new(Vec::new())),
+            live_fills: Arc::new(tokio::sync::Mutex::new(Vec::new())),
+            live_poll_ms: AtomicU64::new(0),
+            slippage: SlippageModel::None,
+            armed: Arc::new(AtomicBool::new(false)),
+            rate_limiter,
+            symbol_filters: Arc::new(tokio::sync::Mutex::new(HashMap::new())),
+            rejections: Arc::new(OrderRejections::default()),
+            retry_policy,
+            retry_stats,
+            netter: parking_lot::Mutex::new(SignalNetter::new(NettingConfig::from_env())),
+            event_ms: AtomicU64::new(0),
         }
     }
-    
-    /// Truncates quantity to Binance's required precision (5 decimal places for BTC)
-    fn truncate_qty(qty: f64, decimals: u32) -> f64 {
-        let factor = 10_f64.powi(decimals as i32);
-        (qty * factor).floor() / factor
+
+    /// Event time of the latest trade in simulation, so replays net like live; wall time
+    /// when live or before any trade has been seen
+    fn netting_clock_ms(&self) -> u64 {
+        match self.event_ms.load(Ordering::Relaxed) {
+            event_ms if self.is_simulation && event_ms > 0 => event_ms,
+            _ => std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_millis() as u64,
+        }
     }
-}
 
-#[async_trait]
-impl Executor for ExecutionManager {
-    async fn execute(&self, signal: Signal) -> Result<f64, Box<dyn std::error::Error + Send + Sync>> {
+    /// Sends one resolved order
+    async fn execute_resolved(&self, order: Order) -> Result<Execution, Box<dyn std::error::Error + Send + Sync>> {
         if !self.is_simulation {
             info!("Executor.execute called.");
+            if !self.armed.load(Ordering::SeqCst) {
+                info!("DRY RUN (not armed): would execute {:?}", order);
+                return Ok(Execution::not_sent(NotSent::Disarmed));
+            }
         }
         
         // === SIMULATION MODE ===
         if self.is_simulation {
-            let mut realized_pnl = 0.0;
-            match signal {
-                Signal::Buy { symbol, price, quantity } => {
+            let mut execution = Execution::default();
+            match order {
+                Order::Market { symbol, side: Side::Buy, price, quantity, .. } => {
                     // info!("SIMULATION: Buying {} x {} @ {:?}", quantity, symbol, price);
-                    let mut bal = self.sim_balances.lock().await;
-                    let mut pos = self.sim_positions.lock().await;
-                    
-                    let usdt = bal.get_mut("USDT").unwrap();
                     let est_price = price.unwrap_or(0.0);
                     if est_price == 0.0 {
                         warn!("SIMULATION: Buy signal received with 0 or missing price. Skipping.");
-                        return Ok(0.0);
-                    }
-                    let fee = quantity * est_price * 0.001;
-                    let cost = quantity * est_price + fee;
-                    
-                    if *usdt >= cost {
-                        *usdt -= cost;
-                        *bal.entry("BTC".to_string()).or_insert(0.0) += quantity;
-                        
-                        if let Some(p) = pos.iter_mut().find(|p| p.symbol == symbol) {
-                            let total_cost = p.amount * p.entry_price + cost;
-                            p.amount += quantity;
-                            p.entry_price = total_cost / p.amount;
-                        } else {
-                            pos.push(PositionInfo {
-                                symbol: symbol.clone(),
-                                amount: quantity,
-                                entry_price: cost / quantity, // Entry price inclusive of fee
-                                unrealized_pnl: 0.0,
-                                market_type: "Spot".to_string(),
-                                side: "Long".to_string(),
-                            });
-                        }
+                        return Ok(Execution::not_sent(NotSent::MissingPrice));
                     }
+                    let fill_price = self.slipped_price(&symbol, est_price, quantity, true).await;
+                    let mut bal = self.sim_balances.lock().await;
+                    let mut pos = self.sim_positions.lock().await;
+                    // An unaffordable buy fills nothing
+                    let filled = self.sim_buy(&mut bal, &mut pos, &symbol, fill_price, quantity, self.fees.taker).map_or(0.0, |_| quantity);
+                    execution.fill = Some(MarketFill { quantity: filled, price: fill_price });
                 }
-                Signal::Sell { symbol, price, quantity } => {
+                Order::Market { symbol, side: Side::Sell, price, quantity, .. } => {
                     // info!("SIMULATION: Selling {} x {} @ {:?}", quantity, symbol, price);
+                    let est_price = price.unwrap_or(0.0);
+                    if est_price == 0.0 {
+                        warn!("SIMULATION: Sell signal received with 0 or missing price. Skipping.");
+                        return Ok(Execution::not_sent(NotSent::MissingPrice));
+                    }
+                    let fill_price = self.slipped_price(&symbol, est_price, quantity, false).await;
                     let mut bal = self.sim_balances.lock().await;
                     let mut pos = self.sim_positions.lock().await;
-                    
-                    let btc = bal.get_mut("BTC").unwrap();
-                    if *btc >= quantity {
-                        *btc -= quantity;
-                        let est_price = price.unwrap_or(0.0);
-                        if est_price == 0.0 {
-                            warn!("SIMULATION: Sell signal received with 0 or missing price. Skipping.");
-                            return Ok(0.0);
-                        }
-                        let revenue = quantity * est_price;
-                        let fee = revenue * 0.001;
-                        *bal.get_mut("USDT").unwrap() += revenue - fee;
-                        
-                        if let Some(idx) = pos.iter().position(|p| p.symbol == symbol) {
-                            let buy_price = pos[idx].entry_price;
-                            // Realized PnL = (Revenue - Fee) - (Buy Cost)
-                            realized_pnl = (revenue - fee) - (buy_price * quantity);
-                            
-                            pos[idx].amount -= quantity;
-                            if pos[idx].amount <= 0.000001 {
-                                pos.remove(idx);
-                            }
-                        }
-                    }
+                    let sold = Self::sim_sell(&mut bal, &mut pos, &self.quote_asset, &symbol, fill_price, quantity, self.fees.taker);
+                    execution.pnl = sold.map_or(0.0, |(pnl, _)| pnl);
+                    execution.fill = Some(MarketFill { quantity: sold.map_or(0.0, |_| quantity), price: fill_price });
+                }
+                Order::Resting(resting) => {
+                    execution.placed = Some(self.place_resting(resting).await?);
                 }
-                Signal::Cancel { .. } => {}
+                Order::Cancel { symbol, order_id } => {
+                    execution.cancelled = Some(self.cancel_order(&symbol, order_id).await?);
+                }
+            }
+            return Ok(execution);
+        }
+
+        // === LIVE FUTURES MODE ===
+        if let Some(futures_worker) = &self.futures_worker {
+            let result = Self::execute_futures(futures_worker, order).await;
+            if result.is_err() {
+                OrderRejections::count(&self.rejections.api_error);
             }
-            return Ok(realized_pnl);
+            return result;
         }
 
         // === LIVE MODE (Using Worker Thread) ===
+        let mut execution = Execution::default();
         if let Some(worker) = &self.worker {
             // First, fetch current balances to check if we can afford the trade
             let balances = match worker.get_account().await {
                 Ok(b) => b,
                 Err(e) => {
                     error!("Failed to fetch balances: {}", e);
+                    OrderRejections::count(&self.rejections.api_error);
                     return Err(Box::new(std::io::Error::new(std::io::ErrorKind::Other, e)));
                 }
             };
             
-            let usdt_balance = balances.iter().find(|(a, _)| a == "USDT").map(|(_, v)| *v).unwrap_or(0.0);
-            let btc_balance = balances.iter().find(|(a, _)| a == "BTC").map(|(_, v)| *v).unwrap_or(0.0);
+            let base = base_asset(order.symbol(), &self.quote_asset).to_string();
+            let quote_balance = balances.iter().find(|(a, _)| *a == self.quote_asset).map(|(_, v)| *v).unwrap_or(0.0);
+            let base_balance = balances.iter().find(|(a, _)| *a == base).map(|(_, v)| *v).unwrap_or(0.0);
             
-            info!("Current balances: USDT={:.2}, BTC={:.6}", usdt_balance, btc_balance);
+            info!("Current balances: {}={:.2}, {}={:.6}", self.quote_asset, quote_balance, base, base_balance);
+            let filters = self.filters_for(order.symbol()).await;
+            let min_notional = filters.min_notional;
             
-            match signal {
-                Signal::Buy { symbol, price, quantity } => {
-                    // Check if we have enough USDT (estimate with current price)
+            match order {
+                Order::Market { symbol, side: Side::Buy, price, quantity, .. } => {
+                    // Check if we have enough of the quote asset (estimate with current price)
                     let est_price = price.unwrap_or(90000.0);
-                    let required_usdt = quantity * est_price * 1.001; // 0.1% buffer for fees
+                    let required_quote = quantity * est_price * 1.001; // 0.1% buffer for fees
                     
-                    if usdt_balance < required_usdt {
+                    if quote_balance < required_quote {
                         // Calculate max affordable quantity
-                        let max_qty = Self::truncate_qty((usdt_balance * 0.995) / est_price, 5);
+                        let max_qty = Self::truncate_qty((quote_balance * 0.995) / est_price, filters.qty_precision);
                         let order_value = max_qty * est_price;
                         
-                        // Check minimum notional value ($5 for BTCUSDT)
-                        if order_value < 5.0 {
-                            warn!("Order value (${:.2}) below minimum notional ($5). Skipping buy.", order_value);
-                            return Ok(0.0);
+                        if order_value < min_notional {
+                            warn!("Order value ({:.2} {}) below minimum notional ({}). Skipping buy.", order_value, self.quote_asset, min_notional);
+                            OrderRejections::count(&self.rejections.notional);
+                            return Ok(Execution::not_sent(NotSent::BelowMinNotional));
                         }
-                        if max_qty < 0.00001 {
-                            warn!("Insufficient USDT balance ({:.2}). Skipping buy.", usdt_balance);
-                            return Ok(0.0);
+                        if max_qty <= 0.0 {
+                            warn!("Insufficient {} balance ({:.2}). Skipping buy.", self.quote_asset, quote_balance);
+                            OrderRejections::count(&self.rejections.balance);
+                            return Ok(Execution::not_sent(NotSent::InsufficientBalance));
                         }
-                        info!("Adjusting quantity from {} to {:.5} based on available balance", quantity, max_qty);
-                        info!("LIVE: Sending MARKET BUY {:.5} x {} to worker", max_qty, symbol);
+                        info!("Adjusting quantity from {} to {} based on available balance", quantity, max_qty);
+                        info!("LIVE: Sending MARKET BUY {} x {} to worker", max_qty, symbol);
                         match worker.market_buy(symbol, max_qty).await {
-                            Ok(order_id) => info!("Order {} executed successfully!", order_id),
+                            Ok(fill) => execution.fill = Some(Self::live_fill(&fill, max_qty)),
                             Err(e) => {
                                 error!("Order failed: {}", e);
+                                OrderRejections::count(&self.rejections.api_error);
                                 return Err(Box::new(std::io::Error::new(std::io::ErrorKind::Other, e)));
                             }
                         }
                     } else {
-                        let qty = Self::truncate_qty(quantity, 5);
+                        let qty = Self::truncate_qty(quantity, filters.qty_precision);
                         let order_value = qty * est_price;
                         
-                        // Check minimum notional value ($5 for BTCUSDT)
-                        if order_value < 5.0 {
-                            warn!("Order value (${:.2}) below minimum notional ($5). Skipping buy.", order_value);
-                            return Ok(0.0);
+                        if order_value < min_notional {
+                            warn!("Order value ({:.2} {}) below minimum notional ({}). Skipping buy.", order_value, self.quote_asset, min_notional);
+                            OrderRejections::count(&self.rejections.notional);
+                            return Ok(Execution::not_sent(NotSent::BelowMinNotional));
                         }
                         
-                        info!("LIVE: Sending MARKET BUY {:.5} x {} to worker", qty, symbol);
+                        info!("LIVE: Sending MARKET BUY {} x {} to worker", qty, symbol);
                         match worker.market_buy(symbol, qty).await {
-                            Ok(order_id) => info!("Order {} executed successfully!", order_id),
+                            Ok(fill) => execution.fill = Some(Self::live_fill(&fill, qty)),
                             Err(e) => {
                                 error!("Order failed: {}", e);
+                                OrderRejections::count(&self.rejections.api_error);
                                 return Err(Box::new(std::io::Error::new(std::io::ErrorKind::Other, e)));
                             }
                         }
                     }
                 }
-                Signal::Sell { symbol, price, quantity } => {
+                Order::Market { symbol, side: Side::Sell, price, quantity, .. } => {
                     let est_price = price.unwrap_or(90000.0);
                     
-                    // Check if we have enough BTC
-                    if btc_balance < quantity {
-                        if btc_balance < 0.00001 {
-                            warn!("Insufficient BTC balance ({:.6}). Skipping sell.", btc_balance);
-                            return Ok(0.0);
+                    // Check if we have enough of the base asset
+                    if base_balance < quantity {
+                        let sell_qty = Self::truncate_qty(base_balance, filters.qty_precision);
+                        if sell_qty <= 0.0 {
+                            warn!("Insufficient {} balance ({:.6}). Skipping sell.", base, base_balance);
+                            OrderRejections::count(&self.rejections.balance);
+                            return Ok(Execution::not_sent(NotSent::InsufficientBalance));
                         }
-                        let sell_qty = Self::truncate_qty(btc_balance, 5);
                         let order_value = sell_qty * est_price;
                         
-                        // Check minimum notional value ($5 for BTCUSDT)
-                        if order_value < 5.0 {
-                            warn!("Order value (${:.2}) below minimum notional ($5). Skipping sell.", order_value);
-                            return Ok(0.0);
+                        if order_value < min_notional {
+                            warn!("Order value ({:.2} {}) below minimum notional ({}). Skipping sell.", order_value, self.quote_asset, min_notional);
+                            OrderRejections::count(&self.rejections.notional);
+                            return Ok(Execution::not_sent(NotSent::BelowMinNotional));
                         }
                         
-                        info!("Adjusting sell quantity from {} to {:.5} based on available balance", quantity, btc_balance);
-                        info!("LIVE: Sending MARKET SELL {:.5} x {} to worker", sell_qty, symbol);
+                        info!("Adjusting sell quantity from {} to {} based on available balance", quantity, sell_qty);
+                        info!("LIVE: Sending MARKET SELL {} x {} to worker", sell_qty, symbol);
                         match worker.market_sell(symbol, sell_qty).await {
-                            Ok(order_id) => info!("Order {} executed successfully!", order_id),
+                            Ok(fill) => execution.fill = Some(Self::live_fill(&fill, sell_qty)),
                             Err(e) => {
                                 error!("Order failed: {}", e);
+                                OrderRejections::count(&self.rejections.api_error);
                                 return Err(Box::new(std::io::Error::new(std::io::ErrorKind::Other, e)));
                             }
                         }
                     } else {
-                        let sell_qty = Self::truncate_qty(quantity, 5);
+                        let sell_qty = Self::truncate_qty(quantity, filters.qty_precision);
                         let order_value = sell_qty * est_price;
                         
-                        // Check minimum notional value ($5 for BTCUSDT)
-                        if order_value < 5.0 {
-                            warn!("Order value (${:.2}) below minimum notional ($5). Skipping sell.", order_value);
-                            return Ok(0.0);
+                        if order_value < min_notional {
+                            warn!("Order value ({:.2} {}) below minimum notional ({}). Skipping sell.", order_value, self.quote_asset, min_notional);
+                            OrderRejections::count(&self.rejections.notional);
+                            return Ok(Execution::not_sent(NotSent::BelowMinNotional));
                         }
                         
-                        info!("LIVE: Sending MARKET SELL {:.5} x {} to worker", sell_qty, symbol);
+                        info!("LIVE: Sending MARKET SELL {} x {} to worker", sell_qty, symbol);
                         match worker.market_sell(symbol, sell_qty).await {
-                            Ok(order_id) => info!("Order {} executed successfully!", order_id),
+                            Ok(fill) => execution.fill = Some(Self::live_fill(&fill, sell_qty)),
                             Err(e) => {
                                 error!("Order failed: {}", e);
+                                OrderRejections::count(&self.rejections.api_error);
                                 return Err(Box::new(std::io::Error::new(std::io::ErrorKind::Other, e)));
                             }
                         }
                     }
                 }
-                Signal::Cancel { symbol, order_id } => {
+                Order::Resting(resting) => {
+                    let placed = self.place_resting(resting).await?;
+                    info!("Limit order {} resting on the book", placed.order_id);
+                    execution.placed = Some(placed);
+                }
+                Order::Cancel { symbol, order_id } => {
                     info!("LIVE: Cancelling order {} for {}", order_id, symbol);
-                    if let Err(e) = worker.cancel_order(symbol, order_id).await {
-                        error!("Cancel failed: {}", e);
+                    execution.cancelled = Some(self.cancel_order(&symbol, order_id).await.is_ok_and(|removed| removed));
+                }
+            }
+        }
+
+        Ok(execution)
+    }
+
+    /// Quote-asset price of `asset`: the last trade seen on its quote pair, else the exchange ticker
+    async fn asset_price(&self, asset: &str, worker: &BinanceWorker) -> Option<f64> {
+        let pair = format!("{}{}", asset, self.quote_asset);
+        if let Some(price) = self.last_prices.lock().await.get(&pair).copied() {
+            return Some(price);
+        }
+        match worker.get_price(pair.clone()).await {
+            Ok(price) if price > 0.0 => Some(price),
+            Ok(_) => None,
+            Err(e) => {
+                warn!("No {} price to value {} commission: {}", pair, asset, e);
+                None
+            }
+        }
+    }
+
+    fn initial_sim_balances(quote_asset: &str, starting: f64) -> HashMap<String, f64> {
+        let mut balances = HashMap::new();
+        balances.insert(quote_asset.to_string(), starting);
+        balances.insert("BTC".to_string(), 0.0);
+        balances
+    }
+
+    /// Sets the quote balance a simulated account starts (and resets) with
+    pub fn with_starting_balance(mut self, usdt: f64) -> Self {
+        self.sim_starting_usdt = usdt;
+        self.sim_balances = std::sync::Arc::new(tokio::sync::Mutex::new(Self::initial_sim_balances(&self.quote_asset, usdt)));
+        self
+    }
+
+    /// Lets simulated buys borrow up to `usdt` of quote beyond the balance, which then goes negative
+    pub fn with_credit_line(mut self, usdt: f64) -> Self {
+        self.sim_credit_usdt = usdt;
+        self
+    }
+
+    /// Sets the slippage model applied to simulated market orders
+    pub fn with_slippage(mut self, slippage: SlippageModel) -> Self {
+        self.slippage = slippage;
+        self
+    }
+
+    /// Moves a simulated market order's price against the trader (buys up, sells down) and records the cost
+    async fn slipped_price(&self, symbol: &str, price: f64, quantity: f64, is_buy: bool) -> f64 {
+        if self.slippage == SlippageModel::None {
+            return price;
+        }
+        let avg_trade_qty = self.sim_trade_sizes.lock().await.get(symbol)
+            .filter(|sizes| !sizes.is_empty())
+            .map(|sizes| sizes.iter().sum::<f64>() / sizes.len() as f64);
+        let rate = self.slippage.rate(quantity, avg_trade_qty);
+        let fill_price = if is_buy { price * (1.0 + rate) } else { price * (1.0 - rate) };
+        *self.sim_slippage_cost.lock().await += (fill_price - price).abs() * quantity;
+        fill_price
+    }
+    
+    /// Places a limit or stop-limit order: in simulation it rests in the local book, live it
+    /// goes to the exchange. Returns the order id.
+    async fn place_resting(&self, order: RestingOrder) -> Result<PlacedOrder, Box<dyn std::error::Error + Send + Sync>> {
+        let RestingOrder { symbol, side, price, quantity, time_in_force, stop_price, post_only } = order;
+        let side = side.as_str();
+        if price <= 0.0 || quantity <= 0.0 || stop_price.is_some_and(|stop| stop.is_nan() || stop <= 0.0) {
+            return Err(Box::new(std::io::Error::new(std::io::ErrorKind::InvalidInput, "Limit order requires positive prices and quantity")));
+        }
+        
+        // === SIMULATION MODE ===
+        if self.is_simulation {
+            let order = SimOrder {
+                order_id: self.next_order_id.fetch_add(1, Ordering::SeqCst),
+                symbol,
+                side: side.to_string(),
+                price,
+                quantity,
+                time_in_force,
+                stop_price,
+            };
+            let order_id = order.order_id;
+            
+            // Like the exchange, refuse a stop that the last trade has already passed
+            if let Some(stop) = stop_price {
+                if let Some(last) = self.last_prices.lock().await.get(&order.symbol).copied()
+                    && order.stop_hit(last) {
+                    return Err(Box::new(std::io::Error::new(std::io::ErrorKind::InvalidInput,
+                        format!("Stop @ {} would trigger immediately (last trade {})", stop, last))));
+                }
+                info!("SIMULATION: Stop-limit {} order {} armed: stop {} limit {}", side, order_id, stop, price);
+                self.sim_open_orders.lock().await.push(order);
+                return Ok(PlacedOrder { order_id, quantity });
+            }
+            
+            // IOC/FOK never rest: fill against the last seen trade price or drop
+            if time_in_force != TimeInForce::GTC {
+                let last_price = self.last_prices.lock().await.get(&order.symbol).copied();
+                match last_price {
+                    Some(p) if order.crosses(p) => {
+                        // Reported with the next trade's fills, like a resting order that filled
+                        if let Some(fill) = self.fill_sim_order(&order).await {
+                            self.sim_fills.lock().await.push(fill);
+                        }
                     }
+                    _ => info!("SIMULATION: {:?} limit order {} not immediately fillable, cancelled", time_in_force, order_id),
+                }
+                return Ok(PlacedOrder { order_id, quantity });
+            }
+            
+            self.sim_open_orders.lock().await.push(order);
+            return Ok(PlacedOrder { order_id, quantity });
+        }
+        
+        if !self.armed.load(Ordering::SeqCst) {
+            info!("DRY RUN (not armed): would place {} limit {} x {} @ {} ({:?}, stop {:?})", side, quantity, symbol, price, time_in_force, stop_price);
+            return Ok(PlacedOrder { order_id: 0, quantity });
+        }
+        
+        if self.futures_worker.is_some() {
+            return Err(Box::new(std::io::Error::new(std::io::ErrorKind::Unsupported, "Limit orders are not supported for futures yet")));
+        }
+        
+        // === LIVE MODE (Using Worker Thread) ===
+        if let Some(worker) = &self.worker {
+            let live_symbol = symbol.clone();
+            let filters = self.filters_for(&symbol).await;
+            let qty = Self::truncate_qty(quantity, filters.qty_precision);
+            let price = Self::round_price(price, filters.price_precision);
+            let order_value = qty * price;
+            
+            if order_value < filters.min_notional {
+                warn!("Order value ({:.2} {}) below minimum notional ({}). Skipping limit order.", order_value, self.quote_asset, filters.min_notional);
+                OrderRejections::count(&self.rejections.notional);
+                return Err(Box::new(std::io::Error::new(std::io::ErrorKind::InvalidInput, "Order value below minimum notional")));
+            }
+            
+            let result = if let Some(stop_price) = stop_price {
+                let stop_price = Self::round_price(stop_price, filters.price_precision);
+                info!("LIVE: Sending STOP-LIMIT {} {} x {} stop {} limit {} to worker", side.to_uppercase(), qty, symbol, stop_price, price);
+                let side = if side == "Buy" { Side::Buy } else { Side::Sell };
+                worker.stop_limit(symbol, side, qty, stop_price, price).await
+            } else if post_only {
+                info!("LIVE: Sending LIMIT_MAKER {} {} x {} @ {} to worker", side.to_uppercase(), qty, symbol, price);
+                let side = if side == "Buy" { Side::Buy } else { Side::Sell };
+                worker.limit_maker(symbol, side, qty, price).await
+            } else if side == "Buy" {
+                info!("LIVE: Sending LIMIT {} {} x {} @ {} to worker", side.to_uppercase(), qty, symbol, price);
+                worker.limit_buy(symbol, qty, price, time_in_force).await
+            } else {
+                info!("LIVE: Sending LIMIT {} {} x {} @ {} to worker", side.to_uppercase(), qty, symbol, price);
+                worker.limit_sell(symbol, qty, price, time_in_force).await
+            };
+            return match result {
+                Ok(order_id) => {
+                    info!("Limit order {} placed successfully!", order_id);
+                    let side = if side == "Buy" { Side::Buy } else { Side::Sell };
+                    self.live_open_orders.lock().await.push(LiveOrder { order_id, symbol: live_symbol, side, executed_qty: 0.0, quote_qty: 0.0 });
+                    Ok(PlacedOrder { order_id, quantity: qty })
+                }
+                Err(e) => {
+                    error!("Limit order failed: {}", e);
+                    OrderRejections::count(&self.rejections.api_error);
+                    Err(Box::new(std::io::Error::new(std::io::ErrorKind::Other, e)))
+                }
+            };
+        }
+        
+        Err(Box::new(std::io::Error::new(std::io::ErrorKind::Other, "No execution worker available")))
+    }
+    
+    /// Starts a background check of the live resting orders at most every LIVE_ORDER_POLL_MS, so
+    /// the trade stream never waits on the exchange. New fills land in `live_fills`.
+    fn poll_live_orders(&self, now_ms: u64) {
+        let Some(worker) = self.worker.clone() else { return };
+        let last = self.live_poll_ms.load(Ordering::Relaxed);
+        if now_ms.saturating_sub(last) < LIVE_ORDER_POLL_MS
+            || self.live_poll_ms.compare_exchange(last, now_ms, Ordering::Relaxed, Ordering::Relaxed).is_err() {
+            return;
+        }
+        let orders = self.live_open_orders.clone();
+        let fills = self.live_fills.clone();
+        let maker_fee = self.fees.maker;
+        tokio::spawn(async move {
+            let tracked = orders.lock().await.clone();
+            for order in tracked {
+                let state = match worker.order_status(order.symbol.clone(), order.order_id).await {
+                    Ok(state) => state,
+                    Err(e) => {
+                        warn!("Could not poll limit order {}: {}", order.order_id, e);
+                        continue;
+                    }
+                };
+                let quantity = state.executed_qty - order.executed_qty;
+                if quantity > 0.0 {
+                    let price = (state.quote_qty - order.quote_qty) / quantity;
+                    info!("LIVE: Limit {} order {} filled {} @ {}", order.side.as_str(), order.order_id, quantity, price);
+                    fills.lock().await.push(Fill {
+                        order_id: order.order_id,
+                        symbol: order.symbol.clone(),
+                        side: order.side.as_str().to_string(),
+                        price,
+                        quantity,
+                        fee: price * quantity * maker_fee,
+                        realized_pnl: 0.0,
+                    });
+                }
+                let mut orders = orders.lock().await;
+                if state.is_closed() {
+                    orders.retain(|o| o.order_id != order.order_id);
+                } else if let Some(tracked) = orders.iter_mut().find(|o| o.order_id == order.order_id) {
+                    tracked.executed_qty = state.executed_qty;
+                    tracked.quote_qty = state.quote_qty;
+                }
+            }
+        });
+    }
+
+    /// Resolves a signal into the one order to send, or None when a SellAll has nothing to sell.
+    /// A SellAll sells what the account holds, capped at `owned` when given. A Buy/Sell that asks
+    /// for a Limit or PostOnly order becomes a GTC limit at its price; a post-only order that
+    /// would trade immediately against the last seen trade price is rejected, as the exchange
+    /// would. Without a price the order goes out at market.
+    async fn resolve(&self, signal: Signal, owned: Option<f64>) -> Result<Option<Order>, Box<dyn std::error::Error + Send + Sync>> {
+        let (symbol, price, quantity, order_type, side) = match signal {
+            Signal::Buy { symbol, price, quantity, order_type } => (symbol, price, quantity, order_type, Side::Buy),
+            Signal::Sell { symbol, price, quantity, order_type } => (symbol, price, quantity, order_type, Side::Sell),
+            Signal::SellAll { symbol, price } => {
+                let held = self.held_quantity(&symbol).await?;
+                let quantity = owned.map_or(held, |owned| held.min(owned));
+                if quantity <= 0.0 {
+                    return Ok(None);
                 }
+                // Only ever sells what is held, so on futures it can go out reduce-only without a lookup
+                return Ok(Some(Order::Market { symbol, side: Side::Sell, price, quantity, reduce_only: true }));
+            }
+            Signal::LimitBuy { symbol, price, quantity, time_in_force } => {
+                return Ok(Some(Order::Resting(RestingOrder { symbol, side: Side::Buy, price, quantity, time_in_force, stop_price: None, post_only: false })));
+            }
+            Signal::LimitSell { symbol, price, quantity, time_in_force } => {
+                return Ok(Some(Order::Resting(RestingOrder { symbol, side: Side::Sell, price, quantity, time_in_force, stop_price: None, post_only: false })));
+            }
+            Signal::StopLimit { symbol, stop_price, limit_price, quantity, side } => {
+                let time_in_force = TimeInForce::GTC;
+                return Ok(Some(Order::Resting(RestingOrder { symbol, side, price: limit_price, quantity, time_in_force, stop_price: Some(stop_price), post_only: false })));
+            }
+            Signal::Cancel { symbol, order_id } => return Ok(Some(Order::Cancel { symbol, order_id })),
+        };
+        if order_type == OrderType::Market {
+            return Ok(Some(Order::Market { symbol, side, price, quantity, reduce_only: false }));
+        }
+        let Some(limit_price) = price.filter(|p| *p > 0.0) else {
+            warn!("{:?} order for {} has no price, sending it at market", order_type, symbol);
+            return Ok(Some(Order::Market { symbol, side, price, quantity, reduce_only: false }));
+        };
+        // Live, the exchange rejects a LIMIT_MAKER that would take; simulation checks the last trade
+        if order_type == OrderType::PostOnly
+            && self.is_simulation
+            && let Some(last) = self.last_prices.lock().await.get(&symbol).copied() {
+            let would_take = if side == Side::Buy { limit_price > last } else { limit_price < last };
+            if would_take {
+                return Err(Box::new(std::io::Error::new(std::io::ErrorKind::InvalidInput,
+                    format!("Post-only order @ {} would take liquidity (last trade {})", limit_price, last))));
+            }
+        }
+        let time_in_force = TimeInForce::GTC;
+        let post_only = order_type == OrderType::PostOnly;
+        Ok(Some(Order::Resting(RestingOrder { symbol, side, price: limit_price, quantity, time_in_force, stop_price: None, post_only })))
+    }
+
+    /// Truncates quantity to the symbol's step size precision
+    fn truncate_qty(qty: f64, decimals: u32) -> f64 {
+        let factor = 10_f64.powi(decimals as i32);
+        (qty * factor).floor() / factor
+    }
+
+    /// Rounds a limit price to the symbol's tick size precision
+    fn round_price(price: f64, decimals: u32) -> f64 {
+        let factor = 10_f64.powi(decimals as i32);
+        (price * factor).round() / factor
+    }
+
+    /// Exchange rules for a spot symbol, fetched once and cached.
+    /// Falls back to the BTCUSDT defaults (not cached) if the fetch fails.
+    async fn filters_for(&self, symbol: &str) -> SymbolFilters {
+        if let Some(filters) = self.symbol_filters.lock().await.get(symbol) {
+            return *filters;
+        }
+        let Some(worker) = &self.worker else {
+            return SymbolFilters::default();
+        };
+        match worker.get_symbol_filters(symbol.to_string()).await {
+            Ok(filters) => {
+                self.symbol_filters.lock().await.insert(symbol.to_string(), filters);
+                filters
+            }
+            Err(e) => {
+                warn!("Using default filters for {}: {}", symbol, e);
+                SymbolFilters::default()
+            }
+        }
+    }
+
+    /// Logs a live market fill, warning when less than `requested` was filled
+    fn live_fill(fill: &OrderFill, requested: f64) -> MarketFill {
+        if fill.executed_qty < requested {
+            warn!("Order {} partially filled: {} of {} @ {:.2}", fill.order_id, fill.executed_qty, requested, fill.avg_fill_price);
+        } else {
+            info!("Order {} executed successfully! {} @ {:.2}", fill.order_id, fill.executed_qty, fill.avg_fill_price);
+        }
+        MarketFill { quantity: fill.executed_qty, price: fill.avg_fill_price }
+    }
+
+    /// True when a futures market order on `symbol` trades against the open position, i.e. it is
+    /// an exit. Unknown positions count as exits: the order still goes out, and being sent
+    /// reduce-only it is rejected by the exchange rather than opening a position.
+    async fn futures_order_reduces(&self, symbol: &str, side: Side) -> bool {
+        let position_amt = match &self.futures_worker {
+            Some(worker) => worker.get_positions().await
+                .map(|positions| positions.iter().filter(|p| p.symbol == symbol).map(|p| p.position_amt).sum::<f64>()),
+            None => Ok(self.sim_positions.lock().await.iter()
+                .filter(|p| p.symbol == symbol)
+                .map(|p| if p.side == "Short" { -p.amount } else { p.amount })
+                .sum::<f64>()),
+        };
+        match position_amt {
+            Ok(position_amt) => if side == Side::Buy { position_amt < 0.0 } else { position_amt > 0.0 },
+            Err(e) => {
+                warn!("Position lookup for {} failed, treating the {:?} as an exit: {}", symbol, side, e);
+                true
+            }
+        }
+    }
+
+    /// What the account holds of `symbol`: the base asset balance on spot, the long position on futures
+    async fn held_quantity(&self, symbol: &str) -> Result<f64, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(match self.market_type {
+            MarketType::Spot => {
+                let base = base_asset(symbol, &self.quote_asset);
+                self.get_balances().await?.into_iter()
+                    .find(|(asset, _)| asset == base)
+                    .map_or(0.0, |(_, amount)| amount)
+            }
+            MarketType::Futures => self.get_positions().await?.into_iter()
+                .filter(|p| p.symbol == symbol && p.side != "Short")
+                .map(|p| p.amount)
+                .sum(),
+        })
+    }
+
+    /// Routes market orders to the futures worker. Futures trade on margin, so no spot balance checks apply.
+    async fn execute_futures(worker: &FuturesWorker, order: Order) -> Result<Execution, Box<dyn std::error::Error + Send + Sync>> {
+        let result = match order {
+            Order::Market { symbol, side: Side::Buy, quantity, reduce_only, .. } => {
+                let qty = Self::truncate_qty(quantity, 3);
+                info!("LIVE FUTURES: Sending MARKET BUY {:.3} x {} to worker{}", qty, symbol, if reduce_only { " (reduce-only)" } else { "" });
+                worker.market_buy(symbol, qty, reduce_only).await
+            }
+            Order::Market { symbol, side: Side::Sell, quantity, reduce_only, .. } => {
+                let qty = Self::truncate_qty(quantity, 3);
+                info!("LIVE FUTURES: Sending MARKET SELL {:.3} x {} to worker{}", qty, symbol, if reduce_only { " (reduce-only)" } else { "" });
+                worker.market_sell(symbol, qty, reduce_only).await
+            }
+            other => {
+                warn!("LIVE FUTURES: {:?} is not supported for futures yet. Skipping.", other);
+                return Ok(Execution::not_sent(NotSent::Unsupported));
+            }
+        };
+        match result {
+            Ok(fill) => {
+                info!("Futures order {} executed successfully! {} @ {:.2}", fill.order_id, fill.executed_qty, fill.avg_fill_price);
+                // An ACK-only response reports no fill; better unknown than a guessed one
+                let fill = (fill.executed_qty > 0.0).then_some(MarketFill { quantity: fill.executed_qty, price: fill.avg_fill_price });
+                Ok(Execution { fill, ..Execution::default() })
+            }
+            Err(e) => {
+                error!("Futures order failed: {}", e);
+                Err(Box::new(std::io::Error::new(std::io::ErrorKind::Other, e)))
             }
         }
+    }
+
+    /// Applies a simulated buy fill. Returns the fee paid, or None if the quote balance plus the
+    /// credit line is insufficient.
+    fn sim_buy(&self, bal: &mut HashMap<String, f64>, pos: &mut Vec<PositionInfo>, symbol: &str, price: f64, quantity: f64, fee_rate: f64) -> Option<f64> {
+        let quote_asset = self.quote_asset.as_str();
+        let fee = quantity * price * fee_rate;
+        let cost = quantity * price + fee;
+        
+        let quote = bal.entry(quote_asset.to_string()).or_insert(0.0);
+        if *quote + self.sim_credit_usdt < cost {
+            return None;
+        }
+        *quote -= cost;
+        *bal.entry(base_asset(symbol, quote_asset).to_string()).or_insert(0.0) += quantity;
+        
+        if let Some(p) = pos.iter_mut().find(|p| p.symbol == symbol) {
+            let total_cost = p.amount * p.entry_price + cost;
+            p.amount += quantity;
+            p.entry_price = total_cost / p.amount;
+        } else {
+            pos.push(PositionInfo {
+                symbol: symbol.to_string(),
+                amount: quantity,
+                entry_price: cost / quantity, // Entry price inclusive of fee
+                unrealized_pnl: 0.0,
+                market_type: "Spot".to_string(),
+                side: "Long".to_string(),
+            });
+        }
+        Some(fee)
+    }
+
+    /// Applies a simulated sell fill. Returns (realized PnL, fee), or None if the base asset balance is insufficient.
+    fn sim_sell(bal: &mut HashMap<String, f64>, pos: &mut Vec<PositionInfo>, quote_asset: &str, symbol: &str, price: f64, quantity: f64, fee_rate: f64) -> Option<(f64, f64)> {
+        let held = bal.entry(base_asset(symbol, quote_asset).to_string()).or_insert(0.0);
+        if *held < quantity {
+            return None;
+        }
+        *held -= quantity;
+        let revenue = quantity * price;
+        let fee = revenue * fee_rate;
+        *bal.entry(quote_asset.to_string()).or_insert(0.0) += revenue - fee;
+        
+        let mut realized_pnl = 0.0;
+        if let Some(idx) = pos.iter().position(|p| p.symbol == symbol) {
+            let buy_price = pos[idx].entry_price;
+            // Realized PnL = (Revenue - Fee) - (Buy Cost)
+            realized_pnl = (revenue - fee) - (buy_price * quantity);
+            
+            pos[idx].amount -= quantity;
+            if pos[idx].amount <= 0.000001 {
+                pos.remove(idx);
+            }
+        }
+        Some((realized_pnl, fee))
+    }
+
+    /// Fills a simulated limit order at its limit price, paying the maker fee
+    async fn fill_sim_order(&self, order: &SimOrder) -> Option<Fill> {
+        let mut bal = self.sim_balances.lock().await;
+        let mut pos = self.sim_positions.lock().await;
+        
+        let result = if order.side == "Buy" {
+            self.sim_buy(&mut bal, &mut pos, &order.symbol, order.price, order.quantity, self.fees.maker)
+                .map(|fee| (0.0, fee))
+        } else {
+            Self::sim_sell(&mut bal, &mut pos, &self.quote_asset, &order.symbol, order.price, order.quantity, self.fees.maker)
+        };
+        
+        match result {
+            Some((realized_pnl, fee)) => Some(Fill {
+                order_id: order.order_id,
+                symbol: order.symbol.clone(),
+                side: order.side.clone(),
+                price: order.price,
+                quantity: order.quantity,
+                fee,
+                realized_pnl,
+            }),
+            None => {
+                warn!("SIMULATION: Insufficient balance to fill limit {} {}. Dropping order.", order.side, order.order_id);
+                None
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl Executor for ExecutionManager {
+    async fn execute_owned(&self, signal: Signal, owned: Option<f64>) -> Result<Execution, Box<dyn std::error::Error + Send + Sync>> {
+        let symbol = signal.symbol().to_string();
+        let Some(order) = self.resolve(signal, owned).await? else {
+            info!("SellAll {}: nothing held. Skipping.", symbol);
+            return Ok(Execution::not_sent(NotSent::NothingHeld));
+        };
+
+        // Net market entries against recent opposing orders on the same symbol
+        let Order::Market { symbol, side, price, mut quantity, mut reduce_only } = order else {
+            return self.execute_resolved(order).await;
+        };
+        let price = match price {
+            Some(price) => Some(price),
+            None => self.last_prices.lock().await.get(&symbol).copied(),
+        };
+        // Only entries may be netted: an opposing order sent earlier has already filled, so
+        // shrinking an exit would just leave that position open. Futures exits go out reduce-only.
+        let opens_position = match self.market_type {
+            MarketType::Spot => side == Side::Buy,
+            MarketType::Futures => {
+                reduce_only = reduce_only || self.futures_order_reduces(&symbol, side).await;
+                !reduce_only
+            }
+        };
+        let now_ms = self.netting_clock_ms();
+        if self.netter.lock().enabled() && opens_position {
+            quantity = self.netter.lock().net(&symbol, side, price, quantity, now_ms);
+            if quantity <= 0.0 {
+                return Ok(Execution::not_sent(NotSent::Netted));
+            }
+        }
+        let order = Order::Market { symbol: symbol.clone(), side, price, quantity, reduce_only };
+        let execution = self.execute_resolved(order).await?;
+        if execution.not_sent.is_none() {
+            let sent = execution.fill.map_or(quantity, |f| f.quantity);
+            self.netter.lock().record(&symbol, side, price, sent, now_ms);
+        }
+        Ok(execution)
+    }
+
+    fn armed_flag(&self) -> Arc<AtomicBool> {
+        self.armed.clone()
+    }
+
+    fn api_weight_used(&self) -> u32 {
+        self.rate_limiter.used_weight()
+    }
+
+    fn order_rejections(&self) -> Arc<OrderRejections> {
+        self.rejections.clone()
+    }
+
+    fn netting_status(&self) -> NettingStatus {
+        self.netter.lock().status()
+    }
 
-        Ok(0.0)
+    fn order_retries(&self) -> RetryStatus {
+        self.retry_stats.status(&self.retry_policy)
     }
 
+    fn market_type(&self) -> MarketType {
+        self.market_type
+    }
+
+    fn quote_asset(&self) -> &str {
+        &self.quote_asset
+    }
+    
     async fn get_balances(&self) -> Result<Vec<(String, f64)>, Box<dyn std::error::Error + Send + Sync>> {
         // Simulation mode - return simulated balances
         if self.is_simulation {
@@ -302,6 +1225,18 @@ impl Executor for ExecutionManager {
             return Ok(bal.iter().map(|(k, v)| (k.clone(), *v)).collect());
         }
         
+        // Live futures - wallet balance per margin asset
+        if let Some(futures_worker) = &self.futures_worker {
+            match futures_worker.get_account().await {
+                Ok(balances) => {
+                    return Ok(balances.into_iter().map(|b| (b.asset, b.wallet_balance)).collect());
+                }
+                Err(e) => {
+                    error!("Failed to get futures balances: {}", e);
+                }
+            }
+        }
+        
         // Live mode - fetch from Binance via worker
         if let Some(worker) = &self.worker {
             match worker.get_account().await {
@@ -317,7 +1252,33 @@ impl Executor for ExecutionManager {
 
     async fn get_positions(&self) -> Result<Vec<PositionInfo>, Box<dyn std::error::Error + Send + Sync>> {
         if self.is_simulation {
-            return Ok(self.sim_positions.lock().await.clone());
+            // Mark open positions to the last traded price; entry price until one is seen
+            let last_prices = self.last_prices.lock().await.clone();
+            let positions = self.sim_positions.lock().await.iter().cloned().map(|mut p| {
+                let mark = last_prices.get(&p.symbol).copied().unwrap_or(p.entry_price);
+                let direction = if p.side == "Short" { -1.0 } else { 1.0 };
+                p.unrealized_pnl = (mark - p.entry_price) * p.amount * direction;
+                p
+            }).collect();
+            return Ok(positions);
+        }
+        if let Some(futures_worker) = &self.futures_worker {
+            match futures_worker.get_positions().await {
+                Ok(positions) => {
+                    return Ok(positions.into_iter().map(|p| PositionInfo {
+                        side: if p.position_amt >= 0.0 { "Long".to_string() } else { "Short".to_string() },
+                        symbol: p.symbol,
+                        amount: p.position_amt.abs(),
+                        entry_price: p.entry_price,
+                        unrealized_pnl: p.unrealized_pnl,
+                        market_type: self.market_type.as_str().to_string(),
+                    }).collect());
+                }
+                Err(e) => {
+                    error!("Failed to get futures positions: {}", e);
+                    return Ok(vec![]);
+                }
+            }
         }
         // For Spot trading, positions are derived from balances
         // (Full implementation would track order history)
@@ -330,18 +1291,19 @@ impl Executor for ExecutionManager {
             return Ok(TradeStats::default());
         }
         
-        // Live mode - fetch trade history from Binance
+        // Live mode - fetch trade history from Binance (spot only)
         if let Some(worker) = &self.worker {
             match worker.get_trade_history(symbol.to_string(), 100).await {
                 Ok(trades) => {
-                    let mut stats = TradeStats::default();
-                    stats.total_trades = trades.len() as u64;
-                    
-                    // Get commission asset from first trade
-                    if let Some(first_trade) = trades.first() {
-                        stats.commission_asset = first_trade.commission_asset.clone();
-                    }
-                    
+                    let mut stats = TradeStats {
+                        total_trades: trades.len() as u64,
+                        commission_asset: self.quote_asset.clone(),
+                        ..TradeStats::default()
+                    };
+                    let base = base_asset(symbol, &self.quote_asset);
+                    // Quote-asset price of each third-party commission asset, looked up once
+                    let mut asset_prices: HashMap<String, Option<f64>> = HashMap::new();
+
                     for trade in &trades {
                         if trade.is_buyer {
                             stats.buy_trades += 1;
@@ -349,9 +1311,38 @@ impl Executor for ExecutionManager {
                             stats.sell_trades += 1;
                         }
                         stats.total_volume += trade.price * trade.qty;
-                        stats.total_commission += trade.commission;
+                        *stats.commission_by_asset.entry(trade.commission_asset.clone()).or_insert(0.0) += trade.commission;
+
+                        let price = if trade.commission_asset == self.quote_asset {
+                            Some(1.0)
+                        } else if !self.commission_conversion {
+                            None
+                        } else if trade.commission_asset == base {
+                            // Charged in what was bought, valued at that trade's price
+                            Some(trade.price)
+                        } else {
+                            match asset_prices.get(&trade.commission_asset) {
+                                Some(price) => *price,
+                                None => {
+                                    let price = self.asset_price(&trade.commission_asset, worker).await;
+                                    asset_prices.insert(trade.commission_asset.clone(), price);
+                                    price
+                                }
+                            }
+                        };
+                        match price {
+                            Some(price) => stats.total_commission += trade.commission * price,
+                            None if !stats.unpriced_commission_assets.contains(&trade.commission_asset) => {
+                                stats.unpriced_commission_assets.push(trade.commission_asset.clone());
+                            }
+                            None => {}
+                        }
                     }
-                    
+                    if !stats.unpriced_commission_assets.is_empty() {
+                        warn!("Trade stats for {}: commission in {:?} not valued in {}",
+                            symbol, stats.unpriced_commission_assets, self.quote_asset);
+                    }
+
                     info!("Trade stats for {}: {} trades ({} buys, {} sells), Volume: ${:.2}, Commission: {:.6} {}",
                         symbol, stats.total_trades, stats.buy_trades, stats.sell_trades, 
                         stats.total_volume, stats.total_commission, stats.commission_asset);
@@ -366,4 +1357,219 @@ impl Executor for ExecutionManager {
         
         Ok(TradeStats::default())
     }
+    async fn on_trade_price(&self, symbol: &str, price: f64, quantity: f64, ts: u64) -> Result<OrderUpdates, Box<dyn std::error::Error + Send + Sync>> {
+        self.last_prices.lock().await.insert(symbol.to_string(), price);
+        self.event_ms.fetch_max(ts, Ordering::Relaxed);
+        // Live stops and resting orders are handled by the exchange; report the fills it confirmed
+        if !self.is_simulation {
+            self.poll_live_orders(ts);
+            let fills = std::mem::take(&mut *self.live_fills.lock().await);
+            return Ok(OrderUpdates { fills, ..OrderUpdates::default() });
+        }
+        
+        if matches!(self.slippage, SlippageModel::VolumeProportional(_)) && quantity > 0.0 {
+            let mut sizes = self.sim_trade_sizes.lock().await;
+            let window = sizes.entry(symbol.to_string()).or_default();
+            window.push_back(quantity);
+            if window.len() > SLIPPAGE_VOLUME_WINDOW {
+                window.pop_front();
+            }
+        }
+        
+        let mut updates = OrderUpdates { fills: std::mem::take(&mut *self.sim_fills.lock().await), ..OrderUpdates::default() };
+        let filled: Vec<SimOrder> = {
+            let mut orders = self.sim_open_orders.lock().await;
+            if orders.is_empty() {
+                return Ok(updates);
+            }
+            // A triggered stop rests as a plain limit order and may fill on the same trade
+            for order in orders.iter_mut().filter(|o| o.symbol == symbol && o.stop_hit(price)) {
+                info!("SIMULATION: Stop {} order {} triggered @ {}, resting limit @ {}", order.side, order.order_id, price, order.price);
+                updates.triggered.push(StopTrigger {
+                    order_id: order.order_id,
+                    symbol: order.symbol.clone(),
+                    side: order.side.clone(),
+                    stop_price: order.stop_price.take().unwrap_or_default(),
+                    limit_price: order.price,
+                    trigger_price: price,
+                });
+            }
+            let (filled, resting): (Vec<SimOrder>, Vec<SimOrder>) = orders
+                .drain(..)
+                .partition(|o| o.symbol == symbol && o.stop_price.is_none() && o.crosses(price));
+            *orders = resting;
+            filled
+        };
+        
+        for order in &filled {
+            info!("SIMULATION: Limit {} order {} filled @ {}", order.side, order.order_id, order.price);
+            if let Some(fill) = self.fill_sim_order(order).await {
+                updates.fills.push(fill);
+            }
+        }
+        Ok(updates)
+    }
+
+    async fn slippage_cost(&self) -> f64 {
+        *self.sim_slippage_cost.lock().await
+    }
+
+    async fn get_funding_rate(&self, symbol: &str) -> Result<f64, Box<dyn std::error::Error + Send + Sync>> {
+        let result = if let Some(futures_worker) = &self.futures_worker {
+            futures_worker.get_funding_rate(symbol.to_string()).await
+        } else {
+            // Funding rates are public, so simulation can read them without a worker
+            let symbol = symbol.to_string();
+            tokio::task::spawn_blocking(move || {
+                use binance::api::Binance;
+                futures_worker::fetch_funding_rate(&binance::futures::market::FuturesMarket::new(None, None), &symbol)
+            })
+            .await
+            .map_err(|e| e.to_string())
+            .and_then(|r| r.map_err(|e| format!("{:?}", e)))
+        };
+        result.map_err(|e| Box::new(std::io::Error::new(std::io::ErrorKind::Other, e)) as Box<dyn std::error::Error + Send + Sync>)
+    }
+
+    async fn get_mark_prices(&self, symbols: &[String]) -> Result<HashMap<String, f64>, Box<dyn std::error::Error + Send + Sync>> {
+        let result = if let Some(futures_worker) = &self.futures_worker {
+            futures_worker.get_mark_prices().await
+        } else if let Some(worker) = &self.worker {
+            let mut prices = HashMap::new();
+            for symbol in symbols {
+                match worker.get_price(symbol.clone()).await {
+                    Ok(price) => { prices.insert(symbol.clone(), price); }
+                    Err(e) => warn!("Failed to get price for {}: {}", symbol, e),
+                }
+            }
+            Ok(prices)
+        } else {
+            // Both prices are public, so a paper account can read them without a worker
+            let wanted = symbols.to_vec();
+            let market_type = self.market_type;
+            tokio::task::spawn_blocking(move || {
+                use binance::api::Binance;
+                match market_type {
+                    MarketType::Futures => futures_worker::fetch_mark_prices(&binance::futures::market::FuturesMarket::new(None, None)),
+                    MarketType::Spot => {
+                        let market = binance::market::Market::new(None, None);
+                        Ok(wanted.iter()
+                            .filter_map(|symbol| market.get_price(symbol.as_str()).ok().map(|ticker| (symbol.clone(), ticker.price)))
+                            .collect())
+                    }
+                }
+            })
+            .await
+            .map_err(|e| e.to_string())
+            .and_then(|r| r.map_err(|e| format!("{:?}", e)))
+        };
+        result
+            .map(|mut prices| {
+                prices.retain(|symbol, _| symbols.contains(symbol));
+                prices
+            })
+            .map_err(|e| Box::new(std::io::Error::new(std::io::ErrorKind::Other, e)) as Box<dyn std::error::Error + Send + Sync>)
+    }
+
+    async fn apply_funding(&self, symbol: &str, funding_rate: f64, mark_price: f64) -> Result<f64, Box<dyn std::error::Error + Send + Sync>> {
+        // Live funding is settled by the exchange
+        if !self.is_simulation || self.market_type != MarketType::Futures {
+            return Ok(0.0);
+        }
+        
+        let positions = self.sim_positions.lock().await;
+        let mut funding_pnl = 0.0;
+        for p in positions.iter().filter(|p| p.symbol == symbol && p.amount > 0.0) {
+            // Positive rate: longs pay shorts
+            let payment = p.amount * mark_price * funding_rate;
+            funding_pnl += if p.side == "Short" { payment } else { -payment };
+        }
+        if funding_pnl != 0.0 {
+            *self.sim_balances.lock().await.entry(self.quote_asset.clone()).or_insert(0.0) += funding_pnl;
+            info!("SIMULATION: Funding {} rate {:.6}% -> {:+.4} {}", symbol, funding_rate * 100.0, funding_pnl, self.quote_asset);
+        }
+        Ok(funding_pnl)
+    }
+
+    async fn reset_simulation(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        if !self.is_simulation {
+            return Err(Box::new(std::io::Error::new(std::io::ErrorKind::Unsupported, "Account reset is only available in simulation mode")));
+        }
+        *self.sim_balances.lock().await = Self::initial_sim_balances(&self.quote_asset, self.sim_starting_usdt);
+        self.sim_positions.lock().await.clear();
+        self.sim_open_orders.lock().await.clear();
+        self.sim_fills.lock().await.clear();
+        self.sim_trade_sizes.lock().await.clear();
+        *self.sim_slippage_cost.lock().await = 0.0;
+        info!("Simulated account reset to starting balances");
+        Ok(())
+    }
+
+    async fn set_leverage(&self, symbol: &str, leverage: u8) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        if let Some(futures_worker) = &self.futures_worker {
+            info!("LIVE FUTURES: Setting leverage for {} to {}x", symbol, leverage);
+            return futures_worker.set_leverage(symbol.to_string(), leverage).await
+                .map_err(|e| Box::new(std::io::Error::new(std::io::ErrorKind::Other, e)) as Box<dyn std::error::Error + Send + Sync>);
+        }
+        Err(Box::new(std::io::Error::new(std::io::ErrorKind::Unsupported, "Leverage requires a live futures executor")))
+    }
+
+    async fn set_margin_type(&self, symbol: &str, margin_type: MarginType) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        if let Some(futures_worker) = &self.futures_worker {
+            info!("LIVE FUTURES: Setting margin type for {} to {}", symbol, margin_type);
+            return futures_worker.set_margin_type(symbol.to_string(), margin_type).await
+                .map_err(|e| Box::new(std::io::Error::new(std::io::ErrorKind::Other, e)) as Box<dyn std::error::Error + Send + Sync>);
+        }
+        Err(Box::new(std::io::Error::new(std::io::ErrorKind::Unsupported, "Margin type requires a live futures executor")))
+    }
+
+    async fn cancel_order(&self, symbol: &str, order_id: u64) -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+        if let Some(worker) = &self.worker {
+            return match worker.cancel_order(symbol.to_string(), order_id).await {
+                Ok(()) => Ok(true),
+                Err(e) => {
+                    error!("Cancel failed: {}", e);
+                    Err(Box::new(std::io::Error::new(std::io::ErrorKind::Other, e)))
+                }
+            };
+        }
+        if self.futures_worker.is_some() {
+            return Err(Box::new(std::io::Error::new(std::io::ErrorKind::Unsupported, "Cancelling single orders is not supported for futures yet")));
+        }
+        
+        // Simulated orders reserve no balance until they fill, so removing one is all a cancel needs
+        let mut orders = self.sim_open_orders.lock().await;
+        let Some(idx) = orders.iter().position(|o| o.order_id == order_id && o.symbol == symbol) else {
+            info!("SIMULATION: Cancel for unknown order {} on {} ignored", order_id, symbol);
+            return Ok(false);
+        };
+        let order = orders.remove(idx);
+        info!("SIMULATION: {} order {} @ {} cancelled", order.side, order.order_id, order.price);
+        Ok(true)
+    }
+
+    async fn cancel_all_orders(&self, symbol: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let result = if let Some(futures_worker) = &self.futures_worker {
+            futures_worker.cancel_all_orders(symbol.to_string()).await
+        } else if let Some(worker) = &self.worker {
+            worker.cancel_all_orders(symbol.to_string()).await
+        } else {
+            self.sim_open_orders.lock().await.retain(|o| o.symbol != symbol);
+            Ok(())
+        };
+        result.map_err(|e| Box::new(std::io::Error::new(std::io::ErrorKind::Other, e)) as Box<dyn std::error::Error + Send + Sync>)
+    }
+
+    fn shutdown(&self) {
+        if let Some(worker) = &self.worker {
+            worker.shutdown();
+        }
+        if let Some(futures_worker) = &self.futures_worker {
+            futures_worker.shutdown();
+        }
+    }
+
+    async fn symbol_filters(&self, symbol: &str) -> SymbolFilters {
+        self.filters_for(symbol).await
+    }
 }
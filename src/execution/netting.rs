@@ -0,0 +1,181 @@
+use crate::strategy::Side;
+use log::{info, warn};
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+
+/// What happens to a market entry that opposes a recent order on the same symbol at about the same price
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum NettingMode {
+    Off,
+    /// Only the part not offset by the recent opposing orders is sent
+    Net,
+    /// The opposing order is dropped entirely
+    Reject,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct NettingConfig {
+    pub mode: NettingMode,
+    pub window_ms: u64,
+    pub price_tolerance_pct: f64,
+}
+
+impl NettingConfig {
+    /// Reads NETTING (off | net | reject, default off), NETTING_WINDOW_MS (default 1000)
+    /// and NETTING_PRICE_TOLERANCE_PCT (default 0.1)
+    pub fn from_env() -> Self {
+        let mode = match std::env::var("NETTING").unwrap_or_default().to_lowercase().as_str() {
+            "net" => NettingMode::Net,
+            "reject" => NettingMode::Reject,
+            "" | "off" => NettingMode::Off,
+            other => {
+                warn!("Unknown NETTING '{}', netting disabled", other);
+                NettingMode::Off
+            }
+        };
+        let window_ms = std::env::var("NETTING_WINDOW_MS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(1_000);
+        let price_tolerance_pct = std::env::var("NETTING_PRICE_TOLERANCE_PCT")
+            .ok()
+            .and_then(|v| v.parse::<f64>().ok())
+            .filter(|v| v.is_finite() && *v >= 0.0)
+            .unwrap_or(0.1);
+        Self { mode, window_ms, price_tolerance_pct }
+    }
+}
+
+/// Netting counters for /api/status
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct NettingStatus {
+    pub mode: NettingMode,
+    pub window_ms: u64,
+    pub price_tolerance_pct: f64,
+    /// Orders sent smaller because recent opposing orders offset part of them
+    pub netted_signals: u64,
+    /// Orders dropped because recent opposing orders offset all of them
+    pub rejected_signals: u64,
+    /// Total quantity not traded thanks to netting
+    pub netted_quantity: f64,
+}
+
+struct RecentOrder {
+    side: Side,
+    ts: u64,
+    price: Option<f64>,
+    remaining: f64, // Not yet offset by a later opposing order
+}
+
+/// Catches a strategy (or two) buying and selling the same symbol at about the same price
+/// within a short window, which only pays fees twice. Every sent market order is recorded,
+/// but only entries are netted: the earlier order has already filled, so shrinking a later
+/// exit would leave its position open instead of undoing anything. Time is event time, so
+/// backtests net exactly like live.
+pub struct SignalNetter {
+    config: NettingConfig,
+    recent: HashMap<String, VecDeque<RecentOrder>>,
+    netted_signals: u64,
+    rejected_signals: u64,
+    netted_quantity: f64,
+}
+
+impl SignalNetter {
+    pub fn new(config: NettingConfig) -> Self {
+        Self {
+            config,
+            recent: HashMap::new(),
+            netted_signals: 0,
+            rejected_signals: 0,
+            netted_quantity: 0.0,
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.config.mode != NettingMode::Off
+    }
+
+    /// Quantity of an incoming market entry still worth sending after offsetting recent
+    /// opposing orders; 0 means drop it. Never call this for an order that reduces a position.
+    pub fn net(&mut self, symbol: &str, side: Side, price: Option<f64>, quantity: f64, ts: u64) -> f64 {
+        if self.config.mode == NettingMode::Off {
+            return quantity;
+        }
+        let Some(recent) = self.recent.get_mut(symbol) else {
+            return quantity;
+        };
+        let window_ms = self.config.window_ms;
+        recent.retain(|o| ts.saturating_sub(o.ts) <= window_ms && o.remaining > 0.0);
+
+        let tolerance = self.config.price_tolerance_pct;
+        let mut opposing = recent.iter_mut()
+            .filter(|o| o.side != side && near(o.price, price, tolerance))
+            .peekable();
+        if opposing.peek().is_none() {
+            return quantity;
+        }
+
+        let remaining = match self.config.mode {
+            NettingMode::Reject => {
+                for o in opposing {
+                    o.remaining = 0.0;
+                }
+                0.0
+            }
+            _ => {
+                let mut remaining = quantity;
+                for o in opposing {
+                    let offset = remaining.min(o.remaining);
+                    o.remaining -= offset;
+                    remaining -= offset;
+                    if remaining <= 0.0 {
+                        break;
+                    }
+                }
+                remaining.max(0.0)
+            }
+        };
+
+        self.netted_quantity += quantity - remaining;
+        if remaining <= 0.0 {
+            self.rejected_signals += 1;
+            info!("Netting: dropped {} {} {} against a recent opposing order", side.as_str(), quantity, symbol);
+        } else {
+            self.netted_signals += 1;
+            info!("Netting: {} {} {} reduced to {}", side.as_str(), quantity, symbol, remaining);
+        }
+        remaining
+    }
+
+    /// Remembers a market order that was sent so later opposing orders can net against it
+    pub fn record(&mut self, symbol: &str, side: Side, price: Option<f64>, quantity: f64, ts: u64) {
+        if self.config.mode == NettingMode::Off || quantity <= 0.0 {
+            return;
+        }
+        let recent = self.recent.entry(symbol.to_string()).or_default();
+        let window_ms = self.config.window_ms;
+        recent.retain(|o| ts.saturating_sub(o.ts) <= window_ms && o.remaining > 0.0);
+        recent.push_back(RecentOrder { side, ts, price, remaining: quantity });
+    }
+
+    pub fn status(&self) -> NettingStatus {
+        NettingStatus {
+            mode: self.config.mode,
+            window_ms: self.config.window_ms,
+            price_tolerance_pct: self.config.price_tolerance_pct,
+            netted_signals: self.netted_signals,
+            rejected_signals: self.rejected_signals,
+            netted_quantity: self.netted_quantity,
+        }
+    }
+}
+
+/// Prices within `tolerance_pct` of each other. Orders without a price match on time alone.
+fn near(a: Option<f64>, b: Option<f64>, tolerance_pct: f64) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) if a > 0.0 && b > 0.0 => (a - b).abs() / a * 100.0 <= tolerance_pct,
+        _ => true,
+    }
+}
@@ -0,0 +1,165 @@
+//! Token-bucket limiter for Binance REST request weight.
+//!
+//! The workers run on plain OS threads, so `call` blocks the calling thread
+//! while it waits for budget or for a 429/418 backoff to expire.
+
+use binance::errors::{Error, ErrorKind};
+use log::warn;
+use parking_lot::Mutex;
+use std::time::{Duration, Instant};
+
+/// Binance's default REST budget
+pub const WEIGHT_PER_MINUTE: u32 = 1200;
+
+// Request weights of the endpoints the workers use
+pub const ORDER_WEIGHT: u32 = 1;
+pub const ORDER_STATUS_WEIGHT: u32 = 4;
+pub const CANCEL_WEIGHT: u32 = 1;
+pub const ACCOUNT_WEIGHT: u32 = 10;
+pub const TRADE_HISTORY_WEIGHT: u32 = 10;
+pub const FUTURES_ACCOUNT_WEIGHT: u32 = 5;
+pub const FUTURES_CONFIG_WEIGHT: u32 = 1;
+pub const FUNDING_RATE_WEIGHT: u32 = 1;
+pub const OPEN_ORDERS_WEIGHT: u32 = 1;
+pub const EXCHANGE_INFO_WEIGHT: u32 = 20;
+pub const TICKER_PRICE_WEIGHT: u32 = 2;
+pub const MARK_PRICE_WEIGHT: u32 = 10;
+
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(120);
+
+struct Bucket {
+    tokens: f64,
+    last_refill: Instant,
+    backoff_until: Option<Instant>,
+    consecutive_limited: u32,
+}
+
+pub struct RateLimiter {
+    capacity: f64,
+    refill_per_sec: f64,
+    bucket: Mutex<Bucket>,
+}
+
+impl RateLimiter {
+    pub fn new(weight_per_minute: u32) -> Self {
+        let capacity = weight_per_minute as f64;
+        Self {
+            capacity,
+            refill_per_sec: capacity / 60.0,
+            bucket: Mutex::new(Bucket {
+                tokens: capacity,
+                last_refill: Instant::now(),
+                backoff_until: None,
+                consecutive_limited: 0,
+            }),
+        }
+    }
+
+    fn refill(&self, bucket: &mut Bucket) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(bucket.last_refill).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.refill_per_sec).min(self.capacity);
+        bucket.last_refill = now;
+    }
+
+    /// Blocks until `weight` is available and no backoff is active, then consumes it
+    pub fn acquire(&self, weight: u32) {
+        let weight = (weight as f64).min(self.capacity);
+        loop {
+            let wait = {
+                let mut bucket = self.bucket.lock();
+                self.refill(&mut bucket);
+                match bucket.backoff_until {
+                    Some(until) if until > Instant::now() => until - Instant::now(),
+                    _ if bucket.tokens >= weight => {
+                        bucket.tokens -= weight;
+                        return;
+                    }
+                    _ => Duration::from_secs_f64((weight - bucket.tokens) / self.refill_per_sec),
+                }
+            };
+            std::thread::sleep(wait);
+        }
+    }
+
+    /// Runs a weighted API call, backing off when Binance answers 429 (rate limited) or 418 (IP ban)
+    pub fn call<T>(&self, weight: u32, f: impl FnOnce() -> binance::errors::Result<T>) -> binance::errors::Result<T> {
+        self.acquire(weight);
+        let result = f();
+        match &result {
+            Err(e) if is_rate_limited(e) => self.back_off(parse_retry_after(&format!("{:?}", e))),
+            Err(_) => {}
+            Ok(_) => self.bucket.lock().consecutive_limited = 0,
+        }
+        result
+    }
+
+    /// Pauses all calls. Without a Retry-After hint the delay doubles on each consecutive limit.
+    fn back_off(&self, retry_after: Option<Duration>) {
+        let mut bucket = self.bucket.lock();
+        bucket.consecutive_limited += 1;
+        let delay = retry_after.unwrap_or_else(|| {
+            (INITIAL_BACKOFF * 2u32.saturating_pow(bucket.consecutive_limited - 1)).min(MAX_BACKOFF)
+        });
+        warn!("Binance rate limit hit. Backing off for {:?}", delay);
+        bucket.backoff_until = Some(Instant::now() + delay);
+        bucket.tokens = 0.0;
+    }
+
+    /// Weight consumed within the rolling minute (approximated by the spent part of the bucket)
+    pub fn used_weight(&self) -> u32 {
+        let mut bucket = self.bucket.lock();
+        self.refill(&mut bucket);
+        (self.capacity - bucket.tokens).round() as u32
+    }
+}
+
+/// HTTP status of a failed request, where binance-rs kept it. Statuses its client has no case
+/// for come back as the message "Received response: <status>".
+fn http_status(error: &Error) -> Option<u16> {
+    match error.kind() {
+        ErrorKind::ReqError(e) => e.status().map(|status| status.as_u16()),
+        ErrorKind::Msg(message) => message.strip_prefix("Received response: ")?.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+/// Binance answered 429 (rate limited) or 418 (IP ban), or reported -1003 (too many requests)
+pub fn is_rate_limited(error: &Error) -> bool {
+    matches!(http_status(error), Some(429 | 418))
+        || matches!(error.kind(), ErrorKind::BinanceError(content) if content.code == -1003)
+}
+
+/// Extracts a Retry-After value (seconds) from an error message, if the client surfaced one
+fn parse_retry_after(message: &str) -> Option<Duration> {
+    let lower = message.to_lowercase();
+    let rest = &lower[lower.find("retry-after")? + "retry-after".len()..];
+    let digits: String = rest
+        .trim_start_matches(|c: char| !c.is_ascii_digit())
+        .chars()
+        .take_while(|c| c.is_ascii_digit())
+        .collect();
+    digits.parse::<u64>().ok().map(Duration::from_secs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use binance::errors::BinanceContentError;
+
+    fn api_error(code: i16, msg: &str) -> Error {
+        ErrorKind::BinanceError(BinanceContentError { code, msg: msg.to_string() }).into()
+    }
+
+    #[test]
+    fn rate_limits_come_from_the_status_not_the_text() {
+        assert!(is_rate_limited(&ErrorKind::Msg("Received response: 429".to_string()).into()));
+        assert!(is_rate_limited(&ErrorKind::Msg("Received response: 418".to_string()).into()));
+        assert!(is_rate_limited(&api_error(-1003, "Too many requests")));
+        // Numbers elsewhere in the answer are not statuses
+        assert!(!is_rate_limited(&api_error(-1021, "Timestamp for this request was 4290ms ahead")));
+        assert!(!is_rate_limited(&api_error(-2010, "Order 418 would immediately match")));
+        assert!(!is_rate_limited(&ErrorKind::Msg("Received response: 503".to_string()).into()));
+    }
+}
@@ -0,0 +1,79 @@
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::collections::HashMap;
+use super::{base_asset, quote_asset_from_env};
+
+/// Difference between the balance implied by our own fills and the exchange's balance
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct BalanceDelta {
+    pub asset: String,
+    pub expected: f64,
+    pub actual: f64,
+    pub delta: f64,
+    pub delta_pct: f64,
+}
+
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct ReconciliationReport {
+    pub timestamp: u64,
+    pub deltas: Vec<BalanceDelta>,
+    pub within_tolerance: bool,
+}
+
+/// Tracks the balances we expect from the fills we sent and compares them with real balances
+pub struct BalanceReconciler {
+    expected: HashMap<String, f64>,
+    quote_asset: String,
+    pub tolerance_pct: f64,
+}
+
+impl BalanceReconciler {
+    pub fn new(tolerance_pct: f64, quote_asset: String) -> Self {
+        Self { expected: HashMap::new(), quote_asset, tolerance_pct }
+    }
+
+    /// Reads RECONCILE_TOLERANCE_PCT (default 1%) and QUOTE_ASSET from the environment
+    pub fn from_env() -> Self {
+        let tolerance_pct = std::env::var("RECONCILE_TOLERANCE_PCT")
+            .ok()
+            .and_then(|v| v.parse::<f64>().ok())
+            .unwrap_or(1.0);
+        Self::new(tolerance_pct, quote_asset_from_env())
+    }
+
+    /// Starts expectations from the given (real) balances
+    pub fn set_baseline(&mut self, balances: &[(String, f64)]) {
+        self.expected = balances.iter().cloned().collect();
+    }
+
+    pub fn has_baseline(&self) -> bool {
+        !self.expected.is_empty()
+    }
+
+    /// Applies a fill we believe happened. Fees are assumed to be paid in the quote asset.
+    pub fn record_fill(&mut self, symbol: &str, is_buy: bool, price: f64, quantity: f64, fee: f64) {
+        let notional = price * quantity;
+        let (quote_change, base_change) = if is_buy {
+            (-(notional + fee), quantity)
+        } else {
+            (notional - fee, -quantity)
+        };
+        *self.expected.entry(self.quote_asset.clone()).or_insert(0.0) += quote_change;
+        *self.expected.entry(base_asset(symbol, &self.quote_asset).to_string()).or_insert(0.0) += base_change;
+    }
+
+    /// Compares every expected asset with the real balances
+    pub fn reconcile(&self, actual: &[(String, f64)], timestamp: u64) -> ReconciliationReport {
+        let actual: HashMap<&str, f64> = actual.iter().map(|(asset, v)| (asset.as_str(), *v)).collect();
+        let mut deltas: Vec<BalanceDelta> = self.expected.iter().map(|(asset, &expected)| {
+            let actual = actual.get(asset.as_str()).copied().unwrap_or(0.0);
+            let delta = actual - expected;
+            let delta_pct = if expected.abs() > 1e-12 { delta / expected.abs() * 100.0 } else if delta.abs() > 1e-8 { 100.0 } else { 0.0 };
+            BalanceDelta { asset: asset.clone(), expected, actual, delta, delta_pct }
+        }).collect();
+        deltas.sort_by(|a, b| a.asset.cmp(&b.asset));
+
+        let within_tolerance = deltas.iter().all(|d| d.delta_pct.abs() <= self.tolerance_pct);
+        ReconciliationReport { timestamp, deltas, within_tolerance }
+    }
+}
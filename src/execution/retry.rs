@@ -0,0 +1,122 @@
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+const MAX_BACKOFF: Duration = Duration::from_secs(2);
+
+/// Longest one order may spend retrying or looking itself up. The spot worker serves every
+/// command on a single thread and sleeps between attempts, so nothing else reaches the
+/// exchange meanwhile.
+pub const MAX_RETRY_TIME: Duration = Duration::from_secs(5);
+
+/// How often a live market order is re-sent after a failure that never reached the exchange
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    /// Attempts in total, including the first; 1 disables retries. Also bounds the lookups of
+    /// an order whose outcome is unknown.
+    pub max_attempts: u32,
+    /// Delay before the first retry, doubling on each further one
+    pub backoff: Duration,
+}
+
+impl RetryPolicy {
+    /// Reads ORDER_RETRY_MAX_ATTEMPTS (default 3) and ORDER_RETRY_BACKOFF_MS (default 500)
+    pub fn from_env() -> Self {
+        let max_attempts = std::env::var("ORDER_RETRY_MAX_ATTEMPTS")
+            .ok()
+            .and_then(|v| v.parse::<u32>().ok())
+            .unwrap_or(3)
+            .max(1);
+        let backoff_ms = std::env::var("ORDER_RETRY_BACKOFF_MS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(500);
+        Self { max_attempts, backoff: Duration::from_millis(backoff_ms) }
+    }
+
+    /// Delay before retry number `retry` (1-based)
+    pub fn backoff(&self, retry: u32) -> Duration {
+        (self.backoff * 2u32.saturating_pow(retry.saturating_sub(1))).min(MAX_BACKOFF)
+    }
+}
+
+/// The request never reached the exchange because the connection could not be made, so
+/// sending it again cannot duplicate the order
+pub fn is_unsent(error: &str) -> bool {
+    let lower = error.to_lowercase();
+    ["connecterror", "error(connect", "dns error", "connectionrefused", "connection refused"]
+        .iter()
+        .any(|marker| lower.contains(marker))
+}
+
+/// The exchange may or may not have executed the order: timeouts, -1006/-1007 and 5xx
+/// answers, which Binance documents as "execution status unknown". Such an order is looked
+/// up, never re-sent.
+pub fn is_status_unknown(error: &str) -> bool {
+    let lower = error.to_lowercase();
+    [
+        "timed out", "timeout", "internal server error", "service unavailable", "bad gateway",
+        "-1006", "-1007",
+    ]
+    .iter()
+    .any(|marker| lower.contains(marker))
+}
+
+/// Binance's answer when no order matches the queried id
+pub fn is_unknown_order(error: &str) -> bool {
+    error.contains("-2013")
+}
+
+/// Client order id shared by every attempt of one order, so the exchange-side lookup can tell
+/// whether an attempt that errored actually went through. Binance allows up to 36 characters.
+pub fn client_order_id() -> String {
+    static SEQ: AtomicU64 = AtomicU64::new(0);
+    let ms = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_millis();
+    format!("six-{}-{}", ms, SEQ.fetch_add(1, Ordering::Relaxed))
+}
+
+/// Retry counters since startup, shared between the worker thread and /api/status
+#[derive(Debug, Default)]
+pub struct RetryStats {
+    pub retries: AtomicU64,
+    pub recovered: AtomicU64,
+    pub exhausted: AtomicU64,
+    pub found_after_error: AtomicU64,
+    pub aborted_balance: AtomicU64,
+}
+
+impl RetryStats {
+    pub fn count(counter: &AtomicU64) {
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn status(&self, policy: &RetryPolicy) -> RetryStatus {
+        RetryStatus {
+            max_attempts: policy.max_attempts,
+            backoff_ms: policy.backoff.as_millis() as u64,
+            retries: self.retries.load(Ordering::Relaxed),
+            recovered: self.recovered.load(Ordering::Relaxed),
+            exhausted: self.exhausted.load(Ordering::Relaxed),
+            found_after_error: self.found_after_error.load(Ordering::Relaxed),
+            aborted_balance: self.aborted_balance.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Order retry counters for /api/status
+#[derive(Debug, Clone, Serialize, JsonSchema)]
+pub struct RetryStatus {
+    pub max_attempts: u32,
+    pub backoff_ms: u64,
+    /// Order attempts re-sent after a connection failure
+    pub retries: u64,
+    /// Orders that succeeded on a retry
+    pub recovered: u64,
+    /// Orders given up after the last attempt, or whose outcome stayed unknown
+    pub exhausted: u64,
+    /// Orders with an ambiguous error that a lookup found on the exchange
+    pub found_after_error: u64,
+    /// Retries abandoned because the balance re-check failed or no longer covered the order
+    pub aborted_balance: u64,
+}
@@ -5,15 +5,19 @@ mod state_machine;
 mod metrics;
 mod web;
 mod database;
+mod notify;
+mod trade_log;
+mod shadow;
 
 use dotenv::dotenv;
-use log::{info, error};
+use log::{info, warn, error};
 use tokio::sync::{mpsc, RwLock};
 use std::sync::Arc;
 
-use execution::{ExecutionManager, Executor};
-use market_data::{MarketDataManager, MarketEvent, backtest::BacktestDataManager, DataFilter};
-use strategy::{PaperTrader, MeanReversionStrategy, TradingStrategy};
+use execution::{ExecutionManager, Executor, FeeSchedule, SlippageModel};
+use market_data::{MarketDataManager, MarketEvent, backtest::BacktestDataManager, DataFilter, LastPriceCache};
+use strategy::{PaperTrader, TradingStrategy, BreakerAction, Signal};
+use state_machine::SystemState;
 use web::{AppState, start_server};
 
 fn main() {
@@ -39,10 +43,28 @@ fn main() {
     // 1. Initial configuration
     let run_mode = std::env::var("RUN_MODE").unwrap_or_else(|_| "live".to_string());
     let is_simulation = run_mode == "backtest";
-    let symbol = "BTCUSDT".to_string();
+    // Comma-separated list, e.g. SYMBOLS=BTCUSDT,ETHUSDT,SOLUSDT. The first one drives the dashboard.
+    let symbols: Vec<String> = std::env::var("SYMBOLS")
+        .unwrap_or_else(|_| "BTCUSDT".to_string())
+        .split(',')
+        .map(|s| s.trim().to_uppercase())
+        .filter(|s| !s.is_empty())
+        .collect();
+    let symbols = if symbols.is_empty() { vec!["BTCUSDT".to_string()] } else { symbols };
+    let symbol = symbols[0].clone();
+    info!("Trading symbols: {:?}", symbols);
 
     // 2. Initialize blocking components early (outside tokio)
-    let execution_manager = ExecutionManager::new(is_simulation);
+    let fees = FeeSchedule::from_env();
+    info!("Fee schedule: maker {:.4}%, taker {:.4}%", fees.maker * 100.0, fees.taker * 100.0);
+    let market_type = match std::env::var("MARKET_TYPE").unwrap_or_default().to_uppercase().as_str() {
+        "FUTURES" => market_data::downloader::MarketType::Futures,
+        _ => market_data::downloader::MarketType::Spot,
+    };
+    info!("Market type: {}", market_type.as_str());
+    let slippage = SlippageModel::from_env();
+    info!("Simulated slippage model: {:?}", slippage);
+    let execution_manager = ExecutionManager::new(is_simulation, fees, market_type).with_slippage(slippage);
     let executor = Arc::new(execution_manager);
 
     // 3. Create the multi-thread Runtime and LEAK IT
@@ -57,13 +79,23 @@ fn main() {
         
         // ... (Database, Shared State, Channels, Web Server as before) ...
         let pool = database::establish_connection().await;
-        if let Err(e) = sqlx::migrate!("./migrations").run(&pool).await {
-            error!("Database migration failed: {}", e);
-        }
+        let migrations_applied = match sqlx::migrate!("./migrations").run(&pool).await {
+            Ok(()) => true,
+            Err(e) => {
+                error!("Database migration failed: {}", e);
+                false
+            }
+        };
 
-        let mut strategy: Box<dyn TradingStrategy> = Box::new(PaperTrader::new());
-        let strategy_name = strategy.name().to_string();
-        let (tx, mut rx) = mpsc::channel(100);
+        let strategy_name = PaperTrader::new().name().to_string();
+        let mut queue_policy = market_data::BackpressurePolicy::from_env();
+        if is_simulation && queue_policy != market_data::BackpressurePolicy::Block {
+            warn!("MARKET_QUEUE_POLICY ignored in backtest mode: every event is replayed");
+            queue_policy = market_data::BackpressurePolicy::Block;
+        }
+        let queue_size = market_data::queue::queue_size_from_env();
+        info!("Market event queue: {} events, {:?} when full", queue_size, queue_policy);
+        let (tx, mut rx) = market_data::event_queue(queue_size, queue_policy);
         let shared_state = Arc::new(RwLock::new(AppState::new(
             run_mode.clone(), 
             strategy_name, 
@@ -73,6 +105,37 @@ fn main() {
             tx.clone()
         )));
 
+        // One strategy instance per symbol
+        {
+            let mut write_guard = shared_state.write().await;
+            write_guard.migrations_applied = migrations_applied;
+            let name = write_guard.strategy_name.clone();
+            write_guard.symbols = symbols.clone();
+            for sym in &symbols {
+                write_guard.strategies.insert(sym.clone(), create_strategy(&name));
+            }
+        }
+
+        let notifier = notify::from_env();
+        let trade_log = trade_log::TradeLog::from_env();
+
+        // Every strategy also runs on the live feed against its own simulated account
+        let shadow_tx = if shadow::enabled_from_env() {
+            let (shadow_tx, shadow_rx) = mpsc::channel(shadow::SHADOW_QUEUE_SIZE);
+            let reports = {
+                let mut write_guard = shared_state.write().await;
+                write_guard.shadow_enabled = true;
+                write_guard.shadow_reports.clone()
+            };
+            let runner = shadow::ShadowRunner::new(pool.clone(), fees, market_type, reports);
+            tokio::spawn(runner.run(shadow_rx));
+            info!("Shadow strategies enabled");
+            Some(shadow_tx)
+        } else {
+            None
+        };
+        let mut shadow_dropped: u64 = 0;
+
         let web_state = shared_state.clone();
         tokio::spawn(async move {
             start_server(3000, web_state).await;
@@ -80,11 +143,13 @@ fn main() {
 
         // Ensure 6 hours of historical data for backtesting
         let downloader_pool = pool.clone();
-        let downloader_symbol = symbol.clone();
+        let downloader_symbols = symbols.clone();
         tokio::spawn(async move {
             let downloader = market_data::HistoricalDownloader::new(downloader_pool);
-            if let Err(e) = downloader.ensure_data(&downloader_symbol, market_data::downloader::MarketType::Spot, 6).await {
-                error!("Historical data download failed: {}", e);
+            for downloader_symbol in downloader_symbols {
+                if let Err(e) = downloader.ensure_data(&downloader_symbol, market_data::downloader::MarketType::Spot, 6).await {
+                    error!("Historical data download failed for {}: {}", downloader_symbol, e);
+                }
             }
         });
 
@@ -101,46 +166,251 @@ fn main() {
             });
         } else {
             info!("RUNNING IN LIVE MODE");
-            let market_data = MarketDataManager::new(symbol.clone(), tx.clone());
+            let market_data = MarketDataManager::new(symbols.clone(), tx.clone());
+            shared_state.write().await.connection_health = market_data.health();
             market_data.connect().await;
             Box::leak(Box::new(market_data));
         }
 
-        // Background Cleanup Task (runs once an hour)
+        // Background Cleanup Task (runs at the top of every hour), also rolls the daily PnL session over
         let cleanup_pool = pool.clone();
+        let cleanup_state = shared_state.clone();
+        let retention = database::repository::RetentionPolicy::from_env();
+        info!("Data retention: spot {}h, futures {}h", retention.spot_hours, retention.futures_hours);
         tokio::spawn(async move {
             loop {
-                tokio::time::sleep(tokio::time::Duration::from_secs(3600)).await;
-                match database::repository::cleanup_old_data(&cleanup_pool, 24).await {
-                    Ok(affected) => info!("Cleaned up {} old records from database", affected),
+                let now_secs = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs();
+                tokio::time::sleep(tokio::time::Duration::from_secs(3600 - now_secs % 3600)).await;
+
+                let now_secs = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs();
+                {
+                    let mut write_guard = cleanup_state.write().await;
+                    if write_guard.daily_session_at(now_secs) != write_guard.daily_session {
+                        info!("Daily session closed: PnL {:+.2} over {} trades from ${:.2}", write_guard.daily_pnl, write_guard.daily_trades, write_guard.daily_start_value);
+                        let start_value = write_guard.total_value();
+                        write_guard.reset_daily(now_secs, start_value);
+                    }
+                }
+
+                match database::repository::cleanup_old_data(&cleanup_pool, &retention).await {
+                    Ok(stats) => info!("Cleaned up {} old trades and {} old order books from database", stats.trades, stats.order_books),
                     Err(e) => error!("Database cleanup failed: {}", e),
                 }
             }
         });
 
+        // Funding task for perpetual futures: refresh rates every 5 minutes,
+        // settle simulated positions at each 8-hour funding boundary
+        if market_type == market_data::downloader::MarketType::Futures {
+            let funding_state = shared_state.clone();
+            let funding_executor = executor.clone();
+            let funding_symbols = symbols.clone();
+            tokio::spawn(async move {
+                const FUNDING_INTERVAL_SECS: u64 = 8 * 3600;
+                let now_secs = || std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs();
+                let mut last_funding_period = now_secs() / FUNDING_INTERVAL_SECS;
+                loop {
+                    for sym in &funding_symbols {
+                        match funding_executor.get_funding_rate(sym).await {
+                            Ok(rate) => { funding_state.write().await.funding_rates.insert(sym.clone(), rate); }
+                            Err(e) => error!("Failed to fetch funding rate for {}: {}", sym, e),
+                        }
+                    }
+
+                    let funding_period = now_secs() / FUNDING_INTERVAL_SECS;
+                    if funding_period != last_funding_period {
+                        last_funding_period = funding_period;
+                        for sym in &funding_symbols {
+                            let (rate, price) = {
+                                let read_guard = funding_state.read().await;
+                                (read_guard.funding_rates.get(sym).copied(), read_guard.price_cache.mark_price(sym))
+                            };
+                            if let (Some(rate), Some(price)) = (rate, price) {
+                                if let Ok(funding_pnl) = funding_executor.apply_funding(sym, rate, price).await {
+                                    if funding_pnl != 0.0 {
+                                        funding_state.write().await.record_realized_pnl(funding_pnl);
+                                    }
+                                }
+                            }
+                        }
+                    }
+                    tokio::time::sleep(tokio::time::Duration::from_secs(300)).await;
+                }
+            });
+        }
+
+        // Graceful shutdown on Ctrl+C: stop trading, optionally pull resting orders,
+        // stop the workers and let in-flight DB writes finish before exiting
+        let shutdown_state = shared_state.clone();
+        let shutdown_executor = executor.clone();
+        let shutdown_pool = pool.clone();
+        let shutdown_writes = shared_state.read().await.db_writes.clone();
+        let shutdown_symbols = symbols.clone();
+        let cancel_on_shutdown = std::env::var("CANCEL_ORDERS_ON_SHUTDOWN")
+            .map(|v| v != "false" && v != "0")
+            .unwrap_or(true);
+        tokio::spawn(async move {
+            if let Err(e) = tokio::signal::ctrl_c().await {
+                error!("Failed to listen for Ctrl+C: {}", e);
+                return;
+            }
+            info!("Ctrl+C received. Shutting down...");
+            {
+                let mut write_guard = shutdown_state.write().await;
+                write_guard.is_trading = false;
+                write_guard.trading_armed.store(false, std::sync::atomic::Ordering::SeqCst);
+            }
+
+            if cancel_on_shutdown {
+                for sym in &shutdown_symbols {
+                    match shutdown_executor.cancel_all_orders(sym).await {
+                        Ok(()) => info!("Cancelled open orders for {}", sym),
+                        Err(e) => error!("Failed to cancel open orders for {}: {}", sym, e),
+                    }
+                }
+            }
+            shutdown_executor.shutdown();
+
+            // Queued writes still need the pool, so let them finish before closing it
+            if !shutdown_writes.drain(std::time::Duration::from_secs(10)).await {
+                warn!("{} database writes still running after 10s; closing the pool anyway", shutdown_writes.in_flight());
+            }
+            // close() waits for checked-out connections, so journal writes already in flight complete
+            shutdown_pool.close().await;
+            info!("Database pool closed. Clean exit.");
+            std::process::exit(0);
+        });
+
         info!("System core initialized. Processing events...");
-        
+
+        // Seed valuation prices so the starting value doesn't depend on the first trade arriving.
+        // A backtest prices everything from its own replayed trades.
+        if !is_simulation {
+            let price_cache = shared_state.read().await.price_cache.clone();
+            info!("Valuation price source: {:?}", price_cache.source());
+            match executor.get_mark_prices(&symbols).await {
+                Ok(prices) => {
+                    for (sym, price) in prices {
+                        price_cache.update_mark(&sym, price);
+                        if price_cache.get(&sym).is_none() {
+                            price_cache.update(&sym, price);
+                        }
+                    }
+                }
+                Err(e) => warn!("Failed to seed valuation prices: {}", e),
+            }
+
+            // Mark prices have no stream here, so poll them
+            if price_cache.source() == market_data::PriceSource::Mark {
+                let mark_executor = executor.clone();
+                let mark_symbols = symbols.clone();
+                let refresh_secs = std::env::var("MARK_PRICE_REFRESH_SECS")
+                    .ok()
+                    .and_then(|v| v.parse::<u64>().ok())
+                    .unwrap_or(10)
+                    .max(1);
+                tokio::spawn(async move {
+                    loop {
+                        tokio::time::sleep(tokio::time::Duration::from_secs(refresh_secs)).await;
+                        match mark_executor.get_mark_prices(&mark_symbols).await {
+                            Ok(prices) => {
+                                for (sym, price) in prices {
+                                    price_cache.update_mark(&sym, price);
+                                }
+                            }
+                            Err(e) => warn!("Failed to refresh mark prices: {}", e),
+                        }
+                    }
+                });
+            }
+        }
+
+        // Rebuild the FIFO lots from the spot fills of earlier runs
+        if !is_simulation {
+            match database::repository::get_cost_basis_fills(&pool).await {
+                Ok(fills) => {
+                    let mut write_guard = shared_state.write().await;
+                    for fill in &fills {
+                        write_guard.cost_basis.record_fill(&fill.symbol, fill.is_buy, fill.timestamp, fill.price, fill.quantity, fill.fee);
+                    }
+                    info!("Cost basis rebuilt from {} recorded fills", fills.len());
+                }
+                Err(e) => error!("Failed to load cost basis fills: {}", e),
+            }
+        }
+
         // Initialize initial balance for yield calculation
         {
             let balances = executor.get_balances().await.unwrap_or_default();
-            let usdt = balances.iter().find(|(k, _)| k == "USDT").map(|(_, v)| *v).unwrap_or(0.0);
-            let btc = balances.iter().find(|(k, _)| k == "BTC").map(|(_, v)| *v).unwrap_or(0.0);
-            let btc_price = 88000.0; // Same estimate used in snapshots
-            let starting_value = usdt + btc * btc_price;
             let mut write_guard = shared_state.write().await;
+            let starting_value = write_guard.price_cache.portfolio_value(&balances);
             write_guard.initial_balance = starting_value;
+            let now_secs = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs();
+            write_guard.reset_daily(now_secs, starting_value);
             info!("Initial portfolio value set to ${:.2} for yield tracking", starting_value);
+            if !is_simulation {
+                write_guard.reconciler.set_baseline(&balances);
+            }
+        }
+
+        // Live balance reconciliation: compare the balances implied by our fills with the exchange
+        if !is_simulation {
+            let reconcile_state = shared_state.clone();
+            let reconcile_executor = executor.clone();
+            let interval_mins = std::env::var("RECONCILE_INTERVAL_MINS")
+                .ok()
+                .and_then(|v| v.parse::<u64>().ok())
+                .unwrap_or(5)
+                .max(1);
+            tokio::spawn(async move {
+                loop {
+                    tokio::time::sleep(tokio::time::Duration::from_secs(interval_mins * 60)).await;
+                    let balances = match reconcile_executor.get_balances().await {
+                        Ok(b) if !b.is_empty() => b,
+                        Ok(_) => continue,
+                        Err(e) => {
+                            error!("Reconciliation balance fetch failed: {}", e);
+                            continue;
+                        }
+                    };
+                    let mut write_guard = reconcile_state.write().await;
+                    if !write_guard.reconciler.has_baseline() {
+                        write_guard.reconciler.set_baseline(&balances);
+                        continue;
+                    }
+                    let ts = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_millis() as u64;
+                    let report = write_guard.reconciler.reconcile(&balances, ts);
+                    if !report.within_tolerance {
+                        for d in report.deltas.iter().filter(|d| d.delta_pct.abs() > write_guard.reconciler.tolerance_pct) {
+                            warn!("Balance drift on {}: expected {:.6}, actual {:.6} ({:+.2}%)", d.asset, d.expected, d.actual, d.delta_pct);
+                        }
+                    }
+                    write_guard.last_reconciliation = Some(report);
+                }
+            });
         }
 
         let mut event_count = 0;
-        let mut data_filter = DataFilter::new(0.05); // 5% outlier threshold
+        // DataFilter tracks ids/prices per symbol so different markets don't collide
+        let mut data_filter = DataFilter::from_env();
+        info!("Data outlier threshold: {:.2}%", data_filter.outlier_threshold() * 100.0);
+        let price_cache: Arc<LastPriceCache> = shared_state.read().await.price_cache.clone();
+        let db_writes = shared_state.read().await.db_writes.clone();
+        let mut candle_aggregator = market_data::CandleAggregator::new(market_data::CANDLE_INTERVAL_SECS);
+        // Only fed when LOCAL_ORDER_BOOK=true subscribes to depth diffs
+        let mut local_books: std::collections::HashMap<String, market_data::LocalOrderBook> = std::collections::HashMap::new();
+        // Snapshots are fetched off the event loop and handed back here with their symbol
+        let (snapshot_tx, mut snapshot_rx) = mpsc::unbounded_channel::<(String, Result<binance::model::OrderBook, String>)>();
 
         // Main Processing Loop
         while let Some(event) = rx.recv().await {
+            let event_symbol = event.symbol().to_string();
+
             // Check for data quality
             if !data_filter.should_process(&event) {
                 let mut write_guard = shared_state.write().await;
                 write_guard.data_quality_score = data_filter.get_quality_score();
+                write_guard.symbol_data_quality = data_filter.get_quality_score_for(&write_guard.symbol);
                 continue;
             }
             
@@ -148,53 +418,206 @@ fn main() {
             if event_count % 100 == 0 {
                 let mut write_guard = shared_state.write().await;
                 write_guard.data_quality_score = data_filter.get_quality_score();
+                write_guard.symbol_data_quality = data_filter.get_quality_score_for(&write_guard.symbol);
+            }
+
+            // Shadows must never slow the live loop, so drop events when they fall behind
+            if let Some(shadow_tx) = &shadow_tx
+                && shadow_tx.try_send(event.clone()).is_err() {
+                shadow_dropped += 1;
+                if shadow_dropped % 1000 == 1 {
+                    warn!("Shadow strategies falling behind: {} events dropped", shadow_dropped);
+                }
             }
 
             // Check for strategy change
             {
-                let current_name = shared_state.read().await.strategy_name.clone();
-                if strategy.name() != current_name {
-                    info!("Swapping strategy from {} to {}", strategy.name(), current_name);
-                    strategy = match current_name.as_str() {
-                        "MeanReversion" => Box::new(MeanReversionStrategy::new()),
-                        _ => Box::new(PaperTrader::new()),
-                    };
+                let mut write_guard = shared_state.write().await;
+                let current_name = write_guard.strategy_name.clone();
+                let mut carried = std::mem::take(&mut write_guard.carried_positions);
+                for sym in write_guard.symbols.clone() {
+                    let needs_swap = write_guard.strategies.get(&sym).map_or(true, |s| s.name() != current_name);
+                    if needs_swap {
+                        info!("Swapping {} strategy to {}", sym, current_name);
+                        let mut next = create_strategy(&current_name);
+                        let now_ms = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_millis() as u64;
+                        for position in carried.iter().filter(|p| p.symbol == sym) {
+                            if next.adopt_position(position, now_ms) {
+                                info!("{} adopted carried {} {} {} @ {:.4}", current_name, position.side, position.amount, sym, position.entry_price);
+                            } else {
+                                warn!("{} cannot manage carried {} {} {}; close it manually", current_name, position.side, position.amount, sym);
+                            }
+                        }
+                        carried.retain(|p| p.symbol != sym);
+                        write_guard.strategies.insert(sym, next);
+                    }
+                }
+                for position in carried {
+                    warn!("Carried {} position on {} has no running strategy; close it manually", position.side, position.symbol);
                 }
             }
 
+            // Take this symbol's strategy out of the shared state while it runs,
+            // since strategies lock the state themselves
+            let (mut strategy, order_events) = {
+                let mut write_guard = shared_state.write().await;
+                match write_guard.strategies.remove(&event_symbol) {
+                    Some(s) => (s, write_guard.order_events.remove(&event_symbol).unwrap_or_default()),
+                    None => continue,
+                }
+            };
+            for order_event in &order_events {
+                strategy.on_order_event(order_event);
+            }
+
             event_count += 1;
             if event_count % 100 == 0 {
                 info!("Main Loop Heartbeat: Received {} events so far.", event_count);
             }
 
-            let opportunities = match event {
+            let mut opportunities = match event {
                 MarketEvent::Trade(ref trade) => {
                     let pool_clone = pool.clone();
                     let trade_clone = trade.clone();
-                    tokio::spawn(async move {
+                    db_writes.spawn(async move {
                         let _ = database::repository::save_trade(&pool_clone, &trade_clone, "SPOT").await;
                     });
-                    strategy.process_trade(trade.clone(), shared_state.clone()).await
+                    // Let resting limit orders fill against the latest trade price
+                    if let Ok(price) = trade.price.parse::<f64>() {
+                        price_cache.update(&trade.symbol, price);
+                        let qty = trade.qty.parse::<f64>().unwrap_or(0.0);
+                        if let Ok(updates) = executor.on_trade_price(&trade.symbol, price, qty, trade.event_time).await {
+                            for trigger in updates.triggered {
+                                let _ = web::LIVE_TX.send(web::LiveEvent::StopTriggered(trigger));
+                            }
+                            let fill_pnl: f64 = updates.fills.iter().map(|f| f.realized_pnl).sum();
+                            if !updates.fills.is_empty() {
+                                let spot = executor.market_type() == market_data::downloader::MarketType::Spot;
+                                let mut write_guard = shared_state.write().await;
+                                for fill in &updates.fills {
+                                    // Live limit fills move the expected balances like market fills do
+                                    if write_guard.trading_armed.load(std::sync::atomic::Ordering::SeqCst) && write_guard.reconciler.has_baseline() {
+                                        write_guard.reconciler.record_fill(&fill.symbol, fill.side == "Buy", fill.price, fill.quantity, fill.fee);
+                                    }
+                                    if spot {
+                                        write_guard.cost_basis.record_fill(&fill.symbol, fill.side == "Buy", trade.event_time, fill.price, fill.quantity, fill.fee);
+                                        if !is_simulation {
+                                            let pool_clone = pool.clone();
+                                            let basis_fill = database::repository::CostBasisFill {
+                                                timestamp: trade.event_time,
+                                                symbol: fill.symbol.clone(),
+                                                is_buy: fill.side == "Buy",
+                                                price: fill.price,
+                                                quantity: fill.quantity,
+                                                fee: fill.fee,
+                                            };
+                                            db_writes.spawn(async move {
+                                                if let Err(e) = database::repository::save_cost_basis_fill(&pool_clone, &basis_fill).await {
+                                                    error!("Failed to persist cost basis fill: {}", e);
+                                                }
+                                            });
+                                        }
+                                    }
+                                    write_guard.record_strategy_fill(strategy.name(), &fill.symbol, fill.side == "Buy", fill.quantity);
+                                    strategy.on_order_event(&strategy::OrderEvent::from(fill));
+                                }
+                            }
+                            if fill_pnl != 0.0 {
+                                let mut write_guard = shared_state.write().await;
+                                write_guard.record_realized_pnl(fill_pnl);
+                                write_guard.record_strategy_pnl(strategy.name(), fill_pnl);
+                            }
+                        }
+                    }
+                    {
+                        let mut write_guard = shared_state.write().await;
+                        write_guard.state_machine.on_market_event(trade.event_time);
+                        write_guard.state_machine.on_price(&trade.symbol, trade.price.parse::<f64>().unwrap_or(0.0), trade.event_time);
+                    }
+                    let mut opps = strategy.process_trade(trade.clone(), shared_state.clone()).await;
+                    let price = trade.price.parse::<f64>().unwrap_or(0.0);
+                    let qty = trade.qty.parse::<f64>().unwrap_or(0.0);
+                    if let Some(candle) = candle_aggregator.update(&trade.symbol, price, qty, trade.event_time) {
+                        opps.extend(strategy.process_candle(candle, shared_state.clone()).await);
+                    }
+                    opps
                 }
                 MarketEvent::AggrTrade(ref agg) => {
                     let pool_clone = pool.clone();
                     let agg_clone = agg.clone();
-                    tokio::spawn(async move {
+                    db_writes.spawn(async move {
                         let _ = database::repository::save_aggr_trade(&pool_clone, &agg_clone, "SPOT").await;
                     });
+                    if let Ok(price) = agg.price.parse::<f64>() {
+                        price_cache.update(&agg.symbol, price);
+                    }
+                    shared_state.write().await.state_machine.on_market_event(agg.event_time);
                     strategy.process_aggr_trade(agg.clone(), shared_state.clone()).await
                 }
-                MarketEvent::OrderBook(ref book) => {
+                MarketEvent::OrderBook(ref book_symbol, ref book) => {
                     let pool_clone = pool.clone();
                     let book_clone = book.clone();
-                    let symbol_clone = symbol.clone();
-                    tokio::spawn(async move {
+                    let symbol_clone = book_symbol.clone();
+                    if let (Some(bid), Some(ask)) = (book.bids.first(), book.asks.first()) {
+                        price_cache.update_mid(book_symbol, (bid.price + ask.price) / 2.0);
+                    }
+                    db_writes.spawn(async move {
                         let _ = database::repository::save_order_book(&pool_clone, &symbol_clone, &book_clone, "SPOT").await;
                     });
                     strategy.process_orderbook(book.clone(), shared_state.clone()).await
                 }
-                MarketEvent::DepthUpdate(_) => Vec::new(),
+                MarketEvent::DepthUpdate(ref depth) => {
+                    while let Ok((snapshot_symbol, result)) = snapshot_rx.try_recv() {
+                        let Some(book) = local_books.get_mut(&snapshot_symbol) else { continue };
+                        match result {
+                            Ok(snapshot) => {
+                                info!("Order book snapshot for {} at update {}", snapshot_symbol, snapshot.last_update_id);
+                                book.apply_snapshot(&snapshot);
+                            }
+                            Err(e) => {
+                                warn!("Order book snapshot for {} failed: {}", snapshot_symbol, e);
+                                book.snapshot_failed();
+                            }
+                        }
+                    }
+                    let book = local_books.entry(depth.symbol.clone())
+                        .or_insert_with(|| market_data::LocalOrderBook::new(&depth.symbol));
+                    if book.should_fetch_snapshot() {
+                        let symbol_clone = depth.symbol.clone();
+                        let snapshot_tx = snapshot_tx.clone();
+                        tokio::spawn(async move {
+                            let fetch_symbol = symbol_clone.clone();
+                            let result = tokio::task::spawn_blocking(move || market_data::LocalOrderBook::fetch_snapshot(&fetch_symbol))
+                                .await
+                                .unwrap_or_else(|e| Err(format!("snapshot task failed: {}", e)));
+                            let _ = snapshot_tx.send((symbol_clone, result));
+                        });
+                    }
+                    match book.apply_diff(depth) {
+                        market_data::DiffResult::Applied => {
+                            let synthesized = book.top(market_data::order_book::EMIT_LEVELS);
+                            let pool_clone = pool.clone();
+                            let book_clone = synthesized.clone();
+                            let symbol_clone = depth.symbol.clone();
+                            if let (Some(bid), Some(ask)) = (synthesized.bids.first(), synthesized.asks.first()) {
+                                price_cache.update_mid(&depth.symbol, (bid.price + ask.price) / 2.0);
+                            }
+                            db_writes.spawn(async move {
+                                let _ = database::repository::save_order_book(&pool_clone, &symbol_clone, &book_clone, "SPOT").await;
+                            });
+                            strategy.process_orderbook(synthesized, shared_state.clone()).await
+                        }
+                        market_data::DiffResult::Stale | market_data::DiffResult::OutOfSync => Vec::new(),
+                    }
+                }
             };
+            let strategy_name = strategy.name().to_string();
+            {
+                let mut write_guard = shared_state.write().await;
+                write_guard.strategies.insert(event_symbol.clone(), strategy);
+                write_guard.assign_opportunity_ids(&mut opportunities);
+                write_guard.last_update_ts = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs();
+            }
 
             // Record portfolio value snapshot for chart (every 5 seconds)
             let now_ts = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs();
@@ -204,30 +627,68 @@ fn main() {
             };
 
             if should_snapshot {
-                let (usdt, btc) = {
+                let balances = {
                     let write_guard = shared_state.write().await;
-                    let balances = write_guard.executor.get_balances().await.unwrap_or_default();
-                    let usdt = balances.iter().find(|(k, _)| k == "USDT").map(|(_, v)| *v).unwrap_or(0.0);
-                    let btc = balances.iter().find(|(k, _)| k == "BTC").map(|(_, v)| *v).unwrap_or(0.0);
-                    (usdt, btc)
+                    write_guard.executor.get_balances().await.unwrap_or_default()
                 };
                 
-                // Estimate total value using approximate BTC price (will improve with market data)
-                let btc_price = 88000.0;
-                let total_value = usdt + btc * btc_price;
+                // Value every balance at its last traded price
+                let total_value = price_cache.portfolio_value(&balances);
                 
                 let mut write_guard = shared_state.write().await;
                 write_guard.push_portfolio_snapshot(total_value);
                 write_guard.last_portfolio_snapshot_ts = now_ts;
+
+                let pool_clone = pool.clone();
+                let symbol_clone = symbol.clone();
+                db_writes.spawn(async move {
+                    if let Err(e) = database::repository::save_portfolio_snapshot(&pool_clone, now_ts, total_value, &symbol_clone).await {
+                        error!("Failed to persist portfolio snapshot: {}", e);
+                    }
+                });
+            }
+
+            // Halt trading on excessive drawdown, resume after the cooldown
+            {
+                let mut write_guard = shared_state.write().await;
+                let (pnl, initial_balance) = (write_guard.realized_pnl, write_guard.initial_balance);
+                if write_guard.is_trading || write_guard.circuit_breaker.is_tripped() {
+                    match write_guard.circuit_breaker.check(pnl, initial_balance) {
+                        BreakerAction::Trip => {
+                            let drawdown_pct = write_guard.circuit_breaker.status().drawdown_pct;
+                            let max_drawdown_pct = write_guard.circuit_breaker.max_drawdown_pct;
+                            warn!("Circuit breaker TRIPPED: drawdown {:.2}% exceeds {:.2}%. Trading halted.", drawdown_pct, max_drawdown_pct);
+                            write_guard.is_trading = false;
+                            notify::send(&notifier, notify::NotifyEvent::CircuitBreakerTripped { drawdown_pct, max_drawdown_pct, timestamp: now_ts * 1000 });
+                        }
+                        BreakerAction::Resume => {
+                            info!("Circuit breaker cooldown elapsed. Resuming trading.");
+                            write_guard.is_trading = true;
+                            write_guard.state_machine.transition_to(SystemState::Trading);
+                        }
+                        BreakerAction::None => {}
+                    }
+                }
+                // Strategies move the state on their own, so keep it pinned while halted
+                if write_guard.circuit_breaker.is_tripped() {
+                    write_guard.state_machine.transition_to(SystemState::Cooldown);
+                }
             }
 
             // Check if trading is allowed before processing opportunities
-            let is_trading = shared_state.read().await.is_trading;
+            let (is_trading, warming_up) = {
+                let read_guard = shared_state.read().await;
+                (read_guard.is_trading, read_guard.state_machine.is_warming_up())
+            };
 
-            if !opportunities.is_empty() && is_trading {
+            if !opportunities.is_empty() && is_trading && !warming_up {
                 info!("Strategy generated {} opportunities", opportunities.len());
+                let open_positions = executor.get_positions().await
+                    .map(|positions| positions.iter().filter(|p| p.amount > 0.0).count())
+                    .unwrap_or(0);
                 let mut write_guard = shared_state.write().await;
-                let (processed_opps, risk_report) = strategy::RiskManager::analyze_opportunities(&opportunities, &write_guard);
+                write_guard.open_positions = open_positions;
+                let (processed_opps, risk_report) = strategy::RiskManager::analyze_opportunities(&opportunities, &strategy_name, &write_guard);
                 
                 write_guard.current_opportunities = processed_opps.clone();
                 write_guard.risk_report = risk_report;
@@ -241,21 +702,146 @@ fn main() {
                     
                     info!("RiskManager selected trade: {:?}", selected_id);
                     write_guard.selected_opportunity_id = selected_id;
-                    write_guard.total_trades += 1;
+
+                    // Rewrite the entry size with (scaled) Kelly once there is enough trade history
+                    let mut sig_clone = sig.clone();
+                    if let Some(kelly) = strategy::RiskManager::kelly_from_state(&write_guard) {
+                        let account_value = write_guard.total_value();
+                        if let Some(sized) = strategy::RiskManager::size_position(&sig_clone, account_value, kelly) {
+                            if sized <= 0.0 {
+                                info!("Kelly fraction is zero (no edge). Skipping entry.");
+                                continue;
+                            }
+                            info!("Kelly sizing: fraction={:.4}, quantity={:.6}", kelly, sized);
+                            sig_clone.set_quantity(sized);
+                        }
+                    }
+                    // Kelly may have grown the entry past the notional cap again
+                    if let (Some(max_notional), Some(price)) = (write_guard.max_notional_usd, strategy::RiskManager::reference_price(&sig_clone, &write_guard))
+                        && strategy::RiskManager::cap_notional(&mut sig_clone, price, max_notional) {
+                        info!("Entry capped at {:.2} USD notional", max_notional);
+                    }
+                    if let (Some(headroom), Some(price)) = (strategy::RiskManager::allocation_headroom(&write_guard, &strategy_name), strategy::RiskManager::reference_price(&sig_clone, &write_guard))
+                        && strategy::RiskManager::cap_notional(&mut sig_clone, price, headroom) {
+                        info!("{} entry capped at its remaining {:.2} USD allocation", strategy_name, headroom);
+                    }
+                    // A SellAll only closes what this strategy bought, not the rest of the account
+                    let owned = write_guard.strategy_holding(&strategy_name, sig_clone.symbol()).max(0.0);
+                    // Hold the entry's notional against the allocation until the exchange answers,
+                    // so entries sent in quick succession cannot all pass the same headroom check
+                    let reserved = match &sig_clone {
+                        Signal::Buy { quantity, .. } | Signal::LimitBuy { quantity, .. } => {
+                            strategy::RiskManager::reference_price(&sig_clone, &write_guard).map_or(0.0, |price| quantity * price)
+                        }
+                        _ => 0.0,
+                    };
+                    write_guard.reserve_notional(&strategy_name, reserved);
 
                     let executor_clone = executor.clone();
                     let shared_state_clone = shared_state.clone();
-                    let sig_clone = sig.clone();
+                    let strategy_name = strategy_name.clone();
+                    let journal_pool = pool.clone();
+                    let notifier = notifier.clone();
+                    let trade_log = trade_log.clone();
                     tokio::spawn(async move {
                         let start_exec = std::time::Instant::now();
-                        match executor_clone.execute(sig_clone).await {
-                            Ok(pnl) => {
+                        let journal_signal = sig_clone.clone();
+                        match executor_clone.execute_owned(sig_clone, Some(owned)).await {
+                            Ok(execution::Execution { not_sent: Some(reason), .. }) => {
+                                info!("{:?} not sent: {:?}", journal_signal, reason);
+                            }
+                            Ok(execution) => {
+                                let (pnl, fill) = (execution.pnl, execution.filled());
+                                {
+                                    let mut write_guard = shared_state_clone.write().await;
+                                    write_guard.total_trades += 1;
+                                    write_guard.daily_trades += 1;
+                                    if let Some(order_event) = strategy::OrderEvent::from_execution(&journal_signal, &execution) {
+                                        write_guard.order_events.entry(journal_signal.symbol().to_string()).or_default().push(order_event);
+                                    }
+                                }
+                                let exec_ts = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_millis() as u64;
+                                let _ = web::LIVE_TX.send(web::LiveEvent::Execution {
+                                    strategy: strategy_name.clone(),
+                                    signal: journal_signal.clone(),
+                                    pnl,
+                                    timestamp: exec_ts,
+                                });
+                                if !matches!(journal_signal, Signal::Cancel { .. }) {
+                                    notify::send(&notifier, notify::NotifyEvent::TradeExecuted {
+                                        strategy: strategy_name.clone(),
+                                        signal: journal_signal.clone(),
+                                        fill_price: fill.map(|f| f.price).or(journal_signal.price()),
+                                        pnl,
+                                        timestamp: exec_ts,
+                                    });
+                                }
+                                // Journal only what the exchange reported as filled, with the estimated taker fee.
+                                // Limit orders fill later and arrive through the executor's order updates.
+                                if let Some(execution::MarketFill { price, quantity }) = fill {
+                                    let ts = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_millis() as u64;
+                                    let fee = price * quantity * fees.taker;
+                                    let spot = executor_clone.market_type() == market_data::downloader::MarketType::Spot;
+                                    {
+                                        let mut write_guard = shared_state_clone.write().await;
+                                        let is_buy = matches!(journal_signal, Signal::Buy { .. });
+                                        // Only orders that actually reach the exchange move the expected balances
+                                        if write_guard.trading_armed.load(std::sync::atomic::Ordering::SeqCst) && write_guard.reconciler.has_baseline() {
+                                            write_guard.reconciler.record_fill(journal_signal.symbol(), is_buy, price, quantity, fee);
+                                        }
+                                        if spot {
+                                            write_guard.cost_basis.record_fill(journal_signal.symbol(), is_buy, ts, price, quantity, fee);
+                                        }
+                                        write_guard.record_strategy_fill(&strategy_name, journal_signal.symbol(), is_buy, quantity);
+                                    }
+                                    if spot && !is_simulation {
+                                        let basis_fill = database::repository::CostBasisFill {
+                                            timestamp: ts,
+                                            symbol: journal_signal.symbol().to_string(),
+                                            is_buy: matches!(journal_signal, Signal::Buy { .. }),
+                                            price,
+                                            quantity,
+                                            fee,
+                                        };
+                                        if let Err(e) = database::repository::save_cost_basis_fill(&journal_pool, &basis_fill).await {
+                                            error!("Failed to persist cost basis fill: {}", e);
+                                        }
+                                    }
+                                    if let Some(trade_log) = &trade_log {
+                                        let record = trade_log::TradeRecord {
+                                            timestamp: ts,
+                                            strategy: &strategy_name,
+                                            symbol: journal_signal.symbol(),
+                                            side: if matches!(journal_signal, Signal::Buy { .. }) { "BUY" } else { "SELL" },
+                                            price,
+                                            quantity,
+                                            fee,
+                                            pnl,
+                                        };
+                                        if let Err(e) = trade_log.append(&record) {
+                                            error!("Failed to write trade log: {}", e);
+                                        }
+                                    }
+                                    if let Err(e) = database::repository::save_executed_order(&journal_pool, &strategy_name, &journal_signal, price, quantity, fee, pnl, ts).await {
+                                        error!("Failed to journal executed order: {}", e);
+                                    }
+                                }
                                 let mut write_guard = shared_state_clone.write().await;
-                                write_guard.realized_pnl += pnl;
+                                write_guard.record_realized_pnl(pnl);
+                                write_guard.record_strategy_pnl(&strategy_name, pnl);
+                                if pnl > 0.0 {
+                                    write_guard.win_trades += 1;
+                                    write_guard.gross_profit += pnl;
+                                } else if pnl < 0.0 {
+                                    write_guard.loss_trades += 1;
+                                    write_guard.gross_loss += pnl.abs();
+                                }
                                 write_guard.metrics.record_execution_latency(start_exec.elapsed());
                             }
                             Err(e) => error!("Execution error: {}", e),
                         }
+                        // Any fill is in the strategy's exposure by now
+                        shared_state_clone.write().await.release_notional(&strategy_name, reserved);
                     });
                 }
             }
@@ -268,3 +854,8 @@ fn main() {
         std::thread::park();
     }
 }
+
+/// Builds the strategy selected on the dashboard
+fn create_strategy(name: &str) -> Box<dyn TradingStrategy> {
+    strategy::StrategyFactory::create_strategy(name).unwrap_or_else(|| Box::new(PaperTrader::new()))
+}
@@ -1,17 +1,16 @@
-use tokio::sync::mpsc;
-use crate::market_data::MarketEvent;
+use crate::market_data::{EventSender, MarketEvent};
 use crate::database::repository;
 use sqlx::{Pool, Postgres};
 use log::info;
 
 pub struct BacktestDataManager {
     symbol: String,
-    tx: mpsc::Sender<MarketEvent>,
+    tx: EventSender,
     pool: Pool<Postgres>,
 }
 
 impl BacktestDataManager {
-    pub fn new(symbol: String, tx: mpsc::Sender<MarketEvent>, pool: Pool<Postgres>) -> Self {
+    pub fn new(symbol: String, tx: EventSender, pool: Pool<Postgres>) -> Self {
         Self { symbol, tx, pool }
     }
 
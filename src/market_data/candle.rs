@@ -0,0 +1,67 @@
+use std::collections::HashMap;
+
+/// Interval of the candles dispatched to strategies by the event loop and backtests
+pub const CANDLE_INTERVAL_SECS: i64 = 60;
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct Candle {
+    pub symbol: String,
+    pub timestamp: i64, // Bucket start (unix seconds)
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub volume: f64,
+}
+
+impl Candle {
+    fn new(symbol: &str, timestamp: i64, price: f64, quantity: f64) -> Self {
+        Self {
+            symbol: symbol.to_string(),
+            timestamp,
+            open: price,
+            high: price,
+            low: price,
+            close: price,
+            volume: quantity,
+        }
+    }
+
+    /// Event time (ms) of the candle's close, used for signal timestamps
+    pub fn close_time_ms(&self, interval_secs: i64) -> u64 {
+        ((self.timestamp + interval_secs) * 1000) as u64
+    }
+}
+
+/// Builds fixed-interval candles from a trade stream, one open candle per symbol
+pub struct CandleAggregator {
+    interval_secs: i64,
+    open_candles: HashMap<String, Candle>,
+}
+
+impl CandleAggregator {
+    pub fn new(interval_secs: i64) -> Self {
+        Self { interval_secs, open_candles: HashMap::new() }
+    }
+
+    /// Adds a trade. Returns the previous candle once a trade lands in a new bucket.
+    pub fn update(&mut self, symbol: &str, price: f64, quantity: f64, event_time_ms: u64) -> Option<Candle> {
+        if price <= 0.0 {
+            return None;
+        }
+        let bucket = (event_time_ms as i64 / 1000) / self.interval_secs * self.interval_secs;
+
+        match self.open_candles.get_mut(symbol) {
+            Some(candle) if candle.timestamp == bucket => {
+                candle.high = candle.high.max(price);
+                candle.low = candle.low.min(price);
+                candle.close = price;
+                candle.volume += quantity;
+                None
+            }
+            // Out-of-order trade for an already closed bucket
+            Some(candle) if bucket < candle.timestamp => None,
+            _ => self.open_candles.insert(symbol.to_string(), Candle::new(symbol, bucket, price, quantity)),
+        }
+    }
+}
@@ -1,10 +1,11 @@
 use binance::market::*;
 use binance::futures::market::*;
 use binance::api::*;
-use binance::model::AggrTradesEvent;
+use binance::model::{AggrTradesEvent, KlineSummaries, TradeEvent};
 use sqlx::{Pool, Postgres};
 use log::{info, error, warn};
 use crate::database::repository;
+use crate::market_data::Candle;
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum MarketType {
@@ -21,13 +22,281 @@ impl MarketType {
     }
 }
 
+/// Candlestick interval for kline downloads
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum KlineInterval {
+    OneMinute,
+    FiveMinutes,
+    FifteenMinutes,
+    OneHour,
+}
+
+impl KlineInterval {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            KlineInterval::OneMinute => "1m",
+            KlineInterval::FiveMinutes => "5m",
+            KlineInterval::FifteenMinutes => "15m",
+            KlineInterval::OneHour => "1h",
+        }
+    }
+
+    pub fn millis(&self) -> u64 {
+        match self {
+            KlineInterval::OneMinute => 60_000,
+            KlineInterval::FiveMinutes => 5 * 60_000,
+            KlineInterval::FifteenMinutes => 15 * 60_000,
+            KlineInterval::OneHour => 60 * 60_000,
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "1m" => Some(KlineInterval::OneMinute),
+            "5m" => Some(KlineInterval::FiveMinutes),
+            "15m" => Some(KlineInterval::FifteenMinutes),
+            "1h" => Some(KlineInterval::OneHour),
+            _ => None,
+        }
+    }
+}
+
+/// What to download: every aggregated trade, or klines (much smaller for multi-day ranges)
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum DataGranularity {
+    #[default]
+    AggTrades,
+    Klines(KlineInterval),
+}
+
+impl DataGranularity {
+    /// Parses "aggTrades" or a kline interval such as "1m"
+    pub fn parse(value: &str) -> Option<Self> {
+        if value.eq_ignore_ascii_case("aggtrades") {
+            return Some(DataGranularity::AggTrades);
+        }
+        KlineInterval::parse(value).map(DataGranularity::Klines)
+    }
+}
+
+/// Replays each kline as four synthetic trades (open, the two extremes, close) spread across
+/// the interval. The extreme nearer the open comes first: low before high on up candles.
+pub fn klines_to_trades(candles: &[Candle], interval: KlineInterval) -> Vec<TradeEvent> {
+    let step = interval.millis() / 4;
+    let mut trade_id = 0;
+    candles.iter().flat_map(|candle| {
+        let open_ms = candle.timestamp as u64 * 1000;
+        let up = candle.close >= candle.open;
+        let path = if up {
+            [candle.open, candle.low, candle.high, candle.close]
+        } else {
+            [candle.open, candle.high, candle.low, candle.close]
+        };
+        path.into_iter().enumerate().map(|(i, price)| {
+            trade_id += 1;
+            TradeEvent {
+                event_type: "trade".to_string(),
+                event_time: open_ms + i as u64 * step,
+                symbol: candle.symbol.clone(),
+                trade_id,
+                price: price.to_string(),
+                qty: (candle.volume / 4.0).to_string(),
+                buyer_order_id: 0,
+                seller_order_id: 0,
+                trade_order_time: open_ms + i as u64 * step,
+                is_buyer_maker: !up,
+                m_ignore: true,
+            }
+        }).collect::<Vec<_>>()
+    }).collect()
+}
+
+/// Silences shorter than this are treated as quiet markets rather than missing data
+pub const DEFAULT_MAX_GAP_MS: u64 = 5 * 60 * 1000;
+
+/// A stretch of time with no stored trades
+#[derive(Debug, Clone, Copy, serde::Serialize)]
+pub struct DataGap {
+    pub start_ts: u64,
+    pub end_ts: u64,
+}
+
+/// A millisecond timestamp as stored in BIGINT columns; open-ended bounds (u64::MAX) clamp to i64::MAX
+fn db_ts(ts: u64) -> i64 {
+    i64::try_from(ts).unwrap_or(i64::MAX)
+}
+
+/// Binance aggTrades API limits the startTime-endTime window to 1 hour
+const MAX_WINDOW_MS: u64 = 3_600_000;
+/// Times a rate-limited (429/418) chunk is retried after the throttle backs off
+const MAX_RATE_LIMIT_RETRIES: u32 = 5;
+const MAX_STAGGER_MS: u64 = 5_000;
+/// Consecutive successful requests before the throttle allows one more in flight
+const RAMP_UP_AFTER: u32 = 20;
+
+/// Request pacing for aggTrades downloads
+#[derive(Debug, Clone, Copy)]
+pub struct DownloadConfig {
+    /// Most requests in flight; the throttle halves it on rate limits and ramps back up
+    pub concurrency: usize,
+    /// Delay before each request; the throttle raises it on rate limits
+    pub stagger_ms: u64,
+    /// Time covered by one request (at most 1 hour)
+    pub window_ms: u64,
+    /// Whether `ensure_data_range` also looks for and re-downloads holes inside the stored range
+    pub repair_gaps: bool,
+}
+
+impl Default for DownloadConfig {
+    fn default() -> Self {
+        Self { concurrency: 5, stagger_ms: 200, window_ms: MAX_WINDOW_MS, repair_gaps: false }
+    }
+}
+
+impl DownloadConfig {
+    /// Reads DL_CONCURRENCY (default 5), DL_STAGGER_MS (default 200),
+    /// DL_WINDOW_MS (default and maximum 3600000) and DL_REPAIR_GAPS (default false)
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let var = |key: &str| std::env::var(key).ok().and_then(|v| v.parse::<u64>().ok());
+        Self {
+            concurrency: var("DL_CONCURRENCY").filter(|v| *v > 0).map_or(defaults.concurrency, |v| v as usize),
+            stagger_ms: var("DL_STAGGER_MS").unwrap_or(defaults.stagger_ms),
+            window_ms: var("DL_WINDOW_MS").filter(|v| *v > 0).map_or(defaults.window_ms, |v| v.min(MAX_WINDOW_MS)),
+            repair_gaps: std::env::var("DL_REPAIR_GAPS").map(|v| v.eq_ignore_ascii_case("true")).unwrap_or(defaults.repair_gaps),
+        }
+    }
+}
+
+struct ThrottleState {
+    in_flight: usize,
+    limit: usize,
+    stagger_ms: u64,
+    successes: u32,
+}
+
+/// Adaptive request gate: a rate-limited answer halves the allowed concurrency and doubles
+/// the stagger; a run of successes restores them one step at a time.
+struct DownloadThrottle {
+    config: DownloadConfig,
+    state: parking_lot::Mutex<ThrottleState>,
+    released: tokio::sync::Notify,
+}
+
+impl DownloadThrottle {
+    fn new(config: DownloadConfig) -> Self {
+        Self {
+            config,
+            state: parking_lot::Mutex::new(ThrottleState {
+                in_flight: 0,
+                limit: config.concurrency,
+                stagger_ms: config.stagger_ms,
+                successes: 0,
+            }),
+            released: tokio::sync::Notify::new(),
+        }
+    }
+
+    async fn acquire(&self) {
+        loop {
+            let released = self.released.notified();
+            tokio::pin!(released);
+            released.as_mut().enable();
+            {
+                let mut state = self.state.lock();
+                if state.in_flight < state.limit {
+                    state.in_flight += 1;
+                    return;
+                }
+            }
+            released.await;
+        }
+    }
+
+    fn release(&self, rate_limited: bool) {
+        let mut state = self.state.lock();
+        state.in_flight -= 1;
+        if rate_limited {
+            state.limit = (state.limit / 2).max(1);
+            state.stagger_ms = (state.stagger_ms * 2).clamp(100, MAX_STAGGER_MS);
+            state.successes = 0;
+            warn!("Download rate limited: concurrency {}, stagger {}ms", state.limit, state.stagger_ms);
+        } else {
+            state.successes += 1;
+            if state.successes >= RAMP_UP_AFTER
+                && (state.limit < self.config.concurrency || state.stagger_ms > self.config.stagger_ms) {
+                state.limit = (state.limit + 1).min(self.config.concurrency);
+                state.stagger_ms = (state.stagger_ms / 2).max(self.config.stagger_ms);
+                state.successes = 0;
+                info!("Download ramping up: concurrency {}, stagger {}ms", state.limit, state.stagger_ms);
+            }
+        }
+        drop(state);
+        self.released.notify_waiters();
+    }
+
+    fn limit(&self) -> usize {
+        self.state.lock().limit
+    }
+
+    fn stagger_ms(&self) -> u64 {
+        self.state.lock().stagger_ms
+    }
+}
+
+/// One aggTrades request (blocking), converted to stream events
+fn fetch_agg_trades(symbol: &str, market_type: MarketType, start_ts: u64, end_ts: u64) -> Result<Vec<AggrTradesEvent>, binance::errors::Error> {
+    let to_event = |time: u64, agg_id: u64, price: f64, qty: f64, first_id: u64, last_id: u64, maker: bool| AggrTradesEvent {
+        event_type: "aggTrade".to_string(),
+        event_time: time,
+        symbol: symbol.to_string(),
+        aggregated_trade_id: agg_id,
+        price: price.to_string(),
+        qty: qty.to_string(),
+        first_break_trade_id: first_id,
+        last_break_trade_id: last_id,
+        trade_order_time: time,
+        is_buyer_maker: maker,
+        m_ignore: true,
+    };
+    match market_type {
+        MarketType::Spot => {
+            let market: Market = Binance::new(None, None);
+            market.get_agg_trades(symbol, None, Some(start_ts), Some(end_ts), Some(1000))
+                .map(|trades| trades.into_iter()
+                    .map(|t| to_event(t.time, t.agg_id, t.price, t.qty, t.first_id, t.last_id, t.maker))
+                    .collect())
+        }
+        MarketType::Futures => {
+            use binance::futures::model::AggTrades;
+            let market: FuturesMarket = Binance::new(None, None);
+            market.get_agg_trades(symbol, None, Some(start_ts), Some(end_ts), Some(1000))
+                .map(|AggTrades::AllAggTrades(trades)| trades.into_iter()
+                    .map(|t| to_event(t.time, t.agg_id, t.price, t.qty, t.first_id, t.last_id, t.maker))
+                    .collect())
+        }
+    }
+}
+
+/// How much of a range has stored trades, measured in fixed time buckets
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct DataCoverage {
+    pub bucket_ms: u64,
+    pub expected_buckets: u64,
+    pub covered_buckets: u64,
+    pub coverage_pct: f64,
+    /// Runs of consecutive empty buckets, clipped to the requested range
+    pub missing: Vec<DataGap>,
+}
+
 pub struct HistoricalDownloader {
     pool: Pool<Postgres>,
+    config: DownloadConfig,
 }
 
 impl HistoricalDownloader {
     pub fn new(pool: Pool<Postgres>) -> Self {
-        Self { pool }
+        Self { pool, config: DownloadConfig::from_env() }
     }
 
     pub async fn ensure_data(&self, symbol: &str, market_type: MarketType, hours: u64) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
@@ -90,7 +359,7 @@ impl HistoricalDownloader {
         
         if !need_before && !need_after {
             info!("Database already has data covering the requested range for {} ({})", symbol, market_str);
-            return Ok(());
+            return self.repair_gaps(symbol, market_type, start_ts, end_ts).await;
         }
         
         let existing_min = db_min.map(|v| v as u64).unwrap_or(end_ts);
@@ -108,102 +377,256 @@ impl HistoricalDownloader {
             self.fetch_and_save_range_public(symbol, market_type, existing_max, end_ts).await?;
         }
         
+        self.repair_gaps(symbol, market_type, start_ts, end_ts).await
+    }
+
+    /// Makes sure klines of `interval` cover [start_ts, end_ts], downloading the missing edges
+    pub async fn ensure_klines_range(&self, symbol: &str, market_type: MarketType, interval: KlineInterval, start_ts: u64, end_ts: u64) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        // Open-ended requests (end_ts = u64::MAX) stop at the present
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)?
+            .as_millis() as u64;
+        let end_ts = end_ts.min(now);
+        let (db_min, db_max): (Option<i64>, Option<i64>) = sqlx::query_as(
+            "SELECT MIN(open_time), MAX(open_time) FROM klines WHERE symbol = $1 AND market_type = $2 AND kline_interval = $3"
+        )
+            .bind(symbol)
+            .bind(market_type.as_str())
+            .bind(interval.as_str())
+            .fetch_one(&self.pool)
+            .await?;
+
+        // The newest kline opens up to one interval before end_ts
+        let covered_from = db_min.map_or(end_ts, |v| v as u64);
+        let covered_to = db_max.map_or(start_ts, |v| v as u64 + interval.millis());
+        if start_ts < covered_from {
+            self.fetch_and_save_klines(symbol, market_type, interval, start_ts, covered_from.min(end_ts)).await?;
+        }
+        if end_ts > covered_to {
+            self.fetch_and_save_klines(symbol, market_type, interval, covered_to.max(start_ts), end_ts).await?;
+        }
+        Ok(())
+    }
+
+    /// Downloads klines in 1000-candle pages (the endpoint's maximum)
+    pub async fn fetch_and_save_klines(&self, symbol: &str, market_type: MarketType, interval: KlineInterval, start_ts: u64, end_ts: u64) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        const PAGE_SIZE: u16 = 1000;
+        let page_ms = interval.millis() * PAGE_SIZE as u64;
+        info!("Fetching {} klines for {} ({}) from {} to {}", interval.as_str(), symbol, market_type.as_str(), start_ts, end_ts);
+
+        let mut page_start = start_ts;
+        let mut saved = 0;
+        while page_start < end_ts {
+            let page_end = (page_start + page_ms).min(end_ts);
+            let sym = symbol.to_string();
+            let result = tokio::task::spawn_blocking(move || match market_type {
+                MarketType::Spot => {
+                    let market: Market = Binance::new(None, None);
+                    market.get_klines(sym, interval.as_str(), PAGE_SIZE, page_start, page_end)
+                }
+                MarketType::Futures => {
+                    let market: FuturesMarket = Binance::new(None, None);
+                    market.get_klines(sym, interval.as_str(), PAGE_SIZE, page_start, page_end)
+                }
+            }).await?;
+
+            match result {
+                Ok(KlineSummaries::AllKlineSummaries(klines)) => {
+                    let candles: Vec<Candle> = klines.into_iter().map(|k| Candle {
+                        symbol: symbol.to_string(),
+                        timestamp: k.open_time / 1000,
+                        open: k.open.parse().unwrap_or(0.0),
+                        high: k.high.parse().unwrap_or(0.0),
+                        low: k.low.parse().unwrap_or(0.0),
+                        close: k.close.parse().unwrap_or(0.0),
+                        volume: k.volume.parse().unwrap_or(0.0),
+                    }).collect();
+                    saved += candles.len();
+                    repository::save_klines(&self.pool, &candles, market_type.as_str(), interval.as_str()).await?;
+                }
+                Err(e) => error!("Binance API error for klines {}-{}: {:?}", page_start, page_end, e),
+            }
+
+            page_start = page_end;
+            // Stay well inside the request weight budget
+            tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+        }
+
+        info!("Saved {} {} klines for {} ({})", saved, interval.as_str(), symbol, market_type.as_str());
+        Ok(())
+    }
+
+    /// Coverage of `[start_ts, end_ts)` by buckets of `bucket_ms` that contain at least one trade
+    pub async fn coverage(&self, symbol: &str, market_type: MarketType, start_ts: u64, end_ts: u64, bucket_ms: u64) -> Result<DataCoverage, Box<dyn std::error::Error + Send + Sync>> {
+        let bucket_ms = bucket_ms.max(1);
+        if end_ts <= start_ts {
+            return Ok(DataCoverage { bucket_ms, expected_buckets: 0, covered_buckets: 0, coverage_pct: 100.0, missing: Vec::new() });
+        }
+        let covered = repository::get_trade_buckets(&self.pool, symbol, market_type.as_str(), bucket_ms as i64, start_ts as i64, end_ts as i64).await?;
+
+        let (first, last) = (start_ts / bucket_ms, (end_ts - 1) / bucket_ms);
+        let expected_buckets = last - first + 1;
+        let mut missing = Vec::new();
+        let mut next = first;
+        for bucket in covered.iter().map(|b| *b as u64).chain(std::iter::once(last + 1)) {
+            if bucket > next {
+                missing.push(DataGap {
+                    start_ts: (next * bucket_ms).max(start_ts),
+                    end_ts: (bucket * bucket_ms).min(end_ts),
+                });
+            }
+            next = bucket + 1;
+        }
+
+        let covered_buckets = covered.len() as u64;
+        Ok(DataCoverage {
+            bucket_ms,
+            expected_buckets,
+            covered_buckets,
+            coverage_pct: covered_buckets as f64 / expected_buckets as f64 * 100.0,
+            missing,
+        })
+    }
+
+    /// Returns every stretch in [start_ts, end_ts] longer than `max_gap_ms` without stored trades,
+    /// including missing data at either end of the range
+    pub async fn find_gaps(&self, symbol: &str, market_type: MarketType, start_ts: u64, end_ts: u64, max_gap_ms: u64) -> Result<Vec<DataGap>, Box<dyn std::error::Error + Send + Sync>> {
+        let market_str = market_type.as_str();
+        let (first, last): (Option<i64>, Option<i64>) = sqlx::query_as(
+            "SELECT MIN(event_time), MAX(event_time) FROM trades WHERE symbol = $1 AND market_type = $2 AND event_time BETWEEN $3 AND $4"
+        )
+            .bind(symbol)
+            .bind(market_str)
+            .bind(db_ts(start_ts))
+            .bind(db_ts(end_ts))
+            .fetch_one(&self.pool)
+            .await?;
+
+        let (Some(first), Some(last)) = (first.map(|v| v as u64), last.map(|v| v as u64)) else {
+            return Ok(if end_ts > start_ts { vec![DataGap { start_ts, end_ts }] } else { Vec::new() });
+        };
+
+        let inner: Vec<(i64, i64)> = sqlx::query_as(
+            r#"
+            SELECT prev_time, event_time FROM (
+                SELECT event_time, LAG(event_time) OVER (ORDER BY event_time) AS prev_time
+                FROM trades
+                WHERE symbol = $1 AND market_type = $2 AND event_time BETWEEN $3 AND $4
+            ) ordered
+            WHERE event_time - prev_time > $5
+            ORDER BY prev_time
+            "#
+        )
+            .bind(symbol)
+            .bind(market_str)
+            .bind(db_ts(start_ts))
+            .bind(db_ts(end_ts))
+            .bind(db_ts(max_gap_ms))
+            .fetch_all(&self.pool)
+            .await?;
+
+        let mut gaps = Vec::with_capacity(inner.len() + 2);
+        if first.saturating_sub(start_ts) > max_gap_ms {
+            gaps.push(DataGap { start_ts, end_ts: first });
+        }
+        gaps.extend(inner.into_iter().map(|(prev, next)| DataGap { start_ts: prev as u64, end_ts: next as u64 }));
+        if end_ts.saturating_sub(last) > max_gap_ms {
+            gaps.push(DataGap { start_ts: last, end_ts });
+        }
+        Ok(gaps)
+    }
+
+    /// Re-downloads just the given gaps
+    pub async fn fill_gaps(&self, symbol: &str, market_type: MarketType, gaps: &[DataGap]) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        for gap in gaps {
+            info!("Re-downloading gap for {} ({}): {} to {}", symbol, market_type.as_str(), gap.start_ts, gap.end_ts);
+            self.fetch_and_save_range_public(symbol, market_type, gap.start_ts, gap.end_ts).await?;
+        }
+        Ok(())
+    }
+
+    /// Fills holes inside a range whose edges are already covered (e.g. chunks that failed to download).
+    /// Does nothing unless DL_REPAIR_GAPS is set.
+    async fn repair_gaps(&self, symbol: &str, market_type: MarketType, start_ts: u64, end_ts: u64) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        if !self.config.repair_gaps {
+            return Ok(());
+        }
+        // Open-ended requests (end_ts = u64::MAX) stop at the present
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)?
+            .as_millis() as u64;
+        let end_ts = end_ts.min(now);
+        let gaps = self.find_gaps(symbol, market_type, start_ts, end_ts, DEFAULT_MAX_GAP_MS).await?;
+        if !gaps.is_empty() {
+            warn!("Found {} data gaps for {} ({}) between {} and {}", gaps.len(), symbol, market_type.as_str(), start_ts, end_ts);
+            self.fill_gaps(symbol, market_type, &gaps).await?;
+        }
         Ok(())
     }
 
     pub async fn fetch_and_save_range_public(&self, symbol: &str, market_type: MarketType, start_ts: u64, end_ts: u64) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         use futures::stream::{self, StreamExt};
         use std::sync::Arc;
-        use tokio::sync::Semaphore;
-        
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
         let symbol_owned = symbol.to_string();
         let market_str = market_type.as_str().to_string();
-        
-        // Binance aggTrades API limits startTime-endTime window to 1 hour (3600000 ms)
-        const MAX_WINDOW_MS: u64 = 3600000;
-        const MAX_CONCURRENT_REQUESTS: usize = 5; // Limit concurrent requests to avoid rate limiting (429)
-        
+        let config = self.config;
+
         // Calculate all chunks
         let mut chunks: Vec<(u64, u64)> = Vec::new();
         let mut chunk_start = start_ts;
         while chunk_start < end_ts {
-            let chunk_end = std::cmp::min(chunk_start + MAX_WINDOW_MS, end_ts);
+            let chunk_end = std::cmp::min(chunk_start + config.window_ms, end_ts);
             chunks.push((chunk_start, chunk_end));
             chunk_start = chunk_end;
         }
-        
+
         let total_chunks = chunks.len();
-        info!("Fetching historical agg_trades for {} ({}) from {} to {} ({} chunks, {} concurrent)", 
-              symbol, market_str, start_ts, end_ts, total_chunks, MAX_CONCURRENT_REQUESTS);
-        
-        let semaphore = Arc::new(Semaphore::new(MAX_CONCURRENT_REQUESTS));
+        info!("Fetching historical agg_trades for {} ({}) from {} to {} ({} chunks, up to {} concurrent, {}ms stagger)",
+              symbol, market_str, start_ts, end_ts, total_chunks, config.concurrency, config.stagger_ms);
+
+        let throttle = Arc::new(DownloadThrottle::new(config));
         let pool = self.pool.clone();
-        
-        // Process chunks in parallel with controlled concurrency
-        let results: Vec<_> = stream::iter(chunks)
+        let started = std::time::Instant::now();
+        let chunks_done = Arc::new(AtomicUsize::new(0));
+        let trades_saved = Arc::new(AtomicUsize::new(0));
+
+        // Process chunks in parallel; the throttle decides how many requests are actually in flight
+        stream::iter(chunks)
             .map(|(cs, ce)| {
                 let sym = symbol_owned.clone();
                 let market_str = market_str.clone();
                 let pool = pool.clone();
-                let semaphore = semaphore.clone();
-                
+                let throttle = throttle.clone();
+                let chunks_done = chunks_done.clone();
+                let trades_saved = trades_saved.clone();
+
                 async move {
-                    let _permit = semaphore.acquire().await.unwrap();
-                    
-                    // Stagger requests to avoid burst (Binance rate limit: 1200/min)
-                    tokio::time::sleep(std::time::Duration::from_millis(200)).await;
-                    
-                    let sym_clone = sym.clone();
-                    let result = tokio::task::spawn_blocking(move || {
-                        match market_type {
-                            MarketType::Spot => {
-                                let market: Market = Binance::new(None, None);
-                                market.get_agg_trades(&sym_clone, None, Some(cs), Some(ce), Some(1000))
-                                    .map(|trades| trades.into_iter().map(|t| AggrTradesEvent {
-                                        event_type: "aggTrade".to_string(),
-                                        event_time: t.time,
-                                        symbol: sym_clone.clone(),
-                                        aggregated_trade_id: t.agg_id,
-                                        price: t.price.to_string(),
-                                        qty: t.qty.to_string(),
-                                        first_break_trade_id: t.first_id,
-                                        last_break_trade_id: t.last_id,
-                                        trade_order_time: t.time,
-                                        is_buyer_maker: t.maker,
-                                        m_ignore: true,
-                                    }).collect::<Vec<AggrTradesEvent>>())
-                            }
-                            MarketType::Futures => {
-                                let market: FuturesMarket = Binance::new(None, None);
-                                market.get_agg_trades(&sym_clone, None, Some(cs), Some(ce), Some(1000))
-                                    .map(|trades_obj| {
-                                        use binance::futures::model::AggTrades;
-                                        match trades_obj {
-                                            AggTrades::AllAggTrades(v) => v.into_iter().map(|t| AggrTradesEvent {
-                                                event_type: "aggTrade".to_string(),
-                                                event_time: t.time,
-                                                symbol: sym_clone.clone(),
-                                                aggregated_trade_id: t.agg_id,
-                                                price: t.price.to_string(),
-                                                qty: t.qty.to_string(),
-                                                first_break_trade_id: t.first_id,
-                                                last_break_trade_id: t.last_id,
-                                                trade_order_time: t.time,
-                                                is_buyer_maker: t.maker,
-                                                m_ignore: true,
-                                            }).collect::<Vec<AggrTradesEvent>>(),
-                                        }
-                                    })
-                            }
+                    let mut attempt = 0;
+                    let result = loop {
+                        throttle.acquire().await;
+                        // Stagger requests to avoid bursts (Binance rate limit: 1200/min)
+                        tokio::time::sleep(std::time::Duration::from_millis(throttle.stagger_ms())).await;
+
+                        let sym_clone = sym.clone();
+                        let result = tokio::task::spawn_blocking(move || fetch_agg_trades(&sym_clone, market_type, cs, ce)).await;
+                        let rate_limited = matches!(&result, Ok(Err(e)) if crate::execution::is_rate_limited(e));
+                        throttle.release(rate_limited);
+                        if rate_limited && attempt < MAX_RATE_LIMIT_RETRIES {
+                            attempt += 1;
+                            continue;
                         }
-                    }).await;
-                    
+                        break result;
+                    };
+
                     match result {
                         Ok(Ok(events)) if !events.is_empty() => {
                             let count = events.len();
                             if let Err(e) = repository::save_aggr_trades_bulk(&pool, &events, &market_str).await {
                                 error!("Failed to save chunk {}-{}: {}", cs, ce, e);
                             } else {
+                                trades_saved.fetch_add(count, Ordering::Relaxed);
                                 info!("Saved {} trades for chunk {}-{} ({})", count, cs, ce, sym);
                             }
                         }
@@ -217,13 +640,34 @@ impl HistoricalDownloader {
                             error!("Task error for chunk {}-{}: {:?}", cs, ce, e);
                         }
                     }
+
+                    let done = chunks_done.fetch_add(1, Ordering::Relaxed) + 1;
+                    let trades = trades_saved.load(Ordering::Relaxed);
+                    let throughput = trades as f64 / started.elapsed().as_secs_f64().max(0.001);
+                    let features = std::collections::HashMap::from([
+                        ("Chunks".to_string(), format!("{}/{}", done, total_chunks)),
+                        ("Trades".to_string(), trades.to_string()),
+                        ("Trades/sec".to_string(), format!("{:.0}", throughput)),
+                        ("Concurrency".to_string(), throttle.limit().to_string()),
+                        ("Stagger ms".to_string(), throttle.stagger_ms().to_string()),
+                    ]);
+                    let _ = crate::web::PROGRESS_TX.send(crate::web::ProgressEvent {
+                        symbol: sym,
+                        strategy_name: "download".to_string(),
+                        progress_pct: (done * 100 / total_chunks) as u32,
+                        status: if done == total_chunks { "completed" } else { "running" }.to_string(),
+                        features,
+                    });
                 }
             })
-            .buffer_unordered(MAX_CONCURRENT_REQUESTS)
-            .collect()
+            .buffer_unordered(config.concurrency)
+            .collect::<Vec<_>>()
             .await;
 
-        info!("Historical data download complete for {} ({}) - processed {} chunks", symbol_owned, market_str, total_chunks);
+        let elapsed = started.elapsed().as_secs_f64();
+        let trades = trades_saved.load(Ordering::Relaxed);
+        info!("Historical data download complete for {} ({}) - processed {} chunks, {} trades in {:.1}s ({:.0} trades/sec)",
+              symbol_owned, market_str, total_chunks, trades, elapsed, trades as f64 / elapsed.max(0.001));
         Ok(())
     }
 }
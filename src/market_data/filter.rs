@@ -1,12 +1,35 @@
 use log::warn;
 use binance::model::{TradeEvent, AggrTradesEvent};
+use std::collections::HashMap;
 use super::MarketEvent;
 
-pub struct DataFilter {
+/// Sequence, timestamp and price tracking for a single symbol
+#[derive(Debug, Default)]
+pub struct SymbolFilterState {
     last_trade_id: u64,
     last_agg_trade_id: u64,
     last_timestamp: u64,
     last_price: Option<f64>,
+
+    pub total_received: u64,
+    pub duplicate_count: u64,
+    pub out_of_order_count: u64,
+    pub outlier_count: u64,
+}
+
+impl SymbolFilterState {
+    fn quality_score(&self) -> f64 {
+        quality_score(self.total_received, self.duplicate_count + self.out_of_order_count + self.outlier_count)
+    }
+}
+
+fn quality_score(total: u64, bad: u64) -> f64 {
+    if total == 0 { return 100.0; }
+    (total.saturating_sub(bad) as f64 / total as f64) * 100.0
+}
+
+pub struct DataFilter {
+    symbols: HashMap<String, SymbolFilterState>,
     outlier_threshold: f64, 
     
     pub total_received: u64,
@@ -18,10 +41,7 @@ pub struct DataFilter {
 impl DataFilter {
     pub fn new(outlier_threshold: f64) -> Self {
         Self {
-            last_trade_id: 0,
-            last_agg_trade_id: 0,
-            last_timestamp: 0,
-            last_price: None,
+            symbols: HashMap::new(),
             outlier_threshold,
             total_received: 0,
             duplicate_count: 0,
@@ -30,83 +50,123 @@ impl DataFilter {
         }
     }
 
+    /// Reads DATA_OUTLIER_THRESHOLD (fractional price jump, default 0.05 = 5%) from the environment
+    pub fn from_env() -> Self {
+        let threshold = std::env::var("DATA_OUTLIER_THRESHOLD")
+            .ok()
+            .and_then(|v| v.parse::<f64>().ok())
+            .filter(|t| *t > 0.0)
+            .unwrap_or(0.05);
+        Self::new(threshold)
+    }
+
+    pub fn outlier_threshold(&self) -> f64 {
+        self.outlier_threshold
+    }
+
     pub fn should_process(&mut self, event: &MarketEvent) -> bool {
         self.total_received += 1;
+        let threshold = self.outlier_threshold;
+        let state = self.symbols.entry(event.symbol().to_string()).or_default();
+        state.total_received += 1;
         
-        match event {
-            MarketEvent::Trade(trade) => self.filter_trade(trade),
-            MarketEvent::AggrTrade(agg) => self.filter_agg_trade(agg),
-            _ => true, // OrderBook and others pass for now
+        let rejection = match event {
+            MarketEvent::Trade(trade) => Self::filter_trade(state, trade, threshold),
+            MarketEvent::AggrTrade(agg) => Self::filter_agg_trade(state, agg, threshold),
+            _ => None, // OrderBook and others pass for now
+        };
+
+        match rejection {
+            None => true,
+            Some(Rejection::Duplicate) => {
+                state.duplicate_count += 1;
+                self.duplicate_count += 1;
+                false
+            }
+            Some(Rejection::OutOfOrder) => {
+                state.out_of_order_count += 1;
+                self.out_of_order_count += 1;
+                false
+            }
+            Some(Rejection::Outlier) => {
+                state.outlier_count += 1;
+                self.outlier_count += 1;
+                false
+            }
         }
     }
 
-    fn filter_trade(&mut self, trade: &TradeEvent) -> bool {
+    fn filter_trade(state: &mut SymbolFilterState, trade: &TradeEvent, threshold: f64) -> Option<Rejection> {
         // 1. Check Duplicates
-        if trade.trade_id <= self.last_trade_id && self.last_trade_id != 0 {
-            self.duplicate_count += 1;
-            warn!("Filtered duplicate trade ID: {}", trade.trade_id);
-            return false;
+        if trade.trade_id <= state.last_trade_id && state.last_trade_id != 0 {
+            warn!("Filtered duplicate {} trade ID: {}", trade.symbol, trade.trade_id);
+            return Some(Rejection::Duplicate);
         }
-        self.last_trade_id = trade.trade_id;
+        state.last_trade_id = trade.trade_id;
 
         // 2. Check Out-of-order
-        if trade.event_time < self.last_timestamp && self.last_timestamp != 0 {
-            self.out_of_order_count += 1;
-            warn!("Filtered out-of-order trade: {} < {}", trade.event_time, self.last_timestamp);
-            return false;
+        if trade.event_time < state.last_timestamp && state.last_timestamp != 0 {
+            warn!("Filtered out-of-order {} trade: {} < {}", trade.symbol, trade.event_time, state.last_timestamp);
+            return Some(Rejection::OutOfOrder);
         }
-        self.last_timestamp = trade.event_time;
+        state.last_timestamp = trade.event_time;
 
         // 3. Check Outliers
         let price = trade.price.parse::<f64>().unwrap_or(0.0);
-        if let Some(lp) = self.last_price {
+        if let Some(lp) = state.last_price {
             let diff = (price - lp).abs() / lp;
-            if diff > self.outlier_threshold {
-                self.outlier_count += 1;
-                warn!("Filtered outlier trade price: {} (prev: {}, diff: {:.2}%)", price, lp, diff * 100.0);
-                return false;
+            if diff > threshold {
+                warn!("Filtered outlier {} trade price: {} (prev: {}, diff: {:.2}%)", trade.symbol, price, lp, diff * 100.0);
+                return Some(Rejection::Outlier);
             }
         }
-        self.last_price = Some(price);
+        state.last_price = Some(price);
 
-        true
+        None
     }
 
-    fn filter_agg_trade(&mut self, agg: &AggrTradesEvent) -> bool {
+    fn filter_agg_trade(state: &mut SymbolFilterState, agg: &AggrTradesEvent, threshold: f64) -> Option<Rejection> {
         // 1. Check Duplicates
-        if agg.aggregated_trade_id <= self.last_agg_trade_id && self.last_agg_trade_id != 0 {
-            self.duplicate_count += 1;
-            warn!("Filtered duplicate aggTrade ID: {}", agg.aggregated_trade_id);
-            return false;
+        if agg.aggregated_trade_id <= state.last_agg_trade_id && state.last_agg_trade_id != 0 {
+            warn!("Filtered duplicate {} aggTrade ID: {}", agg.symbol, agg.aggregated_trade_id);
+            return Some(Rejection::Duplicate);
         }
-        self.last_agg_trade_id = agg.aggregated_trade_id;
+        state.last_agg_trade_id = agg.aggregated_trade_id;
 
         // 2. Check Out-of-order
-        if agg.event_time < self.last_timestamp && self.last_timestamp != 0 {
-            self.out_of_order_count += 1;
-            warn!("Filtered out-of-order aggTrade: {} < {}", agg.event_time, self.last_timestamp);
-            return false;
+        if agg.event_time < state.last_timestamp && state.last_timestamp != 0 {
+            warn!("Filtered out-of-order {} aggTrade: {} < {}", agg.symbol, agg.event_time, state.last_timestamp);
+            return Some(Rejection::OutOfOrder);
         }
-        self.last_timestamp = agg.event_time;
+        state.last_timestamp = agg.event_time;
 
         // 3. Check Outliers
         let price = agg.price.parse::<f64>().unwrap_or(0.0);
-        if let Some(lp) = self.last_price {
+        if let Some(lp) = state.last_price {
             let diff = (price - lp).abs() / lp;
-            if diff > self.outlier_threshold {
-                self.outlier_count += 1;
-                warn!("Filtered outlier aggTrade price: {} (prev: {}, diff: {:.2}%)", price, lp, diff * 100.0);
-                return false;
+            if diff > threshold {
+                warn!("Filtered outlier {} aggTrade price: {} (prev: {}, diff: {:.2}%)", agg.symbol, price, lp, diff * 100.0);
+                return Some(Rejection::Outlier);
             }
         }
-        self.last_price = Some(price);
+        state.last_price = Some(price);
 
-        true
+        None
     }
 
+    /// Quality score across all symbols
     pub fn get_quality_score(&self) -> f64 {
-        if self.total_received == 0 { return 100.0; }
-        let bad = self.duplicate_count + self.out_of_order_count + self.outlier_count;
-        ((self.total_received - bad) as f64 / self.total_received as f64) * 100.0
+        quality_score(self.total_received, self.duplicate_count + self.out_of_order_count + self.outlier_count)
     }
+
+    /// Quality score for one symbol (100 if nothing has been received for it)
+    pub fn get_quality_score_for(&self, symbol: &str) -> f64 {
+        self.symbols.get(symbol).map_or(100.0, SymbolFilterState::quality_score)
+    }
+}
+
+enum Rejection {
+    Duplicate,
+    OutOfOrder,
+    Outlier,
 }
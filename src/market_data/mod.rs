@@ -1,12 +1,22 @@
 pub mod websocket;
 pub mod backtest;
 pub mod filter;
+pub mod price_cache;
+pub mod candle;
+pub mod symbols;
+pub mod order_book;
+pub mod queue;
 
 pub mod downloader;
 
 pub use downloader::HistoricalDownloader;
-pub use websocket::MarketDataManager;
+pub use websocket::{MarketDataManager, ConnectionHealth};
 pub use filter::DataFilter;
+pub use price_cache::{LastPriceCache, PriceSource};
+pub use candle::{Candle, CandleAggregator, CANDLE_INTERVAL_SECS};
+pub use symbols::validate_symbol;
+pub use order_book::{LocalOrderBook, DiffResult};
+pub use queue::{BackpressurePolicy, EventSender, event_queue};
 
 use binance::model::{TradeEvent, DepthOrderBookEvent, OrderBook, AggrTradesEvent};
 
@@ -14,7 +24,20 @@ use binance::model::{TradeEvent, DepthOrderBookEvent, OrderBook, AggrTradesEvent
 pub enum MarketEvent {
     Trade(TradeEvent),
     AggrTrade(AggrTradesEvent),
-    OrderBook(OrderBook),
-    #[allow(dead_code)]
+    /// Partial depth snapshots don't carry a symbol, so the stream's symbol travels with them
+    OrderBook(String, OrderBook),
+    /// Diff from the `@depth` stream, applied to a `LocalOrderBook` (LOCAL_ORDER_BOOK=true)
     DepthUpdate(DepthOrderBookEvent),
 }
+
+impl MarketEvent {
+    /// The (upper-case) symbol this event belongs to
+    pub fn symbol(&self) -> &str {
+        match self {
+            MarketEvent::Trade(trade) => &trade.symbol,
+            MarketEvent::AggrTrade(agg) => &agg.symbol,
+            MarketEvent::OrderBook(symbol, _) => symbol,
+            MarketEvent::DepthUpdate(depth) => &depth.symbol,
+        }
+    }
+}
@@ -0,0 +1,183 @@
+use binance::api::Binance;
+use binance::market::Market;
+use binance::model::{Asks, Bids, DepthOrderBookEvent, OrderBook};
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+/// Depth of the REST snapshot used to seed the book (Binance allows up to 5000)
+pub const SNAPSHOT_DEPTH: u64 = 1000;
+/// Levels per side handed to strategies, matching the depth10 partial stream
+pub const EMIT_LEVELS: usize = 10;
+/// A 1000-level snapshot costs 50 request weight, so resyncs are spaced out
+const SNAPSHOT_MIN_INTERVAL: Duration = Duration::from_secs(1);
+/// Most diffs kept while a snapshot is in flight; past this the replay finds a gap and resyncs
+const MAX_BUFFERED_DIFFS: usize = 10_000;
+
+/// True when LOCAL_ORDER_BOOK=true: subscribe to the diff depth stream and keep
+/// a local book instead of using the depth10 partial snapshots
+pub fn local_order_book_from_env() -> bool {
+    std::env::var("LOCAL_ORDER_BOOK").map(|v| v.eq_ignore_ascii_case("true")).unwrap_or(false)
+}
+
+/// Outcome of applying one diff event
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffResult {
+    /// The book changed and is in sync
+    Applied,
+    /// Already covered by the snapshot; ignored
+    Stale,
+    /// Update ids skipped ahead or no snapshot yet; the book needs a fresh snapshot
+    OutOfSync,
+}
+
+/// Order book kept in sync from a REST snapshot plus `@depth` diff events,
+/// following Binance's "manage a local order book correctly" rules:
+///
+/// 1. Drop any event with `u` <= the snapshot's `lastUpdateId`.
+/// 2. The first applied event must have `U` <= `lastUpdateId + 1` <= `u`.
+/// 3. Every later event must have `U` == previous `u + 1`, otherwise resync.
+/// 4. A level with quantity 0 is removed.
+///
+/// Diffs that arrive while a snapshot is being fetched are buffered and replayed onto it.
+///
+/// Price levels are keyed by `f64::to_bits`, which orders positive prices numerically.
+pub struct LocalOrderBook {
+    symbol: String,
+    bids: BTreeMap<u64, f64>,
+    asks: BTreeMap<u64, f64>,
+    last_update_id: Option<u64>,
+    awaiting_first_event: bool,
+    last_snapshot_attempt: Option<Instant>,
+    snapshot_pending: bool,
+    buffered: Vec<DepthOrderBookEvent>,
+}
+
+impl LocalOrderBook {
+    pub fn new(symbol: &str) -> Self {
+        Self {
+            symbol: symbol.to_uppercase(),
+            bids: BTreeMap::new(),
+            asks: BTreeMap::new(),
+            last_update_id: None,
+            awaiting_first_event: false,
+            last_snapshot_attempt: None,
+            snapshot_pending: false,
+            buffered: Vec::new(),
+        }
+    }
+
+    /// True when out of sync, no snapshot is in flight and the last attempt was long enough ago.
+    /// Records the attempt and starts buffering diffs, so call it right before fetching and
+    /// hand the outcome to `apply_snapshot` or `snapshot_failed`.
+    pub fn should_fetch_snapshot(&mut self) -> bool {
+        if self.is_synced() || self.snapshot_pending
+            || self.last_snapshot_attempt.is_some_and(|t| t.elapsed() < SNAPSHOT_MIN_INTERVAL) {
+            return false;
+        }
+        self.last_snapshot_attempt = Some(Instant::now());
+        self.snapshot_pending = true;
+        self.buffered.clear();
+        true
+    }
+
+    /// Gives up on the snapshot in flight; the next diff may ask for another one
+    pub fn snapshot_failed(&mut self) {
+        self.snapshot_pending = false;
+        self.buffered.clear();
+    }
+
+    pub fn is_synced(&self) -> bool {
+        self.last_update_id.is_some()
+    }
+
+    /// Drops the book so the next diff asks for a new snapshot
+    pub fn reset(&mut self) {
+        self.bids.clear();
+        self.asks.clear();
+        self.last_update_id = None;
+        self.awaiting_first_event = false;
+    }
+
+    /// Fetches a REST snapshot. Blocking; call from `spawn_blocking`.
+    pub fn fetch_snapshot(symbol: &str) -> Result<OrderBook, String> {
+        let market: Market = Binance::new(None, None);
+        market.get_custom_depth(symbol, SNAPSHOT_DEPTH).map_err(|e| format!("{:?}", e))
+    }
+
+    /// Replaces the book with a snapshot and replays the diffs buffered while it was fetched;
+    /// diffs older than it are ignored
+    pub fn apply_snapshot(&mut self, snapshot: &OrderBook) {
+        self.reset();
+        self.snapshot_pending = false;
+        for bid in &snapshot.bids {
+            Self::set_level(&mut self.bids, bid.price, bid.qty);
+        }
+        for ask in &snapshot.asks {
+            Self::set_level(&mut self.asks, ask.price, ask.qty);
+        }
+        self.last_update_id = Some(snapshot.last_update_id);
+        self.awaiting_first_event = true;
+        for event in std::mem::take(&mut self.buffered) {
+            if self.apply_diff(&event) == DiffResult::OutOfSync {
+                break;
+            }
+        }
+    }
+
+    pub fn apply_diff(&mut self, event: &DepthOrderBookEvent) -> DiffResult {
+        let Some(last_update_id) = self.last_update_id else {
+            if self.snapshot_pending && self.buffered.len() < MAX_BUFFERED_DIFFS {
+                self.buffered.push(event.clone());
+            }
+            return DiffResult::OutOfSync;
+        };
+        if event.final_update_id <= last_update_id {
+            return DiffResult::Stale;
+        }
+        let in_sequence = if self.awaiting_first_event {
+            event.first_update_id <= last_update_id + 1
+        } else {
+            event.first_update_id == last_update_id + 1
+        };
+        if !in_sequence {
+            log::warn!("{} depth gap: expected update {}, got {}..{}; resyncing",
+                self.symbol, last_update_id + 1, event.first_update_id, event.final_update_id);
+            self.reset();
+            return DiffResult::OutOfSync;
+        }
+
+        for bid in &event.bids {
+            Self::set_level(&mut self.bids, bid.price, bid.qty);
+        }
+        for ask in &event.asks {
+            Self::set_level(&mut self.asks, ask.price, ask.qty);
+        }
+        self.last_update_id = Some(event.final_update_id);
+        self.awaiting_first_event = false;
+        DiffResult::Applied
+    }
+
+    fn set_level(side: &mut BTreeMap<u64, f64>, price: f64, qty: f64) {
+        if price.is_nan() || price <= 0.0 {
+            return;
+        }
+        if qty > 0.0 {
+            side.insert(price.to_bits(), qty);
+        } else {
+            side.remove(&price.to_bits());
+        }
+    }
+
+    /// Best `levels` bids (highest first) and asks (lowest first) in the snapshot format
+    pub fn top(&self, levels: usize) -> OrderBook {
+        OrderBook {
+            last_update_id: self.last_update_id.unwrap_or(0),
+            bids: self.bids.iter().rev().take(levels)
+                .map(|(price, qty)| Bids { price: f64::from_bits(*price), qty: *qty })
+                .collect(),
+            asks: self.asks.iter().take(levels)
+                .map(|(price, qty)| Asks { price: f64::from_bits(*price), qty: *qty })
+                .collect(),
+        }
+    }
+}
@@ -0,0 +1,122 @@
+use parking_lot::RwLock;
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Which price holdings and positions are valued at
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PriceSource {
+    LastTrade,
+    /// Order book mid, (best bid + best ask) / 2
+    Mid,
+    /// Exchange mark price: the futures mark price, the ticker price on spot
+    Mark,
+}
+
+impl PriceSource {
+    /// Reads VALUATION_PRICE_SOURCE (last_trade | mid | mark, default last_trade)
+    pub fn from_env() -> Self {
+        match std::env::var("VALUATION_PRICE_SOURCE").unwrap_or_default().to_lowercase().as_str() {
+            "mid" => PriceSource::Mid,
+            "mark" => PriceSource::Mark,
+            "" | "last_trade" => PriceSource::LastTrade,
+            other => {
+                log::warn!("Unknown VALUATION_PRICE_SOURCE '{}', using last_trade", other);
+                PriceSource::LastTrade
+            }
+        }
+    }
+}
+
+/// Latest prices per symbol, shared between the event loop and the web layer.
+/// `mark_price` is the one valuation price every portfolio and PnL figure uses.
+pub struct LastPriceCache {
+    prices: RwLock<HashMap<String, f64>>,
+    mids: RwLock<HashMap<String, f64>>,
+    marks: RwLock<HashMap<String, f64>>,
+    quote_asset: String,
+    source: PriceSource,
+}
+
+impl LastPriceCache {
+    /// `quote_asset` is the asset balances are valued in
+    pub fn new(quote_asset: &str) -> Self {
+        Self {
+            prices: RwLock::new(HashMap::new()),
+            mids: RwLock::new(HashMap::new()),
+            marks: RwLock::new(HashMap::new()),
+            quote_asset: quote_asset.to_string(),
+            source: PriceSource::LastTrade,
+        }
+    }
+
+    pub fn with_source(mut self, source: PriceSource) -> Self {
+        self.source = source;
+        self
+    }
+
+    pub fn source(&self) -> PriceSource {
+        self.source
+    }
+
+    /// Records the last traded price
+    pub fn update(&self, symbol: &str, price: f64) {
+        if price > 0.0 {
+            self.prices.write().insert(symbol.to_string(), price);
+        }
+    }
+
+    pub fn update_mid(&self, symbol: &str, mid: f64) {
+        if mid > 0.0 {
+            self.mids.write().insert(symbol.to_string(), mid);
+        }
+    }
+
+    pub fn update_mark(&self, symbol: &str, mark: f64) {
+        if mark > 0.0 {
+            self.marks.write().insert(symbol.to_string(), mark);
+        }
+    }
+
+    /// Last traded price
+    pub fn get(&self, symbol: &str) -> Option<f64> {
+        self.prices.read().get(symbol).copied()
+    }
+
+    /// Valuation price from the configured source, falling back to the last trade until
+    /// that source has reported
+    pub fn mark_price(&self, symbol: &str) -> Option<f64> {
+        let preferred = match self.source {
+            PriceSource::LastTrade => None,
+            PriceSource::Mid => self.mids.read().get(symbol).copied(),
+            PriceSource::Mark => self.marks.read().get(symbol).copied(),
+        };
+        preferred.or_else(|| self.get(symbol))
+    }
+
+    /// Price of an asset in the quote asset, via its `{asset}{quote}` pair
+    pub fn asset_price(&self, asset: &str) -> Option<f64> {
+        if asset == self.quote_asset {
+            return Some(1.0);
+        }
+        self.mark_price(&format!("{}{}", asset, self.quote_asset))
+    }
+
+    /// Total value of a set of balances in the quote asset. Assets without a known price are ignored.
+    pub fn portfolio_value(&self, balances: &[(String, f64)]) -> f64 {
+        balances.iter()
+            .map(|(asset, amount)| amount * self.asset_price(asset).unwrap_or(0.0))
+            .sum()
+    }
+
+    /// Re-marks positions' unrealized PnL at the valuation price. Positions with no known price keep theirs.
+    pub fn mark_positions(&self, positions: &mut [crate::execution::PositionInfo]) {
+        for p in positions.iter_mut() {
+            if let Some(mark) = self.mark_price(&p.symbol) {
+                let direction = if p.side == "Short" { -1.0 } else { 1.0 };
+                p.unrealized_pnl = (mark - p.entry_price) * p.amount * direction;
+            }
+        }
+    }
+}
@@ -0,0 +1,323 @@
+use super::MarketEvent;
+use parking_lot::Mutex;
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use tokio::sync::Notify;
+
+pub const DEFAULT_QUEUE_SIZE: usize = 100;
+
+/// What market data producers do when the event queue is full.
+///
+/// - `Block`: the producer waits for room. Nothing is lost, but a slow consumer stalls the
+///   websocket readers, so in live mode the lag behind the exchange keeps growing.
+///   Required for backtests, where every event must be replayed.
+/// - `DropOldest`: the oldest queued event is discarded to make room. Strategies always act
+///   on the latest market state at the cost of gaps in what they saw. Usually the right
+///   choice for live trading.
+/// - `DropNewest`: the incoming event is discarded. Queued events keep flowing in order,
+///   but what remains is increasingly stale while the consumer catches up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+pub enum BackpressurePolicy {
+    Block,
+    DropOldest,
+    DropNewest,
+}
+
+impl BackpressurePolicy {
+    /// Reads MARKET_QUEUE_POLICY (block | drop_oldest | drop_newest, default block)
+    pub fn from_env() -> Self {
+        match std::env::var("MARKET_QUEUE_POLICY").unwrap_or_default().to_lowercase().as_str() {
+            "drop_oldest" => BackpressurePolicy::DropOldest,
+            "drop_newest" => BackpressurePolicy::DropNewest,
+            "" | "block" => BackpressurePolicy::Block,
+            other => {
+                log::warn!("Unknown MARKET_QUEUE_POLICY '{}', using block", other);
+                BackpressurePolicy::Block
+            }
+        }
+    }
+}
+
+/// Capacity of the market event queue from MARKET_QUEUE_SIZE (default 100)
+pub fn queue_size_from_env() -> usize {
+    std::env::var("MARKET_QUEUE_SIZE")
+        .ok()
+        .and_then(|v| v.parse::<usize>().ok())
+        .filter(|v| *v > 0)
+        .unwrap_or(DEFAULT_QUEUE_SIZE)
+}
+
+/// The receiving side is gone
+#[derive(Debug)]
+pub struct QueueClosed;
+
+impl std::fmt::Display for QueueClosed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "market event queue closed")
+    }
+}
+
+impl std::error::Error for QueueClosed {}
+
+struct Shared {
+    events: Mutex<VecDeque<MarketEvent>>,
+    capacity: usize,
+    policy: BackpressurePolicy,
+    not_empty: Notify,
+    not_full: Notify,
+    dropped: AtomicU64,
+    senders: AtomicUsize,
+    receiver_alive: AtomicBool,
+}
+
+/// Bounded multi-producer, single-consumer queue of market events with a configurable
+/// policy for when it fills up
+pub fn event_queue(capacity: usize, policy: BackpressurePolicy) -> (EventSender, EventReceiver) {
+    let shared = Arc::new(Shared {
+        events: Mutex::new(VecDeque::with_capacity(capacity.max(1))),
+        capacity: capacity.max(1),
+        policy,
+        not_empty: Notify::new(),
+        not_full: Notify::new(),
+        dropped: AtomicU64::new(0),
+        senders: AtomicUsize::new(1),
+        receiver_alive: AtomicBool::new(true),
+    });
+    (EventSender { shared: shared.clone() }, EventReceiver { shared })
+}
+
+pub struct EventSender {
+    shared: Arc<Shared>,
+}
+
+impl EventSender {
+    /// Queues an event, waiting for room only under `BackpressurePolicy::Block`
+    pub async fn send(&self, event: MarketEvent) -> Result<(), QueueClosed> {
+        let shared = &self.shared;
+        loop {
+            let not_full = shared.not_full.notified();
+            tokio::pin!(not_full);
+            not_full.as_mut().enable();
+
+            if !shared.receiver_alive.load(Ordering::Acquire) {
+                return Err(QueueClosed);
+            }
+            {
+                let mut events = shared.events.lock();
+                if events.len() < shared.capacity {
+                    events.push_back(event);
+                    drop(events);
+                    shared.not_empty.notify_one();
+                    return Ok(());
+                }
+                match shared.policy {
+                    BackpressurePolicy::Block => {}
+                    BackpressurePolicy::DropOldest => {
+                        events.pop_front();
+                        events.push_back(event);
+                        drop(events);
+                        self.count_drop();
+                        shared.not_empty.notify_one();
+                        return Ok(());
+                    }
+                    BackpressurePolicy::DropNewest => {
+                        drop(events);
+                        self.count_drop();
+                        return Ok(());
+                    }
+                }
+            }
+            not_full.await;
+        }
+    }
+
+    /// `send` for producers on plain OS threads (the websocket readers)
+    pub fn blocking_send(&self, event: MarketEvent) -> Result<(), QueueClosed> {
+        futures::executor::block_on(self.send(event))
+    }
+
+    fn count_drop(&self) {
+        let dropped = self.shared.dropped.fetch_add(1, Ordering::Relaxed) + 1;
+        if dropped.is_power_of_two() {
+            log::warn!("Market event queue full ({:?}): {} events dropped so far", self.shared.policy, dropped);
+        }
+    }
+
+    pub fn status(&self) -> QueueStatus {
+        QueueStatus {
+            policy: self.shared.policy,
+            capacity: self.shared.capacity,
+            queued: self.shared.events.lock().len(),
+            dropped: self.shared.dropped.load(Ordering::Relaxed),
+        }
+    }
+}
+
+impl Clone for EventSender {
+    fn clone(&self) -> Self {
+        self.shared.senders.fetch_add(1, Ordering::AcqRel);
+        Self { shared: self.shared.clone() }
+    }
+}
+
+impl Drop for EventSender {
+    fn drop(&mut self) {
+        if self.shared.senders.fetch_sub(1, Ordering::AcqRel) == 1 {
+            self.shared.not_empty.notify_one();
+        }
+    }
+}
+
+pub struct EventReceiver {
+    shared: Arc<Shared>,
+}
+
+impl EventReceiver {
+    /// Next event in arrival order; None once every sender is gone and the queue is drained
+    pub async fn recv(&mut self) -> Option<MarketEvent> {
+        let shared = &self.shared;
+        loop {
+            let not_empty = shared.not_empty.notified();
+            tokio::pin!(not_empty);
+            not_empty.as_mut().enable();
+
+            if let Some(event) = shared.events.lock().pop_front() {
+                shared.not_full.notify_one();
+                return Some(event);
+            }
+            if shared.senders.load(Ordering::Acquire) == 0 {
+                return None;
+            }
+            not_empty.await;
+        }
+    }
+}
+
+impl Drop for EventReceiver {
+    fn drop(&mut self) {
+        self.shared.receiver_alive.store(false, Ordering::Release);
+        self.shared.not_full.notify_waiters();
+    }
+}
+
+/// Market event queue state for /api/status
+#[derive(Serialize, Clone, Debug, JsonSchema)]
+pub struct QueueStatus {
+    pub policy: BackpressurePolicy,
+    pub capacity: usize,
+    /// Events waiting for the event loop
+    pub queued: usize,
+    /// Events discarded by a drop policy since startup
+    pub dropped: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use binance::model::OrderBook;
+    use std::time::Duration;
+
+    /// An event tagged with `id` so tests can follow it through the queue
+    fn event(id: u64) -> MarketEvent {
+        MarketEvent::OrderBook("BTCUSDT".to_string(), OrderBook { last_update_id: id, bids: Vec::new(), asks: Vec::new() })
+    }
+
+    fn id(event: MarketEvent) -> u64 {
+        match event {
+            MarketEvent::OrderBook(_, book) => book.last_update_id,
+            _ => unreachable!("tests only queue order books"),
+        }
+    }
+
+    const SHORT: Duration = Duration::from_millis(50);
+
+    #[tokio::test]
+    async fn drop_policies_discard_when_full() {
+        let (tx, mut rx) = event_queue(2, BackpressurePolicy::DropOldest);
+        for i in 1..=3 {
+            tx.send(event(i)).await.unwrap();
+        }
+        assert_eq!(tx.status().dropped, 1);
+        assert_eq!(id(rx.recv().await.unwrap()), 2);
+        assert_eq!(id(rx.recv().await.unwrap()), 3);
+
+        let (tx, mut rx) = event_queue(2, BackpressurePolicy::DropNewest);
+        for i in 1..=3 {
+            tx.send(event(i)).await.unwrap();
+        }
+        assert_eq!(tx.status().dropped, 1);
+        assert_eq!(id(rx.recv().await.unwrap()), 1);
+        assert_eq!(id(rx.recv().await.unwrap()), 2);
+    }
+
+    #[tokio::test]
+    async fn a_blocked_sender_wakes_when_room_frees() {
+        let (tx, mut rx) = event_queue(1, BackpressurePolicy::Block);
+        tx.send(event(1)).await.unwrap();
+        let blocked = tokio::spawn(async move { tx.send(event(2)).await });
+        tokio::time::sleep(SHORT).await;
+        assert!(!blocked.is_finished());
+
+        assert_eq!(id(rx.recv().await.unwrap()), 1);
+        tokio::time::timeout(SHORT, blocked).await.unwrap().unwrap().unwrap();
+        assert_eq!(id(rx.recv().await.unwrap()), 2);
+        // Every sender is gone and the queue is drained
+        assert!(rx.recv().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn dropping_the_receiver_fails_blocked_and_later_sends() {
+        let (tx, rx) = event_queue(1, BackpressurePolicy::Block);
+        tx.send(event(1)).await.unwrap();
+        let blocked_tx = tx.clone();
+        let blocked = tokio::spawn(async move { blocked_tx.send(event(2)).await });
+        tokio::time::sleep(SHORT).await;
+
+        drop(rx);
+        assert!(tokio::time::timeout(SHORT, blocked).await.unwrap().unwrap().is_err());
+        assert!(tx.send(event(3)).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn a_waiting_receiver_wakes_when_the_last_sender_drops() {
+        let (tx, mut rx) = event_queue(4, BackpressurePolicy::Block);
+        let second = tx.clone();
+        let waiting = tokio::spawn(async move { rx.recv().await.map(id) });
+        tokio::time::sleep(SHORT).await;
+
+        drop(tx);
+        tokio::time::sleep(SHORT).await;
+        assert!(!waiting.is_finished());
+        drop(second);
+        assert_eq!(tokio::time::timeout(SHORT, waiting).await.unwrap().unwrap(), None);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn concurrent_blocking_producers_lose_nothing() {
+        const PRODUCERS: u64 = 4;
+        const PER_PRODUCER: u64 = 1000;
+        let (tx, mut rx) = event_queue(8, BackpressurePolicy::Block);
+        for producer in 0..PRODUCERS {
+            let tx = tx.clone();
+            tokio::spawn(async move {
+                for seq in 0..PER_PRODUCER {
+                    tx.send(event(producer * PER_PRODUCER + seq)).await.unwrap();
+                }
+            });
+        }
+        drop(tx);
+
+        let mut next = vec![0; PRODUCERS as usize];
+        while let Some(event) = tokio::time::timeout(Duration::from_secs(5), rx.recv()).await.unwrap() {
+            let id = id(event);
+            let (producer, seq) = ((id / PER_PRODUCER) as usize, id % PER_PRODUCER);
+            // Each producer's events arrive in the order it sent them
+            assert_eq!(seq, next[producer]);
+            next[producer] += 1;
+        }
+        assert!(next.iter().all(|&count| count == PER_PRODUCER));
+    }
+}
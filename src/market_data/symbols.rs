@@ -0,0 +1,51 @@
+use binance::api::Binance;
+use binance::futures::general::FuturesGeneral;
+use binance::general::General;
+use log::{info, warn};
+use std::collections::HashSet;
+use tokio::sync::OnceCell;
+
+/// Spot and futures symbols listed on Binance, fetched on first use.
+/// A failed fetch leaves the cell empty so the next call tries again.
+static VALID_SYMBOLS: OnceCell<HashSet<String>> = OnceCell::const_new();
+
+async fn fetch_valid_symbols() -> Result<HashSet<String>, String> {
+    tokio::task::spawn_blocking(|| {
+        let spot: General = Binance::new(None, None);
+        let futures: FuturesGeneral = Binance::new(None, None);
+        let mut symbols: HashSet<String> = spot.exchange_info()
+            .map_err(|e| format!("spot exchange info: {:?}", e))?
+            .symbols
+            .into_iter()
+            .map(|s| s.symbol)
+            .collect();
+        // Futures listings are not a subset of spot (e.g. 1000PEPEUSDT)
+        match futures.exchange_info() {
+            Ok(info) => symbols.extend(info.symbols.into_iter().map(|s| s.symbol)),
+            Err(e) => warn!("Futures exchange info unavailable, validating against spot only: {:?}", e),
+        }
+        info!("Loaded {} Binance symbols for validation", symbols.len());
+        Ok(symbols)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Upper-cases a symbol and checks it against Binance's exchange info.
+///
+/// If the exchange info cannot be fetched (e.g. offline backtesting) only the
+/// format is checked, so a Binance outage doesn't block local work.
+pub async fn validate_symbol(symbol: &str) -> Result<String, String> {
+    let symbol = symbol.trim().to_uppercase();
+    if symbol.is_empty() || !symbol.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return Err(format!("Invalid symbol {:?}: expected letters and digits only, e.g. BTCUSDT", symbol));
+    }
+    match VALID_SYMBOLS.get_or_try_init(fetch_valid_symbols).await {
+        Ok(valid) if valid.contains(&symbol) => Ok(symbol),
+        Ok(_) => Err(format!("Unknown symbol {}: not listed on Binance", symbol)),
+        Err(e) => {
+            warn!("Could not load Binance symbols ({}), accepting {} unchecked", e, symbol);
+            Ok(symbol)
+        }
+    }
+}
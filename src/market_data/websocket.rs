@@ -1,31 +1,79 @@
-use super::MarketEvent;
+use super::{EventSender, MarketEvent};
 use binance::websockets::*;
 use log::{info, error, warn};
-use std::sync::atomic::AtomicBool;
-use tokio::sync::mpsc;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+const INITIAL_BACKOFF_SECS: u64 = 1;
+const MAX_BACKOFF_SECS: u64 = 60;
+
+/// Connection health shared between the websocket threads and the dashboard
+pub struct ConnectionHealth {
+    connected_streams: AtomicUsize,
+    expected_streams: usize,
+    reconnect_attempts: AtomicU64,
+}
+
+impl ConnectionHealth {
+    pub fn new(expected_streams: usize) -> Self {
+        Self {
+            connected_streams: AtomicUsize::new(0),
+            expected_streams,
+            reconnect_attempts: AtomicU64::new(0),
+        }
+    }
+
+    /// True when every symbol's websocket is connected
+    pub fn is_connected(&self) -> bool {
+        self.connected_streams.load(Ordering::Relaxed) >= self.expected_streams
+    }
+
+    pub fn reconnect_attempts(&self) -> u64 {
+        self.reconnect_attempts.load(Ordering::Relaxed)
+    }
+}
 
 pub struct MarketDataManager {
-    pub symbol: String,
-    sender: mpsc::Sender<MarketEvent>,
+    pub symbols: Vec<String>,
+    sender: EventSender,
+    health: Arc<ConnectionHealth>,
+    /// Stream depth diffs for a local order book instead of depth10 snapshots
+    local_order_book: bool,
 }
 
 impl MarketDataManager {
-    pub fn new(symbol: String, sender: mpsc::Sender<MarketEvent>) -> Self {
-        Self { symbol, sender }
+    pub fn new(symbols: Vec<String>, sender: EventSender) -> Self {
+        let health = Arc::new(ConnectionHealth::new(symbols.len()));
+        let local_order_book = super::order_book::local_order_book_from_env();
+        if local_order_book {
+            info!("Maintaining local order books from depth diffs");
+        }
+        Self { symbols, sender, health, local_order_book }
+    }
+
+    pub fn health(&self) -> Arc<ConnectionHealth> {
+        self.health.clone()
     }
 
+    /// Opens one websocket per symbol; all events fan into the same channel
     pub async fn connect(&self) {
-        let symbol = self.symbol.to_lowercase();
-        let sender = self.sender.clone();
+        for symbol in &self.symbols {
+            Self::connect_symbol(symbol.clone(), self.sender.clone(), self.health.clone(), self.local_order_book);
+        }
+    }
+
+    fn connect_symbol(symbol: String, sender: EventSender, health: Arc<ConnectionHealth>, local_order_book: bool) {
+        let stream_symbol = symbol.to_lowercase();
 
-        info!("Connecting to market data for {}", symbol);
+        info!("Connecting to market data for {}", stream_symbol);
 
         // Use a dedicated standard thread to completely isolate 
         // the blocking binance-rs client from the tokio runtime.
         std::thread::spawn(move || {
             let keep_running = AtomicBool::new(true);
             let sender_clone = sender.clone();
-            let symbol_log = symbol.clone();
+            let book_symbol = symbol.to_uppercase();
             
             let web_socket = WebSockets::new(move |event: WebsocketEvent| {
                 match event {
@@ -42,13 +90,13 @@ impl MarketDataManager {
                         }
                     }
                     WebsocketEvent::OrderBook(depth) => {
-                         // info!("WS Received OrderBook for {}", symbol_log);
-                         if let Err(e) = sender_clone.blocking_send(MarketEvent::OrderBook(depth)) {
+                         // info!("WS Received OrderBook for {}", book_symbol);
+                         if let Err(e) = sender_clone.blocking_send(MarketEvent::OrderBook(book_symbol.clone(), depth)) {
                             error!("Failed to send depth event: {}", e);
                          }
                     }
                     WebsocketEvent::DepthOrderBook(depth) => {
-                         // info!("WS Received DepthUpdate for {}", symbol_log);
+                         // info!("WS Received DepthUpdate for {}", book_symbol);
                          if let Err(e) = sender_clone.blocking_send(MarketEvent::DepthUpdate(depth)) {
                             error!("Failed to send depth event: {}", e);
                          }
@@ -62,20 +110,47 @@ impl MarketDataManager {
             // is NEVER dropped during a tokio shutdown context.
             let web_socket = Box::leak(Box::new(web_socket));
 
-            if let Err(e) = web_socket.connect_multiple_streams(&vec![
-                format!("{}@trade", symbol),
-                format!("{}@aggTrade", symbol),
-                format!("{}@depth10@100ms", symbol),
-            ]) {
-                 error!("Failed to connect WS: {}", e);
-                 return;
-            }
+            let streams = vec![
+                format!("{}@trade", stream_symbol),
+                format!("{}@aggTrade", stream_symbol),
+                if local_order_book {
+                    format!("{}@depth@100ms", stream_symbol)
+                } else {
+                    format!("{}@depth10@100ms", stream_symbol)
+                },
+            ];
+            let mut backoff_secs = INITIAL_BACKOFF_SECS;
+
+            // Reconnect forever; Binance drops every connection at least once a day
+            loop {
+                match web_socket.connect_multiple_streams(&streams) {
+                    Ok(()) => {
+                        info!("WS connected for {}", stream_symbol);
+                        health.connected_streams.fetch_add(1, Ordering::Relaxed);
+                        let connected_at = Instant::now();
+
+                        if let Err(e) = web_socket.event_loop(&keep_running) {
+                            error!("Error in WS event loop for {}: {}", stream_symbol, e);
+                        }
+                        health.connected_streams.fetch_sub(1, Ordering::Relaxed);
+                        let _ = web_socket.disconnect();
+                        warn!("WS event loop exited for {}", stream_symbol);
 
-            if let Err(e) = web_socket.event_loop(&keep_running) {
-                 error!("Error in WS event loop: {}", e);
+                        // A connection that stayed up for a while resets the backoff
+                        if connected_at.elapsed() > Duration::from_secs(MAX_BACKOFF_SECS) {
+                            backoff_secs = INITIAL_BACKOFF_SECS;
+                        }
+                    }
+                    Err(e) => {
+                        error!("Failed to connect WS for {}: {}", stream_symbol, e);
+                    }
+                }
+
+                let attempt = health.reconnect_attempts.fetch_add(1, Ordering::Relaxed) + 1;
+                warn!("Reconnecting WS for {} in {}s (attempt #{})", stream_symbol, backoff_secs, attempt);
+                std::thread::sleep(Duration::from_secs(backoff_secs));
+                backoff_secs = (backoff_secs * 2).min(MAX_BACKOFF_SECS);
             }
-            
-            warn!("WS event loop exited");
         });
     }
 }
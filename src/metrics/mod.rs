@@ -1,4 +1,5 @@
 use hdrhistogram::Histogram;
+use std::fmt::Write;
 use std::sync::Mutex;
 use std::time::Duration;
 
@@ -39,6 +40,33 @@ impl SystemMetrics {
         Self::stats_from_hist(&hist)
     }
 
+    /// Renders both latency histograms in Prometheus text exposition format.
+    /// Quantiles are emitted as separate `{quantile="..."}` series on the same metric.
+    pub fn to_prometheus(&self, labels: &[(&str, &str)]) -> String {
+        let mut out = String::new();
+        let histograms = [
+            ("strategy", "Receive to signal latency", &self.strategy_latency),
+            ("execution", "Signal to order confirmation latency", &self.execution_latency),
+        ];
+        for (stage, help, hist) in histograms {
+            let hist = hist.lock().unwrap();
+            let stats = Self::stats_from_hist(&hist);
+            let name = format!("six_trading_{}_latency_microseconds", stage);
+            let _ = writeln!(out, "# HELP {} {} in microseconds", name, help);
+            let _ = writeln!(out, "# TYPE {} summary", name);
+            for (quantile, value) in [("0.5", stats.p50), ("0.9", stats.p90), ("0.99", stats.p99)] {
+                let mut series_labels = labels.to_vec();
+                series_labels.push(("quantile", quantile));
+                let _ = writeln!(out, "{}{} {}", name, format_labels(&series_labels), value);
+            }
+            let _ = writeln!(out, "{}_count{} {}", name, format_labels(labels), hist.len());
+            out.push_str(&prometheus_metric(&format!("{}_min", name), "Minimum recorded latency", "gauge", labels, stats.min as f64));
+            out.push_str(&prometheus_metric(&format!("{}_mean", name), "Mean recorded latency", "gauge", labels, stats.mean));
+            out.push_str(&prometheus_metric(&format!("{}_max", name), "Maximum recorded latency", "gauge", labels, stats.max as f64));
+        }
+        out
+    }
+
     fn stats_from_hist(hist: &Histogram<u64>) -> LatencyStats {
         LatencyStats {
             min: hist.min(),
@@ -51,7 +79,7 @@ impl SystemMetrics {
     }
 }
 
-#[derive(Debug, serde::Serialize, Clone)]
+#[derive(Debug, serde::Serialize, schemars::JsonSchema, Clone)]
 pub struct LatencyStats {
     pub min: u64,
     pub mean: f64,
@@ -61,3 +89,18 @@ pub struct LatencyStats {
     pub max: u64,
 }
 
+/// Formats a single Prometheus metric with its HELP and TYPE header
+pub fn prometheus_metric(name: &str, help: &str, kind: &str, labels: &[(&str, &str)], value: f64) -> String {
+    format!("# HELP {name} {help}\n# TYPE {name} {kind}\n{name}{} {value}\n", format_labels(labels))
+}
+
+fn format_labels(labels: &[(&str, &str)]) -> String {
+    if labels.is_empty() {
+        return String::new();
+    }
+    let pairs: Vec<String> = labels
+        .iter()
+        .map(|(k, v)| format!("{}=\"{}\"", k, v.replace('\\', "\\\\").replace('"', "\\\"")))
+        .collect();
+    format!("{{{}}}", pairs.join(","))
+}
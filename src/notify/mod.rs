@@ -0,0 +1,90 @@
+use async_trait::async_trait;
+use log::{info, warn};
+use serde::Serialize;
+use std::error::Error;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::strategy::Signal;
+
+/// Something worth telling an operator who is not watching the dashboard
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum NotifyEvent {
+    TradeExecuted {
+        strategy: String,
+        signal: Signal,
+        fill_price: Option<f64>,
+        pnl: f64,
+        timestamp: u64,
+    },
+    CircuitBreakerTripped {
+        drawdown_pct: f64,
+        max_drawdown_pct: f64,
+        timestamp: u64,
+    },
+}
+
+#[async_trait]
+pub trait Notifier: Send + Sync {
+    async fn notify(&self, event: &NotifyEvent) -> Result<(), Box<dyn Error + Send + Sync>>;
+}
+
+/// Used when no notification target is configured
+pub struct NoopNotifier;
+
+#[async_trait]
+impl Notifier for NoopNotifier {
+    async fn notify(&self, _event: &NotifyEvent) -> Result<(), Box<dyn Error + Send + Sync>> {
+        Ok(())
+    }
+}
+
+/// POSTs each event as JSON to a fixed URL
+pub struct WebhookNotifier {
+    client: reqwest::Client,
+    url: String,
+}
+
+impl WebhookNotifier {
+    pub fn new(url: String) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .unwrap_or_default();
+        Self { client, url }
+    }
+}
+
+#[async_trait]
+impl Notifier for WebhookNotifier {
+    async fn notify(&self, event: &NotifyEvent) -> Result<(), Box<dyn Error + Send + Sync>> {
+        self.client.post(&self.url)
+            .json(event)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+}
+
+/// Webhook notifier when NOTIFY_WEBHOOK_URL is set, otherwise a no-op
+pub fn from_env() -> Arc<dyn Notifier> {
+    match std::env::var("NOTIFY_WEBHOOK_URL") {
+        Ok(url) if !url.trim().is_empty() => {
+            info!("Notifications enabled: webhook");
+            Arc::new(WebhookNotifier::new(url.trim().to_string()))
+        }
+        _ => Arc::new(NoopNotifier),
+    }
+}
+
+/// Delivers in the background so a slow endpoint never holds up trading
+pub fn send(notifier: &Arc<dyn Notifier>, event: NotifyEvent) {
+    let notifier = notifier.clone();
+    tokio::spawn(async move {
+        if let Err(e) = notifier.notify(&event).await {
+            warn!("Failed to deliver notification: {}", e);
+        }
+    });
+}
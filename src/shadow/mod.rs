@@ -0,0 +1,208 @@
+//! Shadow strategies - every registered strategy runs on the live feed against
+//! its own simulated account, so their hypothetical PnL can be compared with
+//! the live strategy without risking capital.
+
+use crate::execution::{ExecutionManager, Executor, FeeSchedule};
+use crate::market_data::downloader::MarketType;
+use crate::market_data::{CandleAggregator, MarketEvent, CANDLE_INTERVAL_SECS};
+use crate::strategy::{OrderEvent, StrategyFactory, TradingStrategy};
+use crate::web::{AppState, SharedState};
+use log::{info, warn};
+use parking_lot::RwLock as SyncRwLock;
+use serde::Serialize;
+use sqlx::{Pool, Postgres};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, RwLock};
+
+/// Events queued for the shadow task; the live loop drops events rather than wait
+pub const SHADOW_QUEUE_SIZE: usize = 1000;
+const REPORT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// True when SHADOW_STRATEGIES=true
+pub fn enabled_from_env() -> bool {
+    std::env::var("SHADOW_STRATEGIES").map(|v| v.eq_ignore_ascii_case("true")).unwrap_or(false)
+}
+
+/// Hypothetical results of one strategy on one symbol
+#[derive(Serialize, Clone, Debug)]
+pub struct ShadowReport {
+    pub symbol: String,
+    pub strategy: String,
+    pub total_trades: u64,
+    pub win_trades: u64,
+    pub loss_trades: u64,
+    pub realized_pnl: f64,
+    pub unrealized_pnl: f64,
+    pub yield_pct: f64, // (realized + unrealized) / starting balance
+}
+
+/// Latest reports, shared with AppState for /api/shadow
+pub type ShadowReports = Arc<SyncRwLock<Vec<ShadowReport>>>;
+
+/// One strategy with its own state and simulated executor, as in a backtest pass
+struct ShadowStrategy {
+    strategy: Box<dyn TradingStrategy>,
+    state: SharedState,
+    executor: Arc<ExecutionManager>,
+    candles: CandleAggregator,
+}
+
+impl ShadowStrategy {
+    fn new(name: &str, symbol: &str, db_pool: Pool<Postgres>, fees: FeeSchedule, market_type: MarketType) -> Option<Self> {
+        let strategy = StrategyFactory::create_strategy(name)?;
+        let executor = Arc::new(ExecutionManager::new(true, fees, market_type));
+        let (dummy_tx, _) = crate::market_data::event_queue(1, crate::market_data::BackpressurePolicy::Block);
+        let mut state = AppState::new("shadow".to_string(), name.to_string(), db_pool, symbol.to_string(), executor.clone(), dummy_tx);
+        state.initial_balance = crate::execution::DEFAULT_SIM_BALANCE_USDT;
+        state.stream_live = false;
+        state.record_latencies = false;
+        state.is_trading = true;
+        Some(Self {
+            strategy,
+            state: Arc::new(RwLock::new(state)),
+            executor,
+            candles: CandleAggregator::new(CANDLE_INTERVAL_SECS),
+        })
+    }
+
+    async fn on_event(&mut self, event: &MarketEvent) {
+        let mut opps = match event {
+            MarketEvent::Trade(trade) => {
+                let price = trade.price.parse::<f64>().unwrap_or(0.0);
+                let qty = trade.qty.parse::<f64>().unwrap_or(0.0);
+                {
+                    let mut write_guard = self.state.write().await;
+                    write_guard.price_cache.update(&trade.symbol, price);
+                    write_guard.state_machine.on_market_event(trade.event_time);
+                    write_guard.state_machine.on_price(&trade.symbol, price, trade.event_time);
+                }
+                let fills = self.executor.on_trade_price(&trade.symbol, price, qty, trade.event_time).await.unwrap_or_default().fills;
+                for fill in &fills {
+                    self.strategy.on_order_event(&OrderEvent::from(fill));
+                }
+                for fill in fills.iter().filter(|f| f.realized_pnl != 0.0) {
+                    self.record_pnl(fill.realized_pnl).await;
+                }
+                let mut opps = self.strategy.process_trade(trade.clone(), self.state.clone()).await;
+                if let Some(candle) = self.candles.update(&trade.symbol, price, qty, trade.event_time) {
+                    opps.extend(self.strategy.process_candle(candle, self.state.clone()).await);
+                }
+                opps
+            }
+            MarketEvent::AggrTrade(agg) => {
+                self.state.write().await.state_machine.on_market_event(agg.event_time);
+                self.strategy.process_aggr_trade(agg.clone(), self.state.clone()).await
+            }
+            MarketEvent::OrderBook(_, book) => self.strategy.process_orderbook(book.clone(), self.state.clone()).await,
+            MarketEvent::DepthUpdate(_) => Vec::new(),
+        };
+
+        // Strategies still see warm-up data to build their indicators, but may not trade on it
+        if self.state.read().await.state_machine.is_warming_up() {
+            opps.clear();
+        }
+        for opp in opps {
+            match self.executor.execute_with_fill(opp.signal.clone()).await {
+                Ok(execution) if execution.not_sent.is_some() => {}
+                Ok(execution) => {
+                    if let Some(order_event) = OrderEvent::from_execution(&opp.signal, &execution) {
+                        self.strategy.on_order_event(&order_event);
+                    }
+                    self.state.write().await.total_trades += 1;
+                    if execution.pnl != 0.0 {
+                        self.record_pnl(execution.pnl).await;
+                    }
+                }
+                Err(e) => warn!("Shadow {} execution failed: {}", self.strategy.name(), e),
+            }
+        }
+    }
+
+    async fn record_pnl(&self, pnl: f64) {
+        let mut write_guard = self.state.write().await;
+        write_guard.realized_pnl += pnl;
+        if pnl > 0.0 {
+            write_guard.win_trades += 1;
+        } else {
+            write_guard.loss_trades += 1;
+        }
+    }
+
+    async fn report(&self, symbol: &str) -> ShadowReport {
+        let unrealized_pnl: f64 = self.executor.get_positions().await.unwrap_or_default()
+            .iter()
+            .map(|p| p.unrealized_pnl)
+            .sum();
+        let read_guard = self.state.read().await;
+        let yield_pct = if read_guard.initial_balance > 0.0 {
+            (read_guard.realized_pnl + unrealized_pnl) / read_guard.initial_balance * 100.0
+        } else { 0.0 };
+        ShadowReport {
+            symbol: symbol.to_string(),
+            strategy: self.strategy.name().to_string(),
+            total_trades: read_guard.total_trades,
+            win_trades: read_guard.win_trades,
+            loss_trades: read_guard.loss_trades,
+            realized_pnl: read_guard.realized_pnl,
+            unrealized_pnl,
+            yield_pct,
+        }
+    }
+}
+
+/// Drives a shadow copy of every available strategy per symbol, created on the symbol's first event
+pub struct ShadowRunner {
+    db_pool: Pool<Postgres>,
+    fees: FeeSchedule,
+    market_type: MarketType,
+    shadows: HashMap<String, Vec<ShadowStrategy>>,
+    reports: ShadowReports,
+}
+
+impl ShadowRunner {
+    pub fn new(db_pool: Pool<Postgres>, fees: FeeSchedule, market_type: MarketType, reports: ShadowReports) -> Self {
+        Self { db_pool, fees, market_type, shadows: HashMap::new(), reports }
+    }
+
+    /// Consumes events until the live loop drops its sender
+    pub async fn run(mut self, mut rx: mpsc::Receiver<MarketEvent>) {
+        let mut last_report = Instant::now();
+        while let Some(event) = rx.recv().await {
+            self.on_event(&event).await;
+            if last_report.elapsed() >= REPORT_INTERVAL {
+                self.publish().await;
+                last_report = Instant::now();
+            }
+        }
+    }
+
+    async fn on_event(&mut self, event: &MarketEvent) {
+        let symbol = event.symbol();
+        if !self.shadows.contains_key(symbol) {
+            let shadows: Vec<ShadowStrategy> = StrategyFactory::get_available_strategies()
+                .iter()
+                .filter_map(|name| ShadowStrategy::new(name, symbol, self.db_pool.clone(), self.fees, self.market_type))
+                .collect();
+            info!("Shadowing {} strategies on {}", shadows.len(), symbol);
+            self.shadows.insert(symbol.to_string(), shadows);
+        }
+        if let Some(shadows) = self.shadows.get_mut(symbol) {
+            for shadow in shadows {
+                shadow.on_event(event).await;
+            }
+        }
+    }
+
+    async fn publish(&self) {
+        let mut reports = Vec::new();
+        for (symbol, shadows) in &self.shadows {
+            for shadow in shadows {
+                reports.push(shadow.report(symbol).await);
+            }
+        }
+        reports.sort_by(|a, b| b.yield_pct.total_cmp(&a.yield_pct));
+        *self.reports.write() = reports;
+    }
+}
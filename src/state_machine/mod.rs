@@ -1,6 +1,11 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+/// Trades used to measure volatility for the cooldown
+const VOLATILITY_WINDOW: usize = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 pub enum SystemState {
     Booting,
     Accumulating, // Waiting for enough data
@@ -37,9 +42,81 @@ impl Default for SystemState {
     }
 }
 
+/// How much market data must be seen in Accumulating before Trading is allowed.
+/// Both conditions must hold; a zero disables that condition.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WarmUp {
+    pub min_events: u64,
+    pub min_duration_ms: u64, // Measured in event time, so replayed backtests behave like live
+}
+
+impl Default for WarmUp {
+    fn default() -> Self {
+        Self { min_events: 100, min_duration_ms: 5_000 }
+    }
+}
+
+impl WarmUp {
+    /// Reads WARMUP_EVENTS (default 100) and WARMUP_SECS (default 5) from the environment
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        let min_events = std::env::var("WARMUP_EVENTS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(defaults.min_events);
+        let min_duration_ms = std::env::var("WARMUP_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .map_or(defaults.min_duration_ms, |secs| secs * 1000);
+        Self { min_events, min_duration_ms }
+    }
+}
+
+/// Pauses trading while measured volatility stays high.
+///
+/// Trading -> Cooldown after `trigger_updates` consecutive prices above `threshold_pct`,
+/// Cooldown -> Analyzing once volatility has stayed at or below it for `recovery_ms`,
+/// and Analyzing -> Trading after another `trigger_updates` calm prices.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VolatilityCooldown {
+    pub threshold_pct: f64, // Std dev of trade-to-trade returns, in percent
+    pub trigger_updates: u32,
+    pub recovery_ms: u64, // Measured in event time, like the warm-up
+}
+
+impl VolatilityCooldown {
+    /// Enabled by VOL_COOLDOWN_THRESHOLD_PCT; VOL_COOLDOWN_UPDATES (default 20) and
+    /// VOL_COOLDOWN_RECOVERY_SECS (default 60) tune it
+    pub fn from_env() -> Option<Self> {
+        let threshold_pct = std::env::var("VOL_COOLDOWN_THRESHOLD_PCT")
+            .ok()
+            .and_then(|v| v.parse::<f64>().ok())
+            .filter(|t| t.is_finite() && *t > 0.0)?;
+        let trigger_updates = std::env::var("VOL_COOLDOWN_UPDATES")
+            .ok()
+            .and_then(|v| v.parse::<u32>().ok())
+            .unwrap_or(20)
+            .max(1);
+        let recovery_ms = std::env::var("VOL_COOLDOWN_RECOVERY_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(60) * 1000;
+        Some(Self { threshold_pct, trigger_updates, recovery_ms })
+    }
+}
+
 pub struct StateMachine {
     current_state: SystemState,
     last_transition_time: std::time::Instant,
+    warm_up: WarmUp,
+    warm_up_events: u64,
+    warm_up_start_ms: Option<u64>,
+    volatility_cooldown: Option<VolatilityCooldown>,
+    recent_prices: HashMap<String, VecDeque<f64>>, // Per symbol, so symbols never mix in one window
+    high_volatility_updates: u32,
+    calm_updates: u32,
+    calm_since_ms: Option<u64>,
+    in_volatility_cooldown: bool, // Cooldown entered here, not by the circuit breaker
     // [FromState][ToState] counter
     transition_matrix: [[u64; 5]; 5],
     // Predictive probabilities based on real-time scoring
@@ -47,15 +124,29 @@ pub struct StateMachine {
 }
 
 impl StateMachine {
-    pub fn new() -> Self {
+    pub fn with_warm_up(warm_up: WarmUp) -> Self {
         Self {
             current_state: SystemState::Booting,
             last_transition_time: std::time::Instant::now(),
+            warm_up,
+            warm_up_events: 0,
+            warm_up_start_ms: None,
+            volatility_cooldown: None,
+            recent_prices: HashMap::new(),
+            high_volatility_updates: 0,
+            calm_updates: 0,
+            calm_since_ms: None,
+            in_volatility_cooldown: false,
             transition_matrix: [[0; 5]; 5],
             inferred_matrix: [[0.0; 5]; 5],
         }
     }
 
+    pub fn with_volatility_cooldown(mut self, cooldown: Option<VolatilityCooldown>) -> Self {
+        self.volatility_cooldown = cooldown;
+        self
+    }
+
     pub fn get_state(&self) -> SystemState {
         self.current_state
     }
@@ -71,9 +162,109 @@ impl StateMachine {
 
             self.current_state = new_state;
             self.last_transition_time = std::time::Instant::now();
+            self.high_volatility_updates = 0;
+            self.calm_updates = 0;
+            self.calm_since_ms = None;
+            self.in_volatility_cooldown = false;
+            if new_state == SystemState::Accumulating {
+                self.warm_up_events = 0;
+                self.warm_up_start_ms = None;
+            }
+        }
+    }
+
+    /// Advances the warm-up with one market event: Booting -> Accumulating on the first event,
+    /// Accumulating -> Trading once the warm-up is satisfied. Other states are left alone.
+    pub fn on_market_event(&mut self, ts_ms: u64) {
+        match self.current_state {
+            SystemState::Booting => {
+                self.transition_to(SystemState::Accumulating);
+                self.on_market_event(ts_ms);
+            }
+            SystemState::Accumulating => {
+                self.warm_up_events += 1;
+                self.warm_up_start_ms.get_or_insert(ts_ms);
+                if self.warm_up_complete(ts_ms) {
+                    self.transition_to(SystemState::Trading);
+                }
+            }
+            _ => {}
         }
     }
 
+    /// Feeds one trade price of `symbol` to the volatility cooldown, if configured.
+    /// Each symbol's volatility is measured over its own trades.
+    /// Cooldowns set elsewhere (e.g. the circuit breaker) are left alone.
+    pub fn on_price(&mut self, symbol: &str, price: f64, ts_ms: u64) {
+        let Some(cooldown) = self.volatility_cooldown else {
+            return;
+        };
+        if price <= 0.0 {
+            return;
+        }
+        let window = self.recent_prices.entry(symbol.to_string())
+            .or_insert_with(|| VecDeque::with_capacity(VOLATILITY_WINDOW + 1));
+        window.push_back(price);
+        if window.len() > VOLATILITY_WINDOW + 1 {
+            window.pop_front();
+        }
+        let Some(volatility_pct) = Self::measured_volatility_pct(window) else {
+            return;
+        };
+        let high = volatility_pct > cooldown.threshold_pct;
+
+        match self.current_state {
+            SystemState::Trading | SystemState::Analyzing => {
+                if high {
+                    self.high_volatility_updates += 1;
+                    self.calm_updates = 0;
+                } else {
+                    self.high_volatility_updates = 0;
+                    self.calm_updates += 1;
+                }
+                if self.high_volatility_updates >= cooldown.trigger_updates {
+                    log::warn!("Volatility {:.4}% above {:.4}% for {} updates, cooling down", volatility_pct, cooldown.threshold_pct, cooldown.trigger_updates);
+                    self.transition_to(SystemState::Cooldown);
+                    self.in_volatility_cooldown = true;
+                } else if self.current_state == SystemState::Analyzing && self.calm_updates >= cooldown.trigger_updates {
+                    self.transition_to(SystemState::Trading);
+                }
+            }
+            SystemState::Cooldown if self.in_volatility_cooldown => {
+                if high {
+                    self.calm_since_ms = None;
+                } else if ts_ms.saturating_sub(*self.calm_since_ms.get_or_insert(ts_ms)) >= cooldown.recovery_ms {
+                    log::info!("Volatility back below {:.4}%, resuming analysis", cooldown.threshold_pct);
+                    self.transition_to(SystemState::Analyzing);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Standard deviation of trade-to-trade returns over one symbol's window, in percent
+    fn measured_volatility_pct(window: &VecDeque<f64>) -> Option<f64> {
+        if window.len() < VOLATILITY_WINDOW + 1 {
+            return None;
+        }
+        let returns: Vec<f64> = window.iter().zip(window.iter().skip(1))
+            .map(|(prev, next)| (next - prev) / prev * 100.0)
+            .collect();
+        let mean = returns.iter().sum::<f64>() / returns.len() as f64;
+        let variance = returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / returns.len() as f64;
+        Some(variance.sqrt())
+    }
+
+    fn warm_up_complete(&self, ts_ms: u64) -> bool {
+        let elapsed_ms = self.warm_up_start_ms.map_or(0, |start| ts_ms.saturating_sub(start));
+        self.warm_up_events >= self.warm_up.min_events && elapsed_ms >= self.warm_up.min_duration_ms
+    }
+
+    /// True until the warm-up has moved the machine out of Booting/Accumulating
+    pub fn is_warming_up(&self) -> bool {
+        matches!(self.current_state, SystemState::Booting | SystemState::Accumulating)
+    }
+
     pub fn get_transition_probabilities(&self) -> Vec<Vec<f64>> {
         let mut probs = vec![vec![0.0; 5]; 5];
         for i in 0..5 {
@@ -94,12 +285,18 @@ impl StateMachine {
     /// Update inferred probabilities based on real-time market scores
     pub fn update_inferred_probabilities(&mut self, spread_score: f64, imbalance_score: f64, volatility_score: f64) {
         let current_idx = self.current_state.to_index();
-        
-        // Reset row for current state in inferred matrix
+        if let Some(row) = Self::infer_row(self.current_state, spread_score, imbalance_score, volatility_score) {
+            self.inferred_matrix[current_idx] = row;
+        }
+    }
+
+    /// Transition probabilities out of `state` implied by the market scores, without touching
+    /// any machine. None when the scores give every transition zero weight.
+    pub fn infer_row(state: SystemState, spread_score: f64, imbalance_score: f64, volatility_score: f64) -> Option<[f64; 5]> {
         let mut new_probs = [0.0; 5];
         
         // Logical inference (simplified for demonstration)
-        match self.current_state {
+        match state {
             SystemState::Booting | SystemState::Accumulating => {
                 // If spread is tight and imbalance exists, likely moving to Analyzing
                 new_probs[SystemState::Analyzing.to_index()] = (1.0 - spread_score).max(0.1);
@@ -129,17 +326,6 @@ impl StateMachine {
 
         // Normalize the row
         let sum: f64 = new_probs.iter().sum();
-        if sum > 0.0 {
-            for j in 0..5 {
-                self.inferred_matrix[current_idx][j] = new_probs[j] / sum;
-            }
-        }
-    }
-
-    pub fn is_stable(&self) -> bool {
-        match self.current_state {
-            SystemState::Accumulating => self.last_transition_time.elapsed().as_secs() > 5,
-            _ => true,
-        }
+        (sum > 0.0).then(|| new_probs.map(|p| p / sum))
     }
 }
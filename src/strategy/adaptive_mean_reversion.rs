@@ -1,4 +1,4 @@
-use super::{Signal, TradingStrategy, Opportunity};
+use super::{Signal, OrderType, TradingStrategy, Opportunity, RiskManager, indicators};
 use crate::web::SharedState;
 use crate::state_machine::SystemState;
 use async_trait::async_trait;
@@ -16,40 +16,63 @@ pub struct AdaptiveMeanReversion {
     last_signal_time: u64,
     signal_cooldown_ms: u64,
     recent_volatility: f64,
+    risk_per_trade_usd: f64,
+    sizing_volatility: f64,
+    target_size: f64,
 }
 
+const DEFAULT_BB_PERIOD: usize = 20;
+const DEFAULT_BB_STD_DEV: f64 = 2.0;
+const DEFAULT_RSI_PERIOD: usize = 14;
+const DEFAULT_COOLDOWN_MS: u64 = 45_000;
+/// Entry size bounds and the size used before there is a volatility estimate
+const MIN_SIZE: f64 = 0.0001;
+const MAX_SIZE: f64 = 0.01;
+const FALLBACK_SIZE: f64 = 0.001;
+
 impl AdaptiveMeanReversion {
     pub fn new() -> Self {
+        Self::with_params(DEFAULT_BB_PERIOD, DEFAULT_BB_STD_DEV, DEFAULT_RSI_PERIOD, DEFAULT_COOLDOWN_MS)
+    }
+
+    /// Periods must be > 1 and `bb_std_dev` > 0; invalid values fall back to the defaults
+    pub fn with_params(bb_period: usize, bb_std_dev: f64, rsi_period: usize, signal_cooldown_ms: u64) -> Self {
+        let bb_period = if bb_period > 1 { bb_period } else {
+            log::warn!("AdaptiveMeanReversion: bb_period {} must be > 1, using {}", bb_period, DEFAULT_BB_PERIOD);
+            DEFAULT_BB_PERIOD
+        };
+        let bb_std_dev = if bb_std_dev.is_finite() && bb_std_dev > 0.0 { bb_std_dev } else {
+            log::warn!("AdaptiveMeanReversion: bb_std_dev {} must be > 0, using {}", bb_std_dev, DEFAULT_BB_STD_DEV);
+            DEFAULT_BB_STD_DEV
+        };
+        let rsi_period = if rsi_period > 1 { rsi_period } else {
+            log::warn!("AdaptiveMeanReversion: rsi_period {} must be > 1, using {}", rsi_period, DEFAULT_RSI_PERIOD);
+            DEFAULT_RSI_PERIOD
+        };
         Self {
-            price_history: VecDeque::with_capacity(50),
-            bb_period: 20,
-            bb_std_dev: 2.0,
-            rsi_period: 14,
+            price_history: VecDeque::with_capacity(Self::history_len(bb_period, rsi_period)),
+            bb_period,
+            bb_std_dev,
+            rsi_period,
             last_signal_time: 0,
-            signal_cooldown_ms: 45000, // 45 seconds
+            signal_cooldown_ms,
             recent_volatility: 0.0,
+            risk_per_trade_usd: RiskManager::risk_per_trade_from_env(),
+            sizing_volatility: 0.0,
+            target_size: FALLBACK_SIZE,
         }
     }
 
+    /// Enough prices for the longer indicator (Wilder RSI wants a few periods to settle)
+    fn history_len(bb_period: usize, rsi_period: usize) -> usize {
+        50.max(bb_period.max(rsi_period * 3))
+    }
+
     /// Calculate Bollinger Bands
     fn calculate_bollinger_bands(&self) -> Option<(f64, f64, f64)> {
-        if self.price_history.len() < self.bb_period {
-            return None;
-        }
-
-        let recent_prices: Vec<f64> = self.price_history.iter()
-            .rev()
-            .take(self.bb_period)
-            .copied()
-            .collect();
-
-        let sma = recent_prices.iter().sum::<f64>() / recent_prices.len() as f64;
-        
-        let variance = recent_prices.iter()
-            .map(|p| (p - sma).powi(2))
-            .sum::<f64>() / recent_prices.len() as f64;
-        
-        let std_dev = variance.sqrt();
+        let prices: Vec<f64> = self.price_history.iter().copied().collect();
+        let sma = indicators::sma(&prices, self.bb_period)?;
+        let std_dev = indicators::stddev(&prices, self.bb_period)?;
         
         let upper_band = sma + (std_dev * self.bb_std_dev);
         let lower_band = sma - (std_dev * self.bb_std_dev);
@@ -57,55 +80,25 @@ impl AdaptiveMeanReversion {
         Some((lower_band, sma, upper_band))
     }
 
-    /// Calculate RSI
+    /// Calculate RSI (Wilder-smoothed), neutral until there is enough history
     fn calculate_rsi(&self) -> f64 {
-        if self.price_history.len() < self.rsi_period + 1 {
-            return 50.0; // Neutral
-        }
-
-        let prices: Vec<f64> = self.price_history.iter()
-            .rev()
-            .take(self.rsi_period + 1)
-            .copied()
-            .collect();
-
-        let mut gains = 0.0;
-        let mut losses = 0.0;
-
-        for i in 1..prices.len() {
-            let change = prices[i-1] - prices[i];
-            if change > 0.0 {
-                gains += change;
-            } else {
-                losses += change.abs();
-            }
-        }
-
-        let avg_gain = gains / self.rsi_period as f64;
-        let avg_loss = losses / self.rsi_period as f64;
-
-        if avg_loss == 0.0 {
-            return 100.0;
-        }
-
-        let rs = avg_gain / avg_loss;
-        100.0 - (100.0 / (1.0 + rs))
+        let prices: Vec<f64> = self.price_history.iter().copied().collect();
+        indicators::wilder_rsi(&prices, self.rsi_period).unwrap_or(50.0)
     }
 
     /// Calculate recent volatility for dynamic stop-loss
     fn calculate_volatility(&mut self) -> f64 {
-        if self.price_history.len() < 10 {
-            return 0.0;
+        let prices: Vec<f64> = self.price_history.iter().copied().collect();
+        match (indicators::sma(&prices, 10), indicators::stddev(&prices, 10)) {
+            (Some(mean), Some(std_dev)) if mean > 0.0 => std_dev / mean * 100.0,
+            _ => 0.0,
         }
+    }
 
-        let recent: Vec<f64> = self.price_history.iter().rev().take(10).copied().collect();
-        let mean = recent.iter().sum::<f64>() / recent.len() as f64;
-        
-        let variance = recent.iter()
-            .map(|p| (p - mean).powi(2))
-            .sum::<f64>() / recent.len() as f64;
-
-        variance.sqrt() / mean * 100.0
+    /// Entry size where a one-volatility adverse move (recent std dev in price units) loses RISK_PER_TRADE_USD
+    fn volatility_target_size(&self) -> f64 {
+        RiskManager::volatility_target(self.risk_per_trade_usd, self.sizing_volatility)
+            .map_or(FALLBACK_SIZE, |q| q.clamp(MIN_SIZE, MAX_SIZE))
     }
 }
 
@@ -121,6 +114,8 @@ impl TradingStrategy for AdaptiveMeanReversion {
         vec![
             ("RSI".to_string(), format!("{:.1}", rsi)),
             ("Volatility".to_string(), format!("{:.2}%", self.recent_volatility)),
+            ("Sizing Vol".to_string(), format!("{:.2}", self.sizing_volatility)),
+            ("Target Size".to_string(), format!("{:.4}", self.target_size)),
             ("SMA".to_string(), format!("{:.2}", sma)),
             ("BB Upper".to_string(), format!("{:.2}", upper)),
             ("BB Lower".to_string(), format!("{:.2}", lower)),
@@ -174,36 +169,38 @@ impl AdaptiveMeanReversion {
 
         // Update history
         self.price_history.push_back(price);
-        if self.price_history.len() > 50 {
+        if self.price_history.len() > Self::history_len(self.bb_period, self.rsi_period) {
             self.price_history.pop_front();
         }
 
         // Calculate indicators
         self.recent_volatility = self.calculate_volatility();
+        self.sizing_volatility = self.recent_volatility / 100.0 * price;
+        self.target_size = self.volatility_target_size();
         let rsi = self.calculate_rsi();
         
         let mut opportunities = Vec::new();
-        let current_state = state.read().await.state_machine.get_state();
+        let (current_state, cooldown_ms) = {
+            let read_guard = state.read().await;
+            (read_guard.state_machine.get_state(), read_guard.signal_cooldown_ms(self.name(), self.signal_cooldown_ms))
+        };
 
         // Generate signals with Bollinger Bands and RSI confirmation
         if current_state == SystemState::Trading &&
-           ts - self.last_signal_time > self.signal_cooldown_ms {
+           ts - self.last_signal_time > cooldown_ms {
             
             if let Some((lower_band, sma, upper_band)) = self.calculate_bollinger_bands() {
                 let distance_to_mean = (price - sma).abs() / sma * 100.0;
                 
                 // Oversold + RSI confirmation -> Buy
                 if price < lower_band && rsi < 40.0 {
-                    // Scale position based on distance from mean
-                    let position_multiplier = (distance_to_mean / 0.3).min(2.0);
-                    let position_size = 0.001 * position_multiplier;
-
                     opportunities.push(Opportunity {
-                        id: format!("mean_rev_buy_{}", ts),
+                        id: "mean_rev_buy".to_string(),
                         signal: Signal::Buy {
                             symbol: symbol.clone(),
                             price: Some(price * 1.0001),
-                            quantity: position_size.min(0.01).max(0.0001),
+                            quantity: self.target_size,
+                            order_type: OrderType::Market,
                         },
                         score: ((35.0 - rsi) / 35.0 * 0.5 + distance_to_mean / 2.0).min(0.90),
                         risk_score: (self.recent_volatility / 5.0).min(0.6),
@@ -217,11 +214,12 @@ impl AdaptiveMeanReversion {
                 // Overbought + RSI confirmation -> Sell
                 if price > upper_band && rsi > 60.0 {
                     opportunities.push(Opportunity {
-                        id: format!("mean_rev_sell_{}", ts),
+                        id: "mean_rev_sell".to_string(),
                         signal: Signal::Sell {
                             symbol: symbol.clone(),
                             price: Some(price * 0.9999),
                             quantity: 0.001,
+                            order_type: OrderType::Market,
                         },
                         score: ((rsi - 60.0) / 40.0 * 0.5 + distance_to_mean / 2.0).min(0.85),
                         risk_score: 0.4,
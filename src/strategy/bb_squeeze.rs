@@ -1,4 +1,5 @@
-use super::{Signal, TradingStrategy, Opportunity};
+use super::{Signal, OrderType, TradingStrategy, Opportunity, indicators};
+use crate::market_data::Candle;
 use crate::web::SharedState;
 use crate::state_machine::SystemState;
 use async_trait::async_trait;
@@ -6,38 +7,47 @@ use binance::model::{TradeEvent, OrderBook, AggrTradesEvent};
 use std::collections::VecDeque;
 
 /// Bollinger Band Squeeze Strategy
+///
+/// Bollinger and Keltner bands are both built from closed 1-minute candles so
+/// they share a timeframe; trades are only checked against them for breakouts.
+/// A breakout is traded only once the bands have been squeezed (BB inside KC).
 pub struct BBSqueeze {
-    prices: VecDeque<f64>,
+    candles: VecDeque<Candle>,
     period: usize,
     std_dev: f64,
     kc_mult: f64,  // Keltner Channel multiplier
+    squeeze_seen: bool,
     last_signal_time: u64,
 }
 
+/// Candles kept, in periods; Wilder-smoothed ATR settles over a few periods
+const HISTORY_PERIODS: usize = 3;
+
 impl BBSqueeze {
     pub fn new() -> Self {
+        let period = 20;
         Self {
-            prices: VecDeque::with_capacity(50),
-            period: 20,
+            candles: VecDeque::with_capacity(period * HISTORY_PERIODS),
+            period,
             std_dev: 2.0,
             kc_mult: 1.5,
+            squeeze_seen: false,
             last_signal_time: 0,
         }
     }
 
+    /// (bb_upper, bb_lower, sma, squeeze); None until `period + 1` candles have closed
     fn calculate_metrics(&self) -> Option<(f64, f64, f64, bool)> {
-        if self.prices.len() < self.period { return None; }
-        
-        let recent: Vec<f64> = self.prices.iter().rev().take(self.period).copied().collect();
-        let sma = recent.iter().sum::<f64>() / self.period as f64;
-        let variance = recent.iter().map(|p| (p - sma).powi(2)).sum::<f64>() / self.period as f64;
-        let stdev = variance.sqrt();
+        // process_candle keeps the buffer contiguous, so the first slice holds every candle
+        let (candles, _) = self.candles.as_slices();
+        let closes: Vec<f64> = candles.iter().map(|c| c.close).collect();
+        let sma = indicators::sma(&closes, self.period)?;
+        let stdev = indicators::stddev(&closes, self.period)?;
+        let atr = indicators::atr(candles, self.period)?;
         
         let bb_upper = sma + stdev * self.std_dev;
         let bb_lower = sma - stdev * self.std_dev;
         
-        // Simplified Keltner Channel
-        let atr = stdev; // Mock ATR
         let kc_upper = sma + atr * self.kc_mult;
         let kc_lower = sma - atr * self.kc_mult;
         
@@ -56,42 +66,57 @@ impl TradingStrategy for BBSqueeze {
         vec![
             ("Squeeze".to_string(), metrics.map(|m| m.3.to_string()).unwrap_or("False".to_string())),
             ("BB Width".to_string(), metrics.map(|m| format!("{:.2}", m.0 - m.1)).unwrap_or("0.0".to_string())),
+            ("Squeeze Seen".to_string(), self.squeeze_seen.to_string()),
         ]
     }
 
+    async fn process_candle(&mut self, candle: Candle, _state: SharedState) -> Vec<Opportunity> {
+        self.candles.push_back(candle);
+        if self.candles.len() > self.period * HISTORY_PERIODS {
+            self.candles.pop_front();
+        }
+        self.candles.make_contiguous();
+        if let Some((_, _, _, true)) = self.calculate_metrics() {
+            self.squeeze_seen = true;
+        }
+        Vec::new()
+    }
+
     async fn process_trade(&mut self, trade: TradeEvent, state: SharedState) -> Vec<Opportunity> {
         let price = trade.price.parse::<f64>().unwrap_or(0.0);
         let qty = trade.qty.parse::<f64>().unwrap_or(0.0);
         
-        self.prices.push_back(price);
-        if self.prices.len() > 50 { self.prices.pop_front(); }
-        
         let mut opps = Vec::new();
-        let current_state = state.read().await.state_machine.get_state();
+        let (current_state, cooldown_ms) = {
+            let read_guard = state.read().await;
+            (read_guard.state_machine.get_state(), read_guard.signal_cooldown_ms(self.name(), 60000))
+        };
         
         if let Some((upper, lower, sma, squeeze)) = self.calculate_metrics() {
-            if current_state == SystemState::Trading && trade.event_time - self.last_signal_time > 60000 {
-                // If squeeze is releasing
-                if !squeeze && price > upper {
+            if current_state == SystemState::Trading && trade.event_time - self.last_signal_time > cooldown_ms {
+                // Squeeze is releasing
+                if self.squeeze_seen && !squeeze && price > upper {
                     opps.push(Opportunity {
-                        id: format!("bb_squeeze_buy_{}", trade.event_time),
-                        signal: Signal::Buy { symbol: trade.symbol.clone(), price: Some(price), quantity: 0.001 },
+                        id: "bb_squeeze_buy".to_string(),
+                        signal: Signal::Buy { symbol: trade.symbol.clone(), price: Some(price), quantity: 0.001, order_type: OrderType::Market },
                         score: 0.8,
                         risk_score: 0.4,
                         reason: "BB Squeeze release bullish".to_string(),
                         timestamp: trade.event_time,
                     });
                     self.last_signal_time = trade.event_time;
-                } else if !squeeze && price < lower {
+                    self.squeeze_seen = false;
+                } else if self.squeeze_seen && !squeeze && price < lower {
                     opps.push(Opportunity {
-                        id: format!("bb_squeeze_sell_{}", trade.event_time),
-                        signal: Signal::Sell { symbol: trade.symbol.clone(), price: Some(price), quantity: 0.001 },
+                        id: "bb_squeeze_sell".to_string(),
+                        signal: Signal::Sell { symbol: trade.symbol.clone(), price: Some(price), quantity: 0.001, order_type: OrderType::Market },
                         score: 0.8,
                         risk_score: 0.4,
                         reason: "BB Squeeze release bearish".to_string(),
                         timestamp: trade.event_time,
                     });
                     self.last_signal_time = trade.event_time;
+                    self.squeeze_seen = false;
                 }
             }
         }
@@ -100,12 +125,45 @@ impl TradingStrategy for BBSqueeze {
         opps
     }
 
-    async fn process_aggr_trade(&mut self, trade: AggrTradesEvent, _: SharedState) -> Vec<Opportunity> {
-        let price = trade.price.parse::<f64>().unwrap_or(0.0);
-        self.prices.push_back(price);
-        if self.prices.len() > 50 { self.prices.pop_front(); }
-        Vec::new()
-    }
+    async fn process_aggr_trade(&mut self, _: AggrTradesEvent, _: SharedState) -> Vec<Opportunity> { Vec::new() }
 
     async fn process_orderbook(&mut self, _: OrderBook, _: SharedState) -> Vec<Opportunity> { Vec::new() }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn squeeze_with(period: usize, bars: &[(f64, f64, f64)]) -> BBSqueeze {
+        let mut squeeze = BBSqueeze::new();
+        squeeze.period = period;
+        for &(high, low, close) in bars {
+            squeeze.candles.push_back(Candle { symbol: "BTCUSDT".to_string(), timestamp: 0, open: close, high, low, close, volume: 1.0 });
+        }
+        squeeze.candles.make_contiguous();
+        squeeze
+    }
+
+    #[test]
+    fn wide_candles_with_flat_closes_squeeze() {
+        // Closes never move, so the Bollinger bands collapse onto the SMA, while every
+        // candle still spans 2.0 and the Keltner bands sit at 100 +/- 1.5 * 2.0
+        let flat = squeeze_with(3, &[(101.0, 99.0, 100.0); 4]);
+        let (upper, lower, sma, squeeze) = flat.calculate_metrics().unwrap();
+        assert_eq!((upper, lower, sma), (100.0, 100.0, 100.0));
+        assert!(squeeze);
+        assert!(squeeze_with(3, &[(101.0, 99.0, 100.0); 3]).calculate_metrics().is_none());
+    }
+
+    #[test]
+    fn a_jump_widens_bollinger_past_keltner() {
+        // True ranges 0, 0, 30 give an ATR of 10, so Keltner is 110 +/- 15, while the
+        // closes 100, 100, 130 have a standard deviation of sqrt(200)
+        let jump = squeeze_with(3, &[(100.0, 100.0, 100.0), (100.0, 100.0, 100.0), (100.0, 100.0, 100.0), (130.0, 130.0, 130.0)]);
+        let (upper, lower, sma, squeeze) = jump.calculate_metrics().unwrap();
+        assert!((sma - 110.0).abs() < 1e-9);
+        assert!((upper - (110.0 + 2.0 * 200f64.sqrt())).abs() < 1e-9);
+        assert!((lower - (110.0 - 2.0 * 200f64.sqrt())).abs() < 1e-9);
+        assert!(!squeeze);
+    }
+}
@@ -0,0 +1,179 @@
+use super::Signal;
+
+/// Take-profit / stop-loss settings for a long position.
+/// Percentages are expressed in percent (0.2 = 0.2%).
+#[derive(Debug, Clone, Copy)]
+pub struct BracketConfig {
+    pub take_profit_pct: f64,
+    pub stop_loss_pct: f64,
+    /// When set, the stop follows the best price seen since entry
+    pub trailing: bool,
+    /// Separate trailing stop that exits on this retrace from the peak, alongside the hard stop
+    pub trailing_stop_pct: Option<f64>,
+}
+
+impl BracketConfig {
+    pub fn new(take_profit_pct: f64, stop_loss_pct: f64, trailing: bool) -> Self {
+        Self { take_profit_pct, stop_loss_pct, trailing, trailing_stop_pct: None }
+    }
+
+    /// Adds a trailing stop; whichever of it and the hard stop is hit first closes the position
+    pub fn with_trailing_stop(mut self, trailing_stop_pct: f64) -> Self {
+        self.trailing_stop_pct = Some(trailing_stop_pct);
+        self
+    }
+}
+
+/// Tracks an open long position and emits an exit signal once the bracket is hit
+pub struct BracketTracker {
+    pub config: BracketConfig,
+    symbol: String,
+    entry_price: Option<f64>,
+    best_price: f64,
+}
+
+impl BracketTracker {
+    pub fn new(config: BracketConfig) -> Self {
+        Self {
+            config,
+            symbol: String::new(),
+            entry_price: None,
+            best_price: 0.0,
+        }
+    }
+
+    /// Starts tracking a new position
+    pub fn open(&mut self, symbol: &str, entry_price: f64) {
+        self.symbol = symbol.to_string();
+        self.entry_price = Some(entry_price);
+        self.best_price = entry_price;
+    }
+
+    /// Stops tracking without emitting an exit (e.g. the strategy exited on its own signal)
+    pub fn close(&mut self) {
+        self.entry_price = None;
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.entry_price.is_some()
+    }
+
+    /// Unrealized PnL in percent at the given price
+    pub fn pnl_pct(&self, price: f64) -> f64 {
+        match self.entry_price {
+            Some(entry) if entry > 0.0 => (price - entry) / entry * 100.0,
+            _ => 0.0,
+        }
+    }
+
+    /// Current stop level, following the best price when trailing
+    pub fn stop_price(&self) -> Option<f64> {
+        let entry = self.entry_price?;
+        let anchor = if self.config.trailing { self.best_price } else { entry };
+        Some(anchor * (1.0 - self.config.stop_loss_pct / 100.0))
+    }
+
+    /// Trailing stop level below the peak price since entry, if one is configured
+    pub fn trailing_stop_price(&self) -> Option<f64> {
+        self.entry_price?;
+        let pct = self.config.trailing_stop_pct?;
+        Some(self.best_price * (1.0 - pct / 100.0))
+    }
+
+    /// Take-profit level, if one is configured
+    pub fn target_price(&self) -> Option<f64> {
+        let entry = self.entry_price?;
+        if self.config.take_profit_pct <= 0.0 {
+            return None;
+        }
+        Some(entry * (1.0 + self.config.take_profit_pct / 100.0))
+    }
+
+    /// Closes the position at `price` regardless of the bracket levels (e.g. a time exit).
+    /// Sells whatever is held, which may differ from the entry after risk sizing or partial fills.
+    pub fn exit(&mut self, price: f64) -> Option<Signal> {
+        self.entry_price?;
+        let signal = Signal::SellAll {
+            symbol: self.symbol.clone(),
+            price: Some(price),
+        };
+        self.close();
+        Some(signal)
+    }
+
+    /// Feeds the latest price. Returns a Sell signal and closes the bracket when TP or SL is hit.
+    pub fn update(&mut self, price: f64) -> Option<Signal> {
+        self.entry_price?;
+        if price > self.best_price {
+            self.best_price = price;
+        }
+
+        let hit_target = self.target_price().is_some_and(|tp| price >= tp);
+        let hit_stop = self.stop_price().is_some_and(|sl| price <= sl)
+            || self.trailing_stop_price().is_some_and(|ts| price <= ts);
+        if !hit_target && !hit_stop {
+            return None;
+        }
+
+        self.exit(price)
+    }
+}
+
+/// Default holding limit before a position is force-closed
+pub const DEFAULT_MAX_HOLD_MINS: u64 = 60;
+
+/// Time-based exit: flags a position once it has been open longer than `max_hold_ms`
+#[derive(Debug, Clone, Copy)]
+pub struct HoldTimer {
+    /// None disables the time exit
+    pub max_hold_ms: Option<u64>,
+    entry_ts: Option<u64>,
+    last_ts: u64,
+}
+
+impl HoldTimer {
+    pub fn new(max_hold_ms: Option<u64>) -> Self {
+        Self { max_hold_ms, entry_ts: None, last_ts: 0 }
+    }
+
+    /// Reads MAX_HOLD_MINS (default 60, 0 = no time exit) from the environment
+    pub fn from_env() -> Self {
+        let mins = std::env::var("MAX_HOLD_MINS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(DEFAULT_MAX_HOLD_MINS);
+        Self::new((mins > 0).then_some(mins * 60_000))
+    }
+
+    /// Starts the clock for a new position at event time `ts` (ms)
+    pub fn start(&mut self, ts: u64) {
+        self.entry_ts = Some(ts);
+        self.last_ts = ts;
+    }
+
+    pub fn stop(&mut self) {
+        self.entry_ts = None;
+    }
+
+    /// Feeds the latest event time. Returns true once the open position has outlived the limit.
+    pub fn update(&mut self, ts: u64) -> bool {
+        self.last_ts = self.last_ts.max(ts);
+        match (self.entry_ts, self.max_hold_ms) {
+            (Some(entry_ts), Some(max_hold_ms)) => ts.saturating_sub(entry_ts) > max_hold_ms,
+            _ => false,
+        }
+    }
+
+    /// Time left before the forced exit, as of the last update
+    pub fn remaining_ms(&self) -> Option<u64> {
+        let entry_ts = self.entry_ts?;
+        let max_hold_ms = self.max_hold_ms?;
+        Some(max_hold_ms.saturating_sub(self.last_ts.saturating_sub(entry_ts)))
+    }
+
+    /// "Hold Left" entry for a strategy's features
+    pub fn feature(&self) -> (String, String) {
+        let value = self.remaining_ms().map_or("N/A".to_string(), |ms| format!("{}s", ms / 1000));
+        ("Hold Left".to_string(), value)
+    }
+}
@@ -1,4 +1,4 @@
-use super::{Signal, TradingStrategy, Opportunity};
+use super::{Signal, OrderType, TradingStrategy, Opportunity};
 use crate::web::SharedState;
 use crate::state_machine::SystemState;
 use async_trait::async_trait;
@@ -59,16 +59,19 @@ impl TradingStrategy for BreakoutRangeStrategy {
         self.update_range();
         
         let mut opps = Vec::new();
-        let current_state = state.read().await.state_machine.get_state();
+        let (current_state, cooldown_ms) = {
+            let read_guard = state.read().await;
+            (read_guard.state_machine.get_state(), read_guard.signal_cooldown_ms(self.name(), 60000))
+        };
         
         if current_state == SystemState::Trading && 
            self.consolidation_periods >= 3 && 
-           trade.event_time - self.last_signal_time > 60000 {
+           trade.event_time - self.last_signal_time > cooldown_ms {
             
             if price > self.range_high * 1.0001 {
                 opps.push(Opportunity {
-                    id: format!("breakout_buy_{}", trade.event_time),
-                    signal: Signal::Buy { symbol: trade.symbol.clone(), price: Some(price), quantity: 0.001 },
+                    id: "breakout_buy".to_string(),
+                    signal: Signal::Buy { symbol: trade.symbol.clone(), price: Some(price), quantity: 0.001, order_type: OrderType::Market },
                     score: 0.85,
                     risk_score: 0.4,
                     reason: format!("Bullish breakout after {} periods consolidation", self.consolidation_periods),
@@ -78,8 +81,8 @@ impl TradingStrategy for BreakoutRangeStrategy {
                 self.consolidation_periods = 0;
             } else if price < self.range_low * 0.9999 {
                 opps.push(Opportunity {
-                    id: format!("breakout_sell_{}", trade.event_time),
-                    signal: Signal::Sell { symbol: trade.symbol.clone(), price: Some(price), quantity: 0.001 },
+                    id: "breakout_sell".to_string(),
+                    signal: Signal::Sell { symbol: trade.symbol.clone(), price: Some(price), quantity: 0.001, order_type: OrderType::Market },
                     score: 0.8,
                     risk_score: 0.45,
                     reason: format!("Bearish breakdown after {} periods consolidation", self.consolidation_periods),
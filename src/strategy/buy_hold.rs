@@ -1,4 +1,4 @@
-use super::{Signal, TradingStrategy, Opportunity};
+use super::{Signal, OrderType, TradingStrategy, Opportunity};
 use crate::web::SharedState;
 use async_trait::async_trait;
 use binance::model::{TradeEvent, OrderBook, AggrTradesEvent};
@@ -32,8 +32,8 @@ impl TradingStrategy for BuyAndHold {
         let mut opps = Vec::new();
         if !self.has_bought {
             opps.push(Opportunity {
-                id: format!("buy_hold_{}", trade.event_time),
-                signal: Signal::Buy { symbol: trade.symbol.clone(), price: Some(price), quantity: 0.1 }, // Buy 0.1 BTC
+                id: "buy_hold".to_string(),
+                signal: Signal::Buy { symbol: trade.symbol.clone(), price: Some(price), quantity: 0.1, order_type: OrderType::Market }, // Buy 0.1 BTC
                 score: 1.0,
                 risk_score: 0.0,
                 reason: "Initial Buy and Hold purchase".to_string(),
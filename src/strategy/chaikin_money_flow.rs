@@ -1,4 +1,4 @@
-use super::{Signal, TradingStrategy, Opportunity};
+use super::{Signal, OrderType, TradingStrategy, Opportunity};
 use crate::web::SharedState;
 use crate::state_machine::SystemState;
 use async_trait::async_trait;
@@ -69,13 +69,16 @@ impl TradingStrategy for ChaikinMoneyFlow {
         self.last_cmf = self.calculate_cmf();
         
         let mut opps = Vec::new();
-        let current_state = state.read().await.state_machine.get_state();
+        let (current_state, cooldown_ms) = {
+            let read_guard = state.read().await;
+            (read_guard.state_machine.get_state(), read_guard.signal_cooldown_ms(self.name(), 60000))
+        };
         
-        if current_state == SystemState::Trading && trade.event_time - self.last_signal_time > 60000 {
+        if current_state == SystemState::Trading && trade.event_time - self.last_signal_time > cooldown_ms {
             if self.last_cmf > 0.1 {
                 opps.push(Opportunity {
-                    id: format!("cmf_buy_{}", trade.event_time),
-                    signal: Signal::Buy { symbol: trade.symbol.clone(), price: Some(price), quantity: 0.001 },
+                    id: "cmf_buy".to_string(),
+                    signal: Signal::Buy { symbol: trade.symbol.clone(), price: Some(price), quantity: 0.001, order_type: OrderType::Market },
                     score: 0.7,
                     risk_score: 0.3,
                     reason: format!("CMF Bullish Accumulation: {:.3}", self.last_cmf),
@@ -84,8 +87,8 @@ impl TradingStrategy for ChaikinMoneyFlow {
                 self.last_signal_time = trade.event_time;
             } else if self.last_cmf < -0.1 {
                 opps.push(Opportunity {
-                    id: format!("cmf_sell_{}", trade.event_time),
-                    signal: Signal::Sell { symbol: trade.symbol.clone(), price: Some(price), quantity: 0.001 },
+                    id: "cmf_sell".to_string(),
+                    signal: Signal::Sell { symbol: trade.symbol.clone(), price: Some(price), quantity: 0.001, order_type: OrderType::Market },
                     score: 0.7,
                     risk_score: 0.3,
                     reason: format!("CMF Bearish Distribution: {:.3}", self.last_cmf),
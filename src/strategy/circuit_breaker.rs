@@ -0,0 +1,116 @@
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::time::{Duration, Instant};
+
+/// What the main loop should do after a breaker check
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BreakerAction {
+    None,
+    Trip,
+    Resume,
+}
+
+/// Halts trading when realized PnL falls too far below the session peak
+pub struct CircuitBreaker {
+    /// Drawdown from peak, in percent of initial balance, that trips the breaker
+    pub max_drawdown_pct: f64,
+    /// Auto-resume delay. None means trading stays halted until /api/start_trading.
+    pub cooldown: Option<Duration>,
+    peak_pnl: f64,
+    last_drawdown_pct: f64,
+    tripped_at: Option<Instant>,
+    trip_count: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Default, JsonSchema)]
+pub struct CircuitBreakerStatus {
+    pub tripped: bool,
+    pub trip_count: u64,
+    pub drawdown_pct: f64,
+    pub max_drawdown_pct: f64,
+    pub cooldown_secs: Option<u64>,
+    pub resumes_in_secs: Option<u64>,
+}
+
+impl CircuitBreaker {
+    pub fn new(max_drawdown_pct: f64, cooldown: Option<Duration>) -> Self {
+        Self {
+            max_drawdown_pct,
+            cooldown,
+            peak_pnl: 0.0,
+            last_drawdown_pct: 0.0,
+            tripped_at: None,
+            trip_count: 0,
+        }
+    }
+
+    /// Reads CIRCUIT_BREAKER_DRAWDOWN_PCT (default 5) and CIRCUIT_BREAKER_COOLDOWN_SECS
+    /// (default 300, 0 = manual resume only) from the environment
+    pub fn from_env() -> Self {
+        let max_drawdown_pct = std::env::var("CIRCUIT_BREAKER_DRAWDOWN_PCT")
+            .ok()
+            .and_then(|v| v.parse::<f64>().ok())
+            .unwrap_or(5.0);
+        let cooldown_secs = std::env::var("CIRCUIT_BREAKER_COOLDOWN_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(300);
+        let cooldown = if cooldown_secs > 0 { Some(Duration::from_secs(cooldown_secs)) } else { None };
+        Self::new(max_drawdown_pct, cooldown)
+    }
+
+    pub fn is_tripped(&self) -> bool {
+        self.tripped_at.is_some()
+    }
+
+    /// Feeds the latest realized PnL. Trips on excessive drawdown and resumes once the cooldown has passed.
+    pub fn check(&mut self, realized_pnl: f64, initial_balance: f64) -> BreakerAction {
+        if let Some(tripped_at) = self.tripped_at {
+            return match self.cooldown {
+                Some(cooldown) if tripped_at.elapsed() >= cooldown => {
+                    self.reset(realized_pnl);
+                    BreakerAction::Resume
+                }
+                _ => BreakerAction::None,
+            };
+        }
+
+        if realized_pnl > self.peak_pnl {
+            self.peak_pnl = realized_pnl;
+        }
+        self.last_drawdown_pct = if initial_balance > 0.0 {
+            (self.peak_pnl - realized_pnl) / initial_balance * 100.0
+        } else {
+            0.0
+        };
+
+        if self.last_drawdown_pct >= self.max_drawdown_pct {
+            self.tripped_at = Some(Instant::now());
+            self.trip_count += 1;
+            return BreakerAction::Trip;
+        }
+        BreakerAction::None
+    }
+
+    /// Clears a trip and restarts peak tracking from the current PnL (e.g. manual restart)
+    pub fn reset(&mut self, realized_pnl: f64) {
+        self.tripped_at = None;
+        self.peak_pnl = realized_pnl;
+        self.last_drawdown_pct = 0.0;
+    }
+
+    pub fn status(&self) -> CircuitBreakerStatus {
+        let resumes_in_secs = match (self.tripped_at, self.cooldown) {
+            (Some(tripped_at), Some(cooldown)) => Some(cooldown.saturating_sub(tripped_at.elapsed()).as_secs()),
+            _ => None,
+        };
+        CircuitBreakerStatus {
+            tripped: self.is_tripped(),
+            trip_count: self.trip_count,
+            drawdown_pct: self.last_drawdown_pct,
+            max_drawdown_pct: self.max_drawdown_pct,
+            cooldown_secs: self.cooldown.map(|c| c.as_secs()),
+            resumes_in_secs,
+        }
+    }
+}
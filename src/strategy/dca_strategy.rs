@@ -1,25 +1,52 @@
-use super::{Signal, TradingStrategy, Opportunity};
+use super::{Signal, OrderType, TradingStrategy, Opportunity};
 use crate::web::SharedState;
 use crate::state_machine::SystemState;
 use async_trait::async_trait;
 use binance::model::{TradeEvent, OrderBook, AggrTradesEvent};
 use std::time::Instant;
 
-/// Dollar Cost Averaging - buys at regular intervals
+/// USD spent per buy when DCA_USD_AMOUNT is not set
+const DEFAULT_USD_AMOUNT: f64 = 10.0;
+
+/// Dollar Cost Averaging - buys a fixed USD amount at regular intervals,
+/// so more units are bought when the price is low
 pub struct DCAStrategy {
     trade_count: u64,
     last_spread: f64,
     buy_interval: u64,  // Buy every N trades
+    usd_amount: f64,    // Quote spent per buy
+    total_invested: f64,
+    total_quantity: f64,
 }
 
 impl DCAStrategy {
     pub fn new() -> Self {
+        Self::with_params(Self::usd_amount_from_env(), 50) // Buy every 50 trades for more activity
+    }
+
+    pub fn with_params(usd_amount: f64, buy_interval: u64) -> Self {
         Self {
             trade_count: 0,
             last_spread: 0.0,
-            buy_interval: 50,  // Buy every 50 trades for more activity
+            buy_interval: buy_interval.max(1),
+            usd_amount,
+            total_invested: 0.0,
+            total_quantity: 0.0,
         }
     }
+
+    /// Reads DCA_USD_AMOUNT (default 10 USD)
+    pub fn usd_amount_from_env() -> f64 {
+        std::env::var("DCA_USD_AMOUNT")
+            .ok()
+            .and_then(|v| v.parse::<f64>().ok())
+            .filter(|v| *v > 0.0)
+            .unwrap_or(DEFAULT_USD_AMOUNT)
+    }
+
+    fn average_cost(&self) -> Option<f64> {
+        (self.total_quantity > 0.0).then(|| self.total_invested / self.total_quantity)
+    }
 }
 
 #[async_trait]
@@ -29,6 +56,9 @@ impl TradingStrategy for DCAStrategy {
     fn get_features(&self) -> Vec<(String, String)> {
         vec![
             ("Interval".to_string(), self.buy_interval.to_string()),
+            ("USD per Buy".to_string(), format!("{:.2}", self.usd_amount)),
+            ("Total Invested".to_string(), format!("{:.2}", self.total_invested)),
+            ("Avg Cost".to_string(), self.average_cost().map_or("-".to_string(), |c| format!("{:.2}", c))),
             ("Total Trades".to_string(), self.trade_count.to_string()),
             ("Next Buy In".to_string(), (self.buy_interval - (self.trade_count % self.buy_interval)).to_string()),
         ]
@@ -59,28 +89,27 @@ impl DCAStrategy {
         let start = Instant::now();
         self.trade_count += 1;
         
-        {
-            let mut guard = state.write().await;
-            if guard.state_machine.get_state() == SystemState::Booting {
-                guard.state_machine.transition_to(SystemState::Accumulating);
-            } else if guard.state_machine.get_state() == SystemState::Accumulating && guard.state_machine.is_stable() {
-                guard.state_machine.transition_to(SystemState::Trading);
-            }
-        }
-        
         let mut opps = Vec::new();
         let current_state = state.read().await.state_machine.get_state();
         
-        // Simple DCA: buy at regular intervals
-        if current_state == SystemState::Trading && self.trade_count % self.buy_interval == 0 {
-            opps.push(Opportunity {
-                id: format!("dca_buy_{}", self.trade_count),
-                signal: Signal::Buy { symbol: symbol.clone(), price: Some(price), quantity: 0.0001 },
-                score: 0.6,
-                risk_score: 0.2,
-                reason: format!("DCA interval #{}", self.trade_count / self.buy_interval),
-                timestamp: ts,
-            });
+        // Simple DCA: spend the same USD amount at regular intervals
+        if current_state == SystemState::Trading && self.trade_count % self.buy_interval == 0 && price > 0.0 {
+            let executor = state.read().await.executor.clone();
+            let filters = executor.symbol_filters(&symbol).await;
+            let factor = 10_f64.powi(filters.qty_precision as i32);
+            let quantity = (self.usd_amount / price * factor).floor() / factor;
+            if quantity * price >= filters.min_notional {
+                self.total_invested += quantity * price;
+                self.total_quantity += quantity;
+                opps.push(Opportunity {
+                    id: "dca_buy".to_string(),
+                    signal: Signal::Buy { symbol: symbol.clone(), price: Some(price), quantity, order_type: OrderType::Market },
+                    score: 0.6,
+                    risk_score: 0.2,
+                    reason: format!("DCA interval #{}: {:.2} USD", self.trade_count / self.buy_interval, quantity * price),
+                    timestamp: ts,
+                });
+            }
         }
         
         {
@@ -0,0 +1,70 @@
+use super::Side;
+
+/// Default consecutive qualifying ticks before a signal is emitted (1 = no confirmation)
+pub const DEFAULT_CONFIRM_TICKS: u32 = 1;
+/// Default time from the first qualifying tick within which the rest must arrive
+pub const DEFAULT_CONFIRM_WINDOW_MS: u64 = 10_000;
+
+/// Whipsaw filter: a candidate signal is only confirmed once it has held in the same
+/// direction for `required` consecutive qualifying ticks within `window_ms`.
+/// A tick without a candidate, or with the opposite one, starts the count over.
+#[derive(Debug, Clone, Copy)]
+pub struct SignalDebouncer {
+    pub required: u32,
+    pub window_ms: u64,
+    pending: Option<(Side, u32, u64)>, // (direction, ticks seen, first tick ms)
+}
+
+impl SignalDebouncer {
+    pub fn new(required: u32, window_ms: u64) -> Self {
+        Self { required: required.max(1), window_ms, pending: None }
+    }
+
+    /// Reads SIGNAL_CONFIRM_TICKS (default 1) and SIGNAL_CONFIRM_WINDOW_MS (default 10000) from the environment
+    pub fn from_env() -> Self {
+        let required = std::env::var("SIGNAL_CONFIRM_TICKS")
+            .ok()
+            .and_then(|v| v.parse::<u32>().ok())
+            .unwrap_or(DEFAULT_CONFIRM_TICKS);
+        let window_ms = std::env::var("SIGNAL_CONFIRM_WINDOW_MS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(DEFAULT_CONFIRM_WINDOW_MS);
+        Self::new(required, window_ms)
+    }
+
+    /// Overrides the confirmation window, e.g. for strategies that only see one event per candle
+    pub fn with_window_ms(mut self, window_ms: u64) -> Self {
+        self.window_ms = window_ms;
+        self
+    }
+
+    /// Feeds this tick's candidate (None when the entry condition does not hold).
+    /// Returns true on the tick the candidate is confirmed, then starts over.
+    pub fn confirm(&mut self, candidate: Option<Side>, ts: u64) -> bool {
+        let Some(side) = candidate else {
+            self.pending = None;
+            return false;
+        };
+        let (count, first_ts) = match self.pending {
+            Some((pending_side, count, first_ts)) if pending_side == side && ts.saturating_sub(first_ts) <= self.window_ms => (count + 1, first_ts),
+            _ => (1, ts),
+        };
+        if count >= self.required {
+            self.pending = None;
+            return true;
+        }
+        self.pending = Some((side, count, first_ts));
+        false
+    }
+
+    /// Qualifying ticks seen so far for the pending candidate
+    pub fn pending(&self) -> u32 {
+        self.pending.map_or(0, |(_, count, _)| count)
+    }
+
+    /// "Pending Confirm" entry for a strategy's features
+    pub fn feature(&self) -> (String, String) {
+        ("Pending Confirm".to_string(), format!("{}/{}", self.pending(), self.required))
+    }
+}
@@ -1,4 +1,4 @@
-use super::{Signal, TradingStrategy, Opportunity};
+use super::{Signal, OrderType, TradingStrategy, Opportunity};
 use crate::web::SharedState;
 use crate::state_machine::SystemState;
 use async_trait::async_trait;
@@ -56,13 +56,16 @@ impl TradingStrategy for DonchianChannels {
         self.update_channels();
         
         let mut opps = Vec::new();
-        let current_state = state.read().await.state_machine.get_state();
+        let (current_state, cooldown_ms) = {
+            let read_guard = state.read().await;
+            (read_guard.state_machine.get_state(), read_guard.signal_cooldown_ms(self.name(), 60000))
+        };
         
-        if current_state == SystemState::Trading && trade.event_time - self.last_signal_time > 60000 {
+        if current_state == SystemState::Trading && trade.event_time - self.last_signal_time > cooldown_ms {
             if price >= self.upper {
                 opps.push(Opportunity {
-                    id: format!("donchian_buy_{}", trade.event_time),
-                    signal: Signal::Buy { symbol: trade.symbol.clone(), price: Some(price), quantity: 0.001 },
+                    id: "donchian_buy".to_string(),
+                    signal: Signal::Buy { symbol: trade.symbol.clone(), price: Some(price), quantity: 0.001, order_type: OrderType::Market },
                     score: 0.85,
                     risk_score: 0.35,
                     reason: format!("Donchian Upper Breakout: {:.2}", price),
@@ -71,8 +74,8 @@ impl TradingStrategy for DonchianChannels {
                 self.last_signal_time = trade.event_time;
             } else if price <= self.lower {
                 opps.push(Opportunity {
-                    id: format!("donchian_sell_{}", trade.event_time),
-                    signal: Signal::Sell { symbol: trade.symbol.clone(), price: Some(price), quantity: 0.001 },
+                    id: "donchian_sell".to_string(),
+                    signal: Signal::Sell { symbol: trade.symbol.clone(), price: Some(price), quantity: 0.001, order_type: OrderType::Market },
                     score: 0.85,
                     risk_score: 0.4,
                     reason: format!("Donchian Lower Breakdown: {:.2}", price),
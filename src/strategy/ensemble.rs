@@ -0,0 +1,181 @@
+use super::{Signal, OrderType, TradingStrategy, Opportunity, StrategyFactory};
+use crate::market_data::Candle;
+use crate::web::SharedState;
+use async_trait::async_trait;
+use binance::model::{TradeEvent, OrderBook, AggrTradesEvent};
+
+/// Members used when ENSEMBLE_MEMBERS is not set
+const DEFAULT_MEMBERS: &str = "RSIStrategy,MACDCrossover,BBSqueeze";
+
+/// A member's most recent directional signal
+#[derive(Clone)]
+struct Vote {
+    direction: f64, // +1 buy, -1 sell
+    score: f64,
+    quantity: f64,
+    timestamp: u64,
+}
+
+/// Ensemble Strategy - forwards every event to its members and trades only
+/// when their score-weighted votes agree strongly enough.
+///
+/// Members signal at different moments, so each member's last vote stays live
+/// for `vote_window_ms`. The net vote is (sum of buy scores - sum of sell scores)
+/// divided by the member count, so `threshold` is in [0, 1] regardless of size.
+pub struct EnsembleStrategy {
+    members: Vec<Box<dyn TradingStrategy>>,
+    votes: Vec<Option<Vote>>,
+    threshold: f64,
+    vote_window_ms: u64,
+    last_ts: u64,
+}
+
+impl EnsembleStrategy {
+    pub fn new(members: Vec<Box<dyn TradingStrategy>>, threshold: f64, vote_window_ms: u64) -> Self {
+        let votes = vec![None; members.len()];
+        Self { members, votes, threshold, vote_window_ms, last_ts: 0 }
+    }
+
+    /// Reads ENSEMBLE_MEMBERS (comma-separated strategy names), ENSEMBLE_THRESHOLD
+    /// (default 0.5) and ENSEMBLE_VOTE_WINDOW_SECS (default 60) from the environment
+    pub fn from_env() -> Self {
+        let names = std::env::var("ENSEMBLE_MEMBERS").unwrap_or_else(|_| DEFAULT_MEMBERS.to_string());
+        let members: Vec<Box<dyn TradingStrategy>> = names
+            .split(',')
+            .map(|name| name.trim())
+            .filter(|name| !name.is_empty() && *name != "Ensemble")
+            .filter_map(|name| {
+                let member = StrategyFactory::create_strategy(name);
+                if member.is_none() {
+                    log::warn!("Ensemble: unknown member strategy {}", name);
+                }
+                member
+            })
+            .collect();
+        let threshold = std::env::var("ENSEMBLE_THRESHOLD")
+            .ok()
+            .and_then(|v| v.parse::<f64>().ok())
+            .unwrap_or(0.5);
+        let window_secs = std::env::var("ENSEMBLE_VOTE_WINDOW_SECS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(60);
+        Self::new(members, threshold, window_secs * 1000)
+    }
+
+    fn live_votes(&self) -> impl Iterator<Item = &Vote> {
+        self.votes.iter()
+            .flatten()
+            .filter(|v| self.last_ts.saturating_sub(v.timestamp) <= self.vote_window_ms)
+    }
+
+    fn net_vote(&self) -> f64 {
+        if self.members.is_empty() {
+            return 0.0;
+        }
+        self.live_votes().map(|v| v.direction * v.score).sum::<f64>() / self.members.len() as f64
+    }
+
+    /// Records the directional signals of one member. Exits, stops and cancels are not votes.
+    fn record(&mut self, member: usize, opps: &[Opportunity]) {
+        for opp in opps {
+            let (direction, quantity) = match &opp.signal {
+                Signal::Buy { quantity, .. } | Signal::LimitBuy { quantity, .. } => (1.0, *quantity),
+                Signal::Sell { quantity, .. } | Signal::LimitSell { quantity, .. } => (-1.0, *quantity),
+                Signal::SellAll { .. } | Signal::StopLimit { .. } | Signal::Cancel { .. } => continue,
+            };
+            self.last_ts = self.last_ts.max(opp.timestamp);
+            self.votes[member] = Some(Vote { direction, score: opp.score.clamp(0.0, 1.0), quantity, timestamp: opp.timestamp });
+        }
+    }
+
+    /// Emits one market order once the net vote crosses the threshold, then clears the votes
+    fn combine(&mut self, symbol: &str, price: Option<f64>) -> Vec<Opportunity> {
+        let net = self.net_vote();
+        if net.abs() < self.threshold || net == 0.0 {
+            return Vec::new();
+        }
+        let direction = net.signum();
+        let agreeing: Vec<&Vote> = self.live_votes().filter(|v| v.direction == direction).collect();
+        let quantity = agreeing.iter().map(|v| v.quantity).sum::<f64>() / agreeing.len() as f64;
+        let reason = format!("Ensemble: {} of {} members agree (net vote {:+.2})", agreeing.len(), self.members.len(), net);
+
+        let signal = if direction > 0.0 {
+            Signal::Buy { symbol: symbol.to_string(), price, quantity, order_type: OrderType::Market }
+        } else {
+            Signal::Sell { symbol: symbol.to_string(), price, quantity, order_type: OrderType::Market }
+        };
+        let opp = Opportunity {
+            id: format!("ensemble_{}", if direction > 0.0 { "buy" } else { "sell" }),
+            signal,
+            score: net.abs().min(1.0),
+            risk_score: 0.3,
+            reason,
+            timestamp: self.last_ts,
+        };
+        self.votes.iter_mut().for_each(|v| *v = None);
+        vec![opp]
+    }
+}
+
+#[async_trait]
+impl TradingStrategy for EnsembleStrategy {
+    fn name(&self) -> &str { "Ensemble" }
+
+    fn get_features(&self) -> Vec<(String, String)> {
+        let mut features: Vec<(String, String)> = self.members.iter().zip(&self.votes).map(|(member, vote)| {
+            let vote = match vote {
+                Some(v) if self.last_ts.saturating_sub(v.timestamp) <= self.vote_window_ms => {
+                    format!("{} {:.2}", if v.direction > 0.0 { "Buy" } else { "Sell" }, v.score)
+                }
+                _ => "-".to_string(),
+            };
+            (format!("Vote: {}", member.name()), vote)
+        }).collect();
+        features.push(("Net Vote".to_string(), format!("{:+.2}", self.net_vote())));
+        features.push(("Threshold".to_string(), format!("{:.2}", self.threshold)));
+        features
+    }
+
+    async fn process_trade(&mut self, trade: TradeEvent, state: SharedState) -> Vec<Opportunity> {
+        self.last_ts = self.last_ts.max(trade.event_time);
+        for i in 0..self.members.len() {
+            let opps = self.members[i].process_trade(trade.clone(), state.clone()).await;
+            self.record(i, &opps);
+        }
+        let price = trade.price.parse::<f64>().ok();
+        self.combine(&trade.symbol, price)
+    }
+
+    async fn process_aggr_trade(&mut self, trade: AggrTradesEvent, state: SharedState) -> Vec<Opportunity> {
+        self.last_ts = self.last_ts.max(trade.event_time);
+        for i in 0..self.members.len() {
+            let opps = self.members[i].process_aggr_trade(trade.clone(), state.clone()).await;
+            self.record(i, &opps);
+        }
+        let price = trade.price.parse::<f64>().ok();
+        self.combine(&trade.symbol, price)
+    }
+
+    async fn process_orderbook(&mut self, orderbook: OrderBook, state: SharedState) -> Vec<Opportunity> {
+        let mut symbol = None;
+        for i in 0..self.members.len() {
+            let opps = self.members[i].process_orderbook(orderbook.clone(), state.clone()).await;
+            symbol = symbol.or_else(|| opps.first().map(|o| o.signal.symbol().to_string()));
+            self.record(i, &opps);
+        }
+        // Order books carry no symbol; only a member's signal tells us which market this is
+        match symbol {
+            Some(symbol) => self.combine(&symbol, None),
+            None => Vec::new(),
+        }
+    }
+
+    async fn process_candle(&mut self, candle: Candle, state: SharedState) -> Vec<Opportunity> {
+        for i in 0..self.members.len() {
+            let opps = self.members[i].process_candle(candle.clone(), state.clone()).await;
+            self.record(i, &opps);
+        }
+        self.combine(&candle.symbol, Some(candle.close))
+    }
+}
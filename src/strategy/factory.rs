@@ -6,11 +6,51 @@ use super::{
     VolatilityBreakout, SwingTrader, MartingaleStrategy,
     ParabolicSAR, StochasticOscillator, BBSqueeze, ChaikinMoneyFlow,
     TRIXStrategy, DonchianChannels, HullMA, FibonacciReversion,
-    IchimokuCloud, HeikinAshiTrend, BuyAndHold
+    IchimokuCloud, HeikinAshiTrend, BuyAndHold, EnsembleStrategy,
+    ProbabilisticStateStrategy
 };
+use serde::Serialize;
+use std::collections::HashMap;
 
 pub struct StrategyFactory;
 
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ParamKind {
+    Float,
+    Integer,
+}
+
+/// A tunable parameter, settable via /api/strategy/config and, if `sweepable`, /api/backtest/sweep
+#[derive(Debug, Clone, Serialize)]
+pub struct ParamDescriptor {
+    pub name: &'static str,
+    pub kind: ParamKind,
+    pub default: f64,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub description: &'static str,
+    pub sweepable: bool,
+}
+
+/// What a strategy does, what can be tuned and which feature keys it reports
+#[derive(Debug, Clone, Serialize)]
+pub struct StrategyDescriptor {
+    pub name: String,
+    pub description: &'static str,
+    pub params: Vec<ParamDescriptor>,
+    pub features: Vec<String>,
+}
+
+fn param(name: &'static str, kind: ParamKind, default: f64, min: Option<f64>, max: Option<f64>, description: &'static str) -> ParamDescriptor {
+    ParamDescriptor { name, kind, default, min, max, description, sweepable: false }
+}
+
+/// The runtime `cooldown_ms` override read through `AppState::signal_cooldown_ms`
+fn cooldown(default_ms: f64) -> ParamDescriptor {
+    param("cooldown_ms", ParamKind::Integer, default_ms, Some(0.0), None, "Minimum time between entry signals in ms")
+}
+
 impl StrategyFactory {
     pub fn create_strategy(name: &str) -> Option<Box<dyn TradingStrategy>> {
         match name {
@@ -19,6 +59,7 @@ impl StrategyFactory {
             "MomentumBreakout" => Some(Box::new(MomentumBreakout::new())),
             "AdaptiveMeanReversion" => Some(Box::new(AdaptiveMeanReversion::new())),
             "VWAPStrategy" => Some(Box::new(VWAPStrategy::new())),
+            "VWAPSession" => Some(Box::new(VWAPStrategy::new().with_session(86_400))),
             "ScalperStrategy" => Some(Box::new(ScalperStrategy::new())),
             "BreakoutRange" => Some(Box::new(BreakoutRangeStrategy::new())),
             "MACDCrossover" => Some(Box::new(MACDCrossover::new())),
@@ -29,6 +70,7 @@ impl StrategyFactory {
             "VolatilityBreakout" => Some(Box::new(VolatilityBreakout::new())),
             "SwingTrader" => Some(Box::new(SwingTrader::new())),
             "Martingale" => Some(Box::new(MartingaleStrategy::new())),
+            "AntiMartingale" => Some(Box::new(MartingaleStrategy::new().with_anti_martingale())),
             "ParabolicSAR" => Some(Box::new(ParabolicSAR::new())),
             "StochasticOscillator" => Some(Box::new(StochasticOscillator::new())),
             "BBSqueeze" => Some(Box::new(BBSqueeze::new())),
@@ -40,10 +82,159 @@ impl StrategyFactory {
             "IchimokuCloud" => Some(Box::new(IchimokuCloud::new())),
             "HeikinAshiTrend" => Some(Box::new(HeikinAshiTrend::new())),
             "BuyAndHold" => Some(Box::new(BuyAndHold::new())),
+            "Ensemble" => Some(Box::new(EnsembleStrategy::from_env())),
+            "ProbabilisticState" => Some(Box::new(ProbabilisticStateStrategy::new())),
             _ => None,
         }
     }
 
+    /// Builds a strategy with overridden parameters. Unknown or missing parameters keep their defaults.
+    pub fn create_strategy_with_params(name: &str, params: &HashMap<String, f64>) -> Option<Box<dyn TradingStrategy>> {
+        let param = |key: &str, default: f64| params.get(key).copied().unwrap_or(default);
+        match name {
+            "RSIStrategy" => Some(Box::new(RSIStrategy::with_params(
+                param("rsi_period", 14.0).round() as usize,
+                param("oversold", 30.0),
+                param("overbought", 70.0),
+            ))),
+            "DCAStrategy" => Some(Box::new(DCAStrategy::with_params(
+                param("usd_amount", DCAStrategy::usd_amount_from_env()),
+                param("buy_interval", 50.0).round().max(1.0) as u64,
+            ))),
+            "GridTrading" => Some(Box::new(GridTrading::with_params(
+                param("grid_size", 0.05),
+                param("num_levels", 5.0).round().max(1.0) as usize,
+            ))),
+            "TrendFollower" => Some(Box::new(TrendFollower::with_params(
+                param("max_adds", 0.0).round().max(0.0) as u32,
+            ))),
+            "AdaptiveMeanReversion" => Some(Box::new(AdaptiveMeanReversion::with_params(
+                param("bb_period", 20.0).round().max(0.0) as usize,
+                param("bb_std_dev", 2.0),
+                param("rsi_period", 14.0).round().max(0.0) as usize,
+                param("cooldown_ms", 45_000.0).max(0.0) as u64,
+            ))),
+            "ProbabilisticState" => Some(Box::new(ProbabilisticStateStrategy::with_params(
+                param("entry_threshold", 0.8).clamp(0.0, 1.0),
+                param("exit_threshold", 0.5).clamp(0.0, 1.0),
+            ))),
+            _ => Self::create_strategy(name),
+        }
+    }
+
+    /// Parameters accepted by `create_strategy_with_params` for a strategy
+    pub fn sweepable_params(name: &str) -> &'static [&'static str] {
+        match name {
+            "RSIStrategy" => &["rsi_period", "oversold", "overbought"],
+            "DCAStrategy" => &["usd_amount", "buy_interval"],
+            "GridTrading" => &["grid_size", "num_levels"],
+            "TrendFollower" => &["max_adds"],
+            "AdaptiveMeanReversion" => &["bb_period", "bb_std_dev", "rsi_period", "cooldown_ms"],
+            "ProbabilisticState" => &["entry_threshold", "exit_threshold"],
+            _ => &[],
+        }
+    }
+
+    pub fn description(name: &str) -> &'static str {
+        match name {
+            "MeanReversion" => "Buys below and sells above a short moving average",
+            "PaperTrader" => "Mock signals on a fixed trade count, for exercising the pipeline",
+            "MomentumBreakout" => "Enters on price velocity with a volume surge; ATR stop and target exits",
+            "AdaptiveMeanReversion" => "Bollinger Band reversion confirmed by RSI",
+            "VWAPStrategy" => "Trades reversions to the rolling volume weighted average price",
+            "VWAPSession" => "Trades reversions to a VWAP that resets every 24h session",
+            "ScalperStrategy" => "High frequency small profit trades on spread and short-term moves",
+            "BreakoutRange" => "Trades breakouts from consolidation ranges",
+            "MACDCrossover" => "Trades MACD and signal line crossovers",
+            "GridTrading" => "Buys and sells at fixed percentage grid levels, recentering after a trend",
+            "RSIStrategy" => "Buys oversold and sells overbought RSI readings",
+            "TrendFollower" => "EMA crossover trend following with optional pyramiding",
+            "DCAStrategy" => "Buys a fixed USD amount at regular intervals",
+            "VolatilityBreakout" => "Buys a 1% breakout above the recent high, exits on PnL or max hold",
+            "SwingTrader" => "Rides strong momentum moves with a trailing stop",
+            "Martingale" => "Doubles position size after each loss (high risk)",
+            "AntiMartingale" => "Doubles position size after each win and resets after a loss",
+            "ParabolicSAR" => "Trades Parabolic SAR trend reversals",
+            "StochasticOscillator" => "Trades %K/%D crossovers in oversold and overbought zones",
+            "BBSqueeze" => "Trades breakouts after Bollinger Bands squeeze inside the Keltner Channel",
+            "ChaikinMoneyFlow" => "Trades on accumulation and distribution from Chaikin Money Flow",
+            "TRIXStrategy" => "Trades triple-smoothed EMA momentum crossovers",
+            "DonchianChannels" => "Trades breakouts of the Donchian channel high and low",
+            "HullMA" => "Trades Hull Moving Average direction changes",
+            "FibonacciReversion" => "Buys pullbacks to Fibonacci retracement levels",
+            "IchimokuCloud" => "Trades price crossing the Ichimoku cloud (simplified)",
+            "HeikinAshiTrend" => "Trades confirmed Heikin-Ashi candle trend flips",
+            "BuyAndHold" => "Buys once and holds; a benchmark for the other strategies",
+            "Ensemble" => "Trades when score-weighted votes of its member strategies agree",
+            "ProbabilisticState" => "Buys when the state machine's inferred probability of Trading is high, exits when it fades",
+            _ => "",
+        }
+    }
+
+    /// Tunable parameters with their defaults. Constructor parameters come from
+    /// `create_strategy_with_params`, `cooldown_ms` from the runtime config override.
+    pub fn params(name: &str) -> Vec<ParamDescriptor> {
+        use ParamKind::{Float, Integer};
+        let mut params = match name {
+            "RSIStrategy" => vec![
+                param("rsi_period", Integer, 14.0, Some(1.0), None, "RSI lookback in trades"),
+                param("oversold", Float, 30.0, Some(0.0), Some(100.0), "Buy below this RSI"),
+                param("overbought", Float, 70.0, Some(0.0), Some(100.0), "Sell above this RSI"),
+            ],
+            "DCAStrategy" => vec![
+                param("usd_amount", Float, DCAStrategy::usd_amount_from_env(), Some(0.0), None, "USD spent per buy"),
+                param("buy_interval", Integer, 50.0, Some(1.0), None, "Trades between buys"),
+            ],
+            "GridTrading" => vec![
+                param("grid_size", Float, 0.05, Some(0.0), None, "Spacing between grid levels in percent"),
+                param("num_levels", Integer, 5.0, Some(1.0), None, "Levels on each side of the center"),
+                cooldown(10_000.0),
+            ],
+            "TrendFollower" => vec![
+                param("max_adds", Integer, 0.0, Some(0.0), None, "Pyramid adds allowed while in profit"),
+            ],
+            "AdaptiveMeanReversion" => vec![
+                param("bb_period", Integer, 20.0, Some(2.0), None, "Bollinger Band lookback in trades"),
+                param("bb_std_dev", Float, 2.0, Some(0.0), None, "Band width in standard deviations"),
+                param("rsi_period", Integer, 14.0, Some(2.0), None, "RSI lookback in trades"),
+                cooldown(45_000.0),
+            ],
+            "ProbabilisticState" => vec![
+                param("entry_threshold", Float, 0.8, Some(0.0), Some(1.0), "Buy when the inferred probability of Trading reaches this"),
+                param("exit_threshold", Float, 0.5, Some(0.0), Some(1.0), "Sell everything when it falls below this"),
+                cooldown(30_000.0),
+            ],
+            "MomentumBreakout" | "ChaikinMoneyFlow" | "BBSqueeze" | "HullMA" | "TRIXStrategy"
+            | "IchimokuCloud" | "DonchianChannels" | "BreakoutRange" | "StochasticOscillator" => vec![cooldown(60_000.0)],
+            "HeikinAshiTrend" | "VWAPStrategy" | "VWAPSession" => vec![cooldown(30_000.0)],
+            "FibonacciReversion" => vec![cooldown(120_000.0)],
+            "MACDCrossover" => vec![cooldown(45_000.0)],
+            "ScalperStrategy" => vec![cooldown(5_000.0)],
+            _ => Vec::new(),
+        };
+        let sweepable = Self::sweepable_params(name);
+        for p in params.iter_mut() {
+            p.sweepable = sweepable.contains(&p.name);
+        }
+        params
+    }
+
+    /// Metadata for every available strategy, for building config forms.
+    /// Feature keys are read from a fresh instance so they can't drift from `get_features`.
+    pub fn describe() -> Vec<StrategyDescriptor> {
+        Self::get_available_strategies()
+            .into_iter()
+            .map(|name| StrategyDescriptor {
+                description: Self::description(&name),
+                params: Self::params(&name),
+                features: Self::create_strategy(&name)
+                    .map(|s| s.get_features().into_iter().map(|(key, _)| key).collect())
+                    .unwrap_or_default(),
+                name,
+            })
+            .collect()
+    }
+
     pub fn get_available_strategies() -> Vec<String> {
         vec![
             "MeanReversion".to_string(),
@@ -51,6 +242,7 @@ impl StrategyFactory {
             "MomentumBreakout".to_string(),
             "AdaptiveMeanReversion".to_string(),
             "VWAPStrategy".to_string(),
+            "VWAPSession".to_string(),
             "ScalperStrategy".to_string(),
             "BreakoutRange".to_string(),
             "MACDCrossover".to_string(),
@@ -61,6 +253,7 @@ impl StrategyFactory {
             "VolatilityBreakout".to_string(),
             "SwingTrader".to_string(),
             "Martingale".to_string(),
+            "AntiMartingale".to_string(),
             "ParabolicSAR".to_string(),
             "StochasticOscillator".to_string(),
             "BBSqueeze".to_string(),
@@ -72,6 +265,8 @@ impl StrategyFactory {
             "IchimokuCloud".to_string(),
             "HeikinAshiTrend".to_string(),
             "BuyAndHold".to_string(),
+            "Ensemble".to_string(),
+            "ProbabilisticState".to_string(),
         ]
     }
 }
@@ -1,4 +1,4 @@
-use super::{Signal, TradingStrategy, Opportunity};
+use super::{Signal, OrderType, TradingStrategy, Opportunity};
 use crate::web::SharedState;
 use crate::state_machine::SystemState;
 use async_trait::async_trait;
@@ -57,16 +57,19 @@ impl TradingStrategy for FibonacciReversion {
         if self.prices.len() > 100 { self.prices.pop_front(); }
         
         let mut opps = Vec::new();
-        let current_state = state.read().await.state_machine.get_state();
+        let (current_state, cooldown_ms) = {
+            let read_guard = state.read().await;
+            (read_guard.state_machine.get_state(), read_guard.signal_cooldown_ms(self.name(), 120000))
+        };
         
         if let Some((_, low, levels)) = self.calculate_levels() {
             let fib_618 = levels[3];
-            if current_state == SystemState::Trading && trade.event_time - self.last_signal_time > 120000 {
+            if current_state == SystemState::Trading && trade.event_time - self.last_signal_time > cooldown_ms {
                 // Buy near 61.8% retracement from bottom
                 if (price - fib_618).abs() / price < 0.001 && price > low {
                     opps.push(Opportunity {
-                        id: format!("fib_buy_{}", trade.event_time),
-                        signal: Signal::Buy { symbol: trade.symbol.clone(), price: Some(price), quantity: 0.001 },
+                        id: "fib_buy".to_string(),
+                        signal: Signal::Buy { symbol: trade.symbol.clone(), price: Some(price), quantity: 0.001, order_type: OrderType::Market },
                         score: 0.8,
                         risk_score: 0.3,
                         reason: format!("Fib 0.618 Retracement support: {:.2}", fib_618),
@@ -1,40 +1,123 @@
-use super::{Signal, TradingStrategy, Opportunity};
+use super::{Signal, OrderType, Side, TradingStrategy, Opportunity, OrderEvent};
+use super::bracket::HoldTimer;
 use crate::web::SharedState;
 use crate::state_machine::SystemState;
 use async_trait::async_trait;
 use binance::model::{TradeEvent, OrderBook, AggrTradesEvent};
 
-/// Grid Trading Strategy - Buy low, sell high with price grids
+/// Grid orders the executor has not reported resting after this long (event time) are taken
+/// as never sent: only one signal per event is executed, and orders can be refused.
+const UNCONFIRMED_TIMEOUT_MS: u64 = 60_000;
+
+/// A limit order the grid sent, tracked until it fills or is cancelled
+struct GridOrder {
+    order_id: Option<u64>, // None until the executor reports it resting
+    side: Side,
+    price: f64,
+    remaining: f64,
+    lot: Option<(f64, u64)>, // Sells: entry price and time of the lot being sold
+    sent_ts: u64,
+    stale: bool, // Buy left behind by a recenter, to be cancelled
+    cancel_sent_ts: Option<u64>,
+}
+
+/// Grid Trading Strategy - Buy low, sell high with price grids.
+/// The grid is rebuilt around the current price whenever price leaves its outermost level,
+/// so it keeps trading after a trend. Open lots survive a recenter; resting buys are cancelled.
+/// Lots are only opened or closed by the fills the executor reports for the grid's orders.
 pub struct GridTrading {
     grid_size: f64,      // % between grid levels
+    num_levels: usize,   // Levels on each side of the base price
     grid_levels: Vec<f64>,
     base_price: f64,
-    positions: Vec<(f64, f64)>,  // (entry_price, qty)
+    last_price: f64,
+    recenters: u64,
+    positions: Vec<(f64, f64, u64)>,  // Filled lots: (entry_price, qty, entry_ts)
+    orders: Vec<GridOrder>,
     last_signal_time: u64,
+    last_ts: u64,
+    hold: HoldTimer, // Runs on the oldest open lot
 }
 
 impl GridTrading {
     pub fn new() -> Self {
+        Self::with_params(0.05, 5) // 0.05% grid spacing, ±5 levels
+    }
+
+    pub fn with_params(grid_size: f64, num_levels: usize) -> Self {
         Self {
-            grid_size: 0.05,  // 0.05% grid spacing
+            grid_size,
+            num_levels: num_levels.max(1),
             grid_levels: Vec::new(),
             base_price: 0.0,
+            last_price: 0.0,
+            recenters: 0,
             positions: Vec::new(),
+            orders: Vec::new(),
             last_signal_time: 0,
+            last_ts: 0,
+            hold: HoldTimer::from_env(),
         }
     }
 
     fn setup_grid(&mut self, price: f64) {
         self.base_price = price;
         self.grid_levels.clear();
-        for i in -5..=5 {
+        let n = self.num_levels as i64;
+        for i in -n..=n {
             self.grid_levels.push(price * (1.0 + (i as f64) * self.grid_size / 100.0));
         }
     }
 
+    /// True when price has left the outermost levels
+    fn outside_grid(&self, price: f64) -> bool {
+        match (self.grid_levels.first(), self.grid_levels.last()) {
+            (Some(&lowest), Some(&highest)) => price < lowest || price > highest,
+            _ => false,
+        }
+    }
+
+    /// % distance from the last price to the nearest outermost level
+    fn edge_distance_pct(&self) -> Option<f64> {
+        let (&lowest, &highest) = (self.grid_levels.first()?, self.grid_levels.last()?);
+        if self.last_price <= 0.0 {
+            return None;
+        }
+        Some((highest - self.last_price).min(self.last_price - lowest) / self.last_price * 100.0)
+    }
+
+    /// Points the hold timer at the oldest remaining lot
+    fn restart_hold(&mut self) {
+        match self.positions.first() {
+            Some(&(_, _, entry_ts)) => self.hold.start(entry_ts),
+            None => self.hold.stop(),
+        }
+    }
+
+    /// Hands the unsold part of a sell order's lot back to the open lots
+    fn restore_lot(&mut self, order: &GridOrder) {
+        if let Some((entry, entry_ts)) = order.lot
+            && order.remaining > 0.0 {
+            self.positions.push((entry, order.remaining, entry_ts));
+            self.positions.sort_by_key(|&(_, _, ts)| ts);
+            self.restart_hold();
+        }
+    }
+
+    /// Drops orders never confirmed resting, returning their lots
+    fn expire_unconfirmed(&mut self, now: u64) {
+        let (expired, kept): (Vec<GridOrder>, Vec<GridOrder>) = std::mem::take(&mut self.orders)
+            .into_iter()
+            .partition(|o| o.order_id.is_none() && now.saturating_sub(o.sent_ts) > UNCONFIRMED_TIMEOUT_MS);
+        self.orders = kept;
+        for order in &expired {
+            self.restore_lot(order);
+        }
+    }
+
     fn find_grid_level(&self, price: f64) -> Option<(usize, f64)> {
         for (i, &level) in self.grid_levels.iter().enumerate() {
-            if (price - level).abs() / level < 0.0005 {
+            if (price - level).abs() / level < self.grid_size / 100.0 {
                 return Some((i, level));
             }
         }
@@ -50,7 +133,12 @@ impl TradingStrategy for GridTrading {
         vec![
             ("Base Price".to_string(), format!("{:.2}", self.base_price)),
             ("Positions".to_string(), self.positions.len().to_string()),
-            ("Grid Size".to_string(), format!("{:.1}%", self.grid_size)),
+            ("Open Orders".to_string(), self.orders.len().to_string()),
+            ("Grid Size".to_string(), format!("{:.2}%", self.grid_size)),
+            ("Levels".to_string(), format!("±{}", self.num_levels)),
+            ("Edge Distance".to_string(), self.edge_distance_pct().map_or("-".to_string(), |d| format!("{:.3}%", d))),
+            ("Recenters".to_string(), self.recenters.to_string()),
+            self.hold.feature(),
         ]
     }
 
@@ -58,34 +146,102 @@ impl TradingStrategy for GridTrading {
         let price = trade.price.parse::<f64>().unwrap_or(0.0);
         let qty = trade.qty.parse::<f64>().unwrap_or(0.0);
         
-        if self.base_price == 0.0 { self.setup_grid(price); }
+        if self.base_price == 0.0 {
+            self.setup_grid(price);
+        } else if self.outside_grid(price) {
+            log::info!("GridTrading: price {:.2} left the grid around {:.2}, recentering", price, self.base_price);
+            self.setup_grid(price);
+            self.recenters += 1;
+            for order in self.orders.iter_mut().filter(|o| o.side == Side::Buy) {
+                order.stale = true;
+            }
+        }
+        self.last_price = price;
+        self.last_ts = trade.event_time;
+        self.expire_unconfirmed(trade.event_time);
         
         let mut opps = Vec::new();
-        let current_state = state.read().await.state_machine.get_state();
+        let (current_state, cooldown_ms) = {
+            let read_guard = state.read().await;
+            (read_guard.state_machine.get_state(), read_guard.signal_cooldown_ms(self.name(), 10000))
+        };
         
-        if current_state == SystemState::Trading && trade.event_time - self.last_signal_time > 10000 {
+        // Buys from the old grid are cancelled, re-sent if no answer came back in time
+        if let Some(order) = self.orders.iter_mut().find(|o| {
+            o.stale && o.order_id.is_some() && o.cancel_sent_ts.is_none_or(|sent| trade.event_time.saturating_sub(sent) > UNCONFIRMED_TIMEOUT_MS)
+        }) && let Some(order_id) = order.order_id {
+            order.cancel_sent_ts = Some(trade.event_time);
+            opps.push(Opportunity {
+                id: "grid_cancel".to_string(),
+                signal: Signal::Cancel { symbol: trade.symbol.clone(), order_id },
+                score: 0.9,
+                risk_score: 0.1,
+                reason: format!("Grid buy @ {:.2} left behind by recenter", order.price),
+                timestamp: trade.event_time,
+            });
+        }
+
+        // Lots held past the max holding period are sold oldest first, outside the grid cooldown
+        if current_state == SystemState::Trading && self.hold.update(trade.event_time) {
+            let (entry, entry_qty, _) = self.positions.remove(0);
+            self.restart_hold();
+            let pnl_pct = (price - entry) / entry * 100.0;
+            opps.push(Opportunity {
+                id: "grid_time_exit".to_string(),
+                signal: Signal::Sell { symbol: trade.symbol.clone(), price: Some(price), quantity: entry_qty, order_type: OrderType::Market },
+                score: 0.6,
+                risk_score: 0.4,
+                reason: format!("Grid lot max hold reached ({:+.2}%)", pnl_pct),
+                timestamp: trade.event_time,
+            });
+        }
+
+        if current_state == SystemState::Trading && trade.event_time - self.last_signal_time > cooldown_ms {
             if let Some((level_idx, level_price)) = self.find_grid_level(price) {
                 let mid_level = self.grid_levels.len() / 2;
                 
-                if level_idx < mid_level && self.positions.len() < 5 {
+                let open_buys = self.orders.iter().filter(|o| o.side == Side::Buy && !o.stale).count();
+                let level_taken = self.orders.iter().any(|o| !o.stale && o.price == level_price);
+                if level_idx < mid_level && self.positions.len() + open_buys < 5 && !level_taken {
                     // Below base - accumulate
                     opps.push(Opportunity {
-                        id: format!("grid_buy_{}", trade.event_time),
-                        signal: Signal::Buy { symbol: trade.symbol.clone(), price: Some(price), quantity: 0.0005 },
+                        id: "grid_buy".to_string(),
+                        signal: Signal::Buy { symbol: trade.symbol.clone(), price: Some(level_price), quantity: 0.0005, order_type: OrderType::Limit },
                         score: 0.65,
                         risk_score: 0.3,
                         reason: format!("Grid buy at level {} ({:.2})", level_idx, level_price),
                         timestamp: trade.event_time,
                     });
-                    self.positions.push((price, 0.0005));
+                    self.orders.push(GridOrder {
+                        order_id: None,
+                        side: Side::Buy,
+                        price: level_price,
+                        remaining: 0.0005,
+                        lot: None,
+                        sent_ts: trade.event_time,
+                        stale: false,
+                        cancel_sent_ts: None,
+                    });
                     self.last_signal_time = trade.event_time;
-                } else if level_idx > mid_level && !self.positions.is_empty() {
-                    // Above base - take profit
-                    if let Some((entry, entry_qty)) = self.positions.pop() {
+                } else if level_idx > mid_level && !level_taken && self.positions.last().is_some_and(|&(entry, _, _)| price > entry) {
+                    // Above base - take profit. After a downward recenter the newest lot may
+                    // sit above the whole grid; it stays open until price recovers or the hold expires.
+                    if let Some((entry, entry_qty, entry_ts)) = self.positions.pop() {
+                        self.restart_hold();
+                        self.orders.push(GridOrder {
+                            order_id: None,
+                            side: Side::Sell,
+                            price: level_price,
+                            remaining: entry_qty,
+                            lot: Some((entry, entry_ts)),
+                            sent_ts: trade.event_time,
+                            stale: false,
+                            cancel_sent_ts: None,
+                        });
                         let pnl_pct = (price - entry) / entry * 100.0;
                         opps.push(Opportunity {
-                            id: format!("grid_sell_{}", trade.event_time),
-                            signal: Signal::Sell { symbol: trade.symbol.clone(), price: Some(price), quantity: entry_qty },
+                            id: "grid_sell".to_string(),
+                            signal: Signal::Sell { symbol: trade.symbol.clone(), price: Some(level_price), quantity: entry_qty, order_type: OrderType::Limit },
                             score: 0.7,
                             risk_score: 0.25,
                             reason: format!("Grid sell +{:.2}% profit", pnl_pct),
@@ -103,4 +259,101 @@ impl TradingStrategy for GridTrading {
 
     async fn process_aggr_trade(&mut self, _: AggrTradesEvent, _: SharedState) -> Vec<Opportunity> { Vec::new() }
     async fn process_orderbook(&mut self, _: OrderBook, _: SharedState) -> Vec<Opportunity> { Vec::new() }
+
+    fn on_order_event(&mut self, event: &OrderEvent) {
+        match event {
+            OrderEvent::Placed { order_id, quantity, signal } => {
+                let (side, price) = match signal {
+                    Signal::Buy { price: Some(price), .. } => (Side::Buy, *price),
+                    Signal::Sell { price: Some(price), .. } => (Side::Sell, *price),
+                    _ => return,
+                };
+                if let Some(order) = self.orders.iter_mut().find(|o| o.order_id.is_none() && o.side == side && o.price == price) {
+                    order.order_id = Some(*order_id);
+                    order.remaining = *quantity;
+                }
+            }
+            OrderEvent::Filled { order_id, price, quantity } => {
+                let Some(idx) = self.orders.iter().position(|o| o.order_id == Some(*order_id)) else {
+                    return;
+                };
+                let order = &mut self.orders[idx];
+                order.remaining -= quantity;
+                let (side, done) = (order.side, order.remaining <= f64::EPSILON);
+                if done {
+                    self.orders.remove(idx);
+                }
+                if side == Side::Buy {
+                    self.positions.push((*price, *quantity, self.last_ts));
+                    self.restart_hold();
+                }
+            }
+            OrderEvent::Cancelled { order_id } => {
+                if let Some(idx) = self.orders.iter().position(|o| o.order_id == Some(*order_id)) {
+                    let order = self.orders.remove(idx);
+                    self.restore_lot(&order);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pending(side: Side, price: f64, remaining: f64, lot: Option<(f64, u64)>) -> GridOrder {
+        GridOrder { order_id: None, side, price, remaining, lot, sent_ts: 0, stale: false, cancel_sent_ts: None }
+    }
+
+    fn placed(order_id: u64, quantity: f64, signal: Signal) -> OrderEvent {
+        OrderEvent::Placed { order_id, quantity, signal }
+    }
+
+    #[test]
+    fn lots_open_only_as_buys_fill() {
+        let mut grid = GridTrading::new();
+        grid.orders.push(pending(Side::Buy, 99.0, 0.0005, None));
+        let buy = Signal::Buy { symbol: "BTCUSDT".to_string(), price: Some(99.0), quantity: 0.0005, order_type: OrderType::Limit };
+        grid.on_order_event(&placed(7, 0.0005, buy));
+        assert!(grid.positions.is_empty());
+
+        grid.on_order_event(&OrderEvent::Filled { order_id: 7, price: 99.0, quantity: 0.0002 });
+        assert_eq!(grid.positions.len(), 1);
+        assert_eq!(grid.orders.len(), 1);
+        grid.on_order_event(&OrderEvent::Filled { order_id: 7, price: 99.0, quantity: 0.0003 });
+        assert_eq!(grid.positions.iter().map(|p| p.1).sum::<f64>(), 0.0005);
+        assert!(grid.orders.is_empty());
+
+        // Fills of orders the grid never sent are not its lots
+        grid.on_order_event(&OrderEvent::Filled { order_id: 8, price: 98.0, quantity: 1.0 });
+        assert_eq!(grid.positions.len(), 2);
+    }
+
+    #[test]
+    fn a_cancelled_sell_returns_its_lot() {
+        let mut grid = GridTrading::new();
+        grid.orders.push(pending(Side::Sell, 101.0, 0.0005, Some((99.0, 5))));
+        let sell = Signal::Sell { symbol: "BTCUSDT".to_string(), price: Some(101.0), quantity: 0.0005, order_type: OrderType::Limit };
+        grid.on_order_event(&placed(9, 0.0005, sell));
+        grid.on_order_event(&OrderEvent::Filled { order_id: 9, price: 101.0, quantity: 0.0002 });
+        assert!(grid.positions.is_empty());
+
+        grid.on_order_event(&OrderEvent::Cancelled { order_id: 9 });
+        assert!(grid.orders.is_empty());
+        assert_eq!(grid.positions.len(), 1);
+        assert_eq!((grid.positions[0].0, grid.positions[0].2), (99.0, 5));
+        assert!((grid.positions[0].1 - 0.0003).abs() < 1e-12);
+    }
+
+    #[test]
+    fn unconfirmed_orders_expire() {
+        let mut grid = GridTrading::new();
+        grid.orders.push(pending(Side::Sell, 101.0, 0.0005, Some((99.0, 5))));
+        grid.expire_unconfirmed(UNCONFIRMED_TIMEOUT_MS);
+        assert_eq!(grid.orders.len(), 1);
+        grid.expire_unconfirmed(UNCONFIRMED_TIMEOUT_MS + 1);
+        assert!(grid.orders.is_empty());
+        assert_eq!(grid.positions, vec![(99.0, 0.0005, 5)]);
+    }
 }
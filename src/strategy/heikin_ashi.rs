@@ -1,24 +1,32 @@
-use super::{Signal, TradingStrategy, Opportunity};
+use super::{Signal, OrderType, Side, TradingStrategy, Opportunity};
+use super::debounce::SignalDebouncer;
 use crate::web::SharedState;
 use crate::state_machine::SystemState;
 use async_trait::async_trait;
 use binance::model::{TradeEvent, OrderBook, AggrTradesEvent};
+use crate::market_data::{Candle, CANDLE_INTERVAL_SECS};
 
 /// Heikin-Ashi Trend Strategy
 pub struct HeikinAshiTrend {
     prev_ha_open: f64,
     prev_ha_close: f64,
-    is_bullish: bool,
+    is_bullish: bool, // Trend as of the last confirmed flip
     last_signal_time: u64,
+    debouncer: SignalDebouncer,
 }
 
 impl HeikinAshiTrend {
     pub fn new() -> Self {
+        // Flips are judged once per candle, so the window spans the required candles
+        // rather than SIGNAL_CONFIRM_WINDOW_MS
+        let debouncer = SignalDebouncer::from_env();
+        let window_ms = debouncer.required as u64 * CANDLE_INTERVAL_SECS as u64 * 1000;
         Self {
             prev_ha_open: 0.0,
             prev_ha_close: 0.0,
             is_bullish: false,
             last_signal_time: 0,
+            debouncer: debouncer.with_window_ms(window_ms),
         }
     }
 
@@ -47,6 +55,7 @@ impl TradingStrategy for HeikinAshiTrend {
             ("HA Open".to_string(), format!("{:.2}", self.prev_ha_open)),
             ("HA Close".to_string(), format!("{:.2}", self.prev_ha_close)),
             ("Trend".to_string(), if self.prev_ha_close > self.prev_ha_open { "Bullish" } else { "Bearish" }.to_string()),
+            self.debouncer.feature(),
         ]
     }
 
@@ -54,47 +63,57 @@ impl TradingStrategy for HeikinAshiTrend {
         let price = trade.price.parse::<f64>().unwrap_or(0.0);
         let qty = trade.qty.parse::<f64>().unwrap_or(0.0);
         
-        let (ha_open, ha_close) = self.update_ha(price, price, price, price); // Simplified HA
+        // Signals come from closed candles in process_candle
+        { let mut w = state.write().await; w.push_data_point_at(price, qty, None, 0, 0, 0.0, trade.event_time); }
+        Vec::new()
+    }
+
+    async fn process_aggr_trade(&mut self, _: AggrTradesEvent, _: SharedState) -> Vec<Opportunity> { Vec::new() }
+
+    async fn process_orderbook(&mut self, _: OrderBook, _: SharedState) -> Vec<Opportunity> { Vec::new() }
+
+    async fn process_candle(&mut self, candle: Candle, state: SharedState) -> Vec<Opportunity> {
+        let (ha_open, ha_close) = self.update_ha(candle.open, candle.high, candle.low, candle.close);
         let current_bullish = ha_close > ha_open;
+        let event_time = candle.close_time_ms(CANDLE_INTERVAL_SECS);
         
+        // A flip only counts once the new colour has held for the confirmation window
+        let flip = (current_bullish != self.is_bullish).then_some(if current_bullish { Side::Buy } else { Side::Sell });
+        if !self.debouncer.confirm(flip, event_time) {
+            return Vec::new();
+        }
+
         let mut opps = Vec::new();
-        let current_state = state.read().await.state_machine.get_state();
+        let (current_state, cooldown_ms) = {
+            let read_guard = state.read().await;
+            (read_guard.state_machine.get_state(), read_guard.signal_cooldown_ms(self.name(), 30000))
+        };
         
-        if current_state == SystemState::Trading && trade.event_time - self.last_signal_time > 30000 {
-            if !self.is_bullish && current_bullish {
+        if current_state == SystemState::Trading && event_time.saturating_sub(self.last_signal_time) > cooldown_ms {
+            if current_bullish {
                 opps.push(Opportunity {
-                    id: format!("ha_buy_{}", trade.event_time),
-                    signal: Signal::Buy { symbol: trade.symbol.clone(), price: Some(price), quantity: 0.001 },
+                    id: "ha_buy".to_string(),
+                    signal: Signal::Buy { symbol: candle.symbol.clone(), price: Some(candle.close), quantity: 0.001, order_type: OrderType::Market },
                     score: 0.7,
                     risk_score: 0.3,
                     reason: "Heikin-Ashi Bullish Flip".to_string(),
-                    timestamp: trade.event_time,
+                    timestamp: event_time,
                 });
-                self.last_signal_time = trade.event_time;
-            } else if self.is_bullish && !current_bullish {
+                self.last_signal_time = event_time;
+            } else {
                 opps.push(Opportunity {
-                    id: format!("ha_sell_{}", trade.event_time),
-                    signal: Signal::Sell { symbol: trade.symbol.clone(), price: Some(price), quantity: 0.001 },
+                    id: "ha_sell".to_string(),
+                    signal: Signal::Sell { symbol: candle.symbol.clone(), price: Some(candle.close), quantity: 0.001, order_type: OrderType::Market },
                     score: 0.7,
                     risk_score: 0.3,
                     reason: "Heikin-Ashi Bearish Flip".to_string(),
-                    timestamp: trade.event_time,
+                    timestamp: event_time,
                 });
-                self.last_signal_time = trade.event_time;
+                self.last_signal_time = event_time;
             }
         }
         
         self.is_bullish = current_bullish;
-        
-        { let mut w = state.write().await; w.push_data_point_at(price, qty, opps.first().map(|o| match &o.signal { Signal::Buy{..} => "Buy", Signal::Sell{..} => "Sell", _ => "Cancel" }.to_string()), 0, 0, 0.0, trade.event_time); }
         opps
     }
-
-    async fn process_aggr_trade(&mut self, trade: AggrTradesEvent, _: SharedState) -> Vec<Opportunity> {
-        let price = trade.price.parse::<f64>().unwrap_or(0.0);
-        self.update_ha(price, price, price, price);
-        Vec::new()
-    }
-
-    async fn process_orderbook(&mut self, _: OrderBook, _: SharedState) -> Vec<Opportunity> { Vec::new() }
 }
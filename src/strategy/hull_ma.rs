@@ -1,4 +1,4 @@
-use super::{Signal, TradingStrategy, Opportunity};
+use super::{Signal, OrderType, TradingStrategy, Opportunity, indicators};
 use crate::web::SharedState;
 use crate::state_machine::SystemState;
 use async_trait::async_trait;
@@ -8,6 +8,7 @@ use std::collections::VecDeque;
 /// Hull Moving Average (HMA) Strategy
 pub struct HullMA {
     prices: VecDeque<f64>,
+    raw_hma: VecDeque<f64>, // 2*WMA(n/2) - WMA(n) history for the final smoothing pass
     period: usize,
     hma: f64,
     prev_hma: f64,
@@ -18,6 +19,7 @@ impl HullMA {
     pub fn new() -> Self {
         Self {
             prices: VecDeque::with_capacity(100),
+            raw_hma: VecDeque::with_capacity(20),
             period: 20,
             hma: 0.0,
             prev_hma: 0.0,
@@ -26,31 +28,26 @@ impl HullMA {
     }
 
     fn wma(prices: &[f64], period: usize) -> f64 {
-        if prices.len() < period { return 0.0; }
-        let mut denominator = 0.0;
-        let mut numerator = 0.0;
-        for i in 0..period {
-            let weight = (i + 1) as f64;
-            numerator += prices[prices.len() - period + i] * weight;
-            denominator += weight;
-        }
-        numerator / denominator
+        indicators::wma(prices, period).unwrap_or(0.0)
     }
 
-    fn calculate_hma(&self) -> f64 {
+    fn calculate_hma(&mut self) -> f64 {
         if self.prices.len() < self.period { return 0.0; }
         
         // HMA = WMA(2*WMA(n/2) - WMA(n), sqrt(n))
         let half_period = self.period / 2;
-        let sqrt_period = (self.period as f64).sqrt() as usize;
+        let sqrt_period = ((self.period as f64).sqrt() as usize).max(1);
         
-        let wma_half = Self::wma(self.prices.as_slices().0, half_period);
-        let wma_full = Self::wma(self.prices.as_slices().0, self.period);
+        let prices = self.prices.make_contiguous();
+        let wma_half = Self::wma(prices, half_period);
+        let wma_full = Self::wma(prices, self.period);
         
-        let raw_hma = 2.0 * wma_half - wma_full;
+        self.raw_hma.push_back(2.0 * wma_half - wma_full);
+        if self.raw_hma.len() > sqrt_period { self.raw_hma.pop_front(); }
         
-        // This is simplified as we'd need another history for raw_hma to do the final WMA
-        raw_hma
+        // Final smoothing pass once enough raw values have accumulated
+        if self.raw_hma.len() < sqrt_period { return 0.0; }
+        Self::wma(self.raw_hma.make_contiguous(), sqrt_period)
     }
 }
 
@@ -76,13 +73,16 @@ impl TradingStrategy for HullMA {
         self.hma = self.calculate_hma();
         
         let mut opps = Vec::new();
-        let current_state = state.read().await.state_machine.get_state();
+        let (current_state, cooldown_ms) = {
+            let read_guard = state.read().await;
+            (read_guard.state_machine.get_state(), read_guard.signal_cooldown_ms(self.name(), 60000))
+        };
         
-        if current_state == SystemState::Trading && trade.event_time - self.last_signal_time > 60000 && self.prev_hma > 0.0 {
+        if current_state == SystemState::Trading && trade.event_time - self.last_signal_time > cooldown_ms && self.prev_hma > 0.0 {
             if self.hma > self.prev_hma * 1.0001 {
                 opps.push(Opportunity {
-                    id: format!("hma_buy_{}", trade.event_time),
-                    signal: Signal::Buy { symbol: trade.symbol.clone(), price: Some(price), quantity: 0.001 },
+                    id: "hma_buy".to_string(),
+                    signal: Signal::Buy { symbol: trade.symbol.clone(), price: Some(price), quantity: 0.001, order_type: OrderType::Market },
                     score: 0.8,
                     risk_score: 0.3,
                     reason: "HMA Turning Up".to_string(),
@@ -91,8 +91,8 @@ impl TradingStrategy for HullMA {
                 self.last_signal_time = trade.event_time;
             } else if self.hma < self.prev_hma * 0.9999 {
                 opps.push(Opportunity {
-                    id: format!("hma_sell_{}", trade.event_time),
-                    signal: Signal::Sell { symbol: trade.symbol.clone(), price: Some(price), quantity: 0.001 },
+                    id: "hma_sell".to_string(),
+                    signal: Signal::Sell { symbol: trade.symbol.clone(), price: Some(price), quantity: 0.001, order_type: OrderType::Market },
                     score: 0.8,
                     risk_score: 0.3,
                     reason: "HMA Turning Down".to_string(),
@@ -115,3 +115,23 @@ impl TradingStrategy for HullMA {
 
     async fn process_orderbook(&mut self, _: OrderBook, _: SharedState) -> Vec<Opportunity> { Vec::new() }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hma_applies_the_final_wma_pass() {
+        let mut hull = HullMA::new();
+        hull.period = 4; // WMA(2) and WMA(4), smoothed by WMA(2)
+        for price in [1.0, 2.0, 3.0, 4.0] {
+            hull.prices.push_back(price);
+        }
+        // Raw 2 * 11/3 - 3 = 13/3, but the smoothing pass needs two raw values
+        assert_eq!(hull.calculate_hma(), 0.0);
+        hull.prices.push_back(5.0);
+        // Raw 2 * 14/3 - 4 = 16/3; WMA(13/3, 16/3) = (13/3 + 2 * 16/3) / 3 = 5,
+        // so on a straight line the HMA sits on the latest price without lag
+        assert!((hull.calculate_hma() - 5.0).abs() < 1e-9);
+    }
+}
@@ -1,4 +1,4 @@
-use super::{Signal, TradingStrategy, Opportunity};
+use super::{Signal, OrderType, TradingStrategy, Opportunity};
 use crate::web::SharedState;
 use crate::state_machine::SystemState;
 use async_trait::async_trait;
@@ -61,14 +61,17 @@ impl TradingStrategy for IchimokuCloud {
         self.kijun = self.calculate_n_period_mid(self.kijun_period);
         
         let mut opps = Vec::new();
-        let current_state = state.read().await.state_machine.get_state();
+        let (current_state, cooldown_ms) = {
+            let read_guard = state.read().await;
+            (read_guard.state_machine.get_state(), read_guard.signal_cooldown_ms(self.name(), 60000))
+        };
         
-        if current_state == SystemState::Trading && trade.event_time - self.last_signal_time > 60000 && prev_kijun > 0.0 {
+        if current_state == SystemState::Trading && trade.event_time - self.last_signal_time > cooldown_ms && prev_kijun > 0.0 {
             // Tenkan crosses Kijun from below
             if prev_tenkan <= prev_kijun && self.tenkan > self.kijun {
                 opps.push(Opportunity {
-                    id: format!("ichimoku_buy_{}", trade.event_time),
-                    signal: Signal::Buy { symbol: trade.symbol.clone(), price: Some(price), quantity: 0.001 },
+                    id: "ichimoku_buy".to_string(),
+                    signal: Signal::Buy { symbol: trade.symbol.clone(), price: Some(price), quantity: 0.001, order_type: OrderType::Market },
                     score: 0.8,
                     risk_score: 0.3,
                     reason: "Tenkan-Kijun Bullish Cross".to_string(),
@@ -77,8 +80,8 @@ impl TradingStrategy for IchimokuCloud {
                 self.last_signal_time = trade.event_time;
             } else if prev_tenkan >= prev_kijun && self.tenkan < self.kijun {
                 opps.push(Opportunity {
-                    id: format!("ichimoku_sell_{}", trade.event_time),
-                    signal: Signal::Sell { symbol: trade.symbol.clone(), price: Some(price), quantity: 0.001 },
+                    id: "ichimoku_sell".to_string(),
+                    signal: Signal::Sell { symbol: trade.symbol.clone(), price: Some(price), quantity: 0.001, order_type: OrderType::Market },
                     score: 0.8,
                     risk_score: 0.3,
                     reason: "Tenkan-Kijun Bearish Cross".to_string(),
@@ -0,0 +1,207 @@
+//! Technical indicators shared by the strategies. All functions operate on
+//! price (or candle) slices ordered oldest to newest and look at the most recent `period` values.
+//!
+//! The examples are documentation only: this is a binary crate, so rustdoc never runs them.
+//! The unit tests at the bottom check the same values.
+
+use crate::market_data::Candle;
+
+/// Simple moving average of the last `period` prices
+///
+/// # Examples
+///
+/// ```ignore
+/// assert_eq!(sma(&[1.0, 2.0, 3.0, 4.0, 5.0], 3), Some(4.0));
+/// assert_eq!(sma(&[1.0, 2.0], 3), None);
+/// ```
+pub fn sma(prices: &[f64], period: usize) -> Option<f64> {
+    if period == 0 || prices.len() < period {
+        return None;
+    }
+    let recent = &prices[prices.len() - period..];
+    Some(recent.iter().sum::<f64>() / period as f64)
+}
+
+/// Population standard deviation of the last `period` prices
+///
+/// # Examples
+///
+/// ```ignore
+/// assert_eq!(stddev(&[2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0], 8), Some(2.0));
+/// ```
+pub fn stddev(prices: &[f64], period: usize) -> Option<f64> {
+    let mean = sma(prices, period)?;
+    let recent = &prices[prices.len() - period..];
+    let variance = recent.iter().map(|p| (p - mean).powi(2)).sum::<f64>() / period as f64;
+    Some(variance.sqrt())
+}
+
+/// Exponential moving average over the whole slice, seeded with the SMA of the first `period` prices
+///
+/// # Examples
+///
+/// ```ignore
+/// // k = 0.5: seed 2, then 3, then 4
+/// assert_eq!(ema(&[1.0, 2.0, 3.0, 4.0, 5.0], 3), Some(4.0));
+/// ```
+pub fn ema(prices: &[f64], period: usize) -> Option<f64> {
+    if period == 0 || prices.len() < period {
+        return None;
+    }
+    let k = 2.0 / (period as f64 + 1.0);
+    let seed = prices[..period].iter().sum::<f64>() / period as f64;
+    Some(prices[period..].iter().fold(seed, |ema, &p| p * k + ema * (1.0 - k)))
+}
+
+/// Linearly weighted moving average of the last `period` prices (newest has weight `period`)
+///
+/// # Examples
+///
+/// ```ignore
+/// // (1 * 1 + 2 * 2 + 3 * 3) / 6
+/// assert_eq!(wma(&[9.0, 1.0, 2.0, 3.0], 3), Some(14.0 / 6.0));
+/// ```
+pub fn wma(prices: &[f64], period: usize) -> Option<f64> {
+    if period == 0 || prices.len() < period {
+        return None;
+    }
+    let recent = &prices[prices.len() - period..];
+    let numerator: f64 = recent.iter().enumerate().map(|(i, p)| p * (i + 1) as f64).sum();
+    let denominator = (period * (period + 1) / 2) as f64;
+    Some(numerator / denominator)
+}
+
+/// Average true range with Wilder's smoothing.
+///
+/// True range is `max(high - low, |high - prev_close|, |low - prev_close|)`, so gaps between
+/// candles count. Seeds with the simple average of the first `period` true ranges, then smooths
+/// the rest with `atr = (prev_atr * (period - 1) + tr) / period`. Like `ema`, it runs over the
+/// whole slice, so a longer history gives a settled value. Needs `period + 1` candles.
+///
+/// # Examples
+///
+/// ```ignore
+/// // True ranges 2, 2 and 4 after the first candle
+/// let candles = [(10.0, 8.0, 9.0), (11.0, 9.0, 10.0), (12.0, 10.0, 11.0), (13.0, 9.0, 12.0)]
+///     .map(|(high, low, close)| Candle { symbol: "BTCUSDT".to_string(), timestamp: 0, open: close, high, low, close, volume: 1.0 });
+/// assert_eq!(atr(&candles, 3), Some(8.0 / 3.0));
+/// assert_eq!(atr(&candles[..3], 3), None);
+/// ```
+pub fn atr(candles: &[Candle], period: usize) -> Option<f64> {
+    if period == 0 || candles.len() < period + 1 {
+        return None;
+    }
+    let mut true_ranges = candles.windows(2).map(|w| {
+        let (prev_close, bar) = (w[0].close, &w[1]);
+        (bar.high - bar.low)
+            .max((bar.high - prev_close).abs())
+            .max((bar.low - prev_close).abs())
+    });
+    let seed = true_ranges.by_ref().take(period).sum::<f64>() / period as f64;
+    Some(true_ranges.fold(seed, |atr, tr| (atr * (period - 1) as f64 + tr) / period as f64))
+}
+
+/// RSI with Wilder's smoothing, as used by TradingView.
+///
+/// Seeds the average gain/loss with a simple average over the first `period`
+/// changes, then smooths the rest with `avg = (prev_avg * (period - 1) + current) / period`.
+/// Returns None until there are at least `period + 1` prices.
+pub fn wilder_rsi(prices: &[f64], period: usize) -> Option<f64> {
+    if period == 0 || prices.len() < period + 1 {
+        return None;
+    }
+
+    let mut changes = prices.windows(2).map(|w| w[1] - w[0]);
+    let (mut avg_gain, mut avg_loss) = changes
+        .by_ref()
+        .take(period)
+        .fold((0.0, 0.0), |(g, l), c| (g + c.max(0.0), l + (-c).max(0.0)));
+    avg_gain /= period as f64;
+    avg_loss /= period as f64;
+
+    let n = period as f64;
+    for change in changes {
+        avg_gain = (avg_gain * (n - 1.0) + change.max(0.0)) / n;
+        avg_loss = (avg_loss * (n - 1.0) + (-change).max(0.0)) / n;
+    }
+
+    if avg_loss == 0.0 {
+        return Some(if avg_gain == 0.0 { 50.0 } else { 100.0 });
+    }
+    let rs = avg_gain / avg_loss;
+    Some(100.0 - (100.0 / (1.0 + rs)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candle(high: f64, low: f64, close: f64) -> Candle {
+        Candle { symbol: "BTCUSDT".to_string(), timestamp: 0, open: close, high, low, close, volume: 1.0 }
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-9, "expected {}, got {}", expected, actual);
+    }
+
+    #[test]
+    fn sma_and_stddev_use_the_last_period_prices() {
+        assert_close(sma(&[1.0, 2.0, 3.0, 4.0, 5.0], 3).unwrap(), 4.0);
+        assert_close(stddev(&[2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0], 8).unwrap(), 2.0);
+        assert!(sma(&[1.0, 2.0], 3).is_none());
+        assert!(stddev(&[1.0, 2.0], 0).is_none());
+    }
+
+    #[test]
+    fn ema_seeds_with_the_first_sma() {
+        // k = 0.5, seed (1 + 2 + 3) / 3 = 2, then 4 * 0.5 + 2 * 0.5 = 3, then 5 * 0.5 + 3 * 0.5 = 4
+        assert_close(ema(&[1.0, 2.0, 3.0, 4.0, 5.0], 3).unwrap(), 4.0);
+        assert_close(ema(&[1.0, 2.0, 3.0], 3).unwrap(), 2.0);
+    }
+
+    #[test]
+    fn wma_weights_the_newest_price_most() {
+        // (1 * 1 + 2 * 2 + 3 * 3) / 6
+        assert_close(wma(&[9.0, 1.0, 2.0, 3.0], 3).unwrap(), 14.0 / 6.0);
+        assert!(wma(&[1.0], 2).is_none());
+    }
+
+    #[test]
+    fn atr_seeds_with_mean_true_range_then_smooths() {
+        let candles = [
+            candle(10.0, 8.0, 9.0),
+            candle(11.0, 9.0, 10.0),  // TR 2
+            candle(12.0, 10.0, 11.0), // TR 2
+            candle(13.0, 9.0, 12.0),  // TR 4
+            candle(16.0, 15.0, 15.5), // Gap up: high - prev close = 4, high - low only 1
+        ];
+        // Seed (2 + 2 + 4) / 3, then (8/3 * 2 + 4) / 3
+        assert_close(atr(&candles[..4], 3).unwrap(), 8.0 / 3.0);
+        assert_close(atr(&candles, 3).unwrap(), 28.0 / 9.0);
+    }
+
+    #[test]
+    fn atr_needs_period_plus_one_candles() {
+        let candles = [candle(10.0, 8.0, 9.0), candle(11.0, 9.0, 10.0), candle(12.0, 10.0, 11.0)];
+        assert!(atr(&candles, 3).is_none());
+        assert!(atr(&candles, 0).is_none());
+    }
+
+    #[test]
+    fn wilder_rsi_smooths_after_the_simple_seed() {
+        // Changes +1, -0.5, +1, then -0.5
+        let prices = [10.0, 11.0, 10.5, 11.5, 11.0];
+        // Seed: gain 2/3, loss 1/6, RS 4
+        assert_close(wilder_rsi(&prices[..4], 3).unwrap(), 80.0);
+        // Gain (2/3 * 2 + 0) / 3 = 4/9, loss (1/6 * 2 + 0.5) / 3 = 5/18, RS 1.6
+        assert_close(wilder_rsi(&prices, 3).unwrap(), 100.0 - 100.0 / 2.6);
+    }
+
+    #[test]
+    fn wilder_rsi_edge_cases() {
+        assert_close(wilder_rsi(&[5.0, 5.0, 5.0, 5.0], 3).unwrap(), 50.0);
+        assert_close(wilder_rsi(&[1.0, 2.0, 3.0, 4.0], 3).unwrap(), 100.0);
+        assert_close(wilder_rsi(&[4.0, 3.0, 2.0, 1.0], 3).unwrap(), 0.0);
+        assert!(wilder_rsi(&[1.0, 2.0, 3.0], 3).is_none());
+    }
+}
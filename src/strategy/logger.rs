@@ -1,4 +1,4 @@
-use super::{Signal, TradingStrategy};
+use super::{Signal, OrderType, TradingStrategy};
 use crate::web::SharedState;
 use crate::state_machine::SystemState;
 use async_trait::async_trait;
@@ -99,20 +99,9 @@ impl PaperTrader {
         self.trade_count += 1;
         
         let mut volatility_score = 0.0;
-        // 1. Update State Machine
+        // 1. Update inferred state probabilities (warm-up transitions are driven by the event loop)
         {
             let mut write_guard = state.write().await;
-            
-            let current = write_guard.state_machine.get_state();
-            if current == SystemState::Booting {
-                info!("Market Data received: Transitioning Booting -> Accumulating");
-                write_guard.state_machine.transition_to(SystemState::Accumulating);
-            } else if current == SystemState::Accumulating {
-                 if write_guard.state_machine.is_stable() {
-                     write_guard.state_machine.transition_to(SystemState::Trading);
-                 }
-            }
-
             if let Some(lp) = self.last_price {
                 volatility_score = (price - lp).abs() / lp * 1000.0;
             }
@@ -128,8 +117,8 @@ impl PaperTrader {
             // High Confidence Buy Opportunity (Mock)
             if self.trade_count % 5 == 0 {
                 opportunities.push(super::Opportunity {
-                    id: format!("buy_{}", self.trade_count),
-                    signal: Signal::Buy { symbol: symbol.clone(), price: Some(price * 0.999), quantity: 0.001 },
+                    id: "buy".to_string(),
+                    signal: Signal::Buy { symbol: symbol.clone(), price: Some(price * 0.999), quantity: 0.001, order_type: OrderType::Market },
                     score: 0.85,
                     risk_score: 0.2,
                     reason: "Strong momentum detected with low volatility".to_string(),
@@ -140,8 +129,8 @@ impl PaperTrader {
             // Scalp Sell Opportunity (Mock)
             if self.trade_count % 8 == 0 {
                 opportunities.push(super::Opportunity {
-                    id: format!("sell_{}", self.trade_count),
-                    signal: Signal::Sell { symbol: symbol.clone(), price: Some(price * 1.001), quantity: 0.001 },
+                    id: "sell".to_string(),
+                    signal: Signal::Sell { symbol: symbol.clone(), price: Some(price * 1.001), quantity: 0.001, order_type: OrderType::Market },
                     score: 0.65,
                     risk_score: 0.4,
                     reason: "Local resistance breakout attempt".to_string(),
@@ -1,4 +1,4 @@
-use super::{Signal, TradingStrategy, Opportunity};
+use super::{Signal, OrderType, TradingStrategy, Opportunity};
 use crate::web::SharedState;
 use crate::state_machine::SystemState;
 use async_trait::async_trait;
@@ -69,17 +69,20 @@ impl TradingStrategy for MACDCrossover {
         self.prev_histogram = histogram;
         
         let mut opps = Vec::new();
-        let current_state = state.read().await.state_machine.get_state();
+        let (current_state, cooldown_ms) = {
+            let read_guard = state.read().await;
+            (read_guard.state_machine.get_state(), read_guard.signal_cooldown_ms(self.name(), 45000))
+        };
         
         if current_state == SystemState::Trading && 
            self.prices.len() >= 26 && 
-           trade.event_time - self.last_signal_time > 45000 {
+           trade.event_time - self.last_signal_time > cooldown_ms {
             
             // Bullish crossover
             if prev_hist < 0.0 && histogram > 0.0 {
                 opps.push(Opportunity {
-                    id: format!("macd_buy_{}", trade.event_time),
-                    signal: Signal::Buy { symbol: trade.symbol.clone(), price: Some(price), quantity: 0.001 },
+                    id: "macd_buy".to_string(),
+                    signal: Signal::Buy { symbol: trade.symbol.clone(), price: Some(price), quantity: 0.001, order_type: OrderType::Market },
                     score: 0.75,
                     risk_score: 0.35,
                     reason: "MACD bullish crossover".to_string(),
@@ -90,8 +93,8 @@ impl TradingStrategy for MACDCrossover {
             // Bearish crossover
             else if prev_hist > 0.0 && histogram < 0.0 {
                 opps.push(Opportunity {
-                    id: format!("macd_sell_{}", trade.event_time),
-                    signal: Signal::Sell { symbol: trade.symbol.clone(), price: Some(price), quantity: 0.001 },
+                    id: "macd_sell".to_string(),
+                    signal: Signal::Sell { symbol: trade.symbol.clone(), price: Some(price), quantity: 0.001, order_type: OrderType::Market },
                     score: 0.7,
                     risk_score: 0.4,
                     reason: "MACD bearish crossover".to_string(),
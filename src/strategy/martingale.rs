@@ -1,11 +1,16 @@
-use super::{Signal, TradingStrategy, Opportunity};
+use super::{Signal, OrderType, TradingStrategy, Opportunity};
+use super::bracket::HoldTimer;
 use crate::web::SharedState;
 use crate::state_machine::SystemState;
 use async_trait::async_trait;
 use binance::model::{TradeEvent, OrderBook, AggrTradesEvent};
 use std::time::Instant;
 
-/// Martingale strategy - doubles down on losses (high risk)
+/// Most doublings applied to the base size (32x)
+const MAX_DOUBLINGS: u32 = 5;
+
+/// Martingale strategy - doubles down on losses (high risk).
+/// In anti-martingale mode it doubles after wins and resets after a loss instead.
 pub struct MartingaleStrategy {
     trade_count: u64,
     last_spread: f64,
@@ -13,6 +18,9 @@ pub struct MartingaleStrategy {
     position_size: f64,
     in_position: bool,
     consecutive_losses: u32,
+    consecutive_wins: u32,
+    anti_martingale: bool,
+    hold: HoldTimer,
 }
 
 impl MartingaleStrategy {
@@ -24,19 +32,63 @@ impl MartingaleStrategy {
             position_size: 0.0001,
             in_position: false,
             consecutive_losses: 0,
+            consecutive_wins: 0,
+            anti_martingale: false,
+            hold: HoldTimer::from_env(),
+        }
+    }
+
+    /// Grow size after wins and reset after losses (registered as "AntiMartingale")
+    pub fn with_anti_martingale(mut self) -> Self {
+        self.anti_martingale = true;
+        self
+    }
+
+    /// The streak that drives sizing in the active mode
+    fn streak(&self) -> u32 {
+        if self.anti_martingale { self.consecutive_wins } else { self.consecutive_losses }
+    }
+
+    fn next_size(&self) -> f64 {
+        self.position_size * (2.0_f64).powi(self.streak().min(MAX_DOUBLINGS) as i32)
+    }
+
+    fn record_exit(&mut self, won: bool) {
+        if won {
+            self.consecutive_wins += 1;
+            self.consecutive_losses = 0;
+        } else {
+            self.consecutive_losses += 1;
+            self.consecutive_wins = 0;
         }
     }
 }
 
 #[async_trait]
 impl TradingStrategy for MartingaleStrategy {
-    fn name(&self) -> &str { "Martingale" }
+    fn name(&self) -> &str {
+        if self.anti_martingale { "AntiMartingale" } else { "Martingale" }
+    }
+
+    /// Long positions only; TP/SL are measured from the carried entry price
+    fn adopt_position(&mut self, position: &crate::execution::PositionInfo, ts: u64) -> bool {
+        if position.side == "Short" || position.entry_price <= 0.0 || self.in_position {
+            return false;
+        }
+        self.in_position = true;
+        self.last_trade_price = position.entry_price;
+        self.hold.start(ts);
+        true
+    }
     
     fn get_features(&self) -> Vec<(String, String)> {
         vec![
+            ("Mode".to_string(), if self.anti_martingale { "Anti-Martingale" } else { "Martingale" }.to_string()),
+            ("Wins".to_string(), self.consecutive_wins.to_string()),
             ("Losses".to_string(), self.consecutive_losses.to_string()),
-            ("Next Size".to_string(), format!("{:.4}", self.position_size * (2.0_f64).powi(self.consecutive_losses.min(5) as i32))),
+            ("Next Size".to_string(), format!("{:.4}", self.next_size())),
             ("In Position".to_string(), self.in_position.to_string()),
+            self.hold.feature(),
         ]
     }
     
@@ -65,15 +117,6 @@ impl MartingaleStrategy {
         let start = Instant::now();
         self.trade_count += 1;
         
-        {
-            let mut guard = state.write().await;
-            if guard.state_machine.get_state() == SystemState::Booting {
-                guard.state_machine.transition_to(SystemState::Accumulating);
-            } else if guard.state_machine.get_state() == SystemState::Accumulating && guard.state_machine.is_stable() {
-                guard.state_machine.transition_to(SystemState::Trading);
-            }
-        }
-        
         let mut opps = Vec::new();
         let current_state = state.read().await.state_machine.get_state();
         
@@ -82,43 +125,64 @@ impl MartingaleStrategy {
             if !self.in_position && self.trade_count % 100 == 0 {
                 self.in_position = true;
                 self.last_trade_price = price;
-                let size = self.position_size * (2.0_f64).powi(self.consecutive_losses.min(5) as i32);
+                self.hold.start(ts);
+                let size = self.next_size();
+                let reason = if self.anti_martingale {
+                    format!("Anti-martingale entry (size={:.4}, wins={})", size, self.consecutive_wins)
+                } else {
+                    format!("Martingale entry (size={:.4}, losses={})", size, self.consecutive_losses)
+                };
                 
                 opps.push(Opportunity {
-                    id: format!("mart_buy_{}", self.trade_count),
-                    signal: Signal::Buy { symbol: symbol.clone(), price: Some(price), quantity: size },
+                    id: "mart_buy".to_string(),
+                    signal: Signal::Buy { symbol: symbol.clone(), price: Some(price), quantity: size, order_type: OrderType::Market },
                     score: 0.6,
                     risk_score: 0.6,
-                    reason: format!("Martingale entry (size={:.4}, losses={})", size, self.consecutive_losses),
+                    reason,
                     timestamp: ts,
                 });
             }
             
-            // Exit on TP or SL
+            // Exit on TP, SL or max holding period
             if self.in_position {
                 let pnl_pct = (price - self.last_trade_price) / self.last_trade_price * 100.0;
+                let timed_out = self.hold.update(ts);
                 
                 if pnl_pct > 0.2 {  // Take profit
                     self.in_position = false;
-                    self.consecutive_losses = 0;
-                    self.position_size = 0.0001;  // Reset size
+                    self.hold.stop();
+                    self.record_exit(true);
                     opps.push(Opportunity {
-                        id: format!("mart_sell_tp_{}", self.trade_count),
-                        signal: Signal::Sell { symbol: symbol.clone(), price: Some(price), quantity: 0.001 },
+                        id: "mart_sell_tp".to_string(),
+                        signal: Signal::SellAll { symbol: symbol.clone(), price: Some(price) },
                         score: 0.7,
                         risk_score: 0.2,
                         reason: format!("Take profit: {:.2}%", pnl_pct),
                         timestamp: ts,
                     });
-                } else if pnl_pct < -0.2 {  // Stop loss - will double next position
+                } else if pnl_pct < -0.2 {  // Stop loss - doubles next position (resets it in anti mode)
                     self.in_position = false;
-                    self.consecutive_losses += 1;
+                    self.hold.stop();
+                    self.record_exit(false);
+                    let next = if self.anti_martingale { "next resets" } else { "next will double" };
                     opps.push(Opportunity {
-                        id: format!("mart_sell_sl_{}", self.trade_count),
-                        signal: Signal::Sell { symbol: symbol.clone(), price: Some(price), quantity: 0.001 },
+                        id: "mart_sell_sl".to_string(),
+                        signal: Signal::SellAll { symbol: symbol.clone(), price: Some(price) },
                         score: 0.5,
                         risk_score: 0.5,
-                        reason: format!("Stop loss: {:.2}%, next will double", pnl_pct),
+                        reason: format!("Stop loss: {:.2}%, {}", pnl_pct, next),
+                        timestamp: ts,
+                    });
+                } else if timed_out {  // Held too long - a losing exit still counts as a loss
+                    self.in_position = false;
+                    self.hold.stop();
+                    self.record_exit(pnl_pct >= 0.0);
+                    opps.push(Opportunity {
+                        id: "mart_sell_time".to_string(),
+                        signal: Signal::SellAll { symbol: symbol.clone(), price: Some(price) },
+                        score: 0.5,
+                        risk_score: 0.4,
+                        reason: format!("Max hold reached: {:.2}%", pnl_pct),
                         timestamp: ts,
                     });
                 }
@@ -129,7 +193,7 @@ impl MartingaleStrategy {
             let mut guard = state.write().await;
             let action = opps.first().map(|o| match &o.signal {
                 Signal::Buy { .. } => "Buy".to_string(),
-                Signal::Sell { .. } => "Sell".to_string(),
+                Signal::Sell { .. } | Signal::SellAll { .. } => "Sell".to_string(),
                 _ => "Hold".to_string(),
             });
             let strat_lat = guard.metrics.get_strategy_stats().p50;
@@ -1,4 +1,4 @@
-use super::{Signal, TradingStrategy, Opportunity};
+use super::{Signal, OrderType, TradingStrategy, Opportunity, indicators};
 use crate::web::SharedState;
 use crate::state_machine::SystemState;
 use async_trait::async_trait;
@@ -28,8 +28,8 @@ impl TradingStrategy for MeanReversionStrategy {
     }
 
     fn get_features(&self) -> Vec<(String, String)> {
-        let mean: f64 = if self.prices.is_empty() { 0.0 } else { self.prices.iter().sum::<f64>() / self.prices.len() as f64 };
-        let std_dev: f64 = if self.prices.len() < 2 { 0.0 } else { (self.prices.iter().map(|p| (p - mean).powi(2)).sum::<f64>() / self.prices.len() as f64).sqrt() };
+        let mean = indicators::sma(&self.prices, self.prices.len()).unwrap_or(0.0);
+        let std_dev = if self.prices.len() < 2 { 0.0 } else { indicators::stddev(&self.prices, self.prices.len()).unwrap_or(0.0) };
         
         vec![
             ("Mean".to_string(), format!("{:.2}", mean)),
@@ -86,30 +86,19 @@ impl MeanReversionStrategy {
             self.prices.remove(0);
         }
 
-        // 1. Update State Machine
-        {
-            let mut write_guard = state.write().await;
-            let current = write_guard.state_machine.get_state();
-            if current == SystemState::Booting {
-                write_guard.state_machine.transition_to(SystemState::Accumulating);
-            } else if current == SystemState::Accumulating && write_guard.state_machine.is_stable() {
-                write_guard.state_machine.transition_to(SystemState::Trading);
-            }
-        }
-
-        // 2. Opportunity Generation (Mean Reversion Logic)
+        // 1. Opportunity Generation (Mean Reversion Logic)
         let mut opportunities = Vec::new();
         let current_state = state.read().await.state_machine.get_state();
         
         if current_state == SystemState::Trading && self.prices.len() >= 10 {
-            let mean: f64 = self.prices.iter().sum::<f64>() / self.prices.len() as f64;
-            let std_dev: f64 = (self.prices.iter().map(|p| (p - mean).powi(2)).sum::<f64>() / self.prices.len() as f64).sqrt();
+            let mean = indicators::sma(&self.prices, self.prices.len()).unwrap_or(price);
+            let std_dev = indicators::stddev(&self.prices, self.prices.len()).unwrap_or(0.0);
             
             // Buy if price is significantly below mean
             if price < mean - 1.0 * std_dev {
                 opportunities.push(Opportunity {
-                    id: format!("mr_buy_{}", self.trade_count),
-                    signal: Signal::Buy { symbol: symbol.clone(), price: Some(price), quantity: 0.001 },
+                    id: "mr_buy".to_string(),
+                    signal: Signal::Buy { symbol: symbol.clone(), price: Some(price), quantity: 0.001, order_type: OrderType::Market },
                     score: 0.8,
                     risk_score: 0.3,
                     reason: format!("Price is {:.2} below mean", mean - price),
@@ -120,8 +109,8 @@ impl MeanReversionStrategy {
             // Sell if price is significantly above mean
             if price > mean + 1.0 * std_dev {
                 opportunities.push(Opportunity {
-                    id: format!("mr_sell_{}", self.trade_count),
-                    signal: Signal::Sell { symbol: symbol.clone(), price: Some(price), quantity: 0.001 },
+                    id: "mr_sell".to_string(),
+                    signal: Signal::Sell { symbol: symbol.clone(), price: Some(price), quantity: 0.001, order_type: OrderType::Market },
                     score: 0.8,
                     risk_score: 0.3,
                     reason: format!("Price is {:.2} above mean", price - mean),
@@ -130,7 +119,7 @@ impl MeanReversionStrategy {
             }
         }
 
-        // 3. Record history
+        // 2. Record history
         {
             let mut write_guard = state.write().await;
             let action = opportunities.first().map(|o| match &o.signal {
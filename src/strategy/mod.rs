@@ -1,10 +1,17 @@
 use async_trait::async_trait;
 use binance::model::{TradeEvent, OrderBook, AggrTradesEvent};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use crate::web::SharedState;
+use crate::market_data::Candle;
 
 pub mod factory;
+pub mod bracket;
+pub mod circuit_breaker;
+pub mod debounce;
+pub mod indicators;
 pub mod logger;
+pub mod orderbook;
 pub mod mean_reversion;
 pub mod momentum_breakout;
 pub mod adaptive_mean_reversion;
@@ -31,6 +38,8 @@ pub mod fibonacci_reversion;
 pub mod ichimoku_cloud;
 pub mod heikin_ashi;
 pub mod buy_hold;
+pub mod ensemble;
+pub mod probabilistic_state;
 
 pub use logger::PaperTrader;
 pub use mean_reversion::MeanReversionStrategy;
@@ -58,20 +67,55 @@ pub use fibonacci_reversion::FibonacciReversion;
 pub use ichimoku_cloud::IchimokuCloud;
 pub use heikin_ashi::HeikinAshiTrend;
 pub use buy_hold::BuyAndHold;
+pub use ensemble::EnsembleStrategy;
+pub use probabilistic_state::ProbabilisticStateStrategy;
 pub use risk::RiskManager;
+pub use circuit_breaker::{CircuitBreaker, CircuitBreakerStatus, BreakerAction};
 pub use factory::StrategyFactory;
+pub use orderbook::OrderBookFeatures;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub enum Signal {
     Buy {
         symbol: String,
         price: Option<f64>,
         quantity: f64,
+        #[serde(default)]
+        order_type: OrderType,
     },
     Sell {
         symbol: String,
         price: Option<f64>,
         quantity: f64,
+        #[serde(default)]
+        order_type: OrderType,
+    },
+    /// Market sell of whatever is actually held (the base asset balance on spot, the long
+    /// position on futures). Exits use it so they never ask for more or less than the account holds.
+    SellAll {
+        symbol: String,
+        price: Option<f64>,
+    },
+    LimitBuy {
+        symbol: String,
+        price: f64,
+        quantity: f64,
+        time_in_force: TimeInForce,
+    },
+    LimitSell {
+        symbol: String,
+        price: f64,
+        quantity: f64,
+        time_in_force: TimeInForce,
+    },
+    /// Rests a limit order at `limit_price` once the market touches `stop_price`
+    /// (sells when it falls to the stop, buys when it rises to it)
+    StopLimit {
+        symbol: String,
+        stop_price: f64,
+        limit_price: f64,
+        quantity: f64,
+        side: Side,
     },
     Cancel {
         symbol: String,
@@ -79,9 +123,78 @@ pub enum Signal {
     },
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl Signal {
+    pub fn symbol(&self) -> &str {
+        match self {
+            Signal::Buy { symbol, .. }
+            | Signal::Sell { symbol, .. }
+            | Signal::SellAll { symbol, .. }
+            | Signal::LimitBuy { symbol, .. }
+            | Signal::LimitSell { symbol, .. }
+            | Signal::StopLimit { symbol, .. }
+            | Signal::Cancel { symbol, .. } => symbol,
+        }
+    }
+
+    /// Reference price for market orders, limit price for limit orders
+    pub fn price(&self) -> Option<f64> {
+        match self {
+            Signal::Buy { price, .. } | Signal::Sell { price, .. } | Signal::SellAll { price, .. } => *price,
+            Signal::LimitBuy { price, .. } | Signal::LimitSell { price, .. } => Some(*price),
+            Signal::StopLimit { limit_price, .. } => Some(*limit_price),
+            Signal::Cancel { .. } => None,
+        }
+    }
+
+    /// Overrides the order quantity (no-op for SellAll and Cancel)
+    pub fn set_quantity(&mut self, new_quantity: f64) {
+        match self {
+            Signal::Buy { quantity, .. }
+            | Signal::Sell { quantity, .. }
+            | Signal::LimitBuy { quantity, .. }
+            | Signal::LimitSell { quantity, .. }
+            | Signal::StopLimit { quantity, .. } => *quantity = new_quantity,
+            Signal::SellAll { .. } | Signal::Cancel { .. } => {}
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum Side {
+    Buy,
+    Sell,
+}
+
+impl Side {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Side::Buy => "Buy",
+            Side::Sell => "Sell",
+        }
+    }
+}
+
+/// How a Buy/Sell signal reaches the market. Limit and PostOnly rest at the signal's price.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default, JsonSchema)]
+pub enum OrderType {
+    #[default]
+    Market,
+    Limit,
+    PostOnly, // Limit that is rejected instead of taking liquidity
+}
+
+/// How long a resting limit order stays on the book
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default, JsonSchema)]
+pub enum TimeInForce {
+    #[default]
+    GTC, // Good till cancelled
+    IOC, // Immediate or cancel
+    FOK, // Fill or kill
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Opportunity {
-    pub id: String,
+    pub id: String,         // Strategies set a label; the runner appends a per-run sequence number
     pub signal: Signal,
     pub score: f64,         // 0.0 to 1.0
     pub risk_score: f64,    // 0.0 to 1.0
@@ -89,12 +202,47 @@ pub struct Opportunity {
     pub timestamp: u64,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// What became of an order a strategy's signal sent, fed back through `on_order_event`
+#[derive(Debug, Clone)]
+pub enum OrderEvent {
+    /// The signal now rests on the book as `order_id`, for `quantity` after lot-size truncation
+    Placed { order_id: u64, quantity: f64, signal: Signal },
+    /// Some or all of a resting order filled
+    Filled { order_id: u64, price: f64, quantity: f64 },
+    /// A cancel removed the order; whatever had not filled never will
+    Cancelled { order_id: u64 },
+}
+
+impl OrderEvent {
+    /// The event a resting or cancelled order's execution reports, if any
+    pub fn from_execution(signal: &Signal, execution: &crate::execution::Execution) -> Option<Self> {
+        if let Some(placed) = execution.placed {
+            return Some(OrderEvent::Placed { order_id: placed.order_id, quantity: placed.quantity, signal: signal.clone() });
+        }
+        match signal {
+            Signal::Cancel { order_id, .. } if execution.cancelled == Some(true) => Some(OrderEvent::Cancelled { order_id: *order_id }),
+            _ => None,
+        }
+    }
+}
+
+impl From<&crate::execution::Fill> for OrderEvent {
+    fn from(fill: &crate::execution::Fill) -> Self {
+        OrderEvent::Filled { order_id: fill.order_id, price: fill.price, quantity: fill.quantity }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct RiskReport {
     pub total_risk: f64,
     pub leverage_risk: f64,
     pub drawdown_warning: bool,
     pub recommended_max_size: f64,
+    pub open_positions: usize,
+    pub max_open_positions: usize,
+    pub max_notional_usd: Option<f64>,
+    pub notional_capped: usize, // Entries scaled down to the cap in the last batch
+    pub allocation_capped: usize, // Entries scaled down or blocked by the strategy's allocation in the last batch
 }
 
 #[async_trait]
@@ -104,4 +252,16 @@ pub trait TradingStrategy: Send + Sync {
     async fn process_trade(&mut self, trade: TradeEvent, state: SharedState) -> Vec<Opportunity>;
     async fn process_aggr_trade(&mut self, trade: AggrTradesEvent, state: SharedState) -> Vec<Opportunity>;
     async fn process_orderbook(&mut self, orderbook: OrderBook, state: SharedState) -> Vec<Opportunity>;
+    /// Called with each closed 1-minute candle. Strategies that need real highs/lows override this.
+    async fn process_candle(&mut self, _candle: Candle, _state: SharedState) -> Vec<Opportunity> {
+        Vec::new()
+    }
+    /// Takes over a position opened by the previous strategy at event time `ts` (ms) so this
+    /// one manages its exit. Returns false when the strategy can't (the default).
+    fn adopt_position(&mut self, _position: &crate::execution::PositionInfo, _ts: u64) -> bool {
+        false
+    }
+    /// Hears how the limit orders and cancels this strategy sent fared. Fills of orders it
+    /// doesn't know come through too and should be ignored.
+    fn on_order_event(&mut self, _event: &OrderEvent) {}
 }
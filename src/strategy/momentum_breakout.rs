@@ -1,8 +1,11 @@
-use super::{Signal, TradingStrategy, Opportunity};
+use super::{Signal, OrderType, TradingStrategy, Opportunity, RiskManager, indicators};
+use super::orderbook::{OrderBookFeatures, DEFAULT_BOOK_LEVELS};
+use super::bracket::HoldTimer;
 use crate::web::SharedState;
 use crate::state_machine::SystemState;
 use async_trait::async_trait;
 use binance::model::{TradeEvent, OrderBook, AggrTradesEvent};
+use crate::market_data::Candle;
 use std::collections::VecDeque;
 use std::time::Instant;
 
@@ -14,7 +17,31 @@ pub struct MomentumBreakout {
     window_size: usize,
     last_signal_time: u64,
     signal_cooldown_ms: u64,
-    atr: f64,
+    book: Option<OrderBookFeatures>,
+    // 1-minute candles for the ATR behind sizing and exits
+    candles: VecDeque<Candle>,
+    atr: Option<f64>,
+    position: Option<OpenPosition>,
+    hold: HoldTimer,
+    risk_per_trade_usd: f64,
+    target_size: f64,
+}
+
+/// Exit levels are set from ATR at entry: stop 2 ATR below, target 3 ATR above
+const STOP_ATR_MULT: f64 = 2.0;
+const TARGET_ATR_MULT: f64 = 3.0;
+const ATR_PERIOD: usize = 14;
+/// Candles kept for ATR; Wilder smoothing settles over a few periods
+const ATR_HISTORY: usize = ATR_PERIOD * 4;
+/// Entry size bounds and the size used before there is an ATR
+const MIN_SIZE: f64 = 0.0001;
+const MAX_SIZE: f64 = 0.01;
+const FALLBACK_SIZE: f64 = 0.001;
+
+struct OpenPosition {
+    entry_price: f64,
+    stop_price: f64,
+    target_price: f64,
 }
 
 impl MomentumBreakout {
@@ -25,27 +52,21 @@ impl MomentumBreakout {
             window_size: 20,
             last_signal_time: 0,
             signal_cooldown_ms: 60000, // 1 minute cooldown
-            atr: 0.0,
+            book: None,
+            candles: VecDeque::with_capacity(ATR_HISTORY),
+            atr: None,
+            position: None,
+            hold: HoldTimer::from_env(),
+            risk_per_trade_usd: RiskManager::risk_per_trade_from_env(),
+            target_size: FALLBACK_SIZE,
         }
     }
 
-    /// Calculate Average True Range for risk management
-    fn calculate_atr(&mut self) -> f64 {
-        if self.price_history.len() < 2 {
-            return 0.0;
-        }
-
-        let mut ranges = Vec::new();
-        for i in 1..self.price_history.len() {
-            let high_low = (self.price_history[i] - self.price_history[i-1]).abs();
-            ranges.push(high_low);
-        }
-
-        if ranges.is_empty() {
-            return 0.0;
-        }
-
-        ranges.iter().sum::<f64>() / ranges.len() as f64
+    /// Entry size where a 1-ATR adverse move loses RISK_PER_TRADE_USD
+    fn volatility_target_size(&self) -> f64 {
+        self.atr
+            .and_then(|atr| RiskManager::volatility_target(self.risk_per_trade_usd, atr))
+            .map_or(FALLBACK_SIZE, |q| q.clamp(MIN_SIZE, MAX_SIZE))
     }
 
     /// Calculate momentum score based on price velocity
@@ -87,11 +108,25 @@ impl TradingStrategy for MomentumBreakout {
     fn get_features(&self) -> Vec<(String, String)> {
         vec![
             ("Momentum".to_string(), format!("{:.2}%", self.calculate_momentum())),
-            ("ATR".to_string(), format!("{:.2}", self.atr)),
+            ("ATR".to_string(), self.atr.map_or("-".to_string(), |atr| format!("{:.2}", atr))),
             ("Vol Surge".to_string(), format!("{:.2}x", self.calculate_volume_surge())),
+            ("Target Size".to_string(), format!("{:.4}", self.target_size)),
+            ("Book Imbalance".to_string(), self.book.map_or("-".to_string(), |b| format!("{:.2}", b.imbalance))),
+            ("Stop".to_string(), self.position.as_ref().map_or("N/A".to_string(), |p| format!("{:.2}", p.stop_price))),
+            ("Target".to_string(), self.position.as_ref().map_or("N/A".to_string(), |p| format!("{:.2}", p.target_price))),
+            self.hold.feature(),
         ]
     }
 
+    async fn process_candle(&mut self, candle: Candle, _state: SharedState) -> Vec<Opportunity> {
+        self.candles.push_back(candle);
+        if self.candles.len() > ATR_HISTORY {
+            self.candles.pop_front();
+        }
+        self.atr = indicators::atr(self.candles.make_contiguous(), ATR_PERIOD);
+        Vec::new()
+    }
+
     async fn process_trade(&mut self, trade: TradeEvent, state: SharedState) -> Vec<Opportunity> {
         let price = trade.price.parse::<f64>().unwrap_or(0.0);
         let qty = trade.qty.parse::<f64>().unwrap_or(0.0);
@@ -105,13 +140,16 @@ impl TradingStrategy for MomentumBreakout {
     }
 
     async fn process_orderbook(&mut self, orderbook: OrderBook, state: SharedState) -> Vec<Opportunity> {
-        if orderbook.bids.is_empty() || orderbook.asks.is_empty() {
+        let features = OrderBookFeatures::from(&orderbook, DEFAULT_BOOK_LEVELS);
+        if !features.is_two_sided() {
+            self.book = None;
             return Vec::new();
         }
+        self.book = Some(features);
 
-        let mid_price = (orderbook.bids[0].price + orderbook.asks[0].price) / 2.0;
+        let mid_price = features.mid;
         let volume = (orderbook.bids[0].qty + orderbook.asks[0].qty) / 2.0;
-        let spread = orderbook.asks[0].price - orderbook.bids[0].price;
+        let spread = features.spread;
 
         {
             let mut write_guard = state.write().await;
@@ -149,32 +187,75 @@ impl MomentumBreakout {
         }
 
         // Calculate metrics
-        self.atr = self.calculate_atr();
+        self.target_size = self.volatility_target_size();
         let momentum = self.calculate_momentum();
         let volume_surge = self.calculate_volume_surge();
 
         let mut opportunities = Vec::new();
-        let current_state = state.read().await.state_machine.get_state();
+        let (current_state, cooldown_ms) = {
+            let read_guard = state.read().await;
+            (read_guard.state_machine.get_state(), read_guard.signal_cooldown_ms(self.name(), self.signal_cooldown_ms))
+        };
+
+        // ATR stop / target and max-hold exits run every tick, independent of the entry cooldown
+        if current_state == SystemState::Trading {
+            let timed_out = self.hold.update(ts);
+            if let Some(position) = &self.position {
+                let (hit_stop, hit_target) = (price <= position.stop_price, price >= position.target_price);
+                if hit_stop || hit_target || timed_out {
+                    let pnl_pct = (price - position.entry_price) / position.entry_price * 100.0;
+                    let (exit_kind, exit_level) = if hit_stop {
+                        ("ATR stop", position.stop_price)
+                    } else if hit_target {
+                        ("ATR target", position.target_price)
+                    } else {
+                        ("Max hold", price)
+                    };
+                    opportunities.push(Opportunity {
+                        id: "momentum_exit".to_string(),
+                        signal: Signal::SellAll { symbol: symbol.clone(), price: Some(price) },
+                        score: 0.9,
+                        risk_score: 0.2,
+                        reason: format!("{} hit at {:.2} (PnL {:.2}%)", exit_kind, exit_level, pnl_pct),
+                        timestamp: ts,
+                    });
+                    self.position = None;
+                    self.hold.stop();
+                    self.last_signal_time = ts;
+                }
+            }
+        }
 
         // Generate signals only in Trading state with cooldown
         if current_state == SystemState::Trading && 
-           ts - self.last_signal_time > self.signal_cooldown_ms &&
+           opportunities.is_empty() &&
+           ts - self.last_signal_time > cooldown_ms &&
            self.price_history.len() >= self.window_size {
 
-            // Bullish breakout: Strong positive momentum + volume surge
-            if momentum > 0.2 && volume_surge > 1.1 {
-                let stop_loss_distance = self.atr * 2.0;
-                let position_size = 0.001 * (1.0 / (self.atr.max(0.0001) / price)); // ATR-based sizing
+            // Bullish breakout: Strong positive momentum + volume surge, not fighting an ask-heavy book.
+            // No entries until enough candles have closed to place ATR exits.
+            let book_supports_entry = self.book.is_none_or(|b| b.imbalance >= 0.0);
+            if let Some(exit_atr) = self.atr
+                && self.position.is_none() && momentum > 0.2 && volume_surge > 1.1 && book_supports_entry {
+                let stop_loss_distance = exit_atr * STOP_ATR_MULT;
+                let quantity = self.target_size;
+                self.position = Some(OpenPosition {
+                    entry_price: price,
+                    stop_price: price - stop_loss_distance,
+                    target_price: price + exit_atr * TARGET_ATR_MULT,
+                });
+                self.hold.start(ts);
 
                 opportunities.push(Opportunity {
-                    id: format!("momentum_buy_{}", ts),
+                    id: "momentum_buy".to_string(),
                     signal: Signal::Buy {
                         symbol: symbol.clone(),
                         price: Some(price * 1.0001), // Slight premium for market entry
-                        quantity: position_size.min(0.01).max(0.0001),
+                        quantity,
+                        order_type: OrderType::Market,
                     },
                     score: (momentum / 2.0 + volume_surge / 3.0).min(0.95),
-                    risk_score: (self.atr / price * 100.0).min(1.0),
+                    risk_score: (exit_atr / price * 100.0).min(1.0),
                     reason: format!("Momentum breakout: {:.2}% velocity, {:.1}x volume", momentum, volume_surge),
                     timestamp: ts,
                 });
@@ -186,13 +267,16 @@ impl MomentumBreakout {
             if momentum < -0.3 && self.price_history.len() > 10 {
                 let recent_high = self.price_history.iter().rev().take(10).fold(0.0_f64, |a: f64, &b| a.max(b));
                 if price < recent_high * 0.998 {
+                    // A reversal also closes the tracked position, whatever its filled size
+                    let signal = if self.position.take().is_some() {
+                        Signal::SellAll { symbol: symbol.clone(), price: Some(price * 0.9999) }
+                    } else {
+                        Signal::Sell { symbol: symbol.clone(), price: Some(price * 0.9999), quantity: 0.001, order_type: OrderType::Market }
+                    };
+                    self.hold.stop();
                     opportunities.push(Opportunity {
-                        id: format!("momentum_sell_{}", ts),
-                        signal: Signal::Sell {
-                            symbol: symbol.clone(),
-                            price: Some(price * 0.9999),
-                            quantity: 0.001,
-                        },
+                        id: "momentum_sell".to_string(),
+                        signal,
                         score: (momentum.abs() / 2.0).min(0.75),
                         risk_score: 0.3,
                         reason: format!("Momentum reversal detected: {:.2}% decline", momentum),
@@ -209,13 +293,13 @@ impl MomentumBreakout {
             let mut write_guard = state.write().await;
             let action = opportunities.first().map(|o| match &o.signal {
                 Signal::Buy { .. } => "Buy".to_string(),
-                Signal::Sell { .. } => "Sell".to_string(),
+                Signal::Sell { .. } | Signal::SellAll { .. } => "Sell".to_string(),
                 _ => "Cancel".to_string(),
             });
 
             let strat_lat = write_guard.metrics.get_strategy_stats().p50;
             let exec_lat = write_guard.metrics.get_execution_stats().p50;
-            write_guard.push_data_point_at(price, qty, action, strat_lat, exec_lat, self.atr, ts);
+            write_guard.push_data_point_at(price, qty, action, strat_lat, exec_lat, self.atr.unwrap_or(0.0), ts);
         }
 
         state.read().await.metrics.record_strategy_latency(start.elapsed());
@@ -0,0 +1,41 @@
+use binance::model::OrderBook;
+
+/// Number of book levels strategies summarize by default
+pub const DEFAULT_BOOK_LEVELS: usize = 10;
+
+/// Summary of the top of an order book
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct OrderBookFeatures {
+    /// Mid price, 0.0 unless both sides are present
+    pub mid: f64,
+    /// Best ask minus best bid, 0.0 unless both sides are present
+    pub spread: f64,
+    /// (bid depth - ask depth) / total depth in [-1, 1]. Positive means more resting buy interest.
+    pub imbalance: f64,
+    /// Quantity resting on the top `levels` bids
+    pub depth_bid: f64,
+    /// Quantity resting on the top `levels` asks
+    pub depth_ask: f64,
+}
+
+impl OrderBookFeatures {
+    /// Summarizes the top `levels` of each side. Empty books give all zeros; one-sided books give
+    /// zero mid/spread and an imbalance of +1 or -1.
+    pub fn from(book: &OrderBook, levels: usize) -> Self {
+        let depth_bid: f64 = book.bids.iter().take(levels).map(|b| b.qty).sum();
+        let depth_ask: f64 = book.asks.iter().take(levels).map(|a| a.qty).sum();
+        let total_depth = depth_bid + depth_ask;
+        let imbalance = if total_depth > 0.0 { (depth_bid - depth_ask) / total_depth } else { 0.0 };
+
+        let (mid, spread) = match (book.bids.first(), book.asks.first()) {
+            (Some(bid), Some(ask)) => ((bid.price + ask.price) / 2.0, ask.price - bid.price),
+            _ => (0.0, 0.0),
+        };
+
+        Self { mid, spread, imbalance, depth_bid, depth_ask }
+    }
+
+    pub fn is_two_sided(&self) -> bool {
+        self.mid > 0.0
+    }
+}
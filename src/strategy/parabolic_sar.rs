@@ -1,9 +1,9 @@
-use super::{Signal, TradingStrategy, Opportunity};
+use super::{Signal, OrderType, TradingStrategy, Opportunity};
 use crate::web::SharedState;
 use crate::state_machine::SystemState;
 use async_trait::async_trait;
 use binance::model::{TradeEvent, OrderBook, AggrTradesEvent};
-use std::time::Instant;
+use crate::market_data::{Candle, CANDLE_INTERVAL_SECS};
 
 /// Parabolic SAR Strategy
 pub struct ParabolicSAR {
@@ -88,46 +88,39 @@ impl TradingStrategy for ParabolicSAR {
         self.prices.push(price);
         if self.prices.len() > 5 { self.prices.remove(0); }
         
-        // Simplified: use current price as high/low for update
-        self.update_sar(price, price);
-        
-        let mut opps = Vec::new();
-        let current_state = state.read().await.state_machine.get_state();
-        
-        if current_state == SystemState::Trading {
-            if self.is_long && price > self.sar {
-                 // SAR signal bullish
-            } else if !self.is_long && price < self.sar {
-                 // SAR signal bearish
-            }
-            
-            // Generate a trade on trend flip
-            if self.is_long && price > self.sar * 1.001 {
-                opps.push(Opportunity {
-                    id: format!("sar_buy_{}", trade.event_time),
-                    signal: Signal::Buy { symbol: trade.symbol.clone(), price: Some(price), quantity: 0.001 },
-                    score: 0.7,
-                    risk_score: 0.4,
-                    reason: "SAR Bullish flip".to_string(),
-                    timestamp: trade.event_time,
-                });
-            }
-        }
-        
-        { let mut w = state.write().await; w.push_data_point_at(price, qty, opps.first().map(|o| match &o.signal { Signal::Buy{..} => "Buy", Signal::Sell{..} => "Sell", _ => "Cancel" }.to_string()), 0, 0, 0.0, trade.event_time); }
-        opps
-    }
-
-    async fn process_aggr_trade(&mut self, trade: AggrTradesEvent, _: SharedState) -> Vec<Opportunity> {
-        let price = trade.price.parse::<f64>().unwrap_or(0.0);
-        self.update_sar(price, price);
+        // SAR is updated from closed candles in process_candle
+        { let mut w = state.write().await; w.push_data_point_at(price, qty, None, 0, 0, 0.0, trade.event_time); }
         Vec::new()
     }
 
+    async fn process_aggr_trade(&mut self, _: AggrTradesEvent, _: SharedState) -> Vec<Opportunity> { Vec::new() }
+
     async fn process_orderbook(&mut self, ob: OrderBook, _: SharedState) -> Vec<Opportunity> {
         if !ob.bids.is_empty() && !ob.asks.is_empty() {
             self.last_spread = ob.asks[0].price - ob.bids[0].price;
         }
         Vec::new()
     }
+
+    async fn process_candle(&mut self, candle: Candle, state: SharedState) -> Vec<Opportunity> {
+        let was_long = self.is_long;
+        self.update_sar(candle.high, candle.low);
+        let event_time = candle.close_time_ms(CANDLE_INTERVAL_SECS);
+        
+        let mut opps = Vec::new();
+        let current_state = state.read().await.state_machine.get_state();
+        
+        // Generate a trade on trend flip
+        if current_state == SystemState::Trading && !was_long && self.is_long {
+            opps.push(Opportunity {
+                id: "sar_buy".to_string(),
+                signal: Signal::Buy { symbol: candle.symbol.clone(), price: Some(candle.close), quantity: 0.001, order_type: OrderType::Market },
+                score: 0.7,
+                risk_score: 0.4,
+                reason: "SAR Bullish flip".to_string(),
+                timestamp: event_time,
+            });
+        }
+        opps
+    }
 }
@@ -0,0 +1,163 @@
+use super::{Signal, OrderType, TradingStrategy, Opportunity};
+use crate::web::SharedState;
+use crate::state_machine::{StateMachine, SystemState};
+use async_trait::async_trait;
+use binance::model::{TradeEvent, OrderBook, AggrTradesEvent};
+use std::collections::HashMap;
+use std::time::Instant;
+
+/// Trades on the state machine's inferred transition probabilities.
+///
+/// Order book spread/imbalance and trade-to-trade volatility are scored the same way as
+/// PaperTrader and turned into the current state's row with `StateMachine::infer_row`. The
+/// shared machine is only read, so shadow copies never overwrite the live matrix. It buys when the probability of moving into (or staying in)
+/// Trading reaches `entry_threshold` with bids outweighing asks, and sells everything once
+/// that probability falls below `exit_threshold` or the machine leaves Trading.
+pub struct ProbabilisticStateStrategy {
+    entry_threshold: f64,
+    exit_threshold: f64,
+    spread_score: f64,
+    imbalance_score: f64,
+    last_price: HashMap<String, f64>, // Per symbol, for trade-to-trade volatility
+    p_trading: f64,
+    in_position: bool,
+    entry_price: f64,
+    last_signal_time: u64,
+}
+
+impl ProbabilisticStateStrategy {
+    pub fn new() -> Self {
+        Self::with_params(0.8, 0.5)
+    }
+
+    pub fn with_params(entry_threshold: f64, exit_threshold: f64) -> Self {
+        Self {
+            entry_threshold,
+            exit_threshold: exit_threshold.min(entry_threshold),
+            spread_score: 0.0,
+            imbalance_score: 0.0,
+            last_price: HashMap::new(),
+            p_trading: 0.0,
+            in_position: false,
+            entry_price: 0.0,
+            last_signal_time: 0,
+        }
+    }
+}
+
+#[async_trait]
+impl TradingStrategy for ProbabilisticStateStrategy {
+    fn name(&self) -> &str { "ProbabilisticState" }
+
+    fn adopt_position(&mut self, position: &crate::execution::PositionInfo, _ts: u64) -> bool {
+        if position.side == "Short" || position.entry_price <= 0.0 || self.in_position {
+            return false;
+        }
+        self.in_position = true;
+        self.entry_price = position.entry_price;
+        true
+    }
+
+    fn get_features(&self) -> Vec<(String, String)> {
+        vec![
+            ("P(Trading)".to_string(), format!("{:.3}", self.p_trading)),
+            ("Entry Threshold".to_string(), format!("{:.2}", self.entry_threshold)),
+            ("Imbalance".to_string(), format!("{:.3}", self.imbalance_score)),
+            ("In Position".to_string(), self.in_position.to_string()),
+        ]
+    }
+
+    async fn process_trade(&mut self, trade: TradeEvent, state: SharedState) -> Vec<Opportunity> {
+        let price = trade.price.parse::<f64>().unwrap_or(0.0);
+        let qty = trade.qty.parse::<f64>().unwrap_or(0.0);
+        self.handle_trade(trade.symbol, price, qty, trade.event_time, state).await
+    }
+
+    async fn process_aggr_trade(&mut self, trade: AggrTradesEvent, state: SharedState) -> Vec<Opportunity> {
+        let price = trade.price.parse::<f64>().unwrap_or(0.0);
+        let qty = trade.qty.parse::<f64>().unwrap_or(0.0);
+        self.handle_trade(trade.symbol, price, qty, trade.event_time, state).await
+    }
+
+    async fn process_orderbook(&mut self, ob: OrderBook, _state: SharedState) -> Vec<Opportunity> {
+        if ob.bids.is_empty() || ob.asks.is_empty() {
+            return Vec::new();
+        }
+        let best_bid = ob.bids[0].price;
+        let best_ask = ob.asks[0].price;
+        let mid_price = (best_bid + best_ask) / 2.0;
+        let top_qty = ob.bids[0].qty + ob.asks[0].qty;
+        if mid_price > 0.0 {
+            self.spread_score = (best_ask - best_bid) / mid_price * 1000.0;
+        }
+        if top_qty > 0.0 {
+            self.imbalance_score = (ob.bids[0].qty - ob.asks[0].qty) / top_qty;
+        }
+        Vec::new()
+    }
+}
+
+impl ProbabilisticStateStrategy {
+    async fn handle_trade(&mut self, symbol: String, price: f64, qty: f64, ts: u64, state: SharedState) -> Vec<Opportunity> {
+        let start = Instant::now();
+        if price <= 0.0 {
+            return Vec::new();
+        }
+        let volatility_score = self.last_price.insert(symbol.clone(), price).map_or(0.0, |lp| (price - lp).abs() / lp * 1000.0);
+
+        let (current_state, cooldown_ms) = {
+            let read_guard = state.read().await;
+            (read_guard.state_machine.get_state(), read_guard.signal_cooldown_ms(self.name(), 30_000))
+        };
+        // Row of the current state, column of the state we want to be in
+        if let Some(row) = StateMachine::infer_row(current_state, self.spread_score, self.imbalance_score, volatility_score) {
+            self.p_trading = row[SystemState::Trading.to_index()];
+        }
+
+        let mut opps = Vec::new();
+        if self.in_position {
+            let left_trading = current_state != SystemState::Trading;
+            if left_trading || self.p_trading < self.exit_threshold {
+                let pnl_pct = (price - self.entry_price) / self.entry_price * 100.0;
+                self.in_position = false;
+                self.last_signal_time = ts;
+                opps.push(Opportunity {
+                    id: "prob_state_exit".to_string(),
+                    signal: Signal::SellAll { symbol: symbol.clone(), price: Some(price) },
+                    score: 0.7,
+                    risk_score: 0.3,
+                    reason: if left_trading {
+                        format!("State left Trading ({:?}), PnL={:.2}%", current_state, pnl_pct)
+                    } else {
+                        format!("P(Trading) {:.2} < {:.2}, PnL={:.2}%", self.p_trading, self.exit_threshold, pnl_pct)
+                    },
+                    timestamp: ts,
+                });
+            }
+        } else if current_state == SystemState::Trading
+            && self.p_trading >= self.entry_threshold
+            && self.imbalance_score > 0.0
+            && ts.saturating_sub(self.last_signal_time) > cooldown_ms
+        {
+            self.in_position = true;
+            self.entry_price = price;
+            self.last_signal_time = ts;
+            opps.push(Opportunity {
+                id: "prob_state_buy".to_string(),
+                signal: Signal::Buy { symbol: symbol.clone(), price: Some(price), quantity: 0.001, order_type: OrderType::Market },
+                score: self.p_trading,
+                risk_score: 1.0 - self.p_trading,
+                reason: format!("P(Trading) {:.2} >= {:.2}, bid imbalance {:.2}", self.p_trading, self.entry_threshold, self.imbalance_score),
+                timestamp: ts,
+            });
+        }
+
+        {
+            let mut w = state.write().await;
+            let action = opps.first().map(|o| match &o.signal { Signal::Buy { .. } => "Buy", _ => "Sell" }.to_string());
+            w.push_data_point_at(price, qty, action, 0, 0, 0.0, ts);
+        }
+        state.read().await.metrics.record_strategy_latency(start.elapsed());
+        opps
+    }
+}
@@ -1,12 +1,172 @@
-use super::{Opportunity, RiskReport};
+use super::{Opportunity, RiskReport, Signal};
 use crate::web::AppState;
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Closed trades required before Kelly sizing replaces the strategy's own quantity
+const KELLY_MIN_TRADES: u64 = 10;
+/// Default USD lost on a 1-volatility adverse move for volatility-targeted entries
+pub const DEFAULT_RISK_PER_TRADE_USD: f64 = 1.0;
 
 pub struct RiskManager;
 
+/// How much of its allocation a strategy has deployed
+#[derive(Serialize, Clone, Debug, JsonSchema)]
+pub struct AllocationStatus {
+    pub strategy: String,
+    /// Fraction of equity the strategy may hold open
+    pub max_fraction: f64,
+    pub limit_usd: f64,
+    /// Open notional at the last traded prices
+    pub used_usd: f64,
+    pub available_usd: f64,
+}
+
 impl RiskManager {
+    /// Full Kelly fraction f* = p - (1 - p) / b, where b = avg_win / avg_loss.
+    /// Never negative: no edge means no position.
+    pub fn kelly_fraction(win_rate: f64, avg_win: f64, avg_loss: f64) -> f64 {
+        if avg_win <= 0.0 || avg_loss <= 0.0 {
+            return 0.0;
+        }
+        let payoff = avg_win / avg_loss;
+        (win_rate - (1.0 - win_rate) / payoff).clamp(0.0, 1.0)
+    }
+
+    /// Fraction of full Kelly to actually bet (KELLY_SCALE, half-Kelly by default)
+    pub fn kelly_scale() -> f64 {
+        std::env::var("KELLY_SCALE")
+            .ok()
+            .and_then(|v| v.parse::<f64>().ok())
+            .unwrap_or(0.5)
+            .clamp(0.0, 1.0)
+    }
+
+    /// Scaled Kelly fraction from the closed-trade history in AppState,
+    /// or None while there is too little history to trust it
+    pub fn kelly_from_state(state: &AppState) -> Option<f64> {
+        let closed = state.win_trades + state.loss_trades;
+        if closed < KELLY_MIN_TRADES || state.win_trades == 0 || state.loss_trades == 0 {
+            return None;
+        }
+        let win_rate = state.win_trades as f64 / closed as f64;
+        let avg_win = state.gross_profit / state.win_trades as f64;
+        let avg_loss = state.gross_loss / state.loss_trades as f64;
+        Some(Self::kelly_fraction(win_rate, avg_win, avg_loss) * Self::kelly_scale())
+    }
+
+    /// Quantity for an entry that risks `kelly` of the account, or None for signals that aren't
+    /// sized (exits, cancels, entries without a price), which keep their own quantity
+    pub fn size_position(signal: &Signal, account_value: f64, kelly: f64) -> Option<f64> {
+        match signal {
+            Signal::Buy { price: Some(price), .. } if *price > 0.0 => Some(account_value * kelly / price),
+            Signal::LimitBuy { price, .. } if *price > 0.0 => Some(account_value * kelly / price),
+            _ => None,
+        }
+    }
+
+    /// Quantity at which an adverse move of one `volatility` (ATR or similar, in price units)
+    /// loses `risk_usd`. None while there is no usable volatility estimate.
+    pub fn volatility_target(risk_usd: f64, volatility: f64) -> Option<f64> {
+        if !volatility.is_finite() || volatility <= 0.0 || !risk_usd.is_finite() || risk_usd <= 0.0 {
+            return None;
+        }
+        Some(risk_usd / volatility)
+    }
+
+    /// Target risk per trade from RISK_PER_TRADE_USD (default 1.0)
+    pub fn risk_per_trade_from_env() -> f64 {
+        std::env::var("RISK_PER_TRADE_USD")
+            .ok()
+            .and_then(|v| v.parse::<f64>().ok())
+            .filter(|v| v.is_finite() && *v > 0.0)
+            .unwrap_or(DEFAULT_RISK_PER_TRADE_USD)
+    }
+
+    /// Position limit from MAX_OPEN_POSITIONS (default 5)
+    pub fn max_open_positions_from_env() -> usize {
+        std::env::var("MAX_OPEN_POSITIONS")
+            .ok()
+            .and_then(|v| v.parse::<usize>().ok())
+            .unwrap_or(5)
+    }
+
+    /// Per-trade notional cap from MAX_NOTIONAL_USD (unset or 0 = no cap)
+    pub fn max_notional_from_env() -> Option<f64> {
+        std::env::var("MAX_NOTIONAL_USD")
+            .ok()
+            .and_then(|v| v.parse::<f64>().ok())
+            .filter(|v| v.is_finite() && *v > 0.0)
+    }
+
+    /// Per-strategy equity fractions from STRATEGY_ALLOCATIONS, e.g. "MomentumBreakout=0.3,RSIStrategy=0.2"
+    pub fn allocations_from_env() -> HashMap<String, f64> {
+        let mut allocations = HashMap::new();
+        for entry in std::env::var("STRATEGY_ALLOCATIONS").unwrap_or_default().split(',').filter(|e| !e.trim().is_empty()) {
+            match entry.split_once('=').map(|(name, fraction)| (name.trim(), fraction.trim().parse::<f64>())) {
+                Some((name, Ok(fraction))) if !name.is_empty() && (0.0..=1.0).contains(&fraction) => {
+                    allocations.insert(name.to_string(), fraction);
+                }
+                _ => log::warn!("Ignoring STRATEGY_ALLOCATIONS entry '{}' (expected Name=fraction between 0 and 1)", entry),
+            }
+        }
+        allocations
+    }
+
+    /// Notional a strategy may still open, or None if it has no allocation
+    pub fn allocation_headroom(state: &AppState, strategy: &str) -> Option<f64> {
+        let fraction = state.strategy_allocations.get(strategy)?;
+        Some((state.total_value() * fraction - state.strategy_open_notional(strategy)).max(0.0))
+    }
+
+    pub fn allocation_status(state: &AppState) -> Vec<AllocationStatus> {
+        let account_value = state.total_value();
+        let mut status: Vec<AllocationStatus> = state.strategy_allocations.iter()
+            .map(|(strategy, fraction)| {
+                let limit_usd = account_value * fraction;
+                let used_usd = state.strategy_open_notional(strategy);
+                AllocationStatus {
+                    strategy: strategy.clone(),
+                    max_fraction: *fraction,
+                    limit_usd,
+                    used_usd,
+                    available_usd: (limit_usd - used_usd).max(0.0),
+                }
+            })
+            .collect();
+        status.sort_by(|a, b| a.strategy.cmp(&b.strategy));
+        status
+    }
+
+    /// Scales an entry down so quantity * price stays within `max_notional`.
+    /// Returns true if the quantity was reduced. Exits are never resized.
+    pub fn cap_notional(signal: &mut Signal, price: f64, max_notional: f64) -> bool {
+        let quantity = match signal {
+            Signal::Buy { quantity, .. } | Signal::LimitBuy { quantity, .. } => *quantity,
+            _ => return false,
+        };
+        if price <= 0.0 || quantity * price <= max_notional {
+            return false;
+        }
+        signal.set_quantity(max_notional / price);
+        true
+    }
+
+    /// Price used for notional checks: the signal's own price, else the last traded price
+    pub fn reference_price(signal: &Signal, state: &AppState) -> Option<f64> {
+        match signal {
+            Signal::Buy { price: Some(price), .. } => Some(*price),
+            Signal::LimitBuy { price, .. } | Signal::LimitSell { price, .. } => Some(*price),
+            Signal::StopLimit { limit_price, .. } => Some(*limit_price),
+            _ => state.price_cache.get(signal.symbol()),
+        }
+    }
+
     pub fn analyze_opportunities(
         opportunities: &[Opportunity],
-        _state: &AppState
+        strategy: &str,
+        state: &AppState
     ) -> (Vec<Opportunity>, RiskReport) {
         // 1. Calculate general portfolio risk
         let total_risk = if opportunities.len() > 5 { 0.8 } else { 0.3 };
@@ -25,11 +185,56 @@ impl RiskManager {
             }
         }
 
+        // 3. Block new entries once the position limit is reached. Exits always pass.
+        let at_position_limit = state.open_positions >= state.max_open_positions;
+        if at_position_limit {
+            for opp in processed_opps.iter_mut() {
+                if matches!(opp.signal, Signal::Buy { .. } | Signal::LimitBuy { .. }) {
+                    opp.score = 0.0;
+                    opp.risk_score = 1.0;
+                }
+            }
+        }
+
+        // 4. Scale oversized entries down to the notional cap
+        let mut notional_capped = 0;
+        if let Some(max_notional) = state.max_notional_usd {
+            for opp in processed_opps.iter_mut() {
+                let Some(price) = Self::reference_price(&opp.signal, state) else { continue };
+                if Self::cap_notional(&mut opp.signal, price, max_notional) {
+                    notional_capped += 1;
+                }
+            }
+        }
+
+        // 5. Keep entries within what is left of the strategy's allocation; block them once it is used up
+        let mut allocation_capped = 0;
+        if let Some(headroom) = Self::allocation_headroom(state, strategy) {
+            for opp in processed_opps.iter_mut() {
+                if !matches!(opp.signal, Signal::Buy { .. } | Signal::LimitBuy { .. }) {
+                    continue;
+                }
+                if headroom <= 0.0 {
+                    opp.score = 0.0;
+                    opp.risk_score = 1.0;
+                    allocation_capped += 1;
+                } else if let Some(price) = Self::reference_price(&opp.signal, state)
+                    && Self::cap_notional(&mut opp.signal, price, headroom) {
+                    allocation_capped += 1;
+                }
+            }
+        }
+
         let report = RiskReport {
             total_risk,
             leverage_risk,
             drawdown_warning,
             recommended_max_size: 0.005,
+            open_positions: state.open_positions,
+            max_open_positions: state.max_open_positions,
+            max_notional_usd: state.max_notional_usd,
+            notional_capped,
+            allocation_capped,
         };
 
         (processed_opps, report)
@@ -43,3 +248,24 @@ impl RiskManager {
             .map(|o| o.signal.clone())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategy::OrderType;
+
+    #[test]
+    fn kelly_sizes_entries_and_leaves_exits_alone() {
+        let buy = Signal::Buy { symbol: "BTCUSDT".to_string(), price: Some(100.0), quantity: 1.0, order_type: OrderType::Market };
+        assert_eq!(RiskManager::size_position(&buy, 1000.0, 0.25), Some(2.5));
+        // No edge sizes an entry to zero, which the live loop skips
+        assert_eq!(RiskManager::size_position(&buy, 1000.0, 0.0), Some(0.0));
+
+        let sell_all = Signal::SellAll { symbol: "BTCUSDT".to_string(), price: Some(100.0) };
+        assert_eq!(RiskManager::size_position(&sell_all, 1000.0, 0.0), None);
+        let cancel = Signal::Cancel { symbol: "BTCUSDT".to_string(), order_id: 7 };
+        assert_eq!(RiskManager::size_position(&cancel, 1000.0, 0.0), None);
+        let sell = Signal::Sell { symbol: "BTCUSDT".to_string(), price: Some(100.0), quantity: 1.0, order_type: OrderType::Market };
+        assert_eq!(RiskManager::size_position(&sell, 1000.0, 0.25), None);
+    }
+}
@@ -1,4 +1,4 @@
-use super::{Signal, TradingStrategy, Opportunity};
+use super::{Signal, OrderType, TradingStrategy, Opportunity, indicators};
 use crate::web::SharedState;
 use crate::state_machine::SystemState;
 use async_trait::async_trait;
@@ -11,38 +11,28 @@ pub struct RSIStrategy {
     trade_count: u64,
     last_spread: f64,
     rsi_period: usize,
+    oversold: f64,
+    overbought: f64,
 }
 
 impl RSIStrategy {
     pub fn new() -> Self {
+        Self::with_params(14, 30.0, 70.0)
+    }
+
+    pub fn with_params(rsi_period: usize, oversold: f64, overbought: f64) -> Self {
         Self {
             prices: Vec::with_capacity(100),
             trade_count: 0,
             last_spread: 0.0,
-            rsi_period: 14,
+            rsi_period: rsi_period.max(1),
+            oversold,
+            overbought,
         }
     }
     
     fn calculate_rsi(&self) -> Option<f64> {
-        if self.prices.len() < self.rsi_period + 1 {
-            return None;
-        }
-        
-        let changes: Vec<f64> = self.prices.windows(2)
-            .map(|w| w[1] - w[0])
-            .collect();
-        
-        let recent = &changes[changes.len().saturating_sub(self.rsi_period)..];
-        
-        let gains: f64 = recent.iter().filter(|&&x| x > 0.0).sum();
-        let losses: f64 = recent.iter().filter(|&&x| x < 0.0).map(|x| x.abs()).sum();
-        
-        if losses == 0.0 {
-            return Some(100.0);
-        }
-        
-        let rs = gains / losses;
-        Some(100.0 - (100.0 / (1.0 + rs)))
+        indicators::wilder_rsi(&self.prices, self.rsi_period)
     }
 }
 
@@ -83,17 +73,8 @@ impl RSIStrategy {
         let start = Instant::now();
         self.trade_count += 1;
         self.prices.push(price);
-        if self.prices.len() > 50 { self.prices.remove(0); }
-        
-        // State transitions
-        {
-            let mut guard = state.write().await;
-            if guard.state_machine.get_state() == SystemState::Booting {
-                guard.state_machine.transition_to(SystemState::Accumulating);
-            } else if guard.state_machine.get_state() == SystemState::Accumulating && guard.state_machine.is_stable() {
-                guard.state_machine.transition_to(SystemState::Trading);
-            }
-        }
+        let max_history = 50.max(self.rsi_period * 3);
+        if self.prices.len() > max_history { self.prices.remove(0); }
         
         let mut opps = Vec::new();
         let current_state = state.read().await.state_machine.get_state();
@@ -101,10 +82,10 @@ impl RSIStrategy {
         if current_state == SystemState::Trading {
             if let Some(rsi) = self.calculate_rsi() {
                 // Oversold - Buy
-                if rsi < 30.0 {
+                if rsi < self.oversold {
                     opps.push(Opportunity {
-                        id: format!("rsi_buy_{}", self.trade_count),
-                        signal: Signal::Buy { symbol: symbol.clone(), price: Some(price), quantity: 0.001 },
+                        id: "rsi_buy".to_string(),
+                        signal: Signal::Buy { symbol: symbol.clone(), price: Some(price), quantity: 0.001, order_type: OrderType::Market },
                         score: 0.8,
                         risk_score: 0.3,
                         reason: format!("RSI={:.1} (oversold)", rsi),
@@ -112,10 +93,10 @@ impl RSIStrategy {
                     });
                 }
                 // Overbought - Sell
-                if rsi > 70.0 {
+                if rsi > self.overbought {
                     opps.push(Opportunity {
-                        id: format!("rsi_sell_{}", self.trade_count),
-                        signal: Signal::Sell { symbol: symbol.clone(), price: Some(price), quantity: 0.001 },
+                        id: "rsi_sell".to_string(),
+                        signal: Signal::Sell { symbol: symbol.clone(), price: Some(price), quantity: 0.001, order_type: OrderType::Market },
                         score: 0.8,
                         risk_score: 0.3,
                         reason: format!("RSI={:.1} (overbought)", rsi),
@@ -1,4 +1,7 @@
-use super::{Signal, TradingStrategy, Opportunity};
+use super::{Signal, OrderType, Side, TradingStrategy, Opportunity};
+use super::orderbook::{OrderBookFeatures, DEFAULT_BOOK_LEVELS};
+use super::bracket::HoldTimer;
+use super::debounce::SignalDebouncer;
 use crate::web::SharedState;
 use crate::state_machine::SystemState;
 use async_trait::async_trait;
@@ -11,8 +14,14 @@ pub struct ScalperStrategy {
     last_signal_time: u64,
     position_open: bool,
     entry_price: f64,
+    book: Option<OrderBookFeatures>,
+    hold: HoldTimer,
+    debouncer: SignalDebouncer,
 }
 
+/// Entries are skipped when the book leans this far to the ask side
+const MIN_ENTRY_IMBALANCE: f64 = -0.2;
+
 impl ScalperStrategy {
     pub fn new() -> Self {
         Self {
@@ -20,6 +29,9 @@ impl ScalperStrategy {
             last_signal_time: 0,
             position_open: false,
             entry_price: 0.0,
+            book: None,
+            hold: HoldTimer::from_env(),
+            debouncer: SignalDebouncer::from_env(),
         }
     }
 }
@@ -40,6 +52,9 @@ impl TradingStrategy for ScalperStrategy {
         vec![
             ("Trend (bps)".to_string(), format!("{:.1}", trend)),
             ("In Position".to_string(), self.position_open.to_string()),
+            ("Book Imbalance".to_string(), self.book.map_or("-".to_string(), |b| format!("{:.2}", b.imbalance))),
+            self.hold.feature(),
+            self.debouncer.feature(),
         ]
     }
 
@@ -51,7 +66,10 @@ impl TradingStrategy for ScalperStrategy {
         if self.tick_history.len() > 20 { self.tick_history.pop_front(); }
         
         let mut opps = Vec::new();
-        let current_state = state.read().await.state_machine.get_state();
+        let (current_state, cooldown_ms) = {
+            let read_guard = state.read().await;
+            (read_guard.state_machine.get_state(), read_guard.signal_cooldown_ms(self.name(), 5000))
+        };
         
         if current_state == SystemState::Trading && self.tick_history.len() >= 10 {
             let recent: Vec<f64> = self.tick_history.iter().rev().take(5).copied().collect();
@@ -61,12 +79,17 @@ impl TradingStrategy for ScalperStrategy {
             let older_avg = older.iter().sum::<f64>() / 5.0;
             let micro_trend = (recent_avg - older_avg) / older_avg * 10000.0; // basis points
             
-            if !self.position_open && micro_trend > 1.0 && trade.event_time - self.last_signal_time > 5000 {
+            // Without book data the gate stays open (e.g. trade-only backtests)
+            let book_supports_entry = self.book.is_none_or(|b| b.imbalance > MIN_ENTRY_IMBALANCE);
+            let wants_entry = !self.position_open && micro_trend > 1.0 && book_supports_entry && trade.event_time - self.last_signal_time > cooldown_ms;
+            // Entries must hold for the confirmation window; exits are never delayed
+            if self.debouncer.confirm(wants_entry.then_some(Side::Buy), trade.event_time) {
                 self.position_open = true;
                 self.entry_price = price;
+                self.hold.start(trade.event_time);
                 opps.push(Opportunity {
-                    id: format!("scalp_buy_{}", trade.event_time),
-                    signal: Signal::Buy { symbol: trade.symbol.clone(), price: Some(price), quantity: 0.0005 },
+                    id: "scalp_buy".to_string(),
+                    signal: Signal::Buy { symbol: trade.symbol.clone(), price: Some(price), quantity: 0.0005, order_type: OrderType::Market },
                     score: (micro_trend / 10.0).min(0.8),
                     risk_score: 0.5,
                     reason: format!("Micro uptrend: {:.1} bps", micro_trend),
@@ -75,21 +98,23 @@ impl TradingStrategy for ScalperStrategy {
                 self.last_signal_time = trade.event_time;
             } else if self.position_open {
                 let pnl_bps = (price - self.entry_price) / self.entry_price * 10000.0;
-                if pnl_bps > 5.0 || pnl_bps < -3.0 {
+                let timed_out = self.hold.update(trade.event_time);
+                if pnl_bps > 5.0 || pnl_bps < -3.0 || timed_out {
                     self.position_open = false;
+                    self.hold.stop();
                     opps.push(Opportunity {
-                        id: format!("scalp_sell_{}", trade.event_time),
-                        signal: Signal::Sell { symbol: trade.symbol.clone(), price: Some(price), quantity: 0.0005 },
+                        id: "scalp_sell".to_string(),
+                        signal: Signal::SellAll { symbol: trade.symbol.clone(), price: Some(price) },
                         score: 0.7,
                         risk_score: 0.3,
-                        reason: format!("Scalp exit: {:.1} bps P&L", pnl_bps),
+                        reason: format!("Scalp exit: {:.1} bps P&L{}", pnl_bps, if timed_out { " (max hold)" } else { "" }),
                         timestamp: trade.event_time,
                     });
                 }
             }
         }
         
-        { let mut w = state.write().await; w.push_data_point_at(price, qty, opps.first().map(|o| match &o.signal { Signal::Buy{..} => "Buy", Signal::Sell{..} => "Sell", _ => "Cancel" }.to_string()), 0, 0, 0.0, trade.event_time); }
+        { let mut w = state.write().await; w.push_data_point_at(price, qty, opps.first().map(|o| match &o.signal { Signal::Buy{..} => "Buy", Signal::Sell{..} | Signal::SellAll{..} => "Sell", _ => "Cancel" }.to_string()), 0, 0, 0.0, trade.event_time); }
         opps
     }
 
@@ -100,5 +125,9 @@ impl TradingStrategy for ScalperStrategy {
         Vec::new()
     }
 
-    async fn process_orderbook(&mut self, _: OrderBook, _: SharedState) -> Vec<Opportunity> { Vec::new() }
+    async fn process_orderbook(&mut self, orderbook: OrderBook, _: SharedState) -> Vec<Opportunity> {
+        let features = OrderBookFeatures::from(&orderbook, DEFAULT_BOOK_LEVELS);
+        self.book = features.is_two_sided().then_some(features);
+        Vec::new()
+    }
 }
@@ -1,4 +1,4 @@
-use super::{Signal, TradingStrategy, Opportunity};
+use super::{Signal, OrderType, TradingStrategy, Opportunity};
 use crate::web::SharedState;
 use crate::state_machine::SystemState;
 use async_trait::async_trait;
@@ -68,12 +68,15 @@ impl TradingStrategy for StochasticOscillator {
             self.k_values.push_back(k);
             if self.k_values.len() > self.d_period { self.k_values.pop_front(); }
             
-            let current_state = state.read().await.state_machine.get_state();
-            if current_state == SystemState::Trading && trade.event_time - self.last_signal_time > 60000 {
+            let (current_state, cooldown_ms) = {
+                let read_guard = state.read().await;
+                (read_guard.state_machine.get_state(), read_guard.signal_cooldown_ms(self.name(), 60000))
+            };
+            if current_state == SystemState::Trading && trade.event_time - self.last_signal_time > cooldown_ms {
                 if k < 20.0 {
                     opps.push(Opportunity {
-                        id: format!("stoch_buy_{}", trade.event_time),
-                        signal: Signal::Buy { symbol: trade.symbol.clone(), price: Some(price), quantity: 0.001 },
+                        id: "stoch_buy".to_string(),
+                        signal: Signal::Buy { symbol: trade.symbol.clone(), price: Some(price), quantity: 0.001, order_type: OrderType::Market },
                         score: 0.75,
                         risk_score: 0.3,
                         reason: format!("Stochastic Oversold: %K={:.1}", k),
@@ -82,8 +85,8 @@ impl TradingStrategy for StochasticOscillator {
                     self.last_signal_time = trade.event_time;
                 } else if k > 80.0 {
                     opps.push(Opportunity {
-                        id: format!("stoch_sell_{}", trade.event_time),
-                        signal: Signal::Sell { symbol: trade.symbol.clone(), price: Some(price), quantity: 0.001 },
+                        id: "stoch_sell".to_string(),
+                        signal: Signal::Sell { symbol: trade.symbol.clone(), price: Some(price), quantity: 0.001, order_type: OrderType::Market },
                         score: 0.75,
                         risk_score: 0.3,
                         reason: format!("Stochastic Overbought: %K={:.1}", k),
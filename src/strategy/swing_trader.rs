@@ -1,4 +1,5 @@
-use super::{Signal, TradingStrategy, Opportunity};
+use super::{Signal, OrderType, TradingStrategy, Opportunity};
+use super::bracket::HoldTimer;
 use crate::web::SharedState;
 use crate::state_machine::SystemState;
 use async_trait::async_trait;
@@ -12,6 +13,10 @@ pub struct SwingTrader {
     last_spread: f64,
     position: i8,  // -1 short, 0 flat, 1 long
     entry_price: f64,
+    /// Exit once price retraces this far (percent) from the best price since entry
+    trailing_stop_pct: f64,
+    peak_price: f64, // Highest price since entry
+    hold: HoldTimer,
 }
 
 impl SwingTrader {
@@ -22,8 +27,15 @@ impl SwingTrader {
             last_spread: 0.0,
             position: 0,
             entry_price: 0.0,
+            trailing_stop_pct: 0.3,
+            peak_price: 0.0,
+            hold: HoldTimer::from_env(),
         }
     }
+
+    fn trailing_stop_level(&self) -> Option<f64> {
+        (self.position == 1).then(|| self.peak_price * (1.0 - self.trailing_stop_pct / 100.0))
+    }
     
     fn get_momentum(&self) -> f64 {
         if self.prices.len() < 20 { return 0.0; }
@@ -41,6 +53,8 @@ impl TradingStrategy for SwingTrader {
         vec![
             ("Momentum".to_string(), format!("{:.2}%", self.get_momentum())),
             ("Position".to_string(), match self.position { 1 => "Long", -1 => "Short", _ => "Flat" }.to_string()),
+            ("Trailing Stop".to_string(), self.trailing_stop_level().map_or("N/A".to_string(), |p| format!("{:.2}", p))),
+            self.hold.feature(),
             ("PnL (Active)".to_string(), if self.position != 0 { format!("{:.2}%", 0.0) } else { "N/A".to_string() }), // PnL is dynamic, maybe add it to state?
         ]
     }
@@ -72,15 +86,6 @@ impl SwingTrader {
         self.prices.push(price);
         if self.prices.len() > 50 { self.prices.remove(0); }
         
-        {
-            let mut guard = state.write().await;
-            if guard.state_machine.get_state() == SystemState::Booting {
-                guard.state_machine.transition_to(SystemState::Accumulating);
-            } else if guard.state_machine.get_state() == SystemState::Accumulating && guard.state_machine.is_stable() {
-                guard.state_machine.transition_to(SystemState::Trading);
-            }
-        }
-        
         let mut opps = Vec::new();
         let current_state = state.read().await.state_machine.get_state();
         
@@ -91,9 +96,11 @@ impl SwingTrader {
             if self.position == 0 && momentum > 0.1 {
                 self.position = 1;
                 self.entry_price = price;
+                self.peak_price = price;
+                self.hold.start(ts);
                 opps.push(Opportunity {
-                    id: format!("swing_buy_{}", self.trade_count),
-                    signal: Signal::Buy { symbol: symbol.clone(), price: Some(price), quantity: 0.001 },
+                    id: "swing_buy".to_string(),
+                    signal: Signal::Buy { symbol: symbol.clone(), price: Some(price), quantity: 0.001, order_type: OrderType::Market },
                     score: 0.75,
                     risk_score: 0.35,
                     reason: format!("Strong momentum: +{:.2}%", momentum),
@@ -101,17 +108,21 @@ impl SwingTrader {
                 });
             }
             
-            // Exit on reversal or profit target
+            // Exit on reversal, profit target, hard stop, trailing stop or max hold (whichever comes first)
+            let timed_out = self.hold.update(ts);
             if self.position == 1 {
+                self.peak_price = self.peak_price.max(price);
                 let pnl_pct = (price - self.entry_price) / self.entry_price * 100.0;
-                if momentum < -0.2 || pnl_pct > 1.0 || pnl_pct < -0.5 {
+                let trailing_hit = self.trailing_stop_level().is_some_and(|stop| price <= stop);
+                if momentum < -0.2 || pnl_pct > 1.0 || pnl_pct < -0.5 || trailing_hit || timed_out {
                     self.position = 0;
+                    self.hold.stop();
                     opps.push(Opportunity {
-                        id: format!("swing_sell_{}", self.trade_count),
-                        signal: Signal::Sell { symbol: symbol.clone(), price: Some(price), quantity: 0.001 },
+                        id: "swing_sell".to_string(),
+                        signal: Signal::Sell { symbol: symbol.clone(), price: Some(price), quantity: 0.001, order_type: OrderType::Market },
                         score: 0.75,
                         risk_score: 0.3,
-                        reason: format!("Exit: PnL={:.2}%, mom={:.2}%", pnl_pct, momentum),
+                        reason: format!("Exit: PnL={:.2}%, mom={:.2}%{}", pnl_pct, momentum, exit_note(trailing_hit, timed_out)),
                         timestamp: ts,
                     });
                 }
@@ -134,3 +145,14 @@ impl SwingTrader {
         opps
     }
 }
+
+/// Suffix naming the stop that closed a position, if any
+fn exit_note(trailing_hit: bool, timed_out: bool) -> &'static str {
+    if trailing_hit {
+        " (trailing stop)"
+    } else if timed_out {
+        " (max hold)"
+    } else {
+        ""
+    }
+}
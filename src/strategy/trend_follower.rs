@@ -1,37 +1,61 @@
-use super::{Signal, TradingStrategy, Opportunity};
+use super::{Signal, OrderType, TradingStrategy, Opportunity, indicators};
 use crate::web::SharedState;
 use crate::state_machine::SystemState;
 use async_trait::async_trait;
 use binance::model::{TradeEvent, OrderBook, AggrTradesEvent};
 use std::time::Instant;
 
-/// Trend Following strategy using EMA crossover
+/// Size of the initial entry and of every add
+const UNIT_QTY: f64 = 0.001;
+/// EMA5/EMA12 gap (fraction) that opens a position; each add needs the gap to widen by this much again
+const SPREAD_STEP: f64 = 0.001;
+
+/// Trend Following strategy using EMA crossover.
+/// With `max_adds > 0` it pyramids: while the position is in profit and the EMA gap keeps
+/// widening, it adds another unit, up to `max_adds` times. A death cross exits everything.
 pub struct TrendFollower {
     prices: Vec<f64>,
     trade_count: u64,
     last_spread: f64,
-    in_position: bool,
+    max_adds: u32,
+    adds: u32,
+    position_qty: f64,
+    avg_entry: f64,
+    entry_ema_spread: f64, // EMA gap at the last entry or add
 }
 
 impl TrendFollower {
     pub fn new() -> Self {
+        Self::with_params(0)
+    }
+
+    pub fn with_params(max_adds: u32) -> Self {
         Self {
             prices: Vec::with_capacity(100),
             trade_count: 0,
             last_spread: 0.0,
-            in_position: false,
+            max_adds,
+            adds: 0,
+            position_qty: 0.0,
+            avg_entry: 0.0,
+            entry_ema_spread: 0.0,
         }
     }
+
+    fn in_position(&self) -> bool {
+        self.position_qty > 0.0
+    }
+
+    /// Adds one unit at `price`, keeping a quantity-weighted average entry
+    fn add_unit(&mut self, price: f64, ema_spread: f64) {
+        let cost = self.avg_entry * self.position_qty + price * UNIT_QTY;
+        self.position_qty += UNIT_QTY;
+        self.avg_entry = cost / self.position_qty;
+        self.entry_ema_spread = ema_spread;
+    }
     
     fn ema(&self, period: usize) -> f64 {
-        if self.prices.len() < period { return 0.0; }
-        let k = 2.0 / (period as f64 + 1.0);
-        let recent = &self.prices[self.prices.len().saturating_sub(period)..];
-        let mut ema = recent[0];
-        for &p in &recent[1..] {
-            ema = p * k + ema * (1.0 - k);
-        }
-        ema
+        indicators::ema(&self.prices, period).unwrap_or(0.0)
     }
 }
 
@@ -45,7 +69,9 @@ impl TradingStrategy for TrendFollower {
         vec![
             ("EMA5".to_string(), format!("{:.2}", ema5)),
             ("EMA12".to_string(), format!("{:.2}", ema12)),
-            ("Position".to_string(), self.in_position.to_string()),
+            ("Position".to_string(), format!("{:.4}", self.position_qty)),
+            ("Adds".to_string(), format!("{}/{}", self.adds, self.max_adds)),
+            ("Avg Entry".to_string(), if self.in_position() { format!("{:.2}", self.avg_entry) } else { "-".to_string() }),
         ]
     }
     
@@ -76,46 +102,55 @@ impl TrendFollower {
         self.prices.push(price);
         if self.prices.len() > 50 { self.prices.remove(0); }
         
-        {
-            let mut guard = state.write().await;
-            if guard.state_machine.get_state() == SystemState::Booting {
-                guard.state_machine.transition_to(SystemState::Accumulating);
-            } else if guard.state_machine.get_state() == SystemState::Accumulating && guard.state_machine.is_stable() {
-                guard.state_machine.transition_to(SystemState::Trading);
-            }
-        }
-        
         let mut opps = Vec::new();
         let current_state = state.read().await.state_machine.get_state();
         
         if current_state == SystemState::Trading && self.prices.len() >= 20 {
             let ema_short = self.ema(5);
             let ema_long = self.ema(12);
+            let ema_spread = if ema_long > 0.0 { ema_short / ema_long - 1.0 } else { 0.0 };
             
             // Golden cross - buy
-            if ema_short > ema_long * 1.001 && !self.in_position {
-                self.in_position = true;
+            if ema_spread > SPREAD_STEP && !self.in_position() {
+                self.add_unit(price, ema_spread);
                 opps.push(Opportunity {
-                    id: format!("trend_buy_{}", self.trade_count),
-                    signal: Signal::Buy { symbol: symbol.clone(), price: Some(price), quantity: 0.001 },
+                    id: "trend_buy".to_string(),
+                    signal: Signal::Buy { symbol: symbol.clone(), price: Some(price), quantity: UNIT_QTY, order_type: OrderType::Market },
                     score: 0.75,
                     risk_score: 0.35,
                     reason: format!("EMA5={:.2} > EMA12={:.2} (golden cross)", ema_short, ema_long),
                     timestamp: ts,
                 });
+            } else if self.in_position()
+                && self.adds < self.max_adds
+                && price > self.avg_entry
+                && ema_spread > self.entry_ema_spread + SPREAD_STEP {
+                // Trend strengthening on a winning position - pyramid
+                self.add_unit(price, ema_spread);
+                self.adds += 1;
+                opps.push(Opportunity {
+                    id: "trend_add".to_string(),
+                    signal: Signal::Buy { symbol: symbol.clone(), price: Some(price), quantity: UNIT_QTY, order_type: OrderType::Market },
+                    score: 0.7,
+                    risk_score: 0.45,
+                    reason: format!("EMA gap widened to {:.2}% (add {}/{})", ema_spread * 100.0, self.adds, self.max_adds),
+                    timestamp: ts,
+                });
             }
             
-            // Death cross - sell
-            if ema_short < ema_long * 0.999 && self.in_position {
-                self.in_position = false;
+            // Death cross - sell the whole accumulated position
+            if ema_spread < -SPREAD_STEP && self.in_position() {
                 opps.push(Opportunity {
-                    id: format!("trend_sell_{}", self.trade_count),
-                    signal: Signal::Sell { symbol: symbol.clone(), price: Some(price), quantity: 0.001 },
+                    id: "trend_sell".to_string(),
+                    signal: Signal::SellAll { symbol: symbol.clone(), price: Some(price) },
                     score: 0.75,
                     risk_score: 0.35,
-                    reason: format!("EMA5={:.2} < EMA12={:.2} (death cross)", ema_short, ema_long),
+                    reason: format!("EMA5={:.2} < EMA12={:.2} (death cross, avg entry {:.2})", ema_short, ema_long, self.avg_entry),
                     timestamp: ts,
                 });
+                self.position_qty = 0.0;
+                self.avg_entry = 0.0;
+                self.adds = 0;
             }
         }
         
@@ -123,7 +158,7 @@ impl TrendFollower {
             let mut guard = state.write().await;
             let action = opps.first().map(|o| match &o.signal {
                 Signal::Buy { .. } => "Buy".to_string(),
-                Signal::Sell { .. } => "Sell".to_string(),
+                Signal::Sell { .. } | Signal::SellAll { .. } => "Sell".to_string(),
                 _ => "Hold".to_string(),
             });
             let strat_lat = guard.metrics.get_strategy_stats().p50;
@@ -1,4 +1,4 @@
-use super::{Signal, TradingStrategy, Opportunity};
+use super::{Signal, OrderType, TradingStrategy, Opportunity};
 use crate::web::SharedState;
 use crate::state_machine::SystemState;
 use async_trait::async_trait;
@@ -68,13 +68,16 @@ impl TradingStrategy for TRIXStrategy {
         let trix = if prev_ema3 > 0.0 { (self.ema3 - prev_ema3) / prev_ema3 * 100.0 } else { 0.0 };
         
         let mut opps = Vec::new();
-        let current_state = state.read().await.state_machine.get_state();
+        let (current_state, cooldown_ms) = {
+            let read_guard = state.read().await;
+            (read_guard.state_machine.get_state(), read_guard.signal_cooldown_ms(self.name(), 60000))
+        };
         
-        if current_state == SystemState::Trading && trade.event_time - self.last_signal_time > 60000 {
+        if current_state == SystemState::Trading && trade.event_time - self.last_signal_time > cooldown_ms {
             if self.prev_trix < 0.0 && trix > 0.0 {
                 opps.push(Opportunity {
-                    id: format!("trix_buy_{}", trade.event_time),
-                    signal: Signal::Buy { symbol: trade.symbol.clone(), price: Some(price), quantity: 0.001 },
+                    id: "trix_buy".to_string(),
+                    signal: Signal::Buy { symbol: trade.symbol.clone(), price: Some(price), quantity: 0.001, order_type: OrderType::Market },
                     score: 0.75,
                     risk_score: 0.4,
                     reason: "TRIX Bullish Crossover".to_string(),
@@ -83,8 +86,8 @@ impl TradingStrategy for TRIXStrategy {
                 self.last_signal_time = trade.event_time;
             } else if self.prev_trix > 0.0 && trix < 0.0 {
                 opps.push(Opportunity {
-                    id: format!("trix_sell_{}", trade.event_time),
-                    signal: Signal::Sell { symbol: trade.symbol.clone(), price: Some(price), quantity: 0.001 },
+                    id: "trix_sell".to_string(),
+                    signal: Signal::Sell { symbol: trade.symbol.clone(), price: Some(price), quantity: 0.001, order_type: OrderType::Market },
                     score: 0.75,
                     risk_score: 0.4,
                     reason: "TRIX Bearish Crossover".to_string(),
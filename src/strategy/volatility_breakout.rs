@@ -1,4 +1,5 @@
-use super::{Signal, TradingStrategy, Opportunity};
+use super::{Signal, OrderType, TradingStrategy, Opportunity};
+use super::bracket::{BracketConfig, BracketTracker, HoldTimer};
 use crate::web::SharedState;
 use crate::state_machine::SystemState;
 use async_trait::async_trait;
@@ -10,8 +11,8 @@ pub struct VolatilityBreakout {
     prices: Vec<f64>,
     trade_count: u64,
     last_spread: f64,
-    in_position: bool,
-    entry_price: f64,
+    bracket: BracketTracker,
+    hold: HoldTimer,
 }
 
 impl VolatilityBreakout {
@@ -20,8 +21,8 @@ impl VolatilityBreakout {
             prices: Vec::with_capacity(50),
             trade_count: 0,
             last_spread: 0.0,
-            in_position: false,
-            entry_price: 0.0,
+            bracket: BracketTracker::new(BracketConfig::new(0.2, 0.1, false).with_trailing_stop(0.15)),
+            hold: HoldTimer::from_env(),
         }
     }
     
@@ -46,7 +47,9 @@ impl TradingStrategy for VolatilityBreakout {
             ("Range High".to_string(), format!("{:.2}", high)),
             ("Range Low".to_string(), format!("{:.2}", low)),
             ("Volatility".to_string(), format!("{:.2}", high - low)),
-            ("In Position".to_string(), self.in_position.to_string()),
+            ("In Position".to_string(), self.bracket.is_open().to_string()),
+            ("Trailing Stop".to_string(), self.bracket.trailing_stop_price().map_or("N/A".to_string(), |p| format!("{:.2}", p))),
+            self.hold.feature(),
         ]
     }
     
@@ -77,15 +80,6 @@ impl VolatilityBreakout {
         self.prices.push(price);
         if self.prices.len() > 30 { self.prices.remove(0); }
         
-        {
-            let mut guard = state.write().await;
-            if guard.state_machine.get_state() == SystemState::Booting {
-                guard.state_machine.transition_to(SystemState::Accumulating);
-            } else if guard.state_machine.get_state() == SystemState::Accumulating && guard.state_machine.is_stable() {
-                guard.state_machine.transition_to(SystemState::Trading);
-            }
-        }
-        
         let mut opps = Vec::new();
         let current_state = state.read().await.state_machine.get_state();
         
@@ -95,12 +89,12 @@ impl VolatilityBreakout {
             
             if range > 0.0 {
                 // Breakout above range
-                if !self.in_position && price > high + range * 0.01 {
-                    self.in_position = true;
-                    self.entry_price = price;
+                if !self.bracket.is_open() && price > high + range * 0.01 {
+                    self.bracket.open(&symbol, price);
+                    self.hold.start(ts);
                     opps.push(Opportunity {
-                        id: format!("vb_buy_{}", self.trade_count),
-                        signal: Signal::Buy { symbol: symbol.clone(), price: Some(price), quantity: 0.001 },
+                        id: "vb_buy".to_string(),
+                        signal: Signal::Buy { symbol: symbol.clone(), price: Some(price), quantity: 0.001, order_type: OrderType::Market },
                         score: 0.7,
                         risk_score: 0.4,
                         reason: format!("Breakout above {:.2} (+1% range)", high),
@@ -108,20 +102,20 @@ impl VolatilityBreakout {
                     });
                 }
                 
-                // Take profit or stop loss
-                if self.in_position {
-                    let pnl_pct = (price - self.entry_price) / self.entry_price * 100.0;
-                    if pnl_pct > 0.2 || pnl_pct < -0.1 {
-                        self.in_position = false;
-                        opps.push(Opportunity {
-                            id: format!("vb_sell_{}", self.trade_count),
-                            signal: Signal::Sell { symbol: symbol.clone(), price: Some(price), quantity: 0.001 },
-                            score: 0.7,
-                            risk_score: 0.3,
-                            reason: format!("Exit: PnL={:.2}%", pnl_pct),
-                            timestamp: ts,
-                        });
-                    }
+                // Take profit, stop loss or max holding period
+                let pnl_pct = self.bracket.pnl_pct(price);
+                let timed_out = self.hold.update(ts);
+                let exit = self.bracket.update(price).or_else(|| if timed_out { self.bracket.exit(price) } else { None });
+                if let Some(exit) = exit {
+                    self.hold.stop();
+                    opps.push(Opportunity {
+                        id: "vb_sell".to_string(),
+                        signal: exit,
+                        score: 0.7,
+                        risk_score: 0.3,
+                        reason: format!("Exit: PnL={:.2}%{}", pnl_pct, if timed_out { " (max hold)" } else { "" }),
+                        timestamp: ts,
+                    });
                 }
             }
         }
@@ -130,7 +124,7 @@ impl VolatilityBreakout {
             let mut guard = state.write().await;
             let action = opps.first().map(|o| match &o.signal {
                 Signal::Buy { .. } => "Buy".to_string(),
-                Signal::Sell { .. } => "Sell".to_string(),
+                Signal::Sell { .. } | Signal::SellAll { .. } => "Sell".to_string(),
                 _ => "Hold".to_string(),
             });
             let strat_lat = guard.metrics.get_strategy_stats().p50;
@@ -1,4 +1,4 @@
-use super::{Signal, TradingStrategy, Opportunity};
+use super::{Signal, OrderType, TradingStrategy, Opportunity};
 use crate::web::SharedState;
 use crate::state_machine::SystemState;
 use async_trait::async_trait;
@@ -12,6 +12,12 @@ pub struct VWAPStrategy {
     volumes: VecDeque<f64>,
     vwap: f64,
     last_signal_time: u64,
+    /// Session length in ms. When set, VWAP accumulates from the session open instead of a rolling window.
+    session_ms: Option<u64>,
+    session_start: u64,
+    session_pv: f64,
+    session_volume: f64,
+    session_samples: usize,
 }
 
 impl VWAPStrategy {
@@ -21,10 +27,43 @@ impl VWAPStrategy {
             volumes: VecDeque::with_capacity(100),
             vwap: 0.0,
             last_signal_time: 0,
+            session_ms: None,
+            session_start: 0,
+            session_pv: 0.0,
+            session_volume: 0.0,
+            session_samples: 0,
         }
     }
 
+    /// Resets VWAP at every `session_secs` boundary since the Unix epoch (86400 = UTC midnight)
+    pub fn with_session(mut self, session_secs: u64) -> Self {
+        self.session_ms = Some(session_secs.max(1) * 1000);
+        self
+    }
+
+    fn add_sample(&mut self, price: f64, qty: f64, event_time: u64) {
+        if let Some(session_ms) = self.session_ms {
+            let session_start = event_time / session_ms * session_ms;
+            if session_start != self.session_start {
+                self.session_start = session_start;
+                self.session_pv = 0.0;
+                self.session_volume = 0.0;
+                self.session_samples = 0;
+            }
+            self.session_pv += price * qty;
+            self.session_volume += qty;
+            self.session_samples += 1;
+            return;
+        }
+        self.prices.push_back(price);
+        self.volumes.push_back(qty);
+        if self.prices.len() > 100 { self.prices.pop_front(); self.volumes.pop_front(); }
+    }
+
     fn calculate_vwap(&self) -> f64 {
+        if self.session_ms.is_some() {
+            return if self.session_volume == 0.0 { 0.0 } else { self.session_pv / self.session_volume };
+        }
         if self.prices.is_empty() { return 0.0; }
         let pv_sum: f64 = self.prices.iter().zip(self.volumes.iter()).map(|(p, v)| p * v).sum();
         let v_sum: f64 = self.volumes.iter().sum();
@@ -34,9 +73,18 @@ impl VWAPStrategy {
 
 #[async_trait]
 impl TradingStrategy for VWAPStrategy {
-    fn name(&self) -> &str { "VWAPStrategy" }
+    fn name(&self) -> &str {
+        if self.session_ms.is_some() { "VWAPSession" } else { "VWAPStrategy" }
+    }
 
     fn get_features(&self) -> Vec<(String, String)> {
+        if self.session_ms.is_some() {
+            return vec![
+                ("VWAP".to_string(), format!("{:.2}", self.vwap)),
+                ("Session Start".to_string(), self.session_start.to_string()),
+                ("Samples Since Reset".to_string(), self.session_samples.to_string()),
+            ];
+        }
         vec![
             ("VWAP".to_string(), format!("{:.2}", self.vwap)),
             ("Samples".to_string(), self.prices.len().to_string()),
@@ -47,21 +95,22 @@ impl TradingStrategy for VWAPStrategy {
         let price = trade.price.parse::<f64>().unwrap_or(0.0);
         let qty = trade.qty.parse::<f64>().unwrap_or(0.0);
         
-        self.prices.push_back(price);
-        self.volumes.push_back(qty);
-        if self.prices.len() > 100 { self.prices.pop_front(); self.volumes.pop_front(); }
+        self.add_sample(price, qty, trade.event_time);
         
         self.vwap = self.calculate_vwap();
         let mut opps = Vec::new();
-        let current_state = state.read().await.state_machine.get_state();
+        let (current_state, cooldown_ms) = {
+            let read_guard = state.read().await;
+            (read_guard.state_machine.get_state(), read_guard.signal_cooldown_ms(self.name(), 30000))
+        };
         
-        if current_state == SystemState::Trading && trade.event_time - self.last_signal_time > 30000 && self.vwap > 0.0 {
+        if current_state == SystemState::Trading && trade.event_time - self.last_signal_time > cooldown_ms && self.vwap > 0.0 {
             let deviation = (price - self.vwap) / self.vwap * 100.0;
             
             if deviation < -0.1 {
                 opps.push(Opportunity {
-                    id: format!("vwap_buy_{}", trade.event_time),
-                    signal: Signal::Buy { symbol: trade.symbol.clone(), price: Some(price), quantity: 0.001 },
+                    id: "vwap_buy".to_string(),
+                    signal: Signal::Buy { symbol: trade.symbol.clone(), price: Some(price), quantity: 0.001, order_type: OrderType::Market },
                     score: (deviation.abs() / 0.5).min(0.9),
                     risk_score: 0.3,
                     reason: format!("Below VWAP by {:.3}%", deviation.abs()),
@@ -70,8 +119,8 @@ impl TradingStrategy for VWAPStrategy {
                 self.last_signal_time = trade.event_time;
             } else if deviation > 0.1 {
                 opps.push(Opportunity {
-                    id: format!("vwap_sell_{}", trade.event_time),
-                    signal: Signal::Sell { symbol: trade.symbol.clone(), price: Some(price), quantity: 0.001 },
+                    id: "vwap_sell".to_string(),
+                    signal: Signal::Sell { symbol: trade.symbol.clone(), price: Some(price), quantity: 0.001, order_type: OrderType::Market },
                     score: (deviation / 0.5).min(0.85),
                     risk_score: 0.35,
                     reason: format!("Above VWAP by {:.3}%", deviation),
@@ -88,8 +137,7 @@ impl TradingStrategy for VWAPStrategy {
     async fn process_aggr_trade(&mut self, trade: AggrTradesEvent, state: SharedState) -> Vec<Opportunity> {
         let price = trade.price.parse::<f64>().unwrap_or(0.0);
         let qty = trade.qty.parse::<f64>().unwrap_or(0.0);
-        self.prices.push_back(price); self.volumes.push_back(qty);
-        if self.prices.len() > 100 { self.prices.pop_front(); self.volumes.pop_front(); }
+        self.add_sample(price, qty, trade.event_time);
         Vec::new()
     }
 
@@ -0,0 +1,54 @@
+use log::{info, error};
+use parking_lot::Mutex;
+use serde::Serialize;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::sync::Arc;
+
+/// One executed trade, written as a single JSON line
+#[derive(Debug, Clone, Serialize)]
+pub struct TradeRecord<'a> {
+    pub timestamp: u64, // Unix ms
+    pub strategy: &'a str,
+    pub symbol: &'a str,
+    pub side: &'a str, // "BUY" or "SELL"
+    pub price: f64,
+    pub quantity: f64,
+    pub fee: f64,
+    pub pnl: f64,
+}
+
+/// Append-only NDJSON audit trail of executed trades, separate from the text log
+pub struct TradeLog {
+    file: Mutex<File>,
+}
+
+impl TradeLog {
+    pub fn open(path: &str) -> std::io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self { file: Mutex::new(file) })
+    }
+
+    /// Opens TRADE_LOG_FILE for appending; None when unset
+    pub fn from_env() -> Option<Arc<Self>> {
+        let path = std::env::var("TRADE_LOG_FILE").ok().filter(|p| !p.trim().is_empty())?;
+        match Self::open(path.trim()) {
+            Ok(log) => {
+                info!("Writing executed trades to {}", path.trim());
+                Some(Arc::new(log))
+            }
+            Err(e) => {
+                error!("Failed to open trade log {}: {}", path.trim(), e);
+                None
+            }
+        }
+    }
+
+    /// Writes one line and flushes so the file is complete even if the process dies
+    pub fn append(&self, record: &TradeRecord) -> std::io::Result<()> {
+        let line = serde_json::to_string(record)?;
+        let mut file = self.file.lock();
+        writeln!(file, "{}", line)?;
+        file.flush()
+    }
+}
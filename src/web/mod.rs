@@ -1,34 +1,66 @@
 use axum::{
     extract::{State, Query},
+    http::{header, StatusCode},
     routing::get,
     Json, Router,
     response::sse::{Event, KeepAlive, Sse},
 };
 use serde::{Serialize, Deserialize};
+use schemars::JsonSchema;
 use std::collections::VecDeque;
 use std::net::SocketAddr;
 use std::sync::Arc;
-use tokio::sync::{RwLock, mpsc, broadcast};
+use tokio::sync::{RwLock, broadcast};
 use tokio_stream::wrappers::BroadcastStream;
 use tokio_stream::StreamExt;
 use sqlx::{Pool, Postgres};
 use std::convert::Infallible;
-use crate::market_data::downloader::MarketType;
+use crate::market_data::downloader::{klines_to_trades, DataGranularity, MarketType};
 
 use crate::state_machine::{StateMachine, SystemState};
 use crate::metrics::{SystemMetrics, LatencyStats};
 use crate::database::repository;
-use crate::strategy::{TradingStrategy, Signal};
+use crate::strategy::{TradingStrategy, Signal, OrderType};
 #[allow(unused_imports)]
 use crate::strategy::TradingStrategy as _;
 use crate::execution::Executor;
 
-// Global broadcast channel for SSE progress events
+// Global broadcast channels for SSE progress and live dashboard events
 lazy_static::lazy_static! {
     pub static ref PROGRESS_TX: broadcast::Sender<ProgressEvent> = {
         let (tx, _) = broadcast::channel(100);
         tx
     };
+    pub static ref LIVE_TX: broadcast::Sender<LiveEvent> = {
+        let (tx, _) = broadcast::channel(1000);
+        tx
+    };
+    pub static ref REPLAY_TX: broadcast::Sender<ReplayEvent> = {
+        let (tx, _) = broadcast::channel(1000);
+        tx
+    };
+}
+
+/// Pushed to /api/live/stream subscribers as it happens
+#[derive(Serialize, Clone, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum LiveEvent {
+    DataPoint(DataPoint),
+    Execution {
+        strategy: String,
+        signal: crate::strategy::Signal,
+        pnl: f64,
+        timestamp: u64,
+    },
+    StopTriggered(crate::execution::StopTrigger),
+}
+
+/// Data point of a paced backtest, pushed to /api/backtest/replay subscribers
+#[derive(Serialize, Clone, Debug)]
+pub struct ReplayEvent {
+    pub symbol: String,
+    pub strategy_name: String,
+    pub data_point: DataPoint,
 }
 
 #[derive(Serialize, Clone, Debug)]
@@ -36,7 +68,7 @@ pub struct ProgressEvent {
     pub symbol: String,
     pub strategy_name: String,
     pub progress_pct: u32,
-    pub status: String,  // "running", "completed", "error"
+    pub status: String,  // "running", "completed", "cancelled", "error"
     pub features: std::collections::HashMap<String, String>,
 }
 
@@ -53,12 +85,21 @@ pub struct DataPoint {
     pub equity: f64,
 }
 
-#[derive(Serialize, Clone, Debug)]
+#[derive(Serialize, Clone, Debug, JsonSchema)]
 pub struct PortfolioSnapshot {
     pub timestamp: u64,
     pub total_value_usd: f64,
 }
 
+/// Realized performance attributed to a single strategy
+#[derive(Serialize, Clone, Debug, Default, JsonSchema)]
+pub struct StrategyPnl {
+    pub realized_pnl: f64,
+    pub trades: u64,
+    pub win_trades: u64,
+    pub loss_trades: u64,
+}
+
 // Shared Application State
 pub struct AppState {
     pub state_machine: StateMachine,
@@ -68,7 +109,14 @@ pub struct AppState {
     pub run_mode: String,
     pub strategy_name: String,
     pub db_pool: Pool<Postgres>,
+    /// Bounds the background writes spawned by the event loop
+    pub db_writes: Arc<crate::database::WriteLimiter>,
     pub symbol: String,
+    pub symbols: Vec<String>,
+    pub strategies: std::collections::HashMap<String, Box<dyn TradingStrategy>>,
+    /// Order events from executions that finished in the background, per symbol, handed to
+    /// the symbol's strategy before its next market event
+    pub order_events: std::collections::HashMap<String, Vec<crate::strategy::OrderEvent>>,
     pub available_markets: Vec<String>,
     pub current_opportunities: Vec<crate::strategy::Opportunity>,
     pub selected_opportunity_id: Option<String>,
@@ -76,19 +124,78 @@ pub struct AppState {
     pub win_trades: u64,
     pub loss_trades: u64,
     pub realized_pnl: f64,
-    pub last_update_ts: u64,
+    pub gross_profit: f64,
+    pub gross_loss: f64,
+    pub strategy_pnl: std::collections::HashMap<String, StrategyPnl>,
+    pub last_update_ts: u64, // Unix secs of the last market event the main loop processed
     pub risk_report: crate::strategy::RiskReport,
     pub executor: Arc<dyn crate::execution::Executor>,
     pub portfolio_history: VecDeque<PortfolioSnapshot>,
     pub last_portfolio_snapshot_ts: u64,
     pub is_trading: bool,
     pub initial_balance: f64,
+    /// Realized PnL and executed trades since the daily session started
+    pub daily_pnl: f64,
+    pub daily_trades: u64,
+    /// Portfolio value when the daily session started
+    pub daily_start_value: f64,
+    /// UTC hour the daily session rolls over (DAILY_RESET_HOUR_UTC, default 0)
+    pub daily_reset_hour: u64,
+    /// Current daily session, see `daily_session_at`
+    pub daily_session: u64,
     pub available_strategies: Vec<String>,
     pub data_quality_score: f64,
+    pub symbol_data_quality: f64, // Quality score of the selected symbol only
     pub sample_rate: usize,  // Only record 1 in N data points
     pub data_point_counter: usize,
-    pub market_sender: mpsc::Sender<crate::market_data::MarketEvent>,
+    /// Doubles each time `history` fills up, see `push_data_point_at`
+    pub history_thinning: usize,
+    pub market_sender: crate::market_data::EventSender,
     pub current_features: std::collections::HashMap<String, String>,
+    pub connection_health: Arc<crate::market_data::ConnectionHealth>,
+    pub price_cache: Arc<crate::market_data::LastPriceCache>,
+    pub circuit_breaker: crate::strategy::CircuitBreaker,
+    pub last_backtest_report: Option<BacktestReport>,
+    /// Set by /api/backtest/cancel; checked by running backtest passes
+    pub backtest_cancel: Arc<std::sync::atomic::AtomicBool>,
+    pub open_positions: usize,
+    pub max_open_positions: usize,
+    pub max_notional_usd: Option<f64>,
+    /// Max fraction of equity each strategy may hold open, from STRATEGY_ALLOCATIONS.
+    /// Strategies not listed are uncapped.
+    pub strategy_allocations: std::collections::HashMap<String, f64>,
+    /// Net quantity each strategy holds per symbol, built from its own fills
+    pub strategy_exposure: std::collections::HashMap<String, std::collections::HashMap<String, f64>>,
+    /// Notional of each strategy's entries sent but not yet answered, held against its allocation
+    pub strategy_reserved: std::collections::HashMap<String, f64>,
+    pub funding_rates: std::collections::HashMap<String, f64>, // Futures only
+    pub reconciler: crate::execution::BalanceReconciler, // Live only
+    pub last_reconciliation: Option<crate::execution::ReconciliationReport>,
+    /// FIFO tax lots of spot fills, served at /api/cost_basis
+    pub cost_basis: crate::execution::CostBasisTracker,
+    /// Live orders are only sent while armed; shared with the executor
+    pub trading_armed: Arc<std::sync::atomic::AtomicBool>,
+    /// Live order rejection counters; shared with the executor
+    pub order_rejections: Arc<crate::execution::OrderRejections>,
+    /// Runtime parameter overrides keyed by strategy name, set by /api/strategy/config
+    pub strategy_params: std::collections::HashMap<String, std::collections::HashMap<String, f64>>,
+    /// Publish recorded data points on LIVE_TX (off for backtest passes)
+    pub stream_live: bool,
+    /// Publish recorded data points on REPLAY_TX (paced backtest passes only)
+    pub stream_replay: bool,
+    /// Store measured latencies in data points; backtests record 0 so reruns are identical
+    pub record_latencies: bool,
+    /// Per-run sequence appended to opportunity IDs, see `assign_opportunity_ids`
+    pub opportunity_seq: u64,
+    /// Set once startup migrations succeed; reported by /ready
+    pub migrations_applied: bool,
+    /// /health fails once no market event has been processed for this long
+    pub max_event_age_secs: u64,
+    /// Open positions handed to the next strategy swap (/api/select_strategy with `carry`)
+    pub carried_positions: Vec<crate::execution::PositionInfo>,
+    /// Set when SHADOW_STRATEGIES starts a shadow runner; its reports back /api/shadow
+    pub shadow_enabled: bool,
+    pub shadow_reports: crate::shadow::ShadowReports,
 }
 
 impl AppState {
@@ -98,17 +205,23 @@ impl AppState {
         db_pool: Pool<Postgres>, 
         symbol: String,
         executor: Arc<dyn crate::execution::Executor>,
-        market_sender: mpsc::Sender<crate::market_data::MarketEvent>
+        market_sender: crate::market_data::EventSender
     ) -> Self {
-        let available_markets = vec!["BTCUSDT".to_string(), "ETHUSDT".to_string(), "BNBUSDT".to_string(), "SOLUSDT".to_string()];
+        let quote_asset = executor.quote_asset().to_string();
+        let available_markets = ["BTC", "ETH", "BNB", "SOL"].iter().map(|base| format!("{}{}", base, quote_asset)).collect();
         Self {
-            state_machine: StateMachine::new(),
+            state_machine: StateMachine::with_warm_up(crate::state_machine::WarmUp::from_env())
+                .with_volatility_cooldown(crate::state_machine::VolatilityCooldown::from_env()),
             metrics: SystemMetrics::new(),
             history: VecDeque::with_capacity(1000),
             max_history: 1000,
             run_mode,
             strategy_name,
             db_pool,
+            db_writes: Arc::new(crate::database::WriteLimiter::from_env()),
+            symbols: vec![symbol.clone()],
+            strategies: std::collections::HashMap::new(),
+            order_events: std::collections::HashMap::new(),
             symbol,
             available_markets,
             current_opportunities: Vec::new(),
@@ -117,27 +230,97 @@ impl AppState {
             win_trades: 0,
             loss_trades: 0,
             realized_pnl: 0.0,
+            gross_profit: 0.0,
+            gross_loss: 0.0,
+            strategy_pnl: std::collections::HashMap::new(),
             last_update_ts: std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs(),
             risk_report: crate::strategy::RiskReport {
                 total_risk: 0.0,
                 leverage_risk: 0.0,
                 drawdown_warning: false,
                 recommended_max_size: 0.1,
+                open_positions: 0,
+                max_open_positions: crate::strategy::RiskManager::max_open_positions_from_env(),
+                max_notional_usd: crate::strategy::RiskManager::max_notional_from_env(),
+                notional_capped: 0,
+                allocation_capped: 0,
             },
+            trading_armed: executor.armed_flag(),
+            order_rejections: executor.order_rejections(),
             executor,
             portfolio_history: VecDeque::with_capacity(500),
             last_portfolio_snapshot_ts: 0,
             is_trading: false,
-            initial_balance: 10000.0, // Default for simulation, will be updated from balance
+            initial_balance: crate::execution::DEFAULT_SIM_BALANCE_USDT, // Default for simulation, will be updated from balance
+            daily_pnl: 0.0,
+            daily_trades: 0,
+            daily_start_value: crate::execution::DEFAULT_SIM_BALANCE_USDT,
+            daily_reset_hour: std::env::var("DAILY_RESET_HOUR_UTC")
+                .ok()
+                .and_then(|v| v.parse::<u64>().ok())
+                .filter(|h| *h < 24)
+                .unwrap_or(0),
+            daily_session: 0,
             available_strategies: crate::strategy::StrategyFactory::get_available_strategies(),
             data_quality_score: 100.0,
+            symbol_data_quality: 100.0,
             sample_rate: 1,  // Default: record every data point
             data_point_counter: 0,
+            history_thinning: 1,
             market_sender,
             current_features: std::collections::HashMap::new(),
+            connection_health: Arc::new(crate::market_data::ConnectionHealth::new(0)),
+            price_cache: Arc::new(crate::market_data::LastPriceCache::new(&quote_asset).with_source(crate::market_data::PriceSource::from_env())),
+            circuit_breaker: crate::strategy::CircuitBreaker::from_env(),
+            last_backtest_report: None,
+            backtest_cancel: Arc::new(std::sync::atomic::AtomicBool::new(false)),
+            open_positions: 0,
+            max_open_positions: crate::strategy::RiskManager::max_open_positions_from_env(),
+            max_notional_usd: crate::strategy::RiskManager::max_notional_from_env(),
+            strategy_allocations: crate::strategy::RiskManager::allocations_from_env(),
+            strategy_exposure: std::collections::HashMap::new(),
+            strategy_reserved: std::collections::HashMap::new(),
+            funding_rates: std::collections::HashMap::new(),
+            reconciler: crate::execution::BalanceReconciler::from_env(),
+            last_reconciliation: None,
+            cost_basis: crate::execution::CostBasisTracker::new(),
+            strategy_params: std::collections::HashMap::new(),
+            stream_live: true,
+            stream_replay: false,
+            record_latencies: true,
+            opportunity_seq: 0,
+            migrations_applied: false,
+            max_event_age_secs: std::env::var("HEALTH_MAX_EVENT_AGE_SECS")
+                .ok()
+                .and_then(|v| v.parse::<u64>().ok())
+                .unwrap_or(60),
+            carried_positions: Vec::new(),
+            shadow_enabled: false,
+            shadow_reports: Arc::new(parking_lot::RwLock::new(Vec::new())),
         }
     }
+
+    /// Runtime override of a strategy parameter, if one has been configured
+    pub fn strategy_param(&self, strategy: &str, key: &str) -> Option<f64> {
+        self.strategy_params.get(strategy)?.get(key).copied()
+    }
+
+    /// Minimum time between signals: the configured `cooldown_ms` override, else the strategy's default
+    pub fn signal_cooldown_ms(&self, strategy: &str, default_ms: u64) -> u64 {
+        self.strategy_param(strategy, "cooldown_ms")
+            .map(|ms| ms.max(0.0) as u64)
+            .unwrap_or(default_ms)
+    }
     
+    /// Turns each strategy's label (e.g. "rsi_buy") into a run-unique ID such as "rsi_buy_42".
+    /// Timestamps collide when two signals share a millisecond; the sequence never does.
+    pub fn assign_opportunity_ids(&mut self, opps: &mut [crate::strategy::Opportunity]) {
+        for opp in opps {
+            self.opportunity_seq += 1;
+            opp.id = format!("{}_{}", opp.id, self.opportunity_seq);
+        }
+    }
+
     /// Add a portfolio value snapshot
     pub fn push_portfolio_snapshot(&mut self, total_value_usd: f64) {
         let snapshot = PortfolioSnapshot {
@@ -153,6 +336,64 @@ impl AppState {
         }
     }
 
+    /// Attributes an executed trade's realized PnL to the strategy that produced it
+    pub fn record_strategy_pnl(&mut self, strategy: &str, pnl: f64) {
+        let entry = self.strategy_pnl.entry(strategy.to_string()).or_default();
+        entry.realized_pnl += pnl;
+        entry.trades += 1;
+        if pnl > 0.0 {
+            entry.win_trades += 1;
+        } else if pnl < 0.0 {
+            entry.loss_trades += 1;
+        }
+    }
+
+    /// Attributes a fill to the strategy that produced it. Spot holdings never go below zero;
+    /// on futures a sell from flat opens a short.
+    pub fn record_strategy_fill(&mut self, strategy: &str, symbol: &str, is_buy: bool, quantity: f64) {
+        let spot = self.executor.market_type() == crate::market_data::downloader::MarketType::Spot;
+        let held = self.strategy_exposure.entry(strategy.to_string()).or_default().entry(symbol.to_string()).or_insert(0.0);
+        *held += if is_buy { quantity } else { -quantity };
+        if spot && *held < 0.0 {
+            *held = 0.0;
+        }
+    }
+
+    /// Quantity of a symbol a strategy bought and still holds
+    pub fn strategy_holding(&self, strategy: &str, symbol: &str) -> f64 {
+        self.strategy_exposure.get(strategy).and_then(|held| held.get(symbol)).copied().unwrap_or(0.0)
+    }
+
+    /// Notional a strategy holds open across symbols, marked to the last traded price,
+    /// plus what its in-flight entries have reserved
+    pub fn strategy_open_notional(&self, strategy: &str) -> f64 {
+        let held = self.strategy_exposure.get(strategy).map_or(0.0, |held| {
+            held.iter()
+                .filter_map(|(symbol, quantity)| self.price_cache.mark_price(symbol).map(|price| quantity.abs() * price))
+                .sum()
+        });
+        held + self.strategy_reserved.get(strategy).copied().unwrap_or(0.0)
+    }
+
+    /// Holds `notional` against a strategy's allocation while its entry is on the way to the exchange
+    pub fn reserve_notional(&mut self, strategy: &str, notional: f64) {
+        *self.strategy_reserved.entry(strategy.to_string()).or_insert(0.0) += notional;
+    }
+
+    /// Releases a reservation once the exchange answered; the fill, if any, is in the exposure by then
+    pub fn release_notional(&mut self, strategy: &str, notional: f64) {
+        if let Some(reserved) = self.strategy_reserved.get_mut(strategy) {
+            *reserved = (*reserved - notional).max(0.0);
+        }
+    }
+
+    /// Forgets every strategy's holding of a symbol once it has been closed out
+    pub fn clear_symbol_exposure(&mut self, symbol: &str) {
+        for held in self.strategy_exposure.values_mut() {
+            held.remove(symbol);
+        }
+    }
+
     pub fn push_data_point(
         &mut self, 
         price: f64, 
@@ -186,15 +427,33 @@ impl AppState {
             volume,
             state: self.state_machine.get_state(),
             action,
-            strategy_latency: strat_lat,
-            execution_latency: exec_lat,
+            strategy_latency: if self.record_latencies { strat_lat } else { 0 },
+            execution_latency: if self.record_latencies { exec_lat } else { 0 },
             spread,
             equity,
         };
         
         // Only record data point if we're at a sampling interval
         self.data_point_counter += 1;
-        if self.sample_rate <= 1 || self.data_point_counter % self.sample_rate == 0 {
+        let sample_rate = self.effective_sample_rate();
+        if sample_rate <= 1 || self.data_point_counter % sample_rate == 0 {
+            if self.stream_live {
+                // Errors only when nobody is subscribed
+                let _ = LIVE_TX.send(LiveEvent::DataPoint(dp.clone()));
+            }
+            if self.stream_replay {
+                let _ = REPLAY_TX.send(ReplayEvent {
+                    symbol: self.symbol.clone(),
+                    strategy_name: self.strategy_name.clone(),
+                    data_point: dp.clone(),
+                });
+            }
+            // Full: drop every other point and sample half as often, so the chart keeps
+            // covering the whole session instead of only the newest `max_history` points
+            if self.history.len() >= self.max_history && self.max_history >= 2 {
+                self.history = self.history.iter().step_by(2).cloned().collect();
+                self.history_thinning *= 2;
+            }
             self.history.push_back(dp);
             if self.history.len() > self.max_history {
                 self.history.pop_front();
@@ -202,12 +461,47 @@ impl AppState {
         }
     }
 
+    /// Configured `sample_rate` scaled by how often the history has been thinned
+    pub fn effective_sample_rate(&self) -> usize {
+        self.sample_rate.max(1) * self.history_thinning
+    }
+
+    /// Latest portfolio snapshot value, or the starting balance before the first snapshot
+    pub fn total_value(&self) -> f64 {
+        self.portfolio_history.back().map_or(self.initial_balance, |s| s.total_value_usd)
+    }
+
+    /// Adds realized PnL to both the all-time and the daily totals
+    pub fn record_realized_pnl(&mut self, pnl: f64) {
+        self.realized_pnl += pnl;
+        self.daily_pnl += pnl;
+    }
+
+    /// Daily session a unix time (secs) falls in; sessions start at `daily_reset_hour` UTC
+    pub fn daily_session_at(&self, now_secs: u64) -> u64 {
+        now_secs.saturating_sub(self.daily_reset_hour * 3600) / 86_400
+    }
+
+    /// Starts a new daily session from the portfolio value `start_value`
+    pub fn reset_daily(&mut self, now_secs: u64, start_value: f64) {
+        self.daily_pnl = 0.0;
+        self.daily_trades = 0;
+        self.daily_start_value = start_value;
+        self.daily_session = self.daily_session_at(now_secs);
+    }
+
     pub fn clear_all_data(&mut self) {
         self.history.clear();
         self.total_trades = 0;
         self.win_trades = 0;
         self.loss_trades = 0;
         self.realized_pnl = 0.0;
+        self.gross_profit = 0.0;
+        self.gross_loss = 0.0;
+        self.daily_pnl = 0.0;
+        self.daily_trades = 0;
+        self.opportunity_seq = 0;
+        self.history_thinning = 1;
         self.state_machine.transition_to(SystemState::Booting);
     }
 }
@@ -215,35 +509,93 @@ impl AppState {
 pub type SharedState = Arc<RwLock<AppState>>;
 
 
-#[derive(Serialize)]
+/// Body of `GET /api/status`; its JSON schema is served at `GET /api/schema`
+#[derive(Serialize, JsonSchema)]
 struct StatusResponse {
+    /// Current state of the trading state machine
     state: SystemState,
+    /// Strategy decision latency, microseconds
     strategy_metrics: LatencyStats,
+    /// Order execution latency, microseconds
     execution_metrics: LatencyStats,
+    /// RUN_MODE: "live" or "backtest"
     run_mode: String,
     strategy_name: String,
+    /// Latest feature values reported by the active strategy
     features: std::collections::HashMap<String, String>,
+    /// Observed state-to-state transition frequencies, 5x5 indexed [from][to]
     transition_probabilities: Vec<Vec<f64>>,
+    /// Transition probabilities inferred from live market scores, 5x5 indexed [from][to]
     inferred_probabilities: Vec<Vec<f64>>,
     wallet: WalletInfo,
     positions: Vec<crate::execution::PositionInfo>,
+    /// Symbol the dashboard is focused on
     symbol: String,
     available_markets: Vec<String>,
+    /// Opportunities from the latest strategy pass
     opportunities: Vec<crate::strategy::Opportunity>,
     selected_opportunity_id: Option<String>,
     total_trades: u64,
     win_trades: u64,
     loss_trades: u64,
+    /// Percentage of closed trades that were profitable
     win_rate: f64,
+    /// In the quote asset
     realized_pnl: f64,
+    /// Open positions marked to the last price
+    unrealized_pnl: f64,
+    /// Realized results keyed by strategy name
+    strategy_pnl: std::collections::HashMap<String, StrategyPnl>,
+    /// Unix seconds of the last strategy update
     last_update_ts: u64,
     risk_report: crate::strategy::RiskReport,
+    /// Capital used and left per strategy listed in STRATEGY_ALLOCATIONS
+    allocations: Vec<crate::strategy::risk::AllocationStatus>,
     portfolio_history: Vec<PortfolioSnapshot>,
+    /// Account trade statistics from the exchange
     trade_stats: crate::execution::TradeStats,
     is_trading: bool,
+    /// Change in total portfolio value (balances at current prices) as a percentage of the initial balance
     yield_pct: f64,
+    /// Realized PnL since the daily session started (DAILY_RESET_HOUR_UTC)
+    daily_pnl: f64,
+    /// Executed trades since the daily session started
+    daily_trades: u64,
+    /// Portfolio value when the daily session started
+    daily_start_value: f64,
     available_strategies: Vec<String>,
+    /// 0-100, share of received market data not dropped as duplicate, out of order or outlier
     data_quality_score: f64,
+    /// 0-100, the same for the current symbol only
+    symbol_data_quality: f64,
+    /// Effective: 1 in N data points is kept in history
+    sample_rate: usize,
+    ws_connected: bool,
+    ws_reconnect_attempts: u64,
+    /// Market event queue fill level and events dropped by its backpressure policy
+    event_queue: crate::market_data::queue::QueueStatus,
+    circuit_breaker: crate::strategy::CircuitBreakerStatus,
+    /// Only set when running on futures
+    funding_rate: Option<f64>,
+    last_reconciliation: Option<crate::execution::ReconciliationReport>,
+    /// Whether live orders may reach the exchange
+    trading_armed: bool,
+    /// Binance request weight used in the current minute
+    api_weight_used: u32,
+    /// Orders skipped for insufficient balance
+    orders_rejected_balance: u64,
+    /// Orders skipped for being below the minimum notional
+    orders_rejected_notional: u64,
+    /// Orders the exchange rejected with an error
+    orders_api_error: u64,
+    /// Market entries netted or dropped against recent opposing orders (NETTING)
+    netting: crate::execution::NettingStatus,
+    /// Live market orders re-sent after connection failures or looked up after ambiguous ones (ORDER_RETRY_*)
+    order_retries: crate::execution::RetryStatus,
+    /// Price balances, positions and PnL are valued at (VALUATION_PRICE_SOURCE)
+    valuation_price_source: crate::market_data::PriceSource,
+    /// Database connection pool usage and background write backlog
+    db_pool: crate::database::DbPoolStatus,
 }
 
 #[derive(Deserialize)]
@@ -254,10 +606,57 @@ struct BacktestRequest {
     end_ts: Option<u64>,
     #[serde(default)]
     fast_mode: bool,
+    #[serde(default)]
+    walk_forward: Option<WalkForwardConfig>,
+    #[serde(default)]
+    granularity: Option<String>, // "aggTrades" (default) or a kline interval: "1m", "5m", "15m", "1h"
+    #[serde(default)]
+    initial_capital: Option<f64>, // Starting USDT per strategy run; defaults to DEFAULT_SIM_BALANCE_USDT
+    #[serde(default)]
+    margin: Option<MarginConfig>,
+    #[serde(default)]
+    replay_speed: Option<f64>, // e.g. 10.0 = 10x real time, streamed on /api/backtest/replay; unset = max speed
 }
 
-#[derive(Serialize)]
-struct StrategyReport {
+/// Borrowing cost of a spot margin strategy: buys may spend up to `borrowed_notional` beyond
+/// the quote balance, and whatever is drawn accrues `daily_interest_rate` (a fraction, e.g. 0.0002)
+#[derive(Deserialize, Clone, Copy)]
+struct MarginConfig {
+    daily_interest_rate: f64,
+    borrowed_notional: f64,
+}
+
+impl MarginConfig {
+    fn validate(&self) -> Result<(), String> {
+        if !self.daily_interest_rate.is_finite() || self.daily_interest_rate < 0.0 {
+            return Err(format!("Invalid margin.daily_interest_rate {}: expected a fraction >= 0", self.daily_interest_rate));
+        }
+        if !self.borrowed_notional.is_finite() || self.borrowed_notional < 0.0 {
+            return Err(format!("Invalid margin.borrowed_notional {}: expected >= 0", self.borrowed_notional));
+        }
+        Ok(())
+    }
+
+    /// Quote drawn on the credit line: the part of the open longs paid for beyond equity,
+    /// which shows as a negative quote balance
+    fn borrowed(&self, quote_balance: f64) -> f64 {
+        (-quote_balance).clamp(0.0, self.borrowed_notional)
+    }
+
+    /// Interest owed for holding `borrowed` over `elapsed_ms`
+    fn interest(&self, borrowed: f64, elapsed_ms: u64) -> f64 {
+        borrowed * self.daily_interest_rate * elapsed_ms as f64 / MS_PER_DAY
+    }
+}
+
+#[derive(Deserialize, Clone, Copy)]
+struct WalkForwardConfig {
+    train_pct: f64, // Fraction of each window used as in-sample (0.0 - 1.0)
+    windows: usize,
+}
+
+#[derive(Serialize, Clone)]
+pub struct StrategyReport {
     symbol: String,
     strategy_name: String,
     history: Vec<DataPoint>,
@@ -265,56 +664,140 @@ struct StrategyReport {
     total_trades: u64,
     win_rate: f64,
     yield_pct: f64,
+    /// Yield with the positions still open marked at the last replayed price
+    marked_yield_pct: f64,
     realized_pnl: f64,
     max_drawdown: f64,
+    /// Longest time from an equity peak until it was regained (or the backtest ended)
+    max_drawdown_duration_ms: u64,
+    /// (timestamp, running peak equity - equity) at each `history` point
+    drawdown_curve: Vec<(u64, f64)>,
     profit_factor: f64,
     avg_win: f64,
     avg_loss: f64,
     sharpe_ratio: f64,
+    sortino_ratio: f64,
+    calmar_ratio: f64,
     total_fees: f64,
+    total_slippage: f64, // Cost of simulated slippage, reported separately from fees
+    /// Margin interest deducted from realized PnL; 0 unless the request set `margin`
+    total_interest_paid: f64,
+    /// Buy-and-hold yield over the same trades; set by the combinatorial backtest only
+    benchmark_yield_pct: Option<f64>,
+    alpha: Option<f64>, // marked_yield_pct - benchmark_yield_pct, both marked to market
+    /// (bin lower edge %, closed trades) over per-trade returns; see `return_histogram`
+    return_histogram: Vec<(f64, u64)>,
+    walk_forward: Option<WalkForwardReport>,
+    #[serde(skip)]
+    trade_pnls: Vec<f64>,
 }
 
-#[derive(Serialize)]
-struct BacktestReport {
+#[derive(Serialize, Clone)]
+pub struct WalkForwardReport {
+    windows: Vec<WalkForwardWindow>,
+    oos_sharpe: f64, // Sharpe over all out-of-sample trades combined
+    oos_total_trades: usize,
+}
+
+#[derive(Serialize, Clone)]
+pub struct WalkForwardWindow {
+    window: usize,
+    start_ts: u64,
+    split_ts: u64,
+    end_ts: u64,
+    in_sample: WindowMetrics,
+    out_of_sample: WindowMetrics,
+}
+
+#[derive(Serialize, Clone)]
+pub struct WindowMetrics {
+    total_trades: u64,
+    win_rate: f64,
+    yield_pct: f64,
+    realized_pnl: f64,
+    max_drawdown: f64,
+    sharpe_ratio: f64,
+}
+
+impl From<&StrategyReport> for WindowMetrics {
+    fn from(report: &StrategyReport) -> Self {
+        Self {
+            total_trades: report.total_trades,
+            win_rate: report.win_rate,
+            yield_pct: report.yield_pct,
+            realized_pnl: report.realized_pnl,
+            max_drawdown: report.max_drawdown,
+            sharpe_ratio: report.sharpe_ratio,
+        }
+    }
+}
+
+#[derive(Serialize, Clone)]
+pub struct BacktestReport {
     reports: Vec<StrategyReport>,
     initial_capital: f64,
+    cancelled: bool, // Reports are partial when the run was cancelled
+    correlation: CorrelationMatrix,
+}
+
+/// Pairwise Pearson correlation of the reports' equity curve returns, labelled "SYMBOL/strategy"
+#[derive(Serialize, Clone, Default)]
+pub struct CorrelationMatrix {
+    labels: Vec<String>,
+    matrix: Vec<Vec<f64>>,
 }
 
-#[derive(Serialize, Default, Clone)]
+#[derive(Serialize, Default, Clone, JsonSchema)]
 struct WalletInfo {
-    usdt: f64,
-    btc: f64,
+    /// Base asset of the current symbol, e.g. "BTC"
+    base_asset: String,
+    base: f64,
+    /// Asset pairs are quoted in (QUOTE_ASSET), e.g. "USDT"
+    quote_asset: String,
+    quote: f64,
+    /// Every balance the executor reports
     all_balances: Vec<CoinBalance>,
 }
 
-#[derive(Serialize, Clone)]
+#[derive(Serialize, Clone, JsonSchema)]
 struct CoinBalance {
     coin: String,
     amount: f64,
 }
 
 async fn get_status(State(state): State<SharedState>) -> Json<StatusResponse> {
-    let read_guard = state.read().await;
-    let strategy_stats = read_guard.metrics.get_strategy_stats();
-    let execution_stats = read_guard.metrics.get_execution_stats();
-    
+    // Exchange calls run without the state lock so a slow API can't stall the event loop
+    let (executor, symbol) = {
+        let read_guard = state.read().await;
+        (read_guard.executor.clone(), read_guard.symbol.clone())
+    };
+
     // Fetch real-time wallet data
-    let balances = read_guard.executor.get_balances().await.unwrap_or_default();
-    let usdt = balances.iter().find(|(k, _)| k == "USDT").map(|(_, v)| *v).unwrap_or(0.0);
-    let btc = balances.iter().find(|(k, _)| k == "BTC").map(|(_, v)| *v).unwrap_or(0.0);
+    let balances = executor.get_balances().await.unwrap_or_default();
+    let quote_asset = executor.quote_asset().to_string();
+    let base_asset = crate::execution::base_asset(&symbol, &quote_asset).to_string();
+    let balance_of = |asset: &str| balances.iter().find(|(k, _)| k == asset).map(|(_, v)| *v).unwrap_or(0.0);
+    let (base, quote) = (balance_of(&base_asset), balance_of(&quote_asset));
     
     // Convert all balances to CoinBalance struct
     let all_balances: Vec<CoinBalance> = balances.iter()
         .map(|(coin, amount)| CoinBalance { coin: coin.clone(), amount: *amount })
         .collect();
     
-    let wallet = WalletInfo { usdt, btc, all_balances };
+    let wallet = WalletInfo { base_asset, base, quote_asset, quote, all_balances };
     
     // Fetch real-time positions
-    let positions = read_guard.executor.get_positions().await.unwrap_or_default();
+    let mut positions = executor.get_positions().await.unwrap_or_default();
     
     // Fetch trade statistics from Binance
-    let trade_stats = read_guard.executor.get_trade_stats(&read_guard.symbol).await.unwrap_or_default();
+    let trade_stats = executor.get_trade_stats(&symbol).await.unwrap_or_default();
+
+    let read_guard = state.read().await;
+    // Positions, yield and snapshots are all valued at the one configured price source
+    read_guard.price_cache.mark_positions(&mut positions);
+    let unrealized_pnl = positions.iter().map(|p| p.unrealized_pnl).sum();
+    let strategy_stats = read_guard.metrics.get_strategy_stats();
+    let execution_stats = read_guard.metrics.get_execution_stats();
 
     // Calculate win rate
     let win_rate = if read_guard.total_trades > 0 {
@@ -324,10 +807,10 @@ async fn get_status(State(state): State<SharedState>) -> Json<StatusResponse> {
     };
 
     // Calculate yield
-    let total_value = if !read_guard.portfolio_history.is_empty() {
-        read_guard.portfolio_history.back().unwrap().total_value_usd
+    let total_value = if balances.is_empty() {
+        read_guard.total_value()
     } else {
-        read_guard.initial_balance
+        read_guard.price_cache.portfolio_value(&balances)
     };
     let yield_pct = ((total_value - read_guard.initial_balance) / read_guard.initial_balance) * 100.0;
 
@@ -351,17 +834,88 @@ async fn get_status(State(state): State<SharedState>) -> Json<StatusResponse> {
         loss_trades: read_guard.loss_trades,
         win_rate,
         realized_pnl: read_guard.realized_pnl,
+        unrealized_pnl,
+        strategy_pnl: read_guard.strategy_pnl.clone(),
         last_update_ts: read_guard.last_update_ts,
         risk_report: read_guard.risk_report.clone(),
+        allocations: crate::strategy::RiskManager::allocation_status(&read_guard),
         portfolio_history: read_guard.portfolio_history.iter().cloned().collect(),
         trade_stats,
         is_trading: read_guard.is_trading,
         yield_pct,
+        daily_pnl: read_guard.daily_pnl,
+        daily_trades: read_guard.daily_trades,
+        daily_start_value: read_guard.daily_start_value,
         available_strategies: read_guard.available_strategies.clone(),
         data_quality_score: read_guard.data_quality_score,
+        symbol_data_quality: read_guard.symbol_data_quality,
+        sample_rate: read_guard.effective_sample_rate(),
+        ws_connected: read_guard.connection_health.is_connected(),
+        ws_reconnect_attempts: read_guard.connection_health.reconnect_attempts(),
+        event_queue: read_guard.market_sender.status(),
+        circuit_breaker: read_guard.circuit_breaker.status(),
+        funding_rate: read_guard.funding_rates.get(&read_guard.symbol).copied(),
+        last_reconciliation: read_guard.last_reconciliation.clone(),
+        trading_armed: read_guard.trading_armed.load(std::sync::atomic::Ordering::SeqCst),
+        api_weight_used: read_guard.executor.api_weight_used(),
+        orders_rejected_balance: read_guard.order_rejections.balance.load(std::sync::atomic::Ordering::Relaxed),
+        orders_rejected_notional: read_guard.order_rejections.notional.load(std::sync::atomic::Ordering::Relaxed),
+        orders_api_error: read_guard.order_rejections.api_error.load(std::sync::atomic::Ordering::Relaxed),
+        netting: read_guard.executor.netting_status(),
+        order_retries: read_guard.executor.order_retries(),
+        valuation_price_source: read_guard.price_cache.source(),
+        db_pool: crate::database::DbPoolStatus::new(&read_guard.db_pool, &read_guard.db_writes),
     })
 }
 
+/// Liveness: the main loop is still consuming market events and the database answers.
+/// 503 when either is failing; the body names the failing component.
+async fn get_health(State(state): State<SharedState>) -> (StatusCode, Json<serde_json::Value>) {
+    let (pool, last_update_ts, max_age) = {
+        let read_guard = state.read().await;
+        (read_guard.db_pool.clone(), read_guard.last_update_ts, read_guard.max_event_age_secs)
+    };
+    let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs();
+    let event_age = now.saturating_sub(last_update_ts);
+    let event_loop_ok = event_age <= max_age;
+
+    let db_result = tokio::time::timeout(
+        std::time::Duration::from_secs(2),
+        sqlx::query("SELECT 1").execute(&pool)
+    ).await;
+    let db_error = match db_result {
+        Ok(Ok(_)) => None,
+        Ok(Err(e)) => Some(e.to_string()),
+        Err(_) => Some("timed out".to_string()),
+    };
+
+    let healthy = event_loop_ok && db_error.is_none();
+    let status = if healthy { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+    (status, Json(serde_json::json!({
+        "status": if healthy { "ok" } else { "degraded" },
+        "components": {
+            "event_loop": { "ok": event_loop_ok, "last_event_age_secs": event_age, "max_age_secs": max_age },
+            "database": { "ok": db_error.is_none(), "error": db_error },
+        }
+    })))
+}
+
+/// Readiness: migrations ran and every market data stream is connected
+async fn get_ready(State(state): State<SharedState>) -> (StatusCode, Json<serde_json::Value>) {
+    let read_guard = state.read().await;
+    let migrations_ok = read_guard.migrations_applied;
+    let market_data_ok = read_guard.connection_health.is_connected();
+    let ready = migrations_ok && market_data_ok;
+    let status = if ready { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+    (status, Json(serde_json::json!({
+        "status": if ready { "ready" } else { "not_ready" },
+        "components": {
+            "migrations": { "ok": migrations_ok },
+            "market_data": { "ok": market_data_ok, "reconnect_attempts": read_guard.connection_health.reconnect_attempts() },
+        }
+    })))
+}
+
 #[derive(Deserialize)]
 struct HistoryQuery {
     interval: Option<String>, // "1m", "1h" or None for raw
@@ -404,6 +958,144 @@ async fn get_history(
     Json(read_guard.history.iter().cloned().collect())
 }
 
+#[derive(Deserialize)]
+struct CandlesQuery {
+    interval: Option<String>, // "1m", "5m", "15m", "1h", "4h", "1d"
+    symbol: Option<String>,
+    market_type: Option<String>,
+}
+
+async fn get_candles(
+    State(state): State<SharedState>,
+    Query(query): Query<CandlesQuery>
+) -> Json<serde_json::Value> {
+    let (pool, symbol) = {
+        let read_guard = state.read().await;
+        (read_guard.db_pool.clone(), query.symbol.unwrap_or_else(|| read_guard.symbol.clone()))
+    };
+    let interval = query.interval.unwrap_or_else(|| "1m".to_string());
+    let Some(interval_secs) = candle_interval_secs(&interval) else {
+        return Json(serde_json::json!({ "error": format!("Unsupported interval: {}", interval) }));
+    };
+    let market_type = query.market_type.unwrap_or_else(|| "SPOT".to_string());
+
+    match repository::get_ohlcv(&pool, &symbol, &market_type, interval_secs).await {
+        Ok(candles) => Json(serde_json::json!({
+            "symbol": symbol,
+            "interval": interval,
+            "candles": candles
+        })),
+        Err(e) => Json(serde_json::json!({ "error": e.to_string() }))
+    }
+}
+
+/// Bucket width for the candle interval names accepted by /api/candles and /api/trades
+fn candle_interval_secs(interval: &str) -> Option<i64> {
+    match interval {
+        "1m" => Some(60),
+        "5m" => Some(300),
+        "15m" => Some(900),
+        "1h" => Some(3600),
+        "4h" => Some(14400),
+        "1d" => Some(86400),
+        _ => None,
+    }
+}
+
+/// Upper bound on buckets a single /api/trades request may return
+const MAX_TRADE_BUCKETS: i64 = 5000;
+
+#[derive(Deserialize)]
+struct TradesQuery {
+    symbol: Option<String>,
+    market_type: Option<String>,
+    start: Option<i64>, // Unix ms; defaults to 24 hours before `end`
+    end: Option<i64>,   // Unix ms; defaults to now
+    interval: Option<String>, // Same names as /api/candles; defaults to "1m"
+}
+
+/// OHLCV buckets for an arbitrary time window, so charts can zoom without loading everything
+async fn get_trades_range(
+    State(state): State<SharedState>,
+    Query(query): Query<TradesQuery>
+) -> (StatusCode, Json<serde_json::Value>) {
+    let (pool, symbol) = {
+        let read_guard = state.read().await;
+        (read_guard.db_pool.clone(), query.symbol.unwrap_or_else(|| read_guard.symbol.clone()))
+    };
+    let interval = query.interval.unwrap_or_else(|| "1m".to_string());
+    let Some(interval_secs) = candle_interval_secs(&interval) else {
+        return (StatusCode::BAD_REQUEST, Json(serde_json::json!({ "error": format!("Unsupported interval: {}", interval) })));
+    };
+    let end = query.end.unwrap_or_else(|| {
+        std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_millis() as i64
+    });
+    let start = query.start.unwrap_or(end.saturating_sub(24 * 3600 * 1000));
+    if start >= end {
+        return (StatusCode::BAD_REQUEST, Json(serde_json::json!({ "error": "start must be before end" })));
+    }
+    // A span too wide for i64 is over any limit
+    let buckets = end.checked_sub(start).map_or(i64::MAX, |span| span / (interval_secs * 1000) + 1);
+    if buckets > MAX_TRADE_BUCKETS {
+        return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "error": format!("Range spans {} {} buckets, the limit is {}; use a larger interval or a shorter range", buckets, interval, MAX_TRADE_BUCKETS)
+        })));
+    }
+    let market_type = query.market_type.unwrap_or_else(|| "SPOT".to_string()).to_uppercase();
+
+    match repository::get_ohlcv_range(&pool, &symbol, &market_type, interval_secs, start, end).await {
+        Ok(candles) => (StatusCode::OK, Json(serde_json::json!({
+            "symbol": symbol,
+            "market_type": market_type,
+            "interval": interval,
+            "start": start,
+            "end": end,
+            "candles": candles
+        }))),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({ "error": e.to_string() })))
+    }
+}
+
+#[derive(Deserialize)]
+struct PortfolioHistoryQuery {
+    since: Option<u64>, // Unix seconds; defaults to the last 24 hours
+}
+
+async fn get_portfolio_history(
+    State(state): State<SharedState>,
+    Query(query): Query<PortfolioHistoryQuery>
+) -> Json<serde_json::Value> {
+    let pool = state.read().await.db_pool.clone();
+    let since = query.since.unwrap_or_else(|| {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+            .saturating_sub(24 * 3600)
+    });
+
+    match repository::get_portfolio_snapshots(&pool, since).await {
+        Ok(snapshots) => Json(serde_json::json!({ "since": since, "snapshots": snapshots })),
+        Err(e) => Json(serde_json::json!({ "error": e.to_string() }))
+    }
+}
+
+#[derive(Deserialize)]
+struct JournalQuery {
+    limit: Option<usize>, // Defaults to 100
+}
+
+async fn get_journal(
+    State(state): State<SharedState>,
+    Query(query): Query<JournalQuery>
+) -> Json<serde_json::Value> {
+    let pool = state.read().await.db_pool.clone();
+    match repository::get_round_trips(&pool, query.limit.unwrap_or(100)).await {
+        Ok(round_trips) => Json(serde_json::json!({ "round_trips": round_trips })),
+        Err(e) => Json(serde_json::json!({ "error": e.to_string() }))
+    }
+}
+
 // Simple embedded HTML dashboard
 async fn get_dashboard() -> axum::response::Html<&'static str> {
     axum::response::Html(include_str!("dashboard.html"))
@@ -421,61 +1113,443 @@ struct ChangeSymbolQuery {
 #[derive(Deserialize)]
 struct SelectStrategyQuery {
     strategy: String,
+    #[serde(default)]
+    on_open_positions: OpenPositionPolicy,
+}
+
+/// What a strategy switch does when positions are open. The new strategy starts from
+/// scratch, so without care an open position is left with nothing managing its exit.
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+enum OpenPositionPolicy {
+    /// Reject the switch with 409 and list the open positions
+    #[default]
+    Refuse,
+    /// Close every position at market, then switch
+    Flatten,
+    /// Switch and hand the positions to the new strategy (`TradingStrategy::adopt_position`)
+    Carry,
 }
 
 async fn select_strategy(
     State(state): State<SharedState>,
     Json(payload): Json<SelectStrategyQuery>
-) -> Json<serde_json::Value> {
+) -> (StatusCode, Json<serde_json::Value>) {
+    let (executor, price_cache, current, symbols) = {
+        let read_guard = state.read().await;
+        if !read_guard.available_strategies.contains(&payload.strategy) {
+            return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+                "status": "error",
+                "error": format!("Unknown strategy: {}", payload.strategy)
+            })));
+        }
+        (read_guard.executor.clone(), read_guard.price_cache.clone(), read_guard.strategy_name.clone(), read_guard.symbols.clone())
+    };
+    if current == payload.strategy {
+        return (StatusCode::OK, Json(serde_json::json!({ "status": "success", "strategy": payload.strategy })));
+    }
+
+    // Flattening sells what open orders would otherwise keep locked
+    if payload.on_open_positions == OpenPositionPolicy::Flatten
+        && let Err(e) = cancel_open_orders(&executor, &symbols).await {
+        return (StatusCode::BAD_GATEWAY, Json(serde_json::json!({ "status": "error", "error": e })));
+    }
+    let closes = match closing_signals(&executor, &price_cache, &symbols).await {
+        Ok(closes) => closes,
+        Err(e) => return (StatusCode::BAD_GATEWAY, Json(serde_json::json!({ "status": "error", "error": e }))),
+    };
+    let mut flattened = Vec::new();
+    let mut carried = Vec::new();
+    if !closes.is_empty() {
+        let open: Vec<serde_json::Value> = closes.iter()
+            .filter_map(|s| match s {
+                Signal::Buy { symbol, quantity, .. } => Some(serde_json::json!({ "symbol": symbol, "side": "Short", "quantity": quantity })),
+                Signal::Sell { symbol, quantity, .. } => Some(serde_json::json!({ "symbol": symbol, "side": "Long", "quantity": quantity })),
+                _ => None,
+            })
+            .collect();
+        match payload.on_open_positions {
+            OpenPositionPolicy::Refuse => {
+                log::warn!("Strategy switch {} -> {} refused: {} open positions", current, payload.strategy, open.len());
+                return (StatusCode::CONFLICT, Json(serde_json::json!({
+                    "status": "error",
+                    "error": "Open positions would be left unmanaged; retry with on_open_positions = flatten or carry",
+                    "open_positions": open
+                })));
+            }
+            OpenPositionPolicy::Flatten => {
+                log::warn!("Strategy switch {} -> {}: flattening {} open positions first", current, payload.strategy, open.len());
+                flattened = execute_closes(&executor, closes).await;
+                if flattened.iter().any(|r| r["status"] != "success") {
+                    return (StatusCode::BAD_GATEWAY, Json(serde_json::json!({
+                        "status": "error",
+                        "error": "Could not flatten every position; strategy not switched",
+                        "results": flattened
+                    })));
+                }
+            }
+            OpenPositionPolicy::Carry => {
+                log::warn!("Strategy switch {} -> {}: carrying {} open positions into the new strategy", current, payload.strategy, open.len());
+                // Spot live reports no positions, so fall back to the balances being carried, at
+                // their FIFO cost. Without a recorded buy the entry stays 0 and no strategy adopts it.
+                let positions = executor.get_positions().await.unwrap_or_default();
+                let read_guard = state.read().await;
+                carried = closes.iter()
+                    .filter_map(|signal| match signal {
+                        Signal::Buy { symbol, quantity, .. } => Some((symbol, "Short", *quantity)),
+                        Signal::Sell { symbol, quantity, .. } => Some((symbol, "Long", *quantity)),
+                        _ => None,
+                    })
+                    .map(|(symbol, side, amount)| {
+                        positions.iter().find(|p| &p.symbol == symbol).cloned().unwrap_or(crate::execution::PositionInfo {
+                            symbol: symbol.clone(),
+                            amount,
+                            entry_price: read_guard.cost_basis.avg_unit_cost(symbol).unwrap_or(0.0),
+                            unrealized_pnl: 0.0,
+                            market_type: executor.market_type().as_str().to_string(),
+                            side: side.to_string(),
+                        })
+                    })
+                    .collect();
+            }
+        }
+    }
+
     let mut write_guard = state.write().await;
+    for result in flattened.iter().filter(|r| r["status"] == "success") {
+        if let Some(symbol) = result["symbol"].as_str() {
+            write_guard.clear_symbol_exposure(symbol);
+        }
+    }
+    // Carried positions count against the new strategy's allocation
+    if !carried.is_empty()
+        && let Some(held) = write_guard.strategy_exposure.remove(&current) {
+        let target = write_guard.strategy_exposure.entry(payload.strategy.clone()).or_default();
+        for (symbol, quantity) in held {
+            *target.entry(symbol).or_insert(0.0) += quantity;
+        }
+    }
     write_guard.strategy_name = payload.strategy.clone();
+    write_guard.carried_positions = carried;
     log::info!("Strategy selection changed to: {}", payload.strategy);
-    Json(serde_json::json!({ "status": "success", "strategy": payload.strategy }))
+    (StatusCode::OK, Json(serde_json::json!({ "status": "success", "strategy": payload.strategy, "flattened": flattened })))
 }
 
-async fn change_symbol(
+#[derive(Deserialize)]
+struct StrategyConfigRequest {
+    strategy: String,
+    cooldown_ms: Option<u64>,
+    #[serde(default)]
+    params: std::collections::HashMap<String, f64>,
+}
+
+/// Stores runtime parameter overrides for a strategy. Strategies read them at signal time.
+async fn set_strategy_config(
     State(state): State<SharedState>,
-    Json(payload): Json<ChangeSymbolQuery>
-) -> Json<serde_json::Value> {
+    Json(payload): Json<StrategyConfigRequest>
+) -> (StatusCode, Json<serde_json::Value>) {
     let mut write_guard = state.write().await;
-    write_guard.symbol = payload.symbol.clone();
-    log::info!("Symbol changed to: {}", payload.symbol);
-    Json(serde_json::json!({ "status": "success", "symbol": payload.symbol }))
+    if !write_guard.available_strategies.contains(&payload.strategy) {
+        return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "status": "error",
+            "error": format!("Unknown strategy: {}", payload.strategy)
+        })));
+    }
+    if let Some((key, value)) = payload.params.iter().find(|(_, v)| !v.is_finite()) {
+        return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "status": "error",
+            "error": format!("{} must be a finite number, got {}", key, value)
+        })));
+    }
+
+    let params = write_guard.strategy_params.entry(payload.strategy.clone()).or_default();
+    params.extend(payload.params);
+    if let Some(cooldown_ms) = payload.cooldown_ms {
+        params.insert("cooldown_ms".to_string(), cooldown_ms as f64);
+    }
+    let params = params.clone();
+    log::info!("Strategy config for {} updated: {:?}", payload.strategy, params);
+    (StatusCode::OK, Json(serde_json::json!({
+        "status": "success",
+        "strategy": payload.strategy,
+        "params": params
+    })))
 }
 
-async fn get_data_range_api(
+/// Closed FIFO lots with realized gains, plus the remaining open basis per symbol
+async fn get_cost_basis(State(state): State<SharedState>) -> Json<crate::execution::CostBasisReport> {
+    Json(state.read().await.cost_basis.report())
+}
+
+/// JSON schema of the `/api/status` response, for generating typed clients
+async fn get_schema() -> Json<schemars::schema::RootSchema> {
+    Json(schemars::schema_for!(StatusResponse))
+}
+
+/// Hypothetical results of every strategy run in shadow, best yield first, next to the live strategy
+async fn get_shadow(State(state): State<SharedState>) -> Json<serde_json::Value> {
+    let read_guard = state.read().await;
+    let live_yield_pct = if read_guard.initial_balance > 0.0 {
+        read_guard.realized_pnl / read_guard.initial_balance * 100.0
+    } else { 0.0 };
+    Json(serde_json::json!({
+        "enabled": read_guard.shadow_enabled,
+        "live_strategy": read_guard.strategy_name,
+        "live_realized_pnl": read_guard.realized_pnl,
+        "live_yield_pct": live_yield_pct,
+        "strategies": *read_guard.shadow_reports.read(),
+    }))
+}
+
+/// Strategy names, descriptions, tunable parameters and feature keys
+async fn get_strategies() -> Json<Vec<crate::strategy::factory::StrategyDescriptor>> {
+    Json(crate::strategy::StrategyFactory::describe())
+}
+
+async fn get_strategy_config(State(state): State<SharedState>) -> Json<serde_json::Value> {
+    Json(serde_json::json!(state.read().await.strategy_params))
+}
+
+async fn change_symbol(
     State(state): State<SharedState>,
-    Query(params): Query<std::collections::HashMap<String, String>>
-) -> Json<serde_json::Value> {
-    let (pool, symbol) = {
-        let read_guard = state.read().await;
-        let s = params.get("symbol").cloned().unwrap_or_else(|| read_guard.symbol.clone());
-        (read_guard.db_pool.clone(), s)
+    Json(payload): Json<ChangeSymbolQuery>
+) -> (StatusCode, Json<serde_json::Value>) {
+    let symbol = match crate::market_data::validate_symbol(&payload.symbol).await {
+        Ok(symbol) => symbol,
+        Err(e) => return (StatusCode::BAD_REQUEST, Json(serde_json::json!({ "status": "error", "error": e }))),
     };
-    let market_type = params.get("market_type").map(|s| s.as_str()).unwrap_or("SPOT");
+    let mut write_guard = state.write().await;
+    write_guard.symbol = symbol.clone();
+    log::info!("Symbol changed to: {}", symbol);
+    (StatusCode::OK, Json(serde_json::json!({ "status": "success", "symbol": symbol })))
+}
 
-    match repository::get_data_range(&pool, &symbol, market_type).await {
-        Ok((min, max)) => Json(serde_json::json!({
-            "min": min,
-            "max": max
-        })),
-        Err(e) => Json(serde_json::json!({ "error": e.to_string() }))
+async fn get_prometheus_metrics(State(state): State<SharedState>) -> impl axum::response::IntoResponse {
+    let guard = state.read().await;
+    let labels = [("symbol", guard.symbol.as_str()), ("strategy", guard.strategy_name.as_str())];
+
+    let mut body = guard.metrics.to_prometheus(&labels);
+    let retries = guard.executor.order_retries();
+    let series = [
+        ("six_trading_total_trades", "Total executed trades", "counter", guard.total_trades as f64),
+        ("six_trading_win_trades", "Trades closed with a profit", "counter", guard.win_trades as f64),
+        ("six_trading_loss_trades", "Trades closed with a loss", "counter", guard.loss_trades as f64),
+        ("six_trading_realized_pnl", "Realized PnL in the quote asset", "gauge", guard.realized_pnl),
+        ("six_trading_data_quality_score", "Market data quality score (0-1)", "gauge", guard.data_quality_score),
+        ("six_trading_is_trading", "1 when trading is enabled", "gauge", if guard.is_trading { 1.0 } else { 0.0 }),
+        ("six_trading_order_retries", "Market order attempts re-sent after a connection failure", "counter", retries.retries as f64),
+        ("six_trading_order_retries_recovered", "Market orders that succeeded on a retry", "counter", retries.recovered as f64),
+        ("six_trading_order_retries_exhausted", "Market orders still failing after the last attempt", "counter", retries.exhausted as f64),
+    ];
+    for (name, help, kind, value) in series {
+        body.push_str(&crate::metrics::prometheus_metric(name, help, kind, &labels, value));
     }
+
+    ([(header::CONTENT_TYPE, "text/plain; version=0.0.4")], body)
 }
 
 #[derive(Deserialize)]
-struct DownloadDataRequest {
+struct LeverageRequest {
     symbol: String,
-    #[serde(default)]
-    market_type: Option<String>,
-    start_ts: u64,
-    end_ts: u64,
+    leverage: u32,
 }
 
-async fn download_data_api(
-    State(state): State<SharedState>,
-    Json(payload): Json<DownloadDataRequest>
+#[derive(Deserialize)]
+struct MarginTypeRequest {
+    symbol: String,
+    margin_type: String,
+}
+
+async fn set_futures_leverage(
+    State(state): State<SharedState>,
+    Json(payload): Json<LeverageRequest>
+) -> (StatusCode, Json<serde_json::Value>) {
+    if !(1..=125).contains(&payload.leverage) {
+        return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+            "status": "error",
+            "error": format!("leverage must be between 1 and 125, got {}", payload.leverage)
+        })));
+    }
+
+    let executor = state.read().await.executor.clone();
+    match executor.set_leverage(&payload.symbol, payload.leverage as u8).await {
+        Ok(()) => {
+            log::info!("Leverage for {} set to {}x", payload.symbol, payload.leverage);
+            (StatusCode::OK, Json(serde_json::json!({
+                "status": "success",
+                "symbol": payload.symbol,
+                "leverage": payload.leverage
+            })))
+        }
+        Err(e) => (StatusCode::BAD_GATEWAY, Json(serde_json::json!({ "status": "error", "error": e.to_string() })))
+    }
+}
+
+async fn set_futures_margin_type(
+    State(state): State<SharedState>,
+    Json(payload): Json<MarginTypeRequest>
+) -> (StatusCode, Json<serde_json::Value>) {
+    let margin_type = match payload.margin_type.to_uppercase().as_str() {
+        "CROSS" => crate::execution::MarginType::Cross,
+        "ISOLATED" => crate::execution::MarginType::Isolated,
+        other => {
+            return (StatusCode::BAD_REQUEST, Json(serde_json::json!({
+                "status": "error",
+                "error": format!("margin_type must be CROSS or ISOLATED, got {}", other)
+            })));
+        }
+    };
+
+    let executor = state.read().await.executor.clone();
+    match executor.set_margin_type(&payload.symbol, margin_type).await {
+        Ok(()) => {
+            log::info!("Margin type for {} set to {}", payload.symbol, margin_type);
+            (StatusCode::OK, Json(serde_json::json!({
+                "status": "success",
+                "symbol": payload.symbol,
+                "margin_type": payload.margin_type.to_uppercase()
+            })))
+        }
+        Err(e) => (StatusCode::BAD_GATEWAY, Json(serde_json::json!({ "status": "error", "error": e.to_string() })))
+    }
+}
+
+async fn get_data_range_api(
+    State(state): State<SharedState>,
+    Query(params): Query<std::collections::HashMap<String, String>>
+) -> Json<serde_json::Value> {
+    let (pool, symbol) = {
+        let read_guard = state.read().await;
+        let s = params.get("symbol").cloned().unwrap_or_else(|| read_guard.symbol.clone());
+        (read_guard.db_pool.clone(), s)
+    };
+    let market_type = params.get("market_type").map(|s| s.as_str()).unwrap_or("SPOT");
+
+    match repository::get_data_range(&pool, &symbol, market_type).await {
+        Ok((min, max)) => Json(serde_json::json!({
+            "min": min,
+            "max": max
+        })),
+        Err(e) => Json(serde_json::json!({ "error": e.to_string() }))
+    }
+}
+
+/// Share of 1-minute (or `bucket_secs`) buckets in `[start, end)` that hold stored trades, with the
+/// empty stretches. `backfill=true` downloads the empty stretches first and reports the result.
+async fn get_data_coverage(
+    State(state): State<SharedState>,
+    Query(params): Query<std::collections::HashMap<String, String>>
+) -> (StatusCode, Json<serde_json::Value>) {
+    let (pool, symbol) = {
+        let read_guard = state.read().await;
+        let s = params.get("symbol").cloned().unwrap_or_else(|| read_guard.symbol.clone());
+        (read_guard.db_pool.clone(), s)
+    };
+    let market_type = match params.get("market_type").map(|s| s.to_uppercase()).as_deref() {
+        Some("FUTURES") => MarketType::Futures,
+        _ => MarketType::Spot,
+    };
+    let parse = |key: &str| params.get(key).and_then(|v| v.parse::<u64>().ok());
+    let (Some(start_ts), Some(end_ts)) = (parse("start"), parse("end")) else {
+        return (StatusCode::BAD_REQUEST, Json(serde_json::json!({ "error": "start and end (ms) are required" })));
+    };
+    let bucket_ms = parse("bucket_secs").filter(|v| *v > 0).unwrap_or(60) * 1000;
+    let backfill = params.get("backfill").is_some_and(|v| v == "true" || v == "1");
+
+    let downloader = crate::market_data::HistoricalDownloader::new(pool);
+    let mut coverage = match downloader.coverage(&symbol, market_type, start_ts, end_ts, bucket_ms).await {
+        Ok(coverage) => coverage,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({ "error": e.to_string() }))),
+    };
+    let backfilled = backfill && !coverage.missing.is_empty();
+    if backfilled {
+        log::info!("Backfilling {} empty stretches for {} ({:.1}% coverage)", coverage.missing.len(), symbol, coverage.coverage_pct);
+        if let Err(e) = downloader.fill_gaps(&symbol, market_type, &coverage.missing).await {
+            return (StatusCode::BAD_GATEWAY, Json(serde_json::json!({ "error": e.to_string() })));
+        }
+        coverage = match downloader.coverage(&symbol, market_type, start_ts, end_ts, bucket_ms).await {
+            Ok(coverage) => coverage,
+            Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({ "error": e.to_string() }))),
+        };
+    }
+
+    (StatusCode::OK, Json(serde_json::json!({
+        "symbol": symbol,
+        "market_type": market_type.as_str(),
+        "start": start_ts,
+        "end": end_ts,
+        "bucket_ms": coverage.bucket_ms,
+        "expected_buckets": coverage.expected_buckets,
+        "covered_buckets": coverage.covered_buckets,
+        "coverage_pct": coverage.coverage_pct,
+        "missing": coverage.missing,
+        "backfilled": backfilled
+    })))
+}
+
+/// Lists stretches without stored trades. Defaults to the whole stored range of the selected symbol.
+async fn get_data_gaps(
+    State(state): State<SharedState>,
+    Query(params): Query<std::collections::HashMap<String, String>>
+) -> (StatusCode, Json<serde_json::Value>) {
+    let (pool, symbol) = {
+        let read_guard = state.read().await;
+        let s = params.get("symbol").cloned().unwrap_or_else(|| read_guard.symbol.clone());
+        (read_guard.db_pool.clone(), s)
+    };
+    let market_type = match params.get("market_type").map(|s| s.to_uppercase()).as_deref() {
+        Some("FUTURES") => MarketType::Futures,
+        _ => MarketType::Spot,
+    };
+    let parse = |key: &str| params.get(key).and_then(|v| v.parse::<u64>().ok());
+    let max_gap_ms = parse("max_gap_ms").unwrap_or(crate::market_data::downloader::DEFAULT_MAX_GAP_MS);
+
+    let (start_ts, end_ts) = match (parse("start_ts"), parse("end_ts")) {
+        (Some(start), Some(end)) => (start, end),
+        (start, end) => match repository::get_data_range(&pool, &symbol, market_type.as_str()).await {
+            Ok((Some(min), Some(max))) => (start.unwrap_or(min), end.unwrap_or(max)),
+            Ok(_) => return (StatusCode::NOT_FOUND, Json(serde_json::json!({ "error": format!("No stored data for {}", symbol) }))),
+            Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({ "error": e.to_string() }))),
+        },
+    };
+
+    let downloader = crate::market_data::HistoricalDownloader::new(pool);
+    match downloader.find_gaps(&symbol, market_type, start_ts, end_ts, max_gap_ms).await {
+        Ok(gaps) => {
+            let missing_ms = gaps.iter().map(|g| g.end_ts.saturating_sub(g.start_ts)).fold(0u64, u64::saturating_add);
+            (StatusCode::OK, Json(serde_json::json!({
+                "symbol": symbol,
+                "market_type": market_type.as_str(),
+                "start_ts": start_ts,
+                "end_ts": end_ts,
+                "max_gap_ms": max_gap_ms,
+                "missing_ms": missing_ms,
+                "gaps": gaps
+            })))
+        }
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(serde_json::json!({ "error": e.to_string() })))
+    }
+}
+
+#[derive(Deserialize)]
+struct DownloadDataRequest {
+    symbol: String,
+    #[serde(default)]
+    market_type: Option<String>,
+    start_ts: u64,
+    end_ts: u64,
+    #[serde(default)]
+    granularity: Option<String>,
+}
+
+async fn download_data_api(
+    State(state): State<SharedState>,
+    Json(mut payload): Json<DownloadDataRequest>
 ) -> Json<serde_json::Value> {
+    payload.symbol = match crate::market_data::validate_symbol(&payload.symbol).await {
+        Ok(symbol) => symbol,
+        Err(e) => return Json(serde_json::json!({ "success": false, "error": e })),
+    };
     let db_pool = {
         let read_guard = state.read().await;
         read_guard.db_pool.clone()
@@ -490,7 +1564,22 @@ async fn download_data_api(
         _ => crate::market_data::downloader::MarketType::Spot,
     };
     
-    match downloader.fetch_and_save_range_public(&payload.symbol, market_type, payload.start_ts, payload.end_ts).await {
+    let granularity = match payload.granularity.as_deref().map(DataGranularity::parse) {
+        None => DataGranularity::AggTrades,
+        Some(Some(granularity)) => granularity,
+        Some(None) => {
+            return Json(serde_json::json!({
+                "success": false,
+                "error": format!("Unknown granularity {:?}", payload.granularity)
+            }));
+        }
+    };
+
+    let result = match granularity {
+        DataGranularity::AggTrades => downloader.fetch_and_save_range_public(&payload.symbol, market_type, payload.start_ts, payload.end_ts).await,
+        DataGranularity::Klines(interval) => downloader.fetch_and_save_klines(&payload.symbol, market_type, interval, payload.start_ts, payload.end_ts).await,
+    };
+    match result {
         Ok(_) => {
             let duration_hours = (payload.end_ts - payload.start_ts) as f64 / 3600000.0;
             Json(serde_json::json!({
@@ -508,6 +1597,23 @@ async fn download_data_api(
     }
 }
 
+// SSE endpoint for live data points and executions
+async fn sse_live_handler() -> Sse<impl tokio_stream::Stream<Item = Result<Event, Infallible>>> {
+    let rx = LIVE_TX.subscribe();
+    let stream = BroadcastStream::new(rx)
+        .filter_map(|result| {
+            match result {
+                Ok(event) => {
+                    let json = serde_json::to_string(&event).unwrap_or_default();
+                    Some(Ok(Event::default().data(json)))
+                }
+                Err(_) => None, // Lagged subscribers skip what they missed
+            }
+        });
+
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
+
 // SSE endpoint for real-time backtest progress
 async fn sse_progress_handler() -> Sse<impl tokio_stream::Stream<Item = Result<Event, Infallible>>> {
     let rx = PROGRESS_TX.subscribe();
@@ -525,14 +1631,653 @@ async fn sse_progress_handler() -> Sse<impl tokio_stream::Stream<Item = Result<E
     Sse::new(stream).keep_alive(KeepAlive::default())
 }
 
+// SSE endpoint for data points of backtests run with a replay_speed
+async fn sse_replay_handler() -> Sse<impl tokio_stream::Stream<Item = Result<Event, Infallible>>> {
+    let rx = REPLAY_TX.subscribe();
+    let stream = BroadcastStream::new(rx)
+        .filter_map(|result| {
+            match result {
+                Ok(event) => {
+                    let json = serde_json::to_string(&event).unwrap_or_default();
+                    Some(Ok(Event::default().data(json)))
+                }
+                Err(_) => None, // Lagged subscribers skip what they missed
+            }
+        });
+
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
+
+/// Longest a paced replay sleeps for one gap in the data; longer gaps are skipped over
+const MAX_REPLAY_PAUSE: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// Slowest replay_speed accepted; slower paces would mostly sit in MAX_REPLAY_PAUSE
+const MIN_REPLAY_SPEED: f64 = 0.01;
+
+/// Paces a backtest so event time advances `speed` times faster than wall time
+struct ReplayClock {
+    speed: f64,
+    anchor: Option<(std::time::Instant, u64)>, // (wall time, event time) pacing is measured from
+}
+
+impl ReplayClock {
+    fn new(speed: f64) -> Self {
+        Self { speed, anchor: None }
+    }
+
+    /// Sleeps until `event_ms` is due. Pacing against an anchor rather than per-trade
+    /// deltas keeps sub-millisecond sleeps from adding up.
+    async fn wait_until(&mut self, event_ms: u64) {
+        let (start, start_ms) = *self.anchor.get_or_insert((std::time::Instant::now(), event_ms));
+        // Far-off events can't be represented as an Instant; they are just a long gap
+        let wait = std::time::Duration::try_from_secs_f64(event_ms.saturating_sub(start_ms) as f64 / 1000.0 / self.speed)
+            .ok()
+            .and_then(|offset| start.checked_add(offset))
+            .map_or(std::time::Duration::MAX, |due| due.saturating_duration_since(std::time::Instant::now()));
+        if wait > MAX_REPLAY_PAUSE {
+            tokio::time::sleep(MAX_REPLAY_PAUSE).await;
+            self.anchor = Some((std::time::Instant::now(), event_ms));
+        } else if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+/// One backtest run of a single strategy over a slice of trades
+struct BacktestPass<'a> {
+    symbol: &'a str,
+    strategy_name: &'a str,
+    params: &'a std::collections::HashMap<String, f64>, // Overrides for StrategyFactory; empty = defaults
+    trades: &'a [binance::model::TradeEvent],
+    orderbooks: &'a [(u64, binance::model::OrderBook)], // (recorded ms, snapshot), oldest first
+    db_pool: Pool<Postgres>,
+    market_type: crate::market_data::downloader::MarketType,
+    fee_schedule: crate::execution::FeeSchedule,
+    slippage: crate::execution::SlippageModel,
+    initial_capital: f64,
+    margin: Option<MarginConfig>,
+    replay_speed: Option<f64>, // Event-time multiple to pace at and stream data points; None = max speed
+    fast_mode: bool,
+    report_progress: bool,
+    cancel: Arc<std::sync::atomic::AtomicBool>,
+}
+
+impl BacktestPass<'_> {
+    async fn run(self) -> Option<StrategyReport> {
+        log::info!("[{} | {}] Starting backtest...", self.symbol, self.strategy_name);
+    
+        let executor = std::sync::Arc::new(crate::execution::ExecutionManager::new(true, self.fee_schedule, self.market_type)
+            .with_slippage(self.slippage)
+            .with_starting_balance(self.initial_capital)
+            .with_credit_line(self.margin.map_or(0.0, |margin| margin.borrowed_notional)));
+        let (dummy_tx, _) = crate::market_data::event_queue(1, crate::market_data::BackpressurePolicy::Block);
+        let backtest_state = std::sync::Arc::new(RwLock::new(AppState::new(
+            "backtest".to_string(),
+            self.strategy_name.to_string(),
+            self.db_pool.clone(),
+            self.symbol.to_string(),
+            executor.clone(),
+            dummy_tx
+        )));
+
+        {
+            let mut write_guard = backtest_state.write().await;
+            write_guard.clear_all_data();
+            write_guard.max_history = 10_000;
+            write_guard.initial_balance = self.initial_capital;
+            write_guard.stream_live = false;
+            write_guard.stream_replay = self.replay_speed.is_some();
+            write_guard.record_latencies = false;
+            write_guard.is_trading = true;
+            write_guard.strategy_params.insert(self.strategy_name.to_string(), self.params.clone());
+        }
+
+        let mut strategy = match crate::strategy::StrategyFactory::create_strategy_with_params(self.strategy_name, self.params) {
+            Some(s) => s,
+            None => return None,
+        };
+
+        let mut candle_aggregator = crate::market_data::CandleAggregator::new(crate::market_data::CANDLE_INTERVAL_SECS);
+        let mut trade_pnls = Vec::new();
+        let mut trade_returns_pct = Vec::new();
+        let mut peak_pnl = 0.0;
+        let mut max_drawdown = 0.0;
+        let mut gross_profit = 0.0;
+        let mut gross_loss = 0.0;
+        let mut total_fees = 0.0;
+        let mut total_interest_paid = 0.0;
+        let mut borrowed = 0.0; // Refreshed whenever an order fills or executes
+        let mut last_trade_ts: Option<u64> = None;
+        let mut replay_clock = self.replay_speed.map(ReplayClock::new);
+
+        let total_trades_count = self.trades.len();
+        let progress_interval = (total_trades_count / 10).max(1);
+        let sample_rate = (total_trades_count / 2000).max(1);
+        let fast_skip = if self.fast_mode { 10 } else { 1 };
+    
+        {
+            let mut write_guard = backtest_state.write().await;
+            write_guard.sample_rate = sample_rate;
+        }
+
+        // Order book snapshots are replayed before the first trade at or after their timestamp
+        let first_trade_ts = self.trades.first().map_or(0, |t| t.event_time);
+        let mut book_idx = self.orderbooks.partition_point(|(ts, _)| *ts < first_trade_ts);
+
+        let mut cancelled_at = None;
+        for (idx, trade) in self.trades.iter().enumerate() {
+            if self.cancel.load(std::sync::atomic::Ordering::Relaxed) {
+                log::info!("[{} | {}] Backtest cancelled after {}/{} trades", self.symbol, self.strategy_name, idx, total_trades_count);
+                cancelled_at = Some(idx);
+                break;
+            }
+            if self.fast_mode && idx % fast_skip != 0 {
+                continue;
+            }
+            if let Some(clock) = replay_clock.as_mut() {
+                clock.wait_until(trade.event_time).await;
+            }
+        
+            let current_features: std::collections::HashMap<String, String> = strategy.get_features().into_iter().collect();
+
+            if self.report_progress && idx > 0 && idx % progress_interval == 0 {
+                let progress_pct = (idx as f64 / total_trades_count as f64 * 100.0) as u32;
+                let _ = PROGRESS_TX.send(ProgressEvent {
+                    symbol: self.symbol.to_string(),
+                    strategy_name: self.strategy_name.to_string(),
+                    progress_pct,
+                    status: "running".to_string(),
+                    features: current_features.clone(),
+                });
+            }
+
+            // Interest accrues on what was borrowed over the time since the last trade
+            let interest = match (self.margin, last_trade_ts) {
+                (Some(margin), Some(last_ts)) if borrowed > 0.0 => {
+                    margin.interest(borrowed, trade.event_time.saturating_sub(last_ts))
+                }
+                _ => 0.0,
+            };
+            last_trade_ts = Some(trade.event_time);
+            total_interest_paid += interest;
+
+            // Fill any resting limit orders crossed by this trade
+            let trade_price = trade.price.parse::<f64>().unwrap_or(0.0);
+            let warming_up = {
+                let mut write_guard = backtest_state.write().await;
+                write_guard.realized_pnl -= interest;
+                write_guard.price_cache.update(&trade.symbol, trade_price);
+                write_guard.state_machine.on_market_event(trade.event_time);
+                write_guard.state_machine.on_price(&trade.symbol, trade_price, trade.event_time);
+                write_guard.state_machine.is_warming_up()
+            };
+            let trade_qty = trade.qty.parse::<f64>().unwrap_or(0.0);
+            let fills = executor.on_trade_price(&trade.symbol, trade_price, trade_qty, trade.event_time).await.unwrap_or_default().fills;
+            for fill in &fills {
+                strategy.on_order_event(&crate::strategy::OrderEvent::from(fill));
+            }
+            let mut positions_changed = !fills.is_empty();
+            total_fees += fills.iter().map(|f| f.fee).sum::<f64>();
+            let fill_pnl: f64 = fills.iter().map(|f| f.realized_pnl).sum();
+            if fill_pnl != 0.0 {
+                let mut write_guard = backtest_state.write().await;
+                write_guard.realized_pnl += fill_pnl;
+                if fill_pnl > 0.0 {
+                    write_guard.win_trades += 1;
+                    gross_profit += fill_pnl;
+                } else {
+                    write_guard.loss_trades += 1;
+                    gross_loss += fill_pnl.abs();
+                }
+                trade_pnls.push(fill_pnl);
+                trade_returns_pct.extend(fills.iter()
+                    .filter(|f| f.realized_pnl != 0.0 && f.price * f.quantity > 0.0)
+                    .map(|f| f.realized_pnl / (f.price * f.quantity) * 100.0));
+            }
+
+            let mut opps = Vec::new();
+            while let Some((book_ts, book)) = self.orderbooks.get(book_idx) {
+                if *book_ts > trade.event_time {
+                    break;
+                }
+                if let (Some(bid), Some(ask)) = (book.bids.first(), book.asks.first()) {
+                    backtest_state.read().await.price_cache.update_mid(&trade.symbol, (bid.price + ask.price) / 2.0);
+                }
+                opps.extend(strategy.process_orderbook(book.clone(), backtest_state.clone()).await);
+                book_idx += 1;
+            }
+
+            opps.extend(strategy.process_trade(trade.clone(), backtest_state.clone()).await);
+            if let Some(candle) = candle_aggregator.update(&trade.symbol, trade_price, trade_qty, trade.event_time) {
+                opps.extend(strategy.process_candle(candle, backtest_state.clone()).await);
+            }
+        
+            {
+                let mut write_guard = backtest_state.write().await;
+                write_guard.current_features = current_features;
+            }
+            // Strategies still see warm-up data to build their indicators, but may not trade on it
+            if warming_up {
+                opps.clear();
+            }
+            backtest_state.write().await.assign_opportunity_ids(&mut opps);
+        
+            for opp in opps {
+                let price = trade.price.parse::<f64>().unwrap_or(0.0);
+                let is_market = matches!(&opp.signal,
+                    Signal::Buy { order_type: OrderType::Market, .. }
+                    | Signal::Sell { order_type: OrderType::Market, .. }
+                    | Signal::SellAll { .. });
+                let Ok(execution) = executor.execute_with_fill(opp.signal.clone()).await else {
+                    continue;
+                };
+                if let Some(order_event) = crate::strategy::OrderEvent::from_execution(&opp.signal, &execution) {
+                    strategy.on_order_event(&order_event);
+                }
+                // Netted away or otherwise never sent: not a trade
+                if execution.not_sent.is_some() {
+                    continue;
+                }
+                positions_changed = true;
+                let pnl = execution.pnl;
+                // Market orders pay taker on what filled; limit orders pay maker when they fill
+                let notional = match execution.fill {
+                    Some(fill) if is_market => price * fill.quantity,
+                    _ => 0.0,
+                };
+                let fee = notional * self.fee_schedule.taker;
+                total_fees += fee;
+            
+                {
+                    let mut write_guard = backtest_state.write().await;
+                    write_guard.total_trades += 1;
+                    write_guard.realized_pnl += pnl - fee;
+                
+                    if pnl > 0.0 {
+                        write_guard.win_trades += 1;
+                        trade_pnls.push(pnl - fee);
+                        gross_profit += pnl;
+                    } else if pnl < 0.0 {
+                        write_guard.loss_trades += 1;
+                        trade_pnls.push(pnl - fee);
+                        gross_loss += pnl.abs();
+                    }
+                    if pnl != 0.0 && notional > 0.0 {
+                        trade_returns_pct.push((pnl - fee) / notional * 100.0);
+                    }
+                }
+
+                let current_total_pnl = backtest_state.read().await.realized_pnl;
+                if current_total_pnl > peak_pnl { peak_pnl = current_total_pnl; }
+                let drawdown = peak_pnl - current_total_pnl;
+                if drawdown > max_drawdown { max_drawdown = drawdown; }
+            }
+
+            if let Some(margin) = self.margin.filter(|_| positions_changed) {
+                let quote_balance = executor.get_balances().await.unwrap_or_default().into_iter()
+                    .find(|(asset, _)| asset == executor.quote_asset())
+                    .map_or(0.0, |(_, amount)| amount);
+                borrowed = margin.borrowed(quote_balance);
+            }
+        }
+    
+        let final_features: std::collections::HashMap<String, String> = strategy.get_features().into_iter().collect();
+        if self.report_progress {
+            let _ = PROGRESS_TX.send(ProgressEvent {
+                symbol: self.symbol.to_string(),
+                strategy_name: self.strategy_name.to_string(),
+                progress_pct: cancelled_at.map_or(100, |idx| (idx as f64 / total_trades_count as f64 * 100.0) as u32),
+                status: if cancelled_at.is_some() { "cancelled" } else { "completed" }.to_string(),
+                features: final_features.clone(),
+            });
+        }
+
+        let total_slippage = executor.slippage_cost().await;
+        let unrealized_pnl: f64 = executor.get_positions().await.unwrap_or_default()
+            .iter()
+            .map(|p| p.unrealized_pnl)
+            .sum();
+        let report_guard = backtest_state.read().await;
+        let win_rate = if report_guard.total_trades > 0 {
+            (report_guard.win_trades as f64 / report_guard.total_trades as f64) * 100.0
+        } else { 0.0 };
+    
+        let yield_pct = (report_guard.realized_pnl / report_guard.initial_balance) * 100.0;
+        let marked_yield_pct = ((report_guard.realized_pnl + unrealized_pnl) / report_guard.initial_balance) * 100.0;
+        let profit_factor = if gross_loss > 0.0 { gross_profit / gross_loss } else { 0.0 };
+        let avg_win = if report_guard.win_trades > 0 { gross_profit / report_guard.win_trades as f64 } else { 0.0 };
+        let avg_loss = if report_guard.loss_trades > 0 { gross_loss / report_guard.loss_trades as f64 } else { 0.0 };
+
+        let sharpe_ratio = sharpe(&trade_pnls);
+        let sortino_ratio = sortino(&trade_pnls);
+        let last_trade_idx = cancelled_at.unwrap_or(total_trades_count).saturating_sub(1);
+        let span_ms = match (self.trades.first(), self.trades.get(last_trade_idx)) {
+            (Some(first), Some(last)) => last.event_time.saturating_sub(first.event_time),
+            _ => 0,
+        };
+        let max_drawdown_pct = max_drawdown / report_guard.initial_balance * 100.0;
+        let calmar_ratio = calmar(yield_pct, max_drawdown_pct, span_ms);
+        let history: Vec<DataPoint> = report_guard.history.iter().cloned().collect();
+        let (drawdown_curve, max_drawdown_duration_ms) = drawdown_curve(&history);
+
+        Some(StrategyReport {
+            symbol: self.symbol.to_string(),
+            strategy_name: self.strategy_name.to_string(),
+            history,
+            features: final_features,
+            total_trades: report_guard.total_trades,
+            win_rate,
+            yield_pct,
+            marked_yield_pct,
+            realized_pnl: report_guard.realized_pnl,
+            max_drawdown,
+            max_drawdown_duration_ms,
+            drawdown_curve,
+            profit_factor,
+            avg_win,
+            avg_loss,
+            sharpe_ratio,
+            sortino_ratio,
+            calmar_ratio,
+            total_fees,
+            total_slippage,
+            total_interest_paid,
+            benchmark_yield_pct: None,
+            alpha: None,
+            return_histogram: return_histogram(&trade_returns_pct),
+            walk_forward: None,
+            trade_pnls,
+        })
+    }
+}
+
+const MS_PER_YEAR: f64 = 365.0 * 24.0 * 3600.0 * 1000.0;
+const MS_PER_DAY: f64 = 24.0 * 3600.0 * 1000.0;
+
+/// Per-trade return histogram range and bin width, in percent
+const RETURN_HISTOGRAM_MIN_PCT: f64 = -5.0;
+const RETURN_HISTOGRAM_MAX_PCT: f64 = 5.0;
+const RETURN_HISTOGRAM_STEP_PCT: f64 = 0.5;
+
+/// Underwater curve of the equity in `history` and the longest peak-to-recovery time.
+/// A drawdown still open at the last point counts until that point.
+fn drawdown_curve(history: &[DataPoint]) -> (Vec<(u64, f64)>, u64) {
+    let mut curve = Vec::with_capacity(history.len());
+    let mut peak = f64::MIN;
+    let mut peak_ts = 0;
+    let mut max_duration_ms = 0;
+    for dp in history {
+        if dp.equity >= peak {
+            peak = dp.equity;
+            peak_ts = dp.timestamp;
+        } else {
+            max_duration_ms = max_duration_ms.max(dp.timestamp.saturating_sub(peak_ts));
+        }
+        curve.push((dp.timestamp, peak - dp.equity));
+    }
+    (curve, max_duration_ms)
+}
+
+/// Counts per-trade returns (% of the closing notional) into fixed bins keyed by lower edge.
+/// Returns beyond the range land in the first or last bin so tail trades are never dropped.
+fn return_histogram(returns_pct: &[f64]) -> Vec<(f64, u64)> {
+    let bins = ((RETURN_HISTOGRAM_MAX_PCT - RETURN_HISTOGRAM_MIN_PCT) / RETURN_HISTOGRAM_STEP_PCT).round() as usize;
+    let mut counts = vec![0u64; bins];
+    for &ret in returns_pct.iter().filter(|r| r.is_finite()) {
+        let idx = ((ret - RETURN_HISTOGRAM_MIN_PCT) / RETURN_HISTOGRAM_STEP_PCT).floor();
+        counts[(idx.max(0.0) as usize).min(bins - 1)] += 1;
+    }
+    counts.into_iter()
+        .enumerate()
+        .map(|(i, count)| (RETURN_HISTOGRAM_MIN_PCT + i as f64 * RETURN_HISTOGRAM_STEP_PCT, count))
+        .collect()
+}
+
+/// Per-trade Sharpe ratio: mean / sample standard deviation of trade PnLs
+fn sharpe(trade_pnls: &[f64]) -> f64 {
+    let n = trade_pnls.len();
+    if n < 2 {
+        return 0.0;
+    }
+    let mean = trade_pnls.iter().sum::<f64>() / n as f64;
+    let std_dev = (trade_pnls.iter().map(|&x| (x - mean).powi(2)).sum::<f64>() / (n - 1) as f64).sqrt();
+    if std_dev > 0.0 { mean / std_dev } else { 0.0 }
+}
+
+/// Per-trade Sortino ratio: mean / downside deviation (losses only, target 0)
+fn sortino(trade_pnls: &[f64]) -> f64 {
+    if trade_pnls.is_empty() {
+        return 0.0;
+    }
+    let n = trade_pnls.len() as f64;
+    let mean = trade_pnls.iter().sum::<f64>() / n;
+    let downside_dev = (trade_pnls.iter().map(|&x| x.min(0.0).powi(2)).sum::<f64>() / n).sqrt();
+    if downside_dev > 0.0 { mean / downside_dev } else { 0.0 }
+}
+
+/// Yield of putting all capital into the symbol at the first trade (paying the taker fee)
+/// and holding to the last. BuyAndHold's own report only counts realized PnL, which stays
+/// near zero because it never sells, so the benchmark is marked to the final price here.
+fn buy_and_hold_yield_pct(trades: &[binance::model::TradeEvent], taker_fee: f64) -> Option<f64> {
+    let first = trades.first()?.price.parse::<f64>().ok().filter(|p| *p > 0.0)?;
+    let last = trades.last()?.price.parse::<f64>().ok()?;
+    Some((last / first * (1.0 - taker_fee) - 1.0) * 100.0)
+}
+
+/// Calmar ratio: annualized return (linearly scaled from the backtest span) / max drawdown, both in percent
+fn calmar(yield_pct: f64, max_drawdown_pct: f64, span_ms: u64) -> f64 {
+    if span_ms == 0 || max_drawdown_pct <= 0.0 {
+        return 0.0;
+    }
+    let annualized_pct = yield_pct * MS_PER_YEAR / span_ms as f64;
+    annualized_pct / max_drawdown_pct
+}
+
+/// Number of common grid points both equity curves are resampled onto before correlating
+const CORRELATION_POINTS: usize = 200;
+
+fn pearson(a: &[f64], b: &[f64]) -> f64 {
+    let n = a.len().min(b.len());
+    if n < 2 {
+        return 0.0;
+    }
+    let mean_a = a[..n].iter().sum::<f64>() / n as f64;
+    let mean_b = b[..n].iter().sum::<f64>() / n as f64;
+    let (mut cov, mut var_a, mut var_b) = (0.0, 0.0, 0.0);
+    for (x, y) in a.iter().zip(b) {
+        let (da, db) = (x - mean_a, y - mean_b);
+        cov += da * db;
+        var_a += da * da;
+        var_b += db * db;
+    }
+    if var_a > 0.0 && var_b > 0.0 { cov / (var_a * var_b).sqrt() } else { 0.0 }
+}
+
+/// Equity changes between evenly spaced times in [start, end], using the last known equity at each time
+fn equity_returns(history: &[DataPoint], start: u64, end: u64) -> Vec<f64> {
+    let step = ((end - start) / CORRELATION_POINTS as u64).max(1);
+    let mut idx = 0;
+    let mut samples = Vec::with_capacity(CORRELATION_POINTS + 1);
+    let mut t = start;
+    while t <= end {
+        while idx + 1 < history.len() && history[idx + 1].timestamp <= t {
+            idx += 1;
+        }
+        samples.push(history[idx].equity);
+        t += step;
+    }
+    samples.windows(2).map(|w| w[1] - w[0]).collect()
+}
+
+/// Correlates every pair of equity curves over their overlapping time range
+fn equity_correlation(reports: &[StrategyReport]) -> CorrelationMatrix {
+    let n = reports.len();
+    let mut matrix = vec![vec![0.0; n]; n];
+    for i in 0..n {
+        matrix[i][i] = 1.0;
+        for j in (i + 1)..n {
+            let (a, b) = (&reports[i].history, &reports[j].history);
+            let (Some(a_first), Some(a_last), Some(b_first), Some(b_last)) = (a.first(), a.last(), b.first(), b.last()) else {
+                continue;
+            };
+            let start = a_first.timestamp.max(b_first.timestamp);
+            let end = a_last.timestamp.min(b_last.timestamp);
+            if start >= end {
+                continue;
+            }
+            let corr = pearson(&equity_returns(a, start, end), &equity_returns(b, start, end));
+            matrix[i][j] = corr;
+            matrix[j][i] = corr;
+        }
+    }
+    CorrelationMatrix {
+        labels: reports.iter().map(|r| format!("{}/{}", r.symbol, r.strategy_name)).collect(),
+        matrix,
+    }
+}
+
+/// Runs one strategy over rolling train/test windows and reports in-sample vs out-of-sample metrics
+async fn run_walk_forward(base: &BacktestPass<'_>, config: &WalkForwardConfig) -> WalkForwardReport {
+    let windows = config.windows.max(1);
+    let train_pct = config.train_pct.clamp(0.0, 1.0);
+    let window_len = base.trades.len() / windows;
+    let mut reports = Vec::new();
+    let mut oos_pnls = Vec::new();
+
+    for window in 0..windows {
+        let start = window * window_len;
+        let end = if window + 1 == windows { base.trades.len() } else { start + window_len };
+        let split = start + ((end - start) as f64 * train_pct) as usize;
+        if split == start || split == end {
+            continue;
+        }
+
+        let segment = |trades| BacktestPass {
+            symbol: base.symbol,
+            strategy_name: base.strategy_name,
+            params: base.params,
+            trades,
+            orderbooks: base.orderbooks,
+            db_pool: base.db_pool.clone(),
+            market_type: base.market_type,
+            fee_schedule: base.fee_schedule,
+            slippage: base.slippage,
+            initial_capital: base.initial_capital,
+            margin: base.margin,
+            replay_speed: None,
+            fast_mode: base.fast_mode,
+            report_progress: false,
+            cancel: base.cancel.clone(),
+        };
+        let (Some(in_sample), Some(out_of_sample)) = (
+            segment(&base.trades[start..split]).run().await,
+            segment(&base.trades[split..end]).run().await,
+        ) else {
+            continue;
+        };
+
+        oos_pnls.extend_from_slice(&out_of_sample.trade_pnls);
+        reports.push(WalkForwardWindow {
+            window,
+            start_ts: base.trades[start].event_time,
+            split_ts: base.trades[split].event_time,
+            end_ts: base.trades[end - 1].event_time,
+            in_sample: WindowMetrics::from(&in_sample),
+            out_of_sample: WindowMetrics::from(&out_of_sample),
+        });
+    }
+
+    WalkForwardReport {
+        windows: reports,
+        oos_sharpe: sharpe(&oos_pnls),
+        oos_total_trades: oos_pnls.len(),
+    }
+}
+
+/// Parses "SPOT:BTCUSDT" / "FUTURES:BTCUSDT" into market type and symbol
+/// Only checks the format; see `parse_and_validate_symbol_spec` for the listing check
+fn parse_symbol_spec(spec: &str) -> Option<(crate::market_data::downloader::MarketType, String)> {
+    let (market, symbol) = spec.split_once(':')?;
+    if symbol.is_empty() || symbol.contains(':') {
+        return None;
+    }
+    let market_type = match market.to_uppercase().as_str() {
+        "FUTURES" => crate::market_data::downloader::MarketType::Futures,
+        _ => crate::market_data::downloader::MarketType::Spot,
+    };
+    Some((market_type, symbol.to_string()))
+}
+
+/// `parse_symbol_spec` plus `validate_symbol`, with the error message for a 400 response
+async fn parse_and_validate_symbol_spec(spec: &str) -> Result<(crate::market_data::downloader::MarketType, String), String> {
+    let (market_type, symbol) = parse_symbol_spec(spec).ok_or_else(|| format!("Invalid symbol format: {} (expected SPOT:BTCUSDT or FUTURES:BTCUSDT)", spec))?;
+    let symbol = crate::market_data::validate_symbol(&symbol).await?;
+    Ok((market_type, symbol))
+}
+
+/// Downloads any missing history for the range, then loads it from the database.
+/// Kline granularity replays each candle as synthetic trades (see `klines_to_trades`).
+async fn load_backtest_trades(
+    db_pool: &Pool<Postgres>,
+    symbol: &str,
+    market_type: crate::market_data::downloader::MarketType,
+    granularity: DataGranularity,
+    start_ts: u64,
+    end_ts: u64,
+) -> Vec<binance::model::TradeEvent> {
+    let downloader = crate::market_data::HistoricalDownloader::new(db_pool.clone());
+    if let DataGranularity::Klines(interval) = granularity {
+        if let Err(e) = downloader.ensure_klines_range(symbol, market_type, interval, start_ts, end_ts).await {
+            log::error!("Failed to download {} klines for {}: {}", interval.as_str(), symbol, e);
+        }
+        let candles = repository::get_klines_range(db_pool, symbol, market_type.as_str(), interval.as_str(), start_ts, end_ts)
+            .await
+            .unwrap_or_default();
+        return klines_to_trades(&candles, interval);
+    }
+
+    if let Err(e) = downloader.ensure_data_range(symbol, market_type, start_ts, end_ts).await {
+        log::error!("Failed to download historical data for {}: {}", symbol, e);
+    }
+
+    repository::get_historical_trades_range(
+        db_pool,
+        symbol,
+        market_type.as_str(),
+        Some(start_ts),
+        Some(end_ts)
+    ).await.unwrap_or_default()
+}
+
+async fn cancel_backtest(State(state): State<SharedState>) -> Json<serde_json::Value> {
+    state.read().await.backtest_cancel.store(true, std::sync::atomic::Ordering::SeqCst);
+    log::info!("Backtest cancellation requested");
+    Json(serde_json::json!({ "status": "success", "message": "Cancellation requested" }))
+}
+
 async fn execute_isolated_backtest(
     State(state): State<SharedState>,
     Json(payload): Json<BacktestRequest>
-) -> Json<BacktestReport> {
-    let db_pool = {
+) -> Result<Json<BacktestReport>, (StatusCode, Json<serde_json::Value>)> {
+    if let Some(Err(e)) = payload.margin.map(|margin| margin.validate()) {
+        return Err((StatusCode::BAD_REQUEST, Json(serde_json::json!({ "status": "error", "error": e }))));
+    }
+    if let Some(speed) = payload.replay_speed.filter(|speed| !speed.is_finite() || *speed < MIN_REPLAY_SPEED) {
+        return Err((StatusCode::BAD_REQUEST, Json(serde_json::json!({ "status": "error", "error": format!("Invalid replay_speed {}: expected at least {}", speed, MIN_REPLAY_SPEED) }))));
+    }
+    let mut symbols = Vec::with_capacity(payload.symbols.len());
+    for spec in &payload.symbols {
+        match parse_and_validate_symbol_spec(spec).await {
+            Ok(parsed) => symbols.push(parsed),
+            Err(e) => return Err((StatusCode::BAD_REQUEST, Json(serde_json::json!({ "status": "error", "error": e })))),
+        }
+    }
+
+    let (db_pool, cancel) = {
         let read_guard = state.read().await;
-        read_guard.db_pool.clone()
+        (read_guard.db_pool.clone(), read_guard.backtest_cancel.clone())
     };
+    cancel.store(false, std::sync::atomic::Ordering::SeqCst);
 
     log::info!("Executing COMBINATORIAL backtest for symbols {:?} with strategies: {:?}", payload.symbols, payload.strategies);
 
@@ -540,209 +2285,109 @@ async fn execute_isolated_backtest(
     let end_ts = payload.end_ts.unwrap_or(u64::MAX);
 
     let mut symbol_handles = Vec::new();
-    let symbols = payload.symbols.clone();
     let strategies = payload.strategies.clone();
     let fast_mode = payload.fast_mode;
+    let walk_forward_config = payload.walk_forward;
+    let granularity = match payload.granularity.as_deref().map(DataGranularity::parse) {
+        None => DataGranularity::AggTrades,
+        Some(Some(granularity)) => granularity,
+        Some(None) => {
+            log::warn!("Unknown granularity {:?}, falling back to aggTrades", payload.granularity);
+            DataGranularity::AggTrades
+        }
+    };
+    let initial_capital = match payload.initial_capital {
+        Some(capital) if capital.is_finite() && capital > 0.0 => capital,
+        Some(capital) => {
+            log::warn!("Invalid initial_capital {}, falling back to {}", capital, crate::execution::DEFAULT_SIM_BALANCE_USDT);
+            crate::execution::DEFAULT_SIM_BALANCE_USDT
+        }
+        None => crate::execution::DEFAULT_SIM_BALANCE_USDT,
+    };
+    let margin = payload.margin;
+    let replay_speed = payload.replay_speed;
+    let fee_schedule = crate::execution::FeeSchedule::from_env();
+    let slippage = crate::execution::SlippageModel::from_env();
 
-    for symbol_spec in symbols {
+    for (market_type, symbol) in symbols {
         let db_pool_inner = db_pool.clone();
         let strategies_inner = strategies.clone();
+        let cancel_inner = cancel.clone();
         
         let handle = tokio::spawn(async move {
-            let parts: Vec<&str> = symbol_spec.split(':').collect();
-            if parts.len() != 2 {
-                log::error!("Invalid symbol format: {}", symbol_spec);
+            let trades = load_backtest_trades(&db_pool_inner, &symbol, market_type, granularity, start_ts, end_ts).await;
+            if trades.is_empty() {
+                log::warn!("No trades found for {} ({}) in requested range", symbol, market_type.as_str());
                 return Vec::new();
             }
-            
-            let market_type = match parts[0].to_uppercase().as_str() {
-                "FUTURES" => crate::market_data::downloader::MarketType::Futures,
-                _ => crate::market_data::downloader::MarketType::Spot,
-            };
-            let symbol = parts[1].to_string();
 
-            // 0. Download missing data for this symbol
-            {
-                let downloader = crate::market_data::HistoricalDownloader::new(db_pool_inner.clone());
-                if let Err(e) = downloader.ensure_data_range(&symbol, market_type, start_ts, end_ts).await {
-                    log::error!("Failed to download historical data for {}: {}", symbol, e);
-                }
-            }
-
-            // 1. Load historical trades for this symbol
-            let trades = repository::get_historical_trades_range(
-                &db_pool_inner, 
+            let orderbooks = repository::get_historical_orderbooks_range(
+                &db_pool_inner,
                 &symbol,
                 market_type.as_str(),
-                Some(start_ts), 
+                Some(start_ts),
                 Some(end_ts)
             ).await.unwrap_or_default();
-            
-            if trades.is_empty() {
-                log::warn!("No trades found for {} ({}) in requested range", symbol, market_type.as_str());
-                return Vec::new();
-            }
 
-            log::info!("Loaded {} trades for backtesting {}", trades.len(), symbol);
+            log::info!("Loaded {} trades and {} order books for backtesting {}", trades.len(), orderbooks.len(), symbol);
             let trades_arc = std::sync::Arc::new(trades);
+            let orderbooks_arc = std::sync::Arc::new(orderbooks);
             let mut strat_handles = Vec::new();
 
+            let mut strategies_inner = strategies_inner;
+            // Always replay the benchmark so its trades can be compared side by side
+            if !strategies_inner.iter().any(|name| name == "BuyAndHold") {
+                strategies_inner.push("BuyAndHold".to_string());
+            }
+            let benchmark_yield_pct = buy_and_hold_yield_pct(&trades_arc, fee_schedule.taker);
+
             for strat_name in strategies_inner {
                 let trades_clone = trades_arc.clone();
+                let orderbooks_clone = orderbooks_arc.clone();
                 let db_pool_clone = db_pool_inner.clone();
                 let symbol_clone = symbol.clone();
                 let strat_name_clone = strat_name.clone();
+                let cancel_clone = cancel_inner.clone();
 
                 let strat_handle = tokio::spawn(async move {
-                    log::info!("[{} | {}] Starting backtest...", symbol_clone, strat_name_clone);
-                    
-                    let executor = std::sync::Arc::new(crate::execution::ExecutionManager::new(true));
-                    let (dummy_tx, _) = mpsc::channel(1);
-                    let backtest_state = std::sync::Arc::new(RwLock::new(AppState::new(
-                        "backtest".to_string(),
-                        strat_name_clone.clone(),
-                        db_pool_clone,
-                        symbol_clone.clone(),
-                        executor.clone(),
-                        dummy_tx
-                    )));
-
-                    {
-                        let mut write_guard = backtest_state.write().await;
-                        write_guard.clear_all_data();
-                        write_guard.max_history = 10_000;
-                        write_guard.state_machine.transition_to(crate::state_machine::SystemState::Trading);
-                        write_guard.is_trading = true;
-                    }
-
-                    let mut strategy = match crate::strategy::StrategyFactory::create_strategy(&strat_name_clone) {
-                        Some(s) => s,
-                        None => return None,
+                    let no_params = std::collections::HashMap::new();
+                    let pass = BacktestPass {
+                        symbol: &symbol_clone,
+                        strategy_name: &strat_name_clone,
+                        params: &no_params,
+                        trades: &trades_clone,
+                        orderbooks: &orderbooks_clone,
+                        db_pool: db_pool_clone,
+                        market_type,
+                        fee_schedule,
+                        slippage,
+                        initial_capital,
+                        margin,
+                        replay_speed,
+                        fast_mode,
+                        report_progress: true,
+                        cancel: cancel_clone,
                     };
-
-                    let mut trade_pnls = Vec::new();
-                    let mut peak_pnl = 0.0;
-                    let mut max_drawdown = 0.0;
-                    let mut gross_profit = 0.0;
-                    let mut gross_loss = 0.0;
-                    let mut total_fees = 0.0;
-
-                    let total_trades_count = trades_clone.len();
-                    let progress_interval = (total_trades_count / 10).max(1);
-                    let sample_rate = (total_trades_count / 2000).max(1);
-                    let fast_skip = if fast_mode { 10 } else { 1 };
-                    
-                    {
-                        let mut write_guard = backtest_state.write().await;
-                        write_guard.sample_rate = sample_rate;
-                    }
-
-                    for (idx, trade) in trades_clone.iter().enumerate() {
-                        if fast_mode && idx % fast_skip != 0 {
-                            continue;
-                        }
-                        
-                        let current_features: std::collections::HashMap<String, String> = strategy.get_features().into_iter().collect();
-
-                        if idx > 0 && idx % progress_interval == 0 {
-                            let progress_pct = (idx as f64 / total_trades_count as f64 * 100.0) as u32;
-                            let _ = PROGRESS_TX.send(ProgressEvent {
-                                symbol: symbol_clone.clone(),
-                                strategy_name: strat_name_clone.clone(),
-                                progress_pct,
-                                status: "running".to_string(),
-                                features: current_features.clone(),
-                            });
-                        }
-
-                        let opps = strategy.process_trade(trade.clone(), backtest_state.clone()).await;
-                        
-                        {
-                            let mut write_guard = backtest_state.write().await;
-                            write_guard.current_features = current_features;
-                        }
-                        
-                        for opp in opps {
-                            let price = trade.price.parse::<f64>().unwrap_or(0.0);
-                            let fee = match &opp.signal {
-                                Signal::Buy { quantity, .. } => price * quantity * 0.001,
-                                Signal::Sell { quantity, .. } => price * quantity * 0.001,
-                                _ => 0.0,
-                            };
-                            total_fees += fee;
-
-                            let pnl = executor.execute(opp.signal).await.unwrap_or(0.0);
-                            
-                            {
-                                let mut write_guard = backtest_state.write().await;
-                                write_guard.total_trades += 1;
-                                write_guard.realized_pnl += pnl - fee;
-                                
-                                if pnl > 0.0 {
-                                    write_guard.win_trades += 1;
-                                    trade_pnls.push(pnl - fee);
-                                    gross_profit += pnl;
-                                } else if pnl < 0.0 {
-                                    write_guard.loss_trades += 1;
-                                    trade_pnls.push(pnl - fee);
-                                    gross_loss += pnl.abs();
-                                }
-                            }
-
-                            let current_total_pnl = backtest_state.read().await.realized_pnl;
-                            if current_total_pnl > peak_pnl { peak_pnl = current_total_pnl; }
-                            let drawdown = peak_pnl - current_total_pnl;
-                            if drawdown > max_drawdown { max_drawdown = drawdown; }
-                        }
-                    }
-                    
-                    let final_features: std::collections::HashMap<String, String> = strategy.get_features().into_iter().collect();
-                    let _ = PROGRESS_TX.send(ProgressEvent {
-                        symbol: symbol_clone.clone(),
-                        strategy_name: strat_name_clone.clone(),
-                        progress_pct: 100,
-                        status: "completed".to_string(),
-                        features: final_features.clone(),
-                    });
-
-                    let report_guard = backtest_state.read().await;
-                    let win_rate = if report_guard.total_trades > 0 {
-                        (report_guard.win_trades as f64 / report_guard.total_trades as f64) * 100.0
-                    } else { 0.0 };
-                    
-                    let yield_pct = (report_guard.realized_pnl / report_guard.initial_balance) * 100.0;
-                    let profit_factor = if gross_loss > 0.0 { gross_profit / gross_loss } else { 0.0 };
-                    let avg_win = if report_guard.win_trades > 0 { gross_profit / report_guard.win_trades as f64 } else { 0.0 };
-                    let avg_loss = if report_guard.loss_trades > 0 { gross_loss / report_guard.loss_trades as f64 } else { 0.0 };
-
-                    let sharpe_ratio = if !trade_pnls.is_empty() {
-                        let mean = trade_pnls.iter().sum::<f64>() / trade_pnls.len() as f64;
-                        let variance = trade_pnls.iter().map(|&x| (x - mean).powi(2)).sum::<f64>() / trade_pnls.len() as f64;
-                        if variance > 0.0 { mean / variance.sqrt() } else { 0.0 }
-                    } else { 0.0 };
-
-                    Some(StrategyReport {
-                        symbol: symbol_clone,
-                        strategy_name: strat_name_clone,
-                        history: report_guard.history.iter().cloned().collect(),
-                        features: final_features,
-                        total_trades: report_guard.total_trades,
-                        win_rate,
-                        yield_pct,
-                        realized_pnl: report_guard.realized_pnl,
-                        max_drawdown,
-                        profit_factor,
-                        avg_win,
-                        avg_loss,
-                        sharpe_ratio,
-                        total_fees,
-                    })
+                    let walk_forward = match &walk_forward_config {
+                        Some(config) => Some(run_walk_forward(&pass, config).await),
+                        None => None,
+                    };
+                    let mut report = pass.run().await?;
+                    report.walk_forward = walk_forward;
+                    Some(report)
                 });
                 strat_handles.push(strat_handle);
             }
 
             let strat_results = futures::future::join_all(strat_handles).await;
-            strat_results.into_iter().filter_map(|r| r.ok().flatten()).collect::<Vec<StrategyReport>>()
+            let mut reports = strat_results.into_iter().filter_map(|r| r.ok().flatten()).collect::<Vec<StrategyReport>>();
+            if let Some(benchmark) = benchmark_yield_pct {
+                for report in &mut reports {
+                    report.benchmark_yield_pct = Some(benchmark);
+                    report.alpha = Some(report.marked_yield_pct - benchmark);
+                }
+            }
+            reports
         });
         symbol_handles.push(handle);
     }
@@ -754,17 +2399,266 @@ async fn execute_isolated_backtest(
         .flatten()
         .collect();
 
-    log::info!("Combinatorial backtest completed with {} results", strategy_reports.len());
+    let cancelled = cancel.load(std::sync::atomic::Ordering::SeqCst);
+    if cancelled {
+        log::warn!("Combinatorial backtest cancelled with {} partial results", strategy_reports.len());
+    } else {
+        log::info!("Combinatorial backtest completed with {} results", strategy_reports.len());
+    }
 
-    Json(BacktestReport {
+    let correlation = equity_correlation(&strategy_reports);
+    let report = BacktestReport {
         reports: strategy_reports,
-        initial_capital: 10000.0,
-    })
+        initial_capital,
+        cancelled,
+        correlation,
+    };
+    state.write().await.last_backtest_report = Some(report.clone());
+    Ok(Json(report))
+}
+
+/// Upper bound on backtests a single sweep may run
+const MAX_SWEEP_COMBINATIONS: usize = 400;
+
+#[derive(Deserialize)]
+struct SweepRequest {
+    strategy: String,
+    symbol: String, // Format: "SPOT:BTCUSDT" or "FUTURES:BTCUSDT"
+    start_ts: Option<u64>,
+    end_ts: Option<u64>,
+    #[serde(default)]
+    fast_mode: bool,
+    params: Vec<ParamRange>, // One or two parameters
+    #[serde(default)]
+    granularity: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ParamRange {
+    name: String,
+    start: f64,
+    end: f64,
+    step: f64,
+}
+
+impl ParamRange {
+    fn count(&self) -> usize {
+        ((self.end - self.start) / self.step).floor() as usize + 1
+    }
+
+    fn values(&self) -> Vec<f64> {
+        (0..self.count()).map(|i| self.start + i as f64 * self.step).collect()
+    }
+}
+
+#[derive(Serialize)]
+struct SweepAxis {
+    name: String,
+    values: Vec<f64>,
+}
+
+/// Grids are indexed [first param value][second param value]; a single-param sweep has one column
+#[derive(Serialize)]
+struct SweepReport {
+    strategy: String,
+    symbol: String,
+    axes: Vec<SweepAxis>,
+    yield_grid: Vec<Vec<f64>>,
+    sharpe_grid: Vec<Vec<f64>>,
+    trades_grid: Vec<Vec<u64>>,
+}
+
+async fn execute_param_sweep(
+    State(state): State<SharedState>,
+    Json(payload): Json<SweepRequest>
+) -> (StatusCode, Json<serde_json::Value>) {
+    let bad_request = |error: String| (StatusCode::BAD_REQUEST, Json(serde_json::json!({ "status": "error", "error": error })));
+
+    let sweepable = crate::strategy::StrategyFactory::sweepable_params(&payload.strategy);
+    if sweepable.is_empty() {
+        return bad_request(format!("Strategy {} has no sweepable parameters", payload.strategy));
+    }
+    if payload.params.is_empty() || payload.params.len() > 2 {
+        return bad_request("Sweep one or two parameters".to_string());
+    }
+    for range in &payload.params {
+        if !sweepable.contains(&range.name.as_str()) {
+            return bad_request(format!("Unknown parameter {} (expected one of {:?})", range.name, sweepable));
+        }
+        if range.step.is_nan() || range.step <= 0.0 || range.end < range.start {
+            return bad_request(format!("Invalid range for {}: need step > 0 and end >= start", range.name));
+        }
+    }
+    let combinations = payload.params.iter().fold(1usize, |acc, range| acc.saturating_mul(range.count()));
+    if combinations > MAX_SWEEP_COMBINATIONS {
+        return bad_request(format!("Sweep has {} combinations (max {})", combinations, MAX_SWEEP_COMBINATIONS));
+    }
+    let (market_type, symbol) = match parse_and_validate_symbol_spec(&payload.symbol).await {
+        Ok(parsed) => parsed,
+        Err(e) => return bad_request(e),
+    };
+    let granularity = match payload.granularity.as_deref().map(DataGranularity::parse) {
+        None => DataGranularity::AggTrades,
+        Some(Some(granularity)) => granularity,
+        Some(None) => return bad_request(format!("Unknown granularity {:?}", payload.granularity)),
+    };
+
+    let axes: Vec<SweepAxis> = payload.params.iter()
+        .map(|range| SweepAxis { name: range.name.clone(), values: range.values() })
+        .collect();
+    let rows = axes[0].values.len();
+    let cols = axes.get(1).map_or(1, |axis| axis.values.len());
+
+    let (db_pool, cancel) = {
+        let read_guard = state.read().await;
+        (read_guard.db_pool.clone(), read_guard.backtest_cancel.clone())
+    };
+    cancel.store(false, std::sync::atomic::Ordering::SeqCst);
+
+    let trades = load_backtest_trades(&db_pool, &symbol, market_type, granularity, payload.start_ts.unwrap_or(0), payload.end_ts.unwrap_or(u64::MAX)).await;
+    if trades.is_empty() {
+        return (StatusCode::NOT_FOUND, Json(serde_json::json!({ "status": "error", "error": format!("No trades found for {}", payload.symbol) })));
+    }
+    let orderbooks = repository::get_historical_orderbooks_range(&db_pool, &symbol, market_type.as_str(), payload.start_ts, payload.end_ts)
+        .await
+        .unwrap_or_default();
+    log::info!("Sweeping {} over {} combinations on {} trades and {} order books of {}", payload.strategy, rows * cols, trades.len(), orderbooks.len(), symbol);
+
+    let trades = std::sync::Arc::new(trades);
+    let orderbooks = std::sync::Arc::new(orderbooks);
+    let fee_schedule = crate::execution::FeeSchedule::from_env();
+    let slippage = crate::execution::SlippageModel::from_env();
+    let mut handles = Vec::with_capacity(rows * cols);
+    for row in 0..rows {
+        for col in 0..cols {
+            let mut params = std::collections::HashMap::new();
+            params.insert(axes[0].name.clone(), axes[0].values[row]);
+            if let Some(axis) = axes.get(1) {
+                params.insert(axis.name.clone(), axis.values[col]);
+            }
+            let (trades, orderbooks, db_pool, cancel) = (trades.clone(), orderbooks.clone(), db_pool.clone(), cancel.clone());
+            let (symbol, strategy_name, fast_mode) = (symbol.clone(), payload.strategy.clone(), payload.fast_mode);
+            handles.push(tokio::spawn(async move {
+                BacktestPass {
+                    symbol: &symbol,
+                    strategy_name: &strategy_name,
+                    params: &params,
+                    trades: &trades,
+                    orderbooks: &orderbooks,
+                    db_pool,
+                    market_type,
+                    fee_schedule,
+                    slippage,
+                    initial_capital: crate::execution::DEFAULT_SIM_BALANCE_USDT,
+                    margin: None,
+                    replay_speed: None,
+                    fast_mode,
+                    report_progress: false,
+                    cancel,
+                }.run().await
+            }));
+        }
+    }
+
+    let results = futures::future::join_all(handles).await;
+    let mut yield_grid = vec![vec![0.0; cols]; rows];
+    let mut sharpe_grid = vec![vec![0.0; cols]; rows];
+    let mut trades_grid = vec![vec![0; cols]; rows];
+    for (idx, result) in results.into_iter().enumerate() {
+        if let Ok(Some(report)) = result {
+            let (row, col) = (idx / cols, idx % cols);
+            yield_grid[row][col] = report.yield_pct;
+            sharpe_grid[row][col] = report.sharpe_ratio;
+            trades_grid[row][col] = report.total_trades;
+        }
+    }
+
+    let report = SweepReport {
+        strategy: payload.strategy,
+        symbol: payload.symbol,
+        axes,
+        yield_grid,
+        sharpe_grid,
+        trades_grid,
+    };
+    (StatusCode::OK, Json(serde_json::json!(report)))
+}
+
+impl StrategyReport {
+    const CSV_HEADER: &'static str = "symbol,strategy_name,total_trades,win_rate,yield_pct,realized_pnl,max_drawdown,profit_factor,avg_win,avg_loss,sharpe_ratio,sortino_ratio,calmar_ratio,total_fees,total_slippage,oos_sharpe,benchmark_yield_pct,alpha,max_drawdown_duration_ms,total_interest_paid,marked_yield_pct";
+
+    fn to_csv_row(&self) -> String {
+        let oos_sharpe = self.walk_forward.as_ref().map(|wf| wf.oos_sharpe.to_string()).unwrap_or_default();
+        let benchmark_yield_pct = self.benchmark_yield_pct.map(|v| v.to_string()).unwrap_or_default();
+        let alpha = self.alpha.map(|v| v.to_string()).unwrap_or_default();
+        format!(
+            "{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{}",
+            csv_field(&self.symbol), csv_field(&self.strategy_name), self.total_trades, self.win_rate,
+            self.yield_pct, self.realized_pnl, self.max_drawdown, self.profit_factor, self.avg_win,
+            self.avg_loss, self.sharpe_ratio, self.sortino_ratio, self.calmar_ratio, self.total_fees,
+            self.total_slippage, oos_sharpe, benchmark_yield_pct, alpha, self.max_drawdown_duration_ms,
+            self.total_interest_paid, self.marked_yield_pct
+        )
+    }
+}
+
+/// Quotes a CSV field if it contains a delimiter, quote or newline
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[derive(Deserialize)]
+struct ExportQuery {
+    format: Option<String>, // "csv" (default) or "json"
+}
+
+async fn export_backtest(
+    State(state): State<SharedState>,
+    Query(query): Query<ExportQuery>
+) -> axum::response::Response {
+    use axum::response::IntoResponse;
+
+    let read_guard = state.read().await;
+    let Some(report) = read_guard.last_backtest_report.as_ref() else {
+        return (StatusCode::NOT_FOUND, Json(serde_json::json!({ "error": "No backtest has been run yet" }))).into_response();
+    };
+
+    match query.format.as_deref().unwrap_or("csv") {
+        "json" => (
+            [
+                (header::CONTENT_TYPE, "application/json"),
+                (header::CONTENT_DISPOSITION, "attachment; filename=\"backtest_results.json\""),
+            ],
+            Json(report.clone()),
+        ).into_response(),
+        "csv" => {
+            let mut body = String::from(StrategyReport::CSV_HEADER);
+            body.push('\n');
+            for row in &report.reports {
+                body.push_str(&row.to_csv_row());
+                body.push('\n');
+            }
+            (
+                [
+                    (header::CONTENT_TYPE, "text/csv"),
+                    (header::CONTENT_DISPOSITION, "attachment; filename=\"backtest_results.csv\""),
+                ],
+                body,
+            ).into_response()
+        }
+        other => (StatusCode::BAD_REQUEST, Json(serde_json::json!({ "error": format!("Unsupported format: {}", other) }))).into_response(),
+    }
 }
 
 async fn start_trading(State(state): State<SharedState>) -> Json<serde_json::Value> {
     let mut write_guard = state.write().await;
     write_guard.is_trading = true;
+    let pnl = write_guard.realized_pnl;
+    write_guard.circuit_breaker.reset(pnl);
     log::info!("Trading STARTED by user request");
     Json(serde_json::json!({ "status": "success", "is_trading": true }))
 }
@@ -772,22 +2666,212 @@ async fn start_trading(State(state): State<SharedState>) -> Json<serde_json::Val
 async fn stop_trading(State(state): State<SharedState>) -> Json<serde_json::Value> {
     let mut write_guard = state.write().await;
     write_guard.is_trading = false;
+    // A manual stop cancels any pending auto-resume
+    let pnl = write_guard.realized_pnl;
+    write_guard.circuit_breaker.reset(pnl);
     log::info!("Trading STOPPED by user request");
     Json(serde_json::json!({ "status": "success", "is_trading": false }))
 }
 
+/// Market orders closing every open position: on spot the base asset balance of each traded
+/// symbol, so holdings of other coins are left alone; on futures every position
+async fn closing_signals(executor: &Arc<dyn crate::execution::Executor>, price_cache: &crate::market_data::LastPriceCache, symbols: &[String]) -> Result<Vec<Signal>, String> {
+    let closes = match executor.market_type() {
+        MarketType::Spot => {
+            let balances = executor.get_balances().await.map_err(|e| e.to_string())?;
+            symbols.iter()
+                .filter_map(|symbol| {
+                    let base = crate::execution::base_asset(symbol, executor.quote_asset());
+                    let amount = balances.iter().find(|(asset, _)| asset == base).map_or(0.0, |(_, amount)| *amount);
+                    (base != executor.quote_asset() && amount > 0.0).then(|| Signal::Sell {
+                        symbol: symbol.clone(),
+                        price: price_cache.get(symbol),
+                        quantity: amount,
+                        order_type: OrderType::Market,
+                    })
+                })
+                .collect()
+        }
+        MarketType::Futures => executor.get_positions().await
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .filter(|p| p.amount > 0.0)
+            .map(|p| {
+                let price = price_cache.get(&p.symbol);
+                if p.side == "Short" {
+                    Signal::Buy { symbol: p.symbol, price, quantity: p.amount, order_type: OrderType::Market }
+                } else {
+                    Signal::Sell { symbol: p.symbol, price, quantity: p.amount, order_type: OrderType::Market }
+                }
+            })
+            .collect(),
+    };
+    Ok(closes)
+}
+
+/// Cancels the open orders of every traded symbol, so balances they lock can be sold
+async fn cancel_open_orders(executor: &Arc<dyn crate::execution::Executor>, symbols: &[String]) -> Result<(), String> {
+    for symbol in symbols {
+        executor.cancel_all_orders(symbol).await
+            .map_err(|e| format!("Failed to cancel open orders for {}: {}", symbol, e))?;
+    }
+    Ok(())
+}
+
+/// Sends each closing order in turn; one result entry per order, whose "status" is "success"
+/// only when it filled, otherwise "not_sent", "unfilled" or "error"
+async fn execute_closes(executor: &Arc<dyn crate::execution::Executor>, closes: Vec<Signal>) -> Vec<serde_json::Value> {
+    let mut results = Vec::with_capacity(closes.len());
+    for signal in closes {
+        let (side, quantity) = match &signal {
+            Signal::Buy { quantity, .. } => ("Buy", *quantity),
+            Signal::Sell { quantity, .. } => ("Sell", *quantity),
+            _ => continue,
+        };
+        let symbol = signal.symbol().to_string();
+        match executor.execute_with_fill(signal).await {
+            Ok(execution) => match (execution.filled(), execution.not_sent) {
+                (Some(fill), _) => {
+                    log::info!("FLATTEN: {} {} {} done", side, fill.quantity, symbol);
+                    results.push(serde_json::json!({
+                        "symbol": symbol, "side": side, "quantity": quantity, "status": "success",
+                        "filled_quantity": fill.quantity, "price": fill.price, "realized_pnl": execution.pnl
+                    }));
+                }
+                (None, Some(reason)) => {
+                    log::warn!("FLATTEN: {} {} {} not sent: {:?}", side, quantity, symbol, reason);
+                    results.push(serde_json::json!({ "symbol": symbol, "side": side, "quantity": quantity, "status": "not_sent", "reason": format!("{:?}", reason) }));
+                }
+                // Sent, but no fill was reported, so the position may still be open
+                (None, None) => {
+                    log::warn!("FLATTEN: {} {} {} sent without a reported fill", side, quantity, symbol);
+                    results.push(serde_json::json!({ "symbol": symbol, "side": side, "quantity": quantity, "status": "unfilled" }));
+                }
+            },
+            Err(e) => {
+                log::error!("FLATTEN: {} {} {} failed: {}", side, quantity, symbol, e);
+                results.push(serde_json::json!({ "symbol": symbol, "side": side, "quantity": quantity, "status": "error", "error": e.to_string() }));
+            }
+        }
+    }
+    results
+}
+
+/// Panic button: stops trading, cancels open orders, then market-closes everything. Spot sells
+/// the traded symbols' base assets; futures sells longs and buys back shorts. Returns one result
+/// per close order.
+async fn flatten_positions(State(state): State<SharedState>) -> (StatusCode, Json<serde_json::Value>) {
+    let (executor, price_cache, symbols) = {
+        let mut write_guard = state.write().await;
+        write_guard.is_trading = false;
+        (write_guard.executor.clone(), write_guard.price_cache.clone(), write_guard.symbols.clone())
+    };
+    log::warn!("FLATTEN requested: trading stopped, closing all positions");
+
+    if let Err(e) = cancel_open_orders(&executor, &symbols).await {
+        return (StatusCode::BAD_GATEWAY, Json(serde_json::json!({ "status": "error", "error": e })));
+    }
+    let closes = match closing_signals(&executor, &price_cache, &symbols).await {
+        Ok(closes) => closes,
+        Err(e) => return (StatusCode::BAD_GATEWAY, Json(serde_json::json!({ "status": "error", "error": e }))),
+    };
+    let results = execute_closes(&executor, closes).await;
+    {
+        let mut write_guard = state.write().await;
+        for result in results.iter().filter(|r| r["status"] == "success") {
+            if let Some(symbol) = result["symbol"].as_str() {
+                write_guard.clear_symbol_exposure(symbol);
+            }
+        }
+    }
+
+    (StatusCode::OK, Json(serde_json::json!({
+        "status": "success",
+        "is_trading": false,
+        "trading_armed": executor.armed_flag().load(std::sync::atomic::Ordering::SeqCst),
+        "results": results
+    })))
+}
+
+async fn reset_account(State(state): State<SharedState>) -> (StatusCode, Json<serde_json::Value>) {
+    let (executor, pool) = {
+        let read_guard = state.read().await;
+        (read_guard.executor.clone(), read_guard.db_pool.clone())
+    };
+    if let Err(e) = executor.reset_simulation().await {
+        log::warn!("Account reset rejected: {}", e);
+        return (StatusCode::BAD_REQUEST, Json(serde_json::json!({ "status": "error", "message": e.to_string() })));
+    }
+    if let Err(e) = repository::clear_cost_basis_fills(&pool).await {
+        log::error!("Failed to clear cost basis fills: {}", e);
+    }
+
+    let balances = executor.get_balances().await.unwrap_or_default();
+    let mut write_guard = state.write().await;
+    write_guard.clear_all_data();
+    write_guard.strategy_pnl.clear();
+    write_guard.cost_basis.clear();
+    write_guard.strategy_exposure.clear();
+    write_guard.strategy_reserved.clear();
+    write_guard.circuit_breaker.reset(0.0);
+    let starting_value = write_guard.price_cache.portfolio_value(&balances);
+    write_guard.initial_balance = starting_value;
+    let now_secs = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs();
+    // The portfolio history still ends at the old account, so start the day from the fresh value
+    write_guard.reset_daily(now_secs, starting_value);
+    log::info!("Paper trading account reset. Initial portfolio value ${:.2}", starting_value);
+    (StatusCode::OK, Json(serde_json::json!({ "status": "success", "initial_balance": starting_value })))
+}
+
+async fn arm_trading(State(state): State<SharedState>) -> Json<serde_json::Value> {
+    state.read().await.trading_armed.store(true, std::sync::atomic::Ordering::SeqCst);
+    log::warn!("Trading ARMED by user request. Live orders will reach the exchange.");
+    Json(serde_json::json!({ "status": "success", "trading_armed": true }))
+}
+
+async fn disarm_trading(State(state): State<SharedState>) -> Json<serde_json::Value> {
+    state.read().await.trading_armed.store(false, std::sync::atomic::Ordering::SeqCst);
+    log::info!("Trading DISARMED by user request. Live orders are dry-run only.");
+    Json(serde_json::json!({ "status": "success", "trading_armed": false }))
+}
+
 pub async fn start_server(port: u16, state: SharedState) {
     let app = Router::new()
+        .route("/health", get(get_health))
+        .route("/ready", get(get_ready))
         .route("/api/status", get(get_status))
+        .route("/api/schema", get(get_schema))
+        .route("/api/shadow", get(get_shadow))
+        .route("/api/metrics", get(get_prometheus_metrics))
         .route("/api/history", get(get_history))
+        .route("/api/live/stream", get(sse_live_handler))
+        .route("/api/candles", get(get_candles))
+        .route("/api/trades", get(get_trades_range))
+        .route("/api/portfolio_history", get(get_portfolio_history))
+        .route("/api/journal", get(get_journal))
+        .route("/api/cost_basis", get(get_cost_basis))
         .route("/api/data_range", get(get_data_range_api))
+        .route("/api/data_gaps", get(get_data_gaps))
+        .route("/api/data_coverage", get(get_data_coverage))
         .route("/api/change_symbol", axum::routing::post(change_symbol))
         .route("/api/select_strategy", axum::routing::post(select_strategy))
+        .route("/api/strategies", get(get_strategies))
+        .route("/api/strategy/config", get(get_strategy_config).post(set_strategy_config))
         .route("/api/backtest/progress", get(sse_progress_handler))
+        .route("/api/backtest/replay", get(sse_replay_handler))
         .route("/api/backtest/execute", axum::routing::post(execute_isolated_backtest))
+        .route("/api/backtest/sweep", axum::routing::post(execute_param_sweep))
+        .route("/api/backtest/cancel", axum::routing::post(cancel_backtest))
+        .route("/api/backtest/export", get(export_backtest))
         .route("/api/download_data", axum::routing::post(download_data_api))
         .route("/api/start_trading", axum::routing::post(start_trading))
         .route("/api/stop_trading", axum::routing::post(stop_trading))
+        .route("/api/reset_account", axum::routing::post(reset_account))
+        .route("/api/flatten", axum::routing::post(flatten_positions))
+        .route("/api/arm", axum::routing::post(arm_trading))
+        .route("/api/disarm", axum::routing::post(disarm_trading))
+        .route("/api/futures/leverage", axum::routing::post(set_futures_leverage))
+        .route("/api/futures/margin_type", axum::routing::post(set_futures_margin_type))
         .route("/", get(get_dashboard))
         .route("/backtest", get(get_backtest_dashboard))
         .with_state(state);
@@ -799,3 +2883,156 @@ pub async fn start_server(port: u16, state: SharedState) {
     let listener = tokio::net::TcpListener::bind(addr).await.unwrap();
     axum::serve(listener, app).await.unwrap();
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_state() -> SharedState {
+        let pool = sqlx::postgres::PgPoolOptions::new().connect_lazy("postgres://localhost/unused").unwrap();
+        let executor = Arc::new(crate::execution::ExecutionManager::new(
+            true,
+            crate::execution::FeeSchedule::default(),
+            MarketType::Spot,
+        ));
+        let (tx, _) = crate::market_data::event_queue(1, crate::market_data::BackpressurePolicy::Block);
+        let mut state = AppState::new("backtest".to_string(), "DCAStrategy".to_string(), pool, "BTCUSDT".to_string(), executor, tx);
+        state.record_latencies = false;
+        state.state_machine.transition_to(SystemState::Trading);
+        Arc::new(RwLock::new(state))
+    }
+
+    fn trade(price: f64, event_time: u64) -> binance::model::TradeEvent {
+        binance::model::TradeEvent {
+            event_type: "trade".to_string(),
+            event_time,
+            symbol: "BTCUSDT".to_string(),
+            trade_id: event_time,
+            price: price.to_string(),
+            qty: "0.01".to_string(),
+            buyer_order_id: 0,
+            seller_order_id: 0,
+            trade_order_time: event_time,
+            is_buyer_maker: false,
+            m_ignore: false,
+        }
+    }
+
+    /// A DCA backtest over `trades` with no order books, fees at the defaults and no slippage
+    fn dca_backtest<'a>(
+        trades: &'a [binance::model::TradeEvent],
+        params: &'a std::collections::HashMap<String, f64>,
+        initial_capital: f64,
+        margin: Option<MarginConfig>,
+    ) -> BacktestPass<'a> {
+        BacktestPass {
+            symbol: "BTCUSDT",
+            strategy_name: "DCAStrategy",
+            params,
+            trades,
+            orderbooks: &[],
+            db_pool: sqlx::postgres::PgPoolOptions::new().connect_lazy("postgres://localhost/unused").unwrap(),
+            market_type: MarketType::Spot,
+            fee_schedule: crate::execution::FeeSchedule::default(),
+            slippage: crate::execution::SlippageModel::None,
+            initial_capital,
+            margin,
+            replay_speed: None,
+            fast_mode: false,
+            report_progress: false,
+            cancel: Arc::new(std::sync::atomic::AtomicBool::new(false)),
+        }
+    }
+
+    #[tokio::test]
+    async fn backtest_passes_over_the_same_trades_match() {
+        // Past the warm-up (100 events over 5s), DCA buys every 20 trades into a moving price
+        let trades: Vec<_> = (0..200).map(|i| trade(100.0 + (i % 7) as f64, 1_000 + i * 100)).collect();
+        let params = std::collections::HashMap::from([
+            ("usd_amount".to_string(), 20.0),
+            ("buy_interval".to_string(), 20.0),
+        ]);
+        let first = dca_backtest(&trades, &params, 1_000.0, None).run().await.unwrap();
+        let second = dca_backtest(&trades, &params, 1_000.0, None).run().await.unwrap();
+
+        assert!(first.total_trades > 0);
+        assert_eq!(first.history.len(), trades.len());
+        assert!(first.history.iter().all(|d| d.strategy_latency == 0 && d.execution_latency == 0));
+        assert_eq!(serde_json::to_string(&first.history).unwrap(), serde_json::to_string(&second.history).unwrap());
+        // Compared as values: `features` is a HashMap, so its serialized key order varies
+        assert_eq!(serde_json::to_value(&first).unwrap(), serde_json::to_value(&second).unwrap());
+    }
+
+    #[tokio::test]
+    async fn opportunity_ids_stay_unique_and_restart_after_clear() {
+        let state = test_state();
+        let label = |id: &str| crate::strategy::Opportunity {
+            id: id.to_string(),
+            signal: Signal::Buy { symbol: "BTCUSDT".to_string(), price: None, quantity: 0.001, order_type: OrderType::Market },
+            score: 0.0,
+            risk_score: 0.0,
+            reason: String::new(),
+            timestamp: 5,
+        };
+        let mut batch = vec![label("rsi_buy"), label("rsi_buy")];
+        let mut write_guard = state.write().await;
+        write_guard.assign_opportunity_ids(&mut batch);
+        assert_eq!(batch[0].id, "rsi_buy_1");
+        assert_eq!(batch[1].id, "rsi_buy_2");
+
+        write_guard.clear_all_data();
+        let mut batch = vec![label("rsi_buy")];
+        write_guard.assign_opportunity_ids(&mut batch);
+        assert_eq!(batch[0].id, "rsi_buy_1");
+    }
+
+    #[tokio::test]
+    async fn margin_backtest_charges_interest_on_the_borrowed_quote() {
+        // 120 trades warm up and then buy 20 USD on a 10 USD account; the last trade is a day later
+        let mut trades: Vec<_> = (0..120).map(|i| trade(100.0, 1_000 + i * 100)).collect();
+        trades.push(trade(100.0, 1_000 + 119 * 100 + MS_PER_DAY as u64));
+        let params = std::collections::HashMap::from([
+            ("usd_amount".to_string(), 20.0),
+            ("buy_interval".to_string(), 120.0),
+        ]);
+        let margin = MarginConfig { daily_interest_rate: 0.01, borrowed_notional: 100.0 };
+
+        let report = dca_backtest(&trades, &params, 10.0, Some(margin)).run().await.unwrap();
+        // About 10 USD (plus the fee) borrowed for a day at 1%
+        assert!((report.total_interest_paid - 0.1).abs() < 0.001, "interest {}", report.total_interest_paid);
+
+        // Without a credit line the buy is unaffordable and nothing is borrowed
+        let report = dca_backtest(&trades, &params, 10.0, None).run().await.unwrap();
+        assert_eq!(report.total_interest_paid, 0.0);
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-9, "expected {}, got {}", expected, actual);
+    }
+
+    #[test]
+    fn sharpe_divides_the_mean_by_the_sample_std_dev() {
+        // mean 3, sample variance (4 + 1 + 0 + 9) / 3
+        assert_close(sharpe(&[1.0, 2.0, 3.0, 6.0]), 3.0 / (14.0f64 / 3.0).sqrt());
+        assert_close(sharpe(&[5.0]), 0.0);
+        assert_close(sharpe(&[]), 0.0);
+        assert_close(sharpe(&[2.0, 2.0, 2.0]), 0.0);
+    }
+
+    #[test]
+    fn sortino_divides_the_mean_by_downside_deviation() {
+        // mean 1, downside deviation sqrt((1 + 4) / 4)
+        assert_close(sortino(&[2.0, -1.0, -2.0, 5.0]), 1.0 / 1.25f64.sqrt());
+        assert_close(sortino(&[1.0, 2.0]), 0.0);
+        assert_close(sortino(&[]), 0.0);
+    }
+
+    #[test]
+    fn calmar_annualizes_the_yield_over_the_span() {
+        // 10% over half a year is 20% a year, against a 5% drawdown
+        assert_close(calmar(10.0, 5.0, (MS_PER_YEAR / 2.0) as u64), 4.0);
+        assert_close(calmar(-10.0, 5.0, MS_PER_YEAR as u64), -2.0);
+        assert_close(calmar(10.0, 0.0, MS_PER_YEAR as u64), 0.0);
+        assert_close(calmar(10.0, 5.0, 0), 0.0);
+    }
+}